@@ -1,4 +1,3 @@
-use std::fs::File;
 use std::path::PathBuf;
 
 use anyhow::Result;
@@ -13,6 +12,35 @@ struct Args {
     /// path to the jar
     #[arg(long)]
     jar: PathBuf,
+
+    /// main class to run instead of the one in the jar's manifest
+    #[arg(long)]
+    main_class: Option<String>,
+
+    /// additional directories and jars to load classes from, separated by ':'
+    #[arg(long, visible_alias = "cp", value_delimiter = ':')]
+    class_path: Vec<PathBuf>,
+
+    /// java.base.jmod of a JDK, or a directory with its extracted classes, to load the JDK's
+    /// classes from instead of the built-in cache
+    #[arg(long)]
+    jdk: Option<PathBuf>,
+
+    /// verify classes against their stack map frames when they are linked
+    #[arg(long)]
+    verify: bool,
+
+    /// number of objects the heap can hold before an OutOfMemoryError is thrown
+    #[arg(long)]
+    max_heap_items: Option<usize>,
+
+    /// number of frames of each thread's stack before a StackOverflowError is thrown
+    #[arg(long)]
+    max_stack_frames: Option<usize>,
+
+    /// arguments passed to main
+    #[arg(trailing_var_arg = true)]
+    args: Vec<String>,
 }
 
 fn main() -> Result<()> {
@@ -22,9 +50,19 @@ fn main() -> Result<()> {
         .init();
 
     let args = Args::parse();
-    let jar_file = File::open(args.jar)?;
-
-    match jvm::run_jar(jar_file) {
+    match jvm::run_jar_with_args(
+        &args.jar,
+        args.main_class.as_deref(),
+        &args.class_path,
+        args.args,
+        jvm::Options {
+            jdk: args.jdk,
+            verify_classes: args.verify,
+            max_heap_items: args.max_heap_items,
+            max_stack_frames: args.max_stack_frames,
+            output: None,
+        },
+    ) {
         Ok(_) => Ok(()),
         Err(err) => {
             error!("jvm error: {err:?}");
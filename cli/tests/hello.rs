@@ -0,0 +1,12 @@
+use std::process::Command;
+
+#[test]
+fn hello_world() {
+    let output = Command::new(env!("CARGO_BIN_EXE_cli"))
+        .args(["--jar", "../jvm/testdata/Hello.jar"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "Hello, World!\n");
+}
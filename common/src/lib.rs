@@ -15,7 +15,7 @@ impl From<i64> for ThreadId {
     }
 }
 
-#[derive(Eq, Hash, PartialEq, Debug, Clone)]
+#[derive(Eq, Hash, PartialEq, PartialOrd, Ord, Debug, Clone)]
 pub struct HeapId(u64);
 
 impl From<u64> for HeapId {
@@ -24,69 +24,98 @@ impl From<u64> for HeapId {
     }
 }
 
-/// Identifies a class using package and name
+/// Names of the primitive types, whose classes have no package
+const PRIMITIVE_NAMES: [&str; 9] = [
+    "boolean", "byte", "char", "short", "int", "long", "float", "double", "void",
+];
+
+/// Identifies a class using package and name, nested classes keep their `$` separated name
 #[derive(Clone, Eq, Hash, PartialEq)]
 pub struct ClassIdentifier {
     pub package: String,
     pub name: String,
+    /// Dimensions of an array class, whose element class is identified by package and name
+    pub array_dimensions: u8,
 }
 
 impl ClassIdentifier {
-    pub fn new(package: String, name: String) -> Self {
-        Self { package, name }
-    }
+    /// Parses a binary (`java.lang.String`), internal (`java/lang/String`) or descriptor
+    /// (`Ljava/lang/String;`) class name, array descriptors like `[[Ljava/lang/String;` keep
+    /// their dimensions
+    pub fn new(raw: &str) -> Result<Self> {
+        let element = raw.trim_start_matches('[');
+        let array_dimensions = (raw.len() - element.len()).try_into()?;
+        if array_dimensions > 0 {
+            if let Some(name) = primitive_name(element) {
+                return Ok(Self::array(&Self::primitive(name)?, array_dimensions));
+            }
 
-    pub fn parse(raw: &str) -> Result<Self> {
+            return Ok(Self::array(&Self::new(element)?, array_dimensions));
+        }
+
+        let raw = element;
+        // only strip the 'L' of field descriptors, class names like `Lambdas` start with it too
+        let raw = match raw.strip_prefix('L') {
+            Some(name) if raw.ends_with(';') || name.contains(['/', '.']) => name,
+            _ => raw,
+        };
         let raw = raw.replace("/", ".").replace(";", "");
-        let raw: String = raw.chars().skip_while(|c| *c == 'L' || *c == '[').collect();
 
         match raw.as_str() {
             "B" => {
                 return Ok(Self {
                     package: "java.lang".to_owned(),
                     name: "Byte".to_owned(),
+                    array_dimensions: 0,
                 });
             }
             "C" => {
                 return Ok(Self {
                     package: "java.lang".to_owned(),
                     name: "Character".to_owned(),
+                    array_dimensions: 0,
                 });
             }
             "D" => {
                 return Ok(Self {
                     package: "java.lang".to_owned(),
                     name: "Double".to_owned(),
+                    array_dimensions: 0,
                 });
             }
             "F" => {
                 return Ok(Self {
                     package: "java.lang".to_owned(),
                     name: "Float".to_owned(),
+                    array_dimensions: 0,
                 });
             }
             "I" => {
                 return Ok(Self {
                     package: "java.lang".to_owned(),
                     name: "Integer".to_owned(),
+                    array_dimensions: 0,
                 });
             }
             "J" => {
                 return Ok(Self {
                     package: "java.lang".to_owned(),
                     name: "Long".to_owned(),
+                    array_dimensions: 0,
                 });
             }
             "S" => {
                 return Ok(Self {
                     package: "java.lang".to_owned(),
                     name: "Short".to_owned(),
+                    array_dimensions: 0,
                 });
             }
             "Z" => {
                 return Ok(Self {
                     package: "java.lang".to_owned(),
                     name: "Boolean".to_owned(),
+                    array_dimensions: 0,
                 });
             }
             _ => {}
@@ -102,10 +131,130 @@ impl ClassIdentifier {
         Ok(Self {
             package: parts.join("."),
             name,
+            array_dimensions: 0,
         })
     }
 
+    pub fn from_parts(package: &str, name: &str) -> Self {
+        Self {
+            package: package.to_owned(),
+            name: name.to_owned(),
+            array_dimensions: 0,
+        }
+    }
+
+    /// Identifies the array class with `dimensions` of `element`
+    pub fn array(element: &ClassIdentifier, dimensions: u8) -> Self {
+        Self {
+            array_dimensions: element.array_dimensions + dimensions,
+            ..element.clone()
+        }
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.array_dimensions > 0
+    }
+
+    /// Class of the components of an array class, one dimension less than the array itself
+    pub fn component(&self) -> Option<Self> {
+        self.array_dimensions
+            .checked_sub(1)
+            .map(|array_dimensions| Self {
+                array_dimensions,
+                ..self.clone()
+            })
+    }
+
+    /// Class of the elements of an array class, the class itself otherwise
+    pub fn element(&self) -> Self {
+        Self {
+            array_dimensions: 0,
+            ..self.clone()
+        }
+    }
+
+    /// Identifies the class of the primitive type `name`, e.g. `int`
+    pub fn primitive(name: &str) -> Result<Self> {
+        if !PRIMITIVE_NAMES.contains(&name) {
+            bail!("'{name}' is no primitive type");
+        }
+
+        Ok(Self::from_parts("", name))
+    }
+
+    pub fn is_primitive(&self) -> bool {
+        !self.is_array() && self.package.is_empty() && PRIMITIVE_NAMES.contains(&self.name.as_str())
+    }
+
+    /// Internal binary name as used in class files, e.g. `java/lang/String` or
+    /// `[Ljava/lang/String;` for arrays
+    pub fn with_slashes(&self) -> Result<String> {
+        if self.is_array() {
+            return Ok(self.java_name().replace('.', "/"));
+        }
+        if self.package.is_empty() {
+            return Ok(self.name.clone());
+        }
+
+        Ok(format!("{}/{}", self.package.replace('.', "/"), self.name))
+    }
+
+    /// Name as returned by `Class.getName`, e.g. `java.lang.String`, `Outer$Inner` or
+    /// `[Ljava.lang.String;` for arrays
+    pub fn java_name(&self) -> String {
+        let element = if self.package.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.package, self.name)
+        };
+        if !self.is_array() {
+            return element;
+        }
+
+        let dimensions = "[".repeat(self.array_dimensions.into());
+        match primitive_descriptor(self) {
+            Some(descriptor) => format!("{dimensions}{descriptor}"),
+            None => format!("{dimensions}L{element};"),
+        }
+    }
+
+    /// Name as returned by `Class.getSimpleName`, which is empty for anonymous classes.
+    /// Nested classes are recognized by the `$` in their name.
+    pub fn simple_name(&self) -> String {
+        let simple_name = match self.name.rsplit_once('$') {
+            // local classes are prefixed with a number, anonymous ones are only a number
+            Some((_, nested)) => nested.trim_start_matches(|c: char| c.is_ascii_digit()),
+            None => &self.name,
+        };
+
+        format!("{simple_name}{}", "[]".repeat(self.array_dimensions.into()))
+    }
+
+    /// Name as returned by `Class.getCanonicalName`, local and anonymous classes have none
+    pub fn canonical_name(&self) -> Option<String> {
+        let mut nested = self.name.split('$').skip(1);
+        if nested.any(|name| name.starts_with(|c: char| c.is_ascii_digit())) {
+            return None;
+        }
+
+        let name = self.name.replace('$', ".");
+        let name = if self.package.is_empty() {
+            name
+        } else {
+            format!("{}.{name}", self.package)
+        };
+        Some(format!(
+            "{name}{}",
+            "[]".repeat(self.array_dimensions.into())
+        ))
+    }
+
+    /// Path of the class file, array classes have none
     pub fn path(&self) -> Result<String> {
+        if self.is_array() {
+            bail!("array class {self:?} has no class file");
+        }
+
         let mut path = PathBuf::new();
         for package in self.package.split('.') {
             path.push(package);
@@ -119,6 +268,41 @@ impl ClassIdentifier {
     }
 }
 
+/// Name of the primitive type with the field `descriptor`
+fn primitive_name(descriptor: &str) -> Option<&'static str> {
+    Some(match descriptor {
+        "Z" => "boolean",
+        "B" => "byte",
+        "C" => "char",
+        "S" => "short",
+        "I" => "int",
+        "J" => "long",
+        "F" => "float",
+        "D" => "double",
+        _ => return None,
+    })
+}
+
+/// Descriptor of the element class of `identifier`, if it is a primitive one
+fn primitive_descriptor(identifier: &ClassIdentifier) -> Option<char> {
+    if !identifier.package.is_empty() {
+        return None;
+    }
+
+    Some(match identifier.name.as_str() {
+        "boolean" => 'Z',
+        "byte" => 'B',
+        "char" => 'C',
+        "short" => 'S',
+        "int" => 'I',
+        "long" => 'J',
+        "float" => 'F',
+        "double" => 'D',
+        "void" => 'V',
+        _ => return None,
+    })
+}
+
 impl Display for ClassIdentifier {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.name)
@@ -127,14 +311,13 @@ impl Display for ClassIdentifier {
 
 impl Debug for ClassIdentifier {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}", self.package, self.name)
+        write!(f, "{}", self.java_name())
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Hash)]
 pub enum ReferenceValue {
     HeapItem(HeapId),
-    Class(ClassIdentifier),
     Null,
 }
 
@@ -146,20 +329,9 @@ impl ReferenceValue {
         }
     }
 
-    pub fn class_identifier(&self) -> Result<&ClassIdentifier> {
-        match self {
-            ReferenceValue::Class(class_identifier) => Ok(class_identifier),
-            _ => bail!("no class identifier found"),
-        }
-    }
-
     pub fn is_null(&self) -> bool {
         matches!(self, Self::Null)
     }
-
-    pub fn is_class(&self) -> bool {
-        matches!(self, Self::Class(_))
-    }
 }
 
 #[derive(Clone, Debug)]
@@ -265,16 +437,19 @@ impl FrameValue {
     }
 }
 
-impl From<FrameValue> for FieldValue {
-    fn from(value: FrameValue) -> Self {
-        match value {
+impl TryFrom<FrameValue> for FieldValue {
+    type Error = anyhow::Error;
+
+    fn try_from(value: FrameValue) -> Result<Self> {
+        Ok(match value {
             FrameValue::Reference(reference_value) => Self::Reference(reference_value),
             FrameValue::Int(val) => Self::Integer(val),
             FrameValue::Long(val) => Self::Long(val),
             FrameValue::Float(val) => Self::Float(val),
             FrameValue::Double(val) => Self::Double(val),
-            FrameValue::Reserved => panic!("impossible"),
-        }
+            // second slot of a long or double local, it never holds a value of its own
+            FrameValue::Reserved => bail!("reserved slot has no field value"),
+        })
     }
 }
 
@@ -294,58 +469,179 @@ impl From<FieldValue> for FrameValue {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_new_equals_from_parts() {
+        let class_identifier = ClassIdentifier::new("java.lang.String").unwrap();
+        assert_eq!(
+            class_identifier,
+            ClassIdentifier::from_parts("java.lang", "String")
+        );
+        assert_eq!(
+            ClassIdentifier::new("java/lang/String").unwrap(),
+            class_identifier
+        );
+        assert_eq!(
+            ClassIdentifier::new("String").unwrap(),
+            ClassIdentifier::from_parts("", "String")
+        );
+    }
+
+    #[test]
+    fn test_primitive() {
+        let int = ClassIdentifier::primitive("int").unwrap();
+        assert!(int.is_primitive());
+        assert_eq!(format!("{int:?}"), "int");
+        assert!(!ClassIdentifier::new("I").unwrap().is_primitive());
+        assert!(!ClassIdentifier::from_parts("java.lang", "int").is_primitive());
+        assert!(ClassIdentifier::primitive("Integer").is_err());
+    }
+
+    #[test]
+    fn test_java_names() {
+        let nested = ClassIdentifier::new("java/util/Map$Entry").unwrap();
+        assert_eq!(nested.java_name(), "java.util.Map$Entry");
+        assert_eq!(nested.simple_name(), "Entry");
+        assert_eq!(nested.canonical_name().unwrap(), "java.util.Map.Entry");
+
+        let strings = ClassIdentifier::array(&ClassIdentifier::new("java/lang/String").unwrap(), 2);
+        assert!(strings.is_array());
+        assert_eq!(
+            strings.element(),
+            ClassIdentifier::new("java/lang/String").unwrap()
+        );
+        assert_eq!(strings.java_name(), "[[Ljava.lang.String;");
+        assert_eq!(format!("{strings:?}"), "[[Ljava.lang.String;");
+        assert_eq!(strings.simple_name(), "String[][]");
+        assert_eq!(strings.canonical_name().unwrap(), "java.lang.String[][]");
+
+        let ints = ClassIdentifier::array(&ClassIdentifier::primitive("int").unwrap(), 1);
+        assert_eq!(ints.java_name(), "[I");
+        assert_eq!(ints.simple_name(), "int[]");
+        assert_eq!(ints.canonical_name().unwrap(), "int[]");
+
+        let anonymous = ClassIdentifier::new("Main$1").unwrap();
+        assert_eq!(anonymous.java_name(), "Main$1");
+        assert_eq!(anonymous.simple_name(), "");
+        assert!(anonymous.canonical_name().is_none());
+        let local = ClassIdentifier::new("Main$1Local").unwrap();
+        assert_eq!(local.simple_name(), "Local");
+        assert!(local.canonical_name().is_none());
+    }
+
+    #[test]
+    fn test_parse_array() {
+        let strings = ClassIdentifier::new("[[Ljava/lang/String;").unwrap();
+        assert_eq!(strings.array_dimensions, 2);
+        assert_eq!(strings.package, "java.lang");
+        assert_eq!(strings.name, "String");
+        assert_eq!(strings.with_slashes().unwrap(), "[[Ljava/lang/String;");
+        assert!(strings.path().is_err());
+        assert_eq!(
+            strings.component().unwrap(),
+            ClassIdentifier::new("[Ljava/lang/String;").unwrap()
+        );
+        assert!(strings.element().component().is_none());
+
+        let ints = ClassIdentifier::new("[I").unwrap();
+        assert_eq!(ints.array_dimensions, 1);
+        assert!(!ints.is_primitive());
+        assert!(ints.element().is_primitive());
+        assert_eq!(ints.with_slashes().unwrap(), "[I");
+
+        let nested = ClassIdentifier::new("[Ljava/util/Map$Entry;").unwrap();
+        assert_eq!(nested.name, "Map$Entry");
+        assert_eq!(nested.with_slashes().unwrap(), "[Ljava/util/Map$Entry;");
+    }
+
+    #[test]
+    fn test_with_slashes() {
+        let class_identifier = ClassIdentifier::new("java/lang/String").unwrap();
+        assert_eq!(class_identifier.with_slashes().unwrap(), "java/lang/String");
+
+        let class_identifier = ClassIdentifier::new("Main").unwrap();
+        assert_eq!(class_identifier.with_slashes().unwrap(), "Main");
+
+        let raw = "jdk/internal/util/SystemProps$Raw";
+        let class_identifier = ClassIdentifier::new(raw).unwrap();
+        assert_eq!(class_identifier.with_slashes().unwrap(), raw);
+        assert_eq!(
+            ClassIdentifier::new(&class_identifier.with_slashes().unwrap()).unwrap(),
+            class_identifier
+        );
+    }
+
     #[test]
     fn test_parse_slashes() {
-        let class_identifier = ClassIdentifier::parse("java/lang/System").unwrap();
+        let class_identifier = ClassIdentifier::new("java/lang/System").unwrap();
         assert_eq!(class_identifier.package, "java.lang");
         assert_eq!(class_identifier.name, "System");
     }
 
     #[test]
     fn test_parse_dots() {
-        let class_identifier = ClassIdentifier::parse("java.lang.System").unwrap();
+        let class_identifier = ClassIdentifier::new("java.lang.System").unwrap();
         assert_eq!(class_identifier.package, "java.lang");
         assert_eq!(class_identifier.name, "System");
     }
 
+    #[test]
+    fn test_debug_no_package() {
+        let class_identifier = ClassIdentifier::new("Main").unwrap();
+        assert_eq!(format!("{class_identifier:?}"), "Main");
+
+        let class_identifier = ClassIdentifier::new("java/lang/System").unwrap();
+        assert_eq!(format!("{class_identifier:?}"), "java.lang.System");
+    }
+
     #[test]
     fn test_parse_no_package() {
-        let class_identifier = ClassIdentifier::parse("System").unwrap();
+        let class_identifier = ClassIdentifier::new("System").unwrap();
         assert_eq!(class_identifier.package, "");
         assert_eq!(class_identifier.name, "System");
     }
 
     #[test]
     fn test_parse_lowercase_class() {
-        let class_identifier = ClassIdentifier::parse("java.lang").unwrap();
+        let class_identifier = ClassIdentifier::new("java.lang").unwrap();
         assert_eq!(class_identifier.package, "java");
         assert_eq!(class_identifier.name, "lang");
     }
 
     #[test]
     fn test_parse_from_field_descriptor() {
-        let class_identifier = ClassIdentifier::parse("Ljava.lang.System").unwrap();
+        let class_identifier = ClassIdentifier::new("Ljava.lang.System").unwrap();
         assert_eq!(class_identifier.package, "java.lang");
         assert_eq!(class_identifier.name, "System");
     }
 
     #[test]
     fn test_parse_from_field_descriptor_array() {
-        let class_identifier = ClassIdentifier::parse("[[Ljava.lang.System").unwrap();
+        let class_identifier = ClassIdentifier::new("[[Ljava.lang.System").unwrap();
         assert_eq!(class_identifier.package, "java.lang");
         assert_eq!(class_identifier.name, "System");
     }
 
+    #[test]
+    fn test_parse_class_starting_with_l() {
+        let class_identifier = ClassIdentifier::new("Lambdas").unwrap();
+        assert_eq!(class_identifier.package, "");
+        assert_eq!(class_identifier.name, "Lambdas");
+
+        let class_identifier = ClassIdentifier::new("[LLambdas;").unwrap();
+        assert_eq!(class_identifier.package, "");
+        assert_eq!(class_identifier.name, "Lambdas");
+    }
+
     #[test]
     fn test_parse_from_field_descriptor_primitive_int() {
-        let class_identifier = ClassIdentifier::parse("I").unwrap();
+        let class_identifier = ClassIdentifier::new("I").unwrap();
         assert_eq!(class_identifier.package, "java.lang");
         assert_eq!(class_identifier.name, "Integer");
     }
 
     #[test]
     fn test_path() {
-        let class_identifier = ClassIdentifier::new("java.lang".to_owned(), "System".to_owned());
+        let class_identifier = ClassIdentifier::from_parts("java.lang", "System");
         let path = class_identifier.path().unwrap();
         assert_eq!(path, "java/lang/System.class");
     }
@@ -1,15 +1,84 @@
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use zip::ZipArchive;
 
+/// Directory containing the jmods of the JDK, overriding `JAVA_HOME`
+const JDK_MODS_VAR: &str = "ATRIA_JDK_MODS";
+/// Builds an empty class cache when set, classes are then read from a JDK at runtime
+const NO_CACHE_VAR: &str = "ATRIA_NO_JDK_CACHE";
+const JAVA_BASE: &str = "java.base.jmod";
+
 fn main() {
-    let java_home = std::env::var("JAVA_HOME").unwrap();
-    let java_home_path = PathBuf::from(java_home);
-    let java_base_path = java_home_path.join("jmods/java.base.jmod");
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed={JDK_MODS_VAR}");
+    println!("cargo:rerun-if-env-changed={NO_CACHE_VAR}");
+    println!("cargo:rerun-if-env-changed=JAVA_HOME");
+
+    let java_base_path = java_base_path();
+    if let Some(java_base_path) = &java_base_path {
+        println!(
+            "cargo:rustc-env=ATRIA_JAVA_BASE={}",
+            java_base_path.display()
+        );
+    }
 
-    let mut archive = ZipArchive::new(File::open(&java_base_path).unwrap()).unwrap();
+    let class_map = if std::env::var_os(NO_CACHE_VAR).is_some() {
+        HashMap::new()
+    } else if let Some(java_base_path) = java_base_path {
+        println!("cargo:rerun-if-changed={}", java_base_path.display());
+        read_classes(&java_base_path)
+    } else {
+        println!(
+            "cargo:warning=no {JAVA_BASE} found, set {JDK_MODS_VAR} or JAVA_HOME to a JDK 17, building an empty class cache"
+        );
+        HashMap::new()
+    };
+
+    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&class_map).unwrap();
+    std::fs::write("../target/class_cache.bin", bytes).unwrap();
+}
+
+/// Path of `java.base.jmod`, from `ATRIA_JDK_MODS`, `JAVA_HOME` or common JDK install locations
+fn java_base_path() -> Option<PathBuf> {
+    let mut mods_dirs = Vec::new();
+    if let Ok(mods) = std::env::var(JDK_MODS_VAR) {
+        mods_dirs.push(PathBuf::from(mods));
+    }
+    if let Ok(java_home) = std::env::var("JAVA_HOME") {
+        mods_dirs.push(PathBuf::from(java_home).join("jmods"));
+    }
+    mods_dirs.extend(installed_jdks().into_iter().map(|jdk| jdk.join("jmods")));
+
+    mods_dirs
+        .into_iter()
+        .map(|mods| mods.join(JAVA_BASE))
+        .find(|path| path.is_file())
+}
+
+/// Home directories of JDKs in common install locations, the ones of version 17 first
+fn installed_jdks() -> Vec<PathBuf> {
+    let mut jdks: Vec<PathBuf> = ["/usr/lib/jvm", "/Library/Java/JavaVirtualMachines"]
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+        })
+        .map(|jdk| match jdk.join("Contents/Home") {
+            // macOS bundles
+            home if home.is_dir() => home,
+            _ => jdk,
+        })
+        .collect();
+    jdks.sort_by_key(|jdk| !jdk.to_string_lossy().contains("17"));
+    jdks
+}
+
+fn read_classes(java_base_path: &Path) -> HashMap<String, Vec<u8>> {
+    let mut archive = ZipArchive::new(File::open(java_base_path).unwrap()).unwrap();
     let mut file_paths: HashSet<PathBuf> = HashSet::default();
 
     for name in archive.file_names() {
@@ -28,6 +97,5 @@ fn main() {
         class_map.insert(class_name.to_str().unwrap().to_string(), contents);
     }
 
-    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&class_map).unwrap();
-    std::fs::write("../target/class_cache.bin", bytes).unwrap();
+    class_map
 }
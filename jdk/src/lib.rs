@@ -1,14 +1,79 @@
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 use anyhow::Result;
-use rkyv::util::AlignedVec;
+use rkyv::Archived;
 
-static CLASSE_CACHE: &[u8] = include_bytes!("../../target/class_cache.bin");
+type ClassMap = HashMap<String, Vec<u8>>;
 
-pub fn classes() -> Result<HashMap<String, Vec<u8>>> {
-    let mut aligned: AlignedVec = AlignedVec::new();
-    aligned.extend_from_slice(CLASSE_CACHE);
-    let classes: HashMap<String, Vec<u8>> =
-        rkyv::from_bytes::<HashMap<String, Vec<u8>>, rkyv::rancor::Error>(&aligned)?;
-    Ok(classes)
+/// Path of the `java.base.jmod` found by the build script, even if it wasn't cached
+pub const JAVA_BASE: Option<&str> = option_env!("ATRIA_JAVA_BASE");
+
+/// Aligns the included cache, so that it can be accessed without copying it first
+#[repr(C, align(16))]
+struct Aligned<T: ?Sized>(T);
+
+static CLASSE_CACHE: &Aligned<[u8]> = &Aligned(*include_bytes!("../../target/class_cache.bin"));
+static ARCHIVE: OnceLock<&'static Archived<ClassMap>> = OnceLock::new();
+
+/// Classes of the `java.base` module archived by the build script, keyed by their path
+/// (e.g. `java/lang/Object.class`). Only the classes that are read get copied out of the archive.
+pub struct Classes {
+    archive: &'static Archived<ClassMap>,
+    read: usize,
+}
+
+impl Classes {
+    pub fn new() -> Result<Self> {
+        let archive = match ARCHIVE.get() {
+            Some(archive) => archive,
+            None => {
+                let archive =
+                    rkyv::access::<Archived<ClassMap>, rkyv::rancor::Error>(&CLASSE_CACHE.0)?;
+                ARCHIVE.get_or_init(|| archive)
+            }
+        };
+
+        Ok(Self { archive, read: 0 })
+    }
+
+    /// Contents of the class file at `path`
+    pub fn get(&mut self, path: &str) -> Option<Vec<u8>> {
+        let class = self.archive.get(path)?;
+        self.read += 1;
+        Some(class.to_vec())
+    }
+
+    /// Number of classes that have been copied out of the archive
+    pub fn read(&self) -> usize {
+        self.read
+    }
+
+    pub fn len(&self) -> usize {
+        self.archive.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.archive.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_single_class() {
+        // the cache is empty if no JDK was found while building
+        let mut classes = Classes::new().unwrap();
+        assert!(classes.get("java/lang/Missing.class").is_none());
+        if classes.is_empty() {
+            return;
+        }
+
+        let object = classes.get("java/lang/Object.class").unwrap();
+        assert_eq!(object[..4], [0xca, 0xfe, 0xba, 0xbe]);
+        assert_eq!(classes.read(), 1);
+        assert!(classes.len() > 1000);
+    }
 }
@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use common::ClassIdentifier;
+
+use crate::loader::ReadClass;
+
+/// Reads loose class files from a class path directory, with packages as subdirectories
+pub struct DirectoryClassSource {
+    root: PathBuf,
+}
+
+impl DirectoryClassSource {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl ReadClass for DirectoryClassSource {
+    fn read_class(&mut self, identifier: &ClassIdentifier) -> Result<Vec<u8>> {
+        Ok(std::fs::read(self.root.join(identifier.path()?))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{jdk::Jdk, loader::BootstrapClassLoader};
+
+    use super::*;
+
+    #[test]
+    fn load_from_directory() {
+        let root = std::env::temp_dir().join(format!("atria-class-path-{}", std::process::id()));
+        let package = root.join("org").join("example");
+        fs::create_dir_all(&package).unwrap();
+        let testdata = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata");
+        fs::copy(
+            testdata.join("org/example/Greeter.class"),
+            package.join("Greeter.class"),
+        )
+        .unwrap();
+
+        let sources: Vec<Box<dyn ReadClass>> = vec![
+            Box::new(DirectoryClassSource::new(&root)),
+            Box::new(Jdk::new().unwrap()),
+        ];
+        let mut loader = BootstrapClassLoader::new(sources);
+        let class_file = loader
+            .load(&ClassIdentifier::new("org.example.Greeter").unwrap())
+            .unwrap();
+        assert_eq!(
+            class_file
+                .constant_pool
+                .class_name(&class_file.this_class)
+                .unwrap(),
+            "org/example/Greeter"
+        );
+        assert!(
+            loader
+                .load(&ClassIdentifier::new("org.example.Missing").unwrap())
+                .is_err()
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -30,7 +30,9 @@ impl Jar {
 }
 
 pub(crate) struct Manifest {
-    pub main_class: ClassIdentifier,
+    pub main_class: Option<ClassIdentifier>,
+    /// Relative URLs of the jars referenced by the `Class-Path` attribute
+    pub class_path: Vec<String>,
 }
 
 impl Manifest {
@@ -38,16 +40,117 @@ impl Manifest {
         let mut r = archive.by_name("META-INF/MANIFEST.MF")?;
         let mut contents = String::new();
         r.read_to_string(&mut contents)?;
+        Self::parse(&contents)
+    }
 
+    /// Parses the main section of a manifest, lines longer than 72 bytes are continued on the
+    /// following lines starting with a single space
+    fn parse(contents: &str) -> Result<Self> {
+        let mut attributes: Vec<String> = Vec::new();
         for line in contents.lines() {
-            let parts: Vec<&str> = line.split(' ').collect();
-            if parts[0] == "Main-Class:" {
-                return Ok(Self {
-                    main_class: ClassIdentifier::parse(parts[1])?,
-                });
+            if line.is_empty() {
+                break;
+            }
+
+            match (line.strip_prefix(' '), attributes.last_mut()) {
+                (Some(continuation), Some(attribute)) => attribute.push_str(continuation),
+                (Some(_), None) => bail!("MANIFEST.MF starts with a continuation line"),
+                (None, _) => attributes.push(line.to_string()),
             }
         }
 
-        bail!("unable to parse MANIFEST.MF")
+        let mut manifest = Self {
+            main_class: None,
+            class_path: Vec::new(),
+        };
+        for attribute in attributes {
+            let Some((name, value)) = attribute.split_once(':') else {
+                bail!("invalid MANIFEST.MF attribute '{attribute}', expected 'name: value'")
+            };
+
+            // attribute names are case-insensitive
+            let (name, value) = (name.trim().to_ascii_lowercase(), value.trim());
+            match name.as_str() {
+                "main-class" if value.is_empty() => bail!("MANIFEST.MF has an empty Main-Class"),
+                "main-class" => manifest.main_class = Some(ClassIdentifier::new(value)?),
+                "class-path" => {
+                    manifest.class_path = value.split_whitespace().map(String::from).collect()
+                }
+                _ => {}
+            }
+        }
+
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn main_class() {
+        let manifest = Manifest::parse(
+            "Manifest-Version: 1.0\r\nCreated-By: 17 (Oracle Corporation)\r\nMain-Class: org.example.Main\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.main_class,
+            Some(ClassIdentifier::new("org.example.Main").unwrap())
+        );
+        assert!(manifest.class_path.is_empty());
+    }
+
+    #[test]
+    fn folded_class_path() {
+        let manifest = Manifest::parse(
+            "Manifest-Version: 1.0\nClass-Path: lib/spring-core-6.1.0.jar lib/spring-beans-6.1.0.j\n ar lib/spring-context-6.1.0.jar\nMain-Class: Main\n",
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.class_path,
+            [
+                "lib/spring-core-6.1.0.jar",
+                "lib/spring-beans-6.1.0.jar",
+                "lib/spring-context-6.1.0.jar"
+            ]
+        );
+        assert_eq!(
+            manifest.main_class,
+            Some(ClassIdentifier::new("Main").unwrap())
+        );
+    }
+
+    #[test]
+    fn missing_main_class() {
+        let manifest = Manifest::parse("Manifest-Version: 1.0\nClass-Path: a.jar\n").unwrap();
+        assert_eq!(manifest.main_class, None);
+        assert_eq!(manifest.class_path, ["a.jar"]);
+    }
+
+    #[test]
+    fn extra_whitespace() {
+        let manifest =
+            Manifest::parse("Manifest-Version: 1.0\r\nmain-class:\t org.example.Main  \r\n")
+                .unwrap();
+        assert_eq!(
+            manifest.main_class,
+            Some(ClassIdentifier::new("org.example.Main").unwrap())
+        );
+    }
+
+    #[test]
+    fn malformed_lines() {
+        assert!(Manifest::parse("Main-Class\n").is_err());
+        assert!(Manifest::parse("Main-Class:\n").is_err());
+        assert!(Manifest::parse(" Main-Class: Main\n").is_err());
+    }
+
+    #[test]
+    fn only_main_section() {
+        let manifest =
+            Manifest::parse("Manifest-Version: 1.0\n\nName: Main.class\nMain-Class: Main\n")
+                .unwrap();
+        assert_eq!(manifest.main_class, None);
     }
 }
@@ -1,24 +1,159 @@
-use std::collections::HashMap;
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
 
-use crate::loader::ReadClass;
+use crate::{directory::DirectoryClassSource, loader::ReadClass};
 use anyhow::{Context, Result};
+use jdk::Classes;
+use zip::ZipArchive;
 
+/// Classes of the JDK, from the cache built into the binary or from a JDK read at runtime
 pub struct Jdk {
-    classes: HashMap<String, Vec<u8>>,
+    source: Source,
+}
+
+enum Source {
+    Cache(Classes),
+    /// `java.base.jmod`, a zip with the class files in its `classes` directory
+    Jmod(ZipArchive<File>),
+    /// Extracted classes of `java.base`
+    Directory(DirectoryClassSource),
 }
 
 impl Jdk {
+    /// Classes cached by the build script
     pub fn new() -> Result<Self> {
-        let classes = jdk::classes()?;
-        Ok(Self { classes })
+        let classes = Classes::new()?;
+        Ok(Self {
+            source: Source::Cache(classes),
+        })
+    }
+
+    /// Reads the classes from the `java.base.jmod` at `path`
+    pub fn from_jmod(path: &Path) -> Result<Self> {
+        let archive = ZipArchive::new(File::open(path)?)?;
+        Ok(Self {
+            source: Source::Jmod(archive),
+        })
+    }
+
+    /// Reads the classes from the directory `path` containing the extracted classes of
+    /// `java.base`, with packages as subdirectories
+    pub fn from_dir(path: impl Into<PathBuf>) -> Self {
+        Self {
+            source: Source::Directory(DirectoryClassSource::new(path)),
+        }
+    }
+
+    /// [`Self::from_dir`] if `path` is a directory, [`Self::from_jmod`] otherwise
+    pub fn from_path(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            Ok(Self::from_dir(path))
+        } else {
+            Self::from_jmod(path)
+        }
     }
 }
 
 impl ReadClass for Jdk {
     fn read_class(&mut self, identifier: &crate::ClassIdentifier) -> Result<Vec<u8>> {
-        self.classes
-            .get(&identifier.path()?)
-            .context("class not found")
-            .cloned()
+        match &mut self.source {
+            Source::Cache(classes) => classes.get(&identifier.path()?).context("class not found"),
+            Source::Jmod(archive) => {
+                let mut r = archive.by_name(&format!("classes/{}", identifier.path()?))?;
+                let mut contents = Vec::new();
+                r.read_to_end(&mut contents)?;
+                Ok(contents)
+            }
+            Source::Directory(directory) => directory.read_class(identifier),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, io::Cursor};
+
+    use common::ClassIdentifier;
+    use parser::class::{ClassFile, access_flags::AccessFlag, attribute::Attribute};
+
+    use super::*;
+
+    #[test]
+    fn module_info() {
+        // the cache is empty if no JDK was found while building
+        let mut classes = Classes::new().unwrap();
+        if classes.is_empty() {
+            return;
+        }
+
+        let bytes = classes.get("module-info.class").unwrap();
+        let class = ClassFile::new(&mut Cursor::new(bytes)).unwrap();
+        let cp = &class.constant_pool;
+        assert!(class.access_flags.contains(&AccessFlag::Module));
+
+        let module = class
+            .attributes
+            .iter()
+            .find_map(|attr| match attr {
+                Attribute::Module { module, .. } => Some(module),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            cp.module_name(&module.module_name_index).unwrap(),
+            "java.base"
+        );
+        assert!(module.requires.is_empty());
+        assert!(module.exports.iter().any(
+            |export| cp.package_name(&export.package_index).unwrap() == "java/lang"
+                && export.to.is_empty()
+        ));
+        assert!(!module.uses.is_empty());
+        assert!(!module.provides.is_empty());
+
+        let packages = class
+            .attributes
+            .iter()
+            .find_map(|attr| match attr {
+                Attribute::ModulePackages { packages, .. } => Some(packages),
+                _ => None,
+            })
+            .unwrap();
+        let packages: Vec<&str> = packages
+            .iter()
+            .map(|package| cp.package_name(package).unwrap())
+            .collect();
+        assert!(packages.contains(&"java/lang"));
+        assert!(packages.contains(&"jdk/internal/misc"));
+    }
+
+    #[test]
+    fn live_jdk() {
+        // set if a JDK was found while building
+        let Some(java_base) = jdk::JAVA_BASE else {
+            return;
+        };
+
+        let object = ClassIdentifier::from_parts("java.lang", "Object");
+        let missing = ClassIdentifier::from_parts("java.lang", "Missing");
+        let mut jmod = Jdk::from_jmod(Path::new(java_base)).unwrap();
+        let bytes = jmod.read_class(&object).unwrap();
+        assert!(jmod.read_class(&missing).is_err());
+        let class = ClassFile::new(&mut Cursor::new(&bytes)).unwrap();
+        assert_eq!(
+            class.constant_pool.class_name(&class.this_class).unwrap(),
+            "java/lang/Object"
+        );
+
+        let root = std::env::temp_dir().join(format!("atria-java-base-{}", std::process::id()));
+        fs::create_dir_all(root.join("java/lang")).unwrap();
+        fs::write(root.join("java/lang/Object.class"), &bytes).unwrap();
+        let mut directory = Jdk::from_path(&root).unwrap();
+        assert_eq!(directory.read_class(&object).unwrap(), bytes);
+        assert!(directory.read_class(&missing).is_err());
+        fs::remove_dir_all(&root).unwrap();
     }
 }
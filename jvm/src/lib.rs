@@ -1,44 +1,221 @@
 use std::fs::File;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use anyhow::{Result, anyhow, bail};
+use anyhow::{Context, Result, anyhow};
 use common::ClassIdentifier;
 use zip::ZipArchive;
 
-use crate::thread::JvmThread;
+use crate::thread::{JvmThread, OutputSink};
 use crate::{
+    directory::DirectoryClassSource,
     jar::Jar,
     jdk::Jdk,
     loader::{BootstrapClassLoader, ReadClass},
 };
 
+mod directory;
 mod jar;
 mod jdk;
 mod loader;
 pub mod thread;
 
+/// Settings of the JVM running a program, the defaults match the reference implementation's
+#[derive(Default)]
+pub struct Options {
+    /// `java.base.jmod` or a directory with its extracted classes to read the JDK's classes from,
+    /// instead of the cache built into the binary
+    pub jdk: Option<PathBuf>,
+    /// Verifies the code of every linked class against its stack map frames
+    pub verify_classes: bool,
+    /// Number of items the heap can hold before allocations throw an `OutOfMemoryError`
+    pub max_heap_items: Option<usize>,
+    /// Number of frames of each thread's stack before invocations throw a `StackOverflowError`
+    pub max_stack_frames: Option<usize>,
+    /// Receives what the program writes to stdout and stderr instead of the host's streams
+    pub output: Option<Box<dyn OutputSink>>,
+}
+
 pub fn run_jar(file: File) -> Result<()> {
-    let archive = ZipArchive::new(file)?;
-    let mut jar = Jar::new(archive);
-    let main_class = jar.manifest()?.main_class;
-    let sources: Vec<Box<dyn ReadClass>> = vec![Box::new(jar), Box::new(Jdk::new()?)];
-    let class_loader = Arc::new(Mutex::new(BootstrapClassLoader::new(sources)));
-    let main_thread = JvmThread::default("main".to_string(), class_loader);
-
-    let main_handle = JvmThread::run_with_class(main_thread, main_class);
+    run(
+        Jar::new(ZipArchive::new(file)?),
+        None,
+        Vec::new(),
+        Vec::new(),
+        Options::default(),
+    )
+}
+
+/// Runs the `Main-Class` of `main_jar`, loading the classes it depends on from the `extra` jars
+pub fn run_jars(main_jar: File, extra: Vec<File>) -> Result<()> {
+    let class_path = extra
+        .into_iter()
+        .map(|file| Ok(Box::new(Jar::new(ZipArchive::new(file)?)) as Box<dyn ReadClass>))
+        .collect::<Result<_>>()?;
+    run(
+        Jar::new(ZipArchive::new(main_jar)?),
+        None,
+        class_path,
+        Vec::new(),
+        Options::default(),
+    )
+}
+
+/// Runs `main_class`, or the `Main-Class` of the jar's manifest if it's `None`, with `args`.
+/// Classes missing from the jar are searched in the jars of its manifest's `Class-Path`, which
+/// are relative to the jar, and then in the directories and jars of `class_path`.
+pub fn run_jar_with_args(
+    jar: &Path,
+    main_class: Option<&str>,
+    class_path: &[PathBuf],
+    args: Vec<String>,
+    options: Options,
+) -> Result<()> {
+    let directory = jar.parent().unwrap_or(Path::new(""));
+    let mut jar = Jar::new(ZipArchive::new(File::open(jar)?)?);
+    let manifest_class_path = jar
+        .manifest()
+        .map(|manifest| manifest.class_path)
+        .unwrap_or_default();
+
+    let mut sources = Vec::new();
+    for entry in manifest_class_path {
+        let entry = directory.join(entry);
+        // like in the reference implementation, missing entries are ignored
+        if entry.exists() {
+            sources.push(class_path_source(&entry)?);
+        }
+    }
+    for entry in class_path {
+        sources.push(class_path_source(entry)?);
+    }
+
+    run(jar, main_class, sources, args, options)
+}
+
+fn run(
+    mut jar: Jar,
+    main_class: Option<&str>,
+    class_path: Vec<Box<dyn ReadClass>>,
+    args: Vec<String>,
+    options: Options,
+) -> Result<()> {
+    let main_class = match main_class {
+        Some(main_class) => ClassIdentifier::new(main_class)?,
+        None => jar
+            .manifest()?
+            .main_class
+            .context("no main class given and the jar's manifest has no Main-Class")?,
+    };
+    let jdk = match &options.jdk {
+        Some(jdk) => Jdk::from_path(jdk)?,
+        None => Jdk::new()?,
+    };
+    let class_loader = Arc::new(Mutex::new(class_loader(jar, class_path, jdk)));
+    let mut main_thread = JvmThread::default("main".to_string(), class_loader);
+    if options.verify_classes {
+        main_thread.verify_classes();
+    }
+    if let Some(max_frames) = options.max_stack_frames {
+        main_thread.set_max_stack_frames(max_frames);
+    }
+    if let Some(output) = options.output {
+        main_thread.set_output_sink(output)?;
+    }
+    main_thread.set_max_heap_items(options.max_heap_items)?;
+    // waits for the non-daemon threads once main has returned, like `DestroyJavaVM` of HotSpot
+    let destroy_thread = main_thread.new_thread("DestroyJavaVM".to_string());
+
+    let main_handle = JvmThread::run_with_class(main_thread, main_class, args)?;
     main_handle
         .join()
         .map_err(|err| anyhow!("thread error: {err:?}"))??;
-    bail!("TODO: After main thread exits")
+    destroy_thread.join_threads()
+}
+
+/// Loader searching `jar`, then the entries of `class_path` in order and finally `jdk`.
+/// A class is loaded from the first source containing it.
+fn class_loader(jar: Jar, class_path: Vec<Box<dyn ReadClass>>, jdk: Jdk) -> BootstrapClassLoader {
+    let mut sources: Vec<Box<dyn ReadClass>> = vec![Box::new(jar)];
+    sources.extend(class_path);
+    sources.push(Box::new(jdk));
+    BootstrapClassLoader::new(sources)
+}
+
+/// Class source for a class path entry, which is either a directory or a jar
+fn class_path_source(entry: &Path) -> Result<Box<dyn ReadClass>> {
+    if entry.is_dir() {
+        Ok(Box::new(DirectoryClassSource::new(entry)))
+    } else {
+        Ok(Box::new(Jar::new(ZipArchive::new(File::open(entry)?)?)))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::fs::File;
+    use std::io::Write;
 
     use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
+    use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
 
     use super::*;
+    use crate::thread::VecOutputSink;
+
+    #[test]
+    fn run_jar_hello_world() {
+        let file = File::open("testdata/Hello.jar").unwrap();
+        run_jar(file).unwrap();
+    }
+
+    #[test]
+    fn run_with_options() {
+        let output = VecOutputSink::default();
+        let options = Options {
+            verify_classes: true,
+            max_heap_items: Some(1_000_000),
+            max_stack_frames: Some(512),
+            output: Some(Box::new(output.clone())),
+            ..Options::default()
+        };
+        run_jar_with_args(Path::new("testdata/Hello.jar"), None, &[], vec![], options).unwrap();
+        assert_eq!(output.bytes(1), b"Hello, World!\n");
+    }
+
+    /// Writes a jar to the temp directory containing the `classes` compiled in `testdata`
+    fn jar_of(name: &str, classes: &[&str]) -> Jar {
+        let path = std::env::temp_dir().join(format!("atria-{}-{name}.jar", std::process::id()));
+        let mut writer = ZipWriter::new(File::create(&path).unwrap());
+        for class in classes {
+            let class_file = format!("{class}.class");
+            let options =
+                SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+            writer.start_file(class_file.as_str(), options).unwrap();
+            writer
+                .write_all(&std::fs::read(format!("testdata/{class_file}")).unwrap())
+                .unwrap();
+        }
+        writer.finish().unwrap();
+
+        let jar = Jar::new(ZipArchive::new(File::open(&path).unwrap()).unwrap());
+        std::fs::remove_file(path).unwrap();
+        jar
+    }
+
+    #[test]
+    fn super_class_in_other_jar() {
+        let child = ClassIdentifier::new("JarChild").unwrap();
+
+        let mut loader = class_loader(jar_of("alone", &["JarChild"]), vec![], Jdk::new().unwrap());
+        assert!(loader.load(&child).is_err());
+
+        let extra: Vec<Box<dyn ReadClass>> = vec![Box::new(jar_of("parent", &["JarParent"]))];
+        let mut loader = class_loader(jar_of("child", &["JarChild"]), extra, Jdk::new().unwrap());
+        loader.load(&child).unwrap();
+        loader
+            .load(&ClassIdentifier::new("JarParent").unwrap())
+            .unwrap();
+    }
 
     #[test]
     fn system() {
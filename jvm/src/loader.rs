@@ -37,6 +37,7 @@ impl BootstrapClassLoader {
 
             let class_file = parser::parse(&mut Cursor::new(class_bytes))
                 .context("should throw ClassFormatError")?;
+            class_file.constant_pool.validate()?;
 
             Self::check_version(&class_file)?;
             Self::check_name(&class_file, identifier)?;
@@ -45,7 +46,7 @@ impl BootstrapClassLoader {
                 let name = class_file
                     .constant_pool
                     .class_name(&class_file.super_class)?;
-                let identifier = ClassIdentifier::parse(name)?;
+                let identifier = ClassIdentifier::new(name)?;
                 self.load(&identifier)?;
             }
 
@@ -58,6 +59,12 @@ impl BootstrapClassLoader {
         bail!("class {identifier:?} not found")
     }
 
+    /// Makes `class_file`, which wasn't read from any source, loadable as `identifier`
+    pub fn define(&mut self, identifier: ClassIdentifier, class_file: ClassFile) {
+        trace!("defined {identifier}");
+        self.class_files.insert(identifier, class_file);
+    }
+
     fn check_version(class_file: &ClassFile) -> Result<()> {
         if class_file.major_version != 61 && class_file.minor_version != 0 {
             bail!(
@@ -74,7 +81,7 @@ impl BootstrapClassLoader {
         let name = class_file
             .constant_pool
             .class_name(&class_file.this_class)?;
-        if ClassIdentifier::parse(name)? != *identifier {
+        if identifier.with_slashes()? != name {
             bail!(
                 "identifier does not match class file, {identifier} vs {name}, (TODO: throw NoClassDefFoundError)"
             )
@@ -87,3 +94,45 @@ impl BootstrapClassLoader {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::jdk::Jdk;
+
+    use super::*;
+
+    /// Source of the single class `Loop`
+    struct Bytes(Vec<u8>);
+
+    impl ReadClass for Bytes {
+        fn read_class(&mut self, identifier: &ClassIdentifier) -> Result<Vec<u8>> {
+            if identifier.name != "Loop" {
+                bail!("class {identifier:?} not found");
+            }
+            Ok(self.0.clone())
+        }
+    }
+
+    fn loader(class: Vec<u8>) -> BootstrapClassLoader {
+        BootstrapClassLoader::new(vec![Box::new(Bytes(class)), Box::new(Jdk::new().unwrap())])
+    }
+
+    #[test]
+    fn corrupted_constant_pool() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/Loop.class");
+        let mut class = std::fs::read(path).unwrap();
+        let identifier = ClassIdentifier::new("Loop").unwrap();
+        loader(class.clone()).load(&identifier).unwrap();
+
+        // the first item, the method reference to Object.<init>, is followed by the class it
+        // belongs to, point the class index to the method reference itself instead
+        assert_eq!(class[10], 10);
+        class[11..13].copy_from_slice(&1u16.to_be_bytes());
+        let Err(err) = loader(class).load(&identifier) else {
+            panic!("corrupted class was loaded");
+        };
+        assert!(err.to_string().contains("ClassFormatError"), "{err}");
+    }
+}
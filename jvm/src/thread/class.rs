@@ -1,25 +1,58 @@
 use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::sync::{Arc, Mutex};
 
-use anyhow::{Context, Result};
-use common::{ClassIdentifier, FieldValue};
+use anyhow::{Context, Result, anyhow, bail};
+use common::{ClassIdentifier, FieldValue, HeapId, ThreadId};
 use parser::class::{
     ClassFile,
     access_flags::AccessFlag,
+    attribute::BootStrapMethod,
     constant_pool::{CpIndex, CpInfo},
-    descriptor::{FieldDescriptor, MethodDescriptor},
+    descriptor::MethodDescriptor,
     field::Field,
     method::Method,
 };
 use tracing::trace;
 
+use crate::thread::stack::code::Code;
+
+type MethodCodes = HashMap<(u16, u16), Arc<Code>>;
+
+/// Initialization state of a linked class, as described in JLS 12.4.2
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitializationState {
+    Linked,
+    /// The static initializer is running on the thread, `None` for a thread without an id
+    BeingInitialized(Option<ThreadId>),
+    Initialized,
+    /// The static initializer failed, the class can't be used
+    Erroneous,
+}
+
+/// The static initializer of `class` threw, the interpreter throws the pending error at the
+/// instruction which initialized the class
+#[derive(Debug)]
+pub struct InitializerThrew {
+    pub class: ClassIdentifier,
+}
+
+impl Display for InitializerThrew {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "static initializer of {:?} threw", self.class)
+    }
+}
+
+impl std::error::Error for InitializerThrew {}
+
 #[derive(Clone)]
 pub struct Class {
     identifier: ClassIdentifier,
     static_fields: HashMap<String, FieldValue>,
-    class_fields: HashMap<String, FieldValue>,
     class_file: ClassFile,
-    initialized: bool,
-    being_initialized: bool,
+    initialization_state: InitializationState,
+    /// Code of the methods by their name and descriptor index, shared by all clones
+    codes: Arc<Mutex<MethodCodes>>,
 }
 
 impl Class {
@@ -28,36 +61,29 @@ impl Class {
             identifier,
             class_file,
             static_fields: HashMap::default(),
-            class_fields: HashMap::default(),
-            initialized: false,
-            being_initialized: false,
+            initialization_state: InitializationState::Linked,
+            codes: Arc::default(),
         }
     }
 
-    pub fn set_class_field(&mut self, name: String, descriptor: &str) -> Result<()> {
-        self.class_fields
-            .insert(name, FieldDescriptor::new(descriptor)?.into());
-        Ok(())
-    }
-
     pub fn identifier(&self) -> &ClassIdentifier {
         &self.identifier
     }
 
-    pub fn initialized(&self) -> bool {
-        self.initialized
+    pub fn initialization_state(&self) -> &InitializationState {
+        &self.initialization_state
     }
 
-    pub fn finished_initialization(&mut self) {
-        self.initialized = true
+    pub fn initializing(&mut self, thread_id: Option<ThreadId>) {
+        self.initialization_state = InitializationState::BeingInitialized(thread_id);
     }
 
-    pub fn being_initialized(&self) -> bool {
-        self.being_initialized
+    pub fn finished_initialization(&mut self) {
+        self.initialization_state = InitializationState::Initialized;
     }
 
-    pub fn initializing(&mut self) {
-        self.being_initialized = true;
+    pub fn failed_initialization(&mut self) {
+        self.initialization_state = InitializationState::Erroneous;
     }
 
     pub fn has_super_class(&self) -> bool {
@@ -65,7 +91,7 @@ impl Class {
     }
 
     pub fn super_class(&self) -> Result<ClassIdentifier> {
-        ClassIdentifier::parse(
+        ClassIdentifier::new(
             self.class_file
                 .constant_pool
                 .class_name(&self.class_file.super_class)?,
@@ -97,13 +123,21 @@ impl Class {
     }
 
     pub fn class_identifier(&self, index: &CpIndex) -> Result<ClassIdentifier> {
-        ClassIdentifier::parse(self.class_file.constant_pool.class_name(index)?)
+        ClassIdentifier::new(self.class_file.constant_pool.class_name(index)?)
     }
 
     pub fn name_and_type(&self, index: &CpIndex) -> Result<(&str, &str)> {
         self.class_file.constant_pool.name_and_type(index)
     }
 
+    pub fn bootstrap_method(&self, index: u16) -> Result<&BootStrapMethod> {
+        self.class_file.bootstrap_method(index)
+    }
+
+    pub fn source_file(&self) -> Result<Option<&str>> {
+        self.class_file.source_file()
+    }
+
     pub fn is_method_signature_polymorphic(&self, method: &Method) -> Result<bool> {
         self.class_file.is_method_signature_polymorphic(method)
     }
@@ -112,23 +146,47 @@ impl Class {
         MethodDescriptor::new(self.utf8(&method.descriptor_index)?)
     }
 
+    /// Code of `method`, which has to be declared by this class. It's only decoded once.
+    pub fn code(&self, method: &Method) -> Result<Arc<Code>> {
+        let key = (method.name_index.0, method.descriptor_index.0);
+        let mut codes = self
+            .codes
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        if let Some(code) = codes.get(&key) {
+            return Ok(code.clone());
+        }
+
+        if !self.contains_method(method) {
+            bail!("method is not declared in {:?}", self.identifier);
+        }
+        let attribute = method
+            .code()
+            .context(format!("method {} has no code", self.method_name(method)?))?;
+        let code = Arc::new(Code::new(attribute.clone())?);
+        codes.insert(key, code.clone());
+        Ok(code)
+    }
+
     pub fn method_name(&self, method: &Method) -> Result<&str> {
         self.utf8(&method.name_index)
     }
 
+    /// Heap items referenced by static fields
+    pub fn references(&self) -> Vec<HeapId> {
+        self.static_fields
+            .values()
+            .filter_map(|value| value.heap_id().ok())
+            .cloned()
+            .collect()
+    }
+
     pub fn set_static_field(&mut self, name: &str, value: FieldValue) -> Result<()> {
         trace!("setting field {name} to value {value:?}");
         self.static_fields.insert(name.to_string(), value);
         Ok(())
     }
 
-    pub fn get_class_field_value(&self, name: &str) -> Result<FieldValue> {
-        self.class_fields
-            .get(name)
-            .context(format!("field {name} not found in {:?}", self.identifier))
-            .cloned()
-    }
-
     pub fn get_static_field_value(&self, name: &str) -> Result<FieldValue> {
         self.static_fields
             .get(name)
@@ -140,6 +198,10 @@ impl Class {
         &self.class_file.fields
     }
 
+    pub fn methods(&self) -> &Vec<Method> {
+        &self.class_file.methods
+    }
+
     pub fn overriden_method(
         &self,
         m_a: &Method,
@@ -168,6 +230,14 @@ impl Class {
         self.class_file.methods.contains(method)
     }
 
+    /// The `access_flags` item of the class file
+    pub fn access_flags(&self) -> u16 {
+        self.class_file
+            .access_flags
+            .iter()
+            .fold(0, |flags, flag| flags | flag.value())
+    }
+
     pub fn is_interface(&self) -> bool {
         self.class_file
             .access_flags
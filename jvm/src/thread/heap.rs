@@ -1,6 +1,8 @@
 use anyhow::{Result, bail};
 use common::{ClassIdentifier, FieldValue, HeapId, ReferenceValue};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 use anyhow::Context;
 use tracing::debug;
@@ -25,13 +27,19 @@ impl InstanceField {
 pub struct Object {
     class_identifier: ClassIdentifier,
     fields: HashMap<String, InstanceField>,
+    identity_hash: i32,
 }
 
 impl Object {
-    fn new(class_identifier: ClassIdentifier, fields: HashMap<String, InstanceField>) -> Self {
+    fn new(
+        class_identifier: ClassIdentifier,
+        fields: HashMap<String, InstanceField>,
+        identity_hash: i32,
+    ) -> Self {
         Self {
             class_identifier,
             fields,
+            identity_hash,
         }
     }
 
@@ -43,8 +51,16 @@ impl Object {
 #[derive(Debug, Clone)]
 pub enum HeapItem {
     Object(Object),
+    /// `java.lang.Class` object of a loaded class, there is exactly one per class
+    ClassMirror {
+        /// Class represented by the mirror
+        class: ClassIdentifier,
+        /// Instance of `java.lang.Class` holding the fields of the mirror
+        object: Object,
+    },
     ReferenceArray {
         object_id: HeapId,
+        /// Class of the components
         class: ClassIdentifier,
         values: Vec<ReferenceValue>,
     },
@@ -54,29 +70,83 @@ pub enum HeapItem {
 impl HeapItem {
     pub fn is_array(&self) -> bool {
         match self {
-            Self::Object(_) => false,
+            Self::Object(_) | Self::ClassMirror { .. } => false,
             Self::ReferenceArray { .. } | Self::PrimitiveArray(_, _) => true,
         }
     }
 
     pub fn class_identifier(&self) -> Result<ClassIdentifier> {
         Ok(match self {
-            HeapItem::Object(object) => object.class_identifier.clone(),
-            HeapItem::ReferenceArray { class, .. } => class.clone(),
-            HeapItem::PrimitiveArray(array_type, _) => array_type.class_identifier(),
+            HeapItem::Object(object) | HeapItem::ClassMirror { object, .. } => {
+                object.class_identifier.clone()
+            }
+            HeapItem::ReferenceArray { class, .. } => ClassIdentifier::array(class, 1),
+            HeapItem::PrimitiveArray(array_type, _) => {
+                ClassIdentifier::array(&array_type.class_identifier()?, 1)
+            }
         })
     }
 
     pub fn object(&self) -> Result<&Object> {
-        if let Self::Object(object) = self {
-            Ok(object)
+        match self {
+            Self::Object(object) | Self::ClassMirror { object, .. } => Ok(object),
+            _ => bail!("heap item is not a object, is {self:?}"),
+        }
+    }
+
+    /// Class represented by a `java.lang.Class` object
+    pub fn mirrored_class(&self) -> Result<&ClassIdentifier> {
+        if let Self::ClassMirror { class, .. } = self {
+            Ok(class)
         } else {
-            bail!("heap item is not a object, is {self:?}")
+            bail!("heap item is not a class mirror, is {self:?}")
+        }
+    }
+
+    /// Summary of this item, which is stored at `id`
+    pub fn summary(&self, id: &HeapId) -> Result<HeapSummary> {
+        let (fields, length) = match self {
+            Self::Object(object) | Self::ClassMirror { object, .. } => {
+                let mut fields: Vec<_> = object
+                    .fields
+                    .iter()
+                    .map(|(name, field)| (name.clone(), field.value.clone()))
+                    .collect();
+                fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+                (fields, None)
+            }
+            Self::ReferenceArray { values, .. } => (Vec::new(), Some(values.len())),
+            Self::PrimitiveArray(_, values) => (Vec::new(), Some(values.len())),
+        };
+
+        Ok(HeapSummary {
+            id: id.clone(),
+            class: self.class_identifier()?,
+            fields,
+            length,
+        })
+    }
+
+    fn object_mut(&mut self) -> Option<&mut Object> {
+        match self {
+            Self::Object(object) | Self::ClassMirror { object, .. } => Some(object),
+            _ => None,
         }
     }
 }
 
+/// Description of a heap item for debugging programs under the interpreter
 #[derive(Debug, Clone)]
+pub struct HeapSummary {
+    pub id: HeapId,
+    pub class: ClassIdentifier,
+    /// Instance fields of objects by name, empty for arrays
+    pub fields: Vec<(String, FieldValue)>,
+    /// Number of elements of arrays, `None` for objects
+    pub length: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum PrimitiveArrayType {
     Boolean,
     Char,
@@ -103,6 +173,21 @@ impl PrimitiveArrayType {
         })
     }
 
+    /// Type of the primitive `name`, e.g. `int`
+    pub fn from_name(name: &str) -> Result<Self> {
+        Ok(match name {
+            "boolean" => Self::Boolean,
+            "char" => Self::Char,
+            "float" => Self::Float,
+            "double" => Self::Double,
+            "byte" => Self::Byte,
+            "short" => Self::Short,
+            "int" => Self::Int,
+            "long" => Self::Long,
+            _ => bail!("no primitive array type for {name}"),
+        })
+    }
+
     pub fn default(&self) -> PrimitiveArrayValue {
         match self {
             Self::Boolean => PrimitiveArrayValue::Boolean(false),
@@ -116,34 +201,37 @@ impl PrimitiveArrayType {
         }
     }
 
-    pub fn class_identifier(&self) -> ClassIdentifier {
+    pub fn descriptor(&self) -> &str {
         match self {
-            PrimitiveArrayType::Boolean => {
-                ClassIdentifier::new("java.lang".to_owned(), "Boolean".to_owned())
-            }
-            PrimitiveArrayType::Char => {
-                ClassIdentifier::new("java.lang".to_owned(), "Character".to_owned())
-            }
-            PrimitiveArrayType::Float => {
-                ClassIdentifier::new("java.lang".to_owned(), "Float".to_owned())
-            }
-            PrimitiveArrayType::Double => {
-                ClassIdentifier::new("java.lang".to_owned(), "Double".to_owned())
-            }
-            PrimitiveArrayType::Byte => {
-                ClassIdentifier::new("java.lang".to_owned(), "Byte".to_owned())
-            }
-            PrimitiveArrayType::Short => {
-                ClassIdentifier::new("java.lang".to_owned(), "Short".to_owned())
-            }
-            PrimitiveArrayType::Int => {
-                ClassIdentifier::new("java.lang".to_owned(), "Integer".to_owned())
-            }
-            PrimitiveArrayType::Long => {
-                ClassIdentifier::new("java.lang".to_owned(), "Long".to_owned())
-            }
+            Self::Boolean => "Z",
+            Self::Char => "C",
+            Self::Float => "F",
+            Self::Double => "D",
+            Self::Byte => "B",
+            Self::Short => "S",
+            Self::Int => "I",
+            Self::Long => "J",
+        }
+    }
+
+    /// Name of the primitive component type, e.g. `int`
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::Char => "char",
+            Self::Float => "float",
+            Self::Double => "double",
+            Self::Byte => "byte",
+            Self::Short => "short",
+            Self::Int => "int",
+            Self::Long => "long",
         }
     }
+
+    /// Primitive class of the components, not their wrapper class
+    pub fn class_identifier(&self) -> Result<ClassIdentifier> {
+        ClassIdentifier::primitive(self.name())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -168,10 +256,36 @@ impl PrimitiveArrayValue {
     }
 }
 
+/// Access of an array element outside of the bounds of the array, the interpreter throws it as
+/// `java.lang.ArrayIndexOutOfBoundsException`
+#[derive(Debug)]
+pub struct ArrayIndexOutOfBounds {
+    pub index: i64,
+    pub length: usize,
+}
+
+impl Display for ArrayIndexOutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Index {} out of bounds for length {}",
+            self.index, self.length
+        )
+    }
+}
+
+impl std::error::Error for ArrayIndexOutOfBounds {}
+
 #[derive(Default)]
 pub struct Heap {
     current_id: u64,
     items: HashMap<HeapId, HeapItem>,
+    /// Number of allocations after which a collection is due, never collects if `None`
+    gc_threshold: Option<usize>,
+    allocations: usize,
+    /// Number of items the heap can hold, unbounded if `None`
+    max_items: Option<usize>,
+    identity_hash_state: u32,
 }
 
 impl Heap {
@@ -180,29 +294,53 @@ impl Heap {
         class_identifier: ClassIdentifier,
         fields: HashMap<String, InstanceField>,
     ) -> HeapId {
-        let object = Object::new(class_identifier, fields);
-        let heap_item = HeapItem::Object(object);
-        let id: HeapId = self.current_id.into();
-        self.items.insert(id.clone(), heap_item.clone());
-        self.current_id += 1;
-
-        debug!("allocated {heap_item:?} with id {id:?}");
+        let identity_hash = self.next_identity_hash();
+        let object = Object::new(class_identifier, fields, identity_hash);
+        self.insert(HeapItem::Object(object))
+    }
 
-        id
+    /// Allocates the `java.lang.Class` object of `class` with the instance `fields` of
+    /// `java.lang.Class`
+    pub fn allocate_class_mirror(
+        &mut self,
+        class: ClassIdentifier,
+        fields: HashMap<String, InstanceField>,
+    ) -> HeapId {
+        let identity_hash = self.next_identity_hash();
+        let object = Object::new(
+            ClassIdentifier::from_parts("java.lang", "Class"),
+            fields,
+            identity_hash,
+        );
+        self.insert(HeapItem::ClassMirror { class, object })
     }
 
     pub fn allocate_array(&mut self, class: ClassIdentifier, length: usize) -> HeapId {
-        let heap_item = HeapItem::ReferenceArray {
+        self.insert(HeapItem::ReferenceArray {
             object_id: self.current_id.into(),
             class,
             values: vec![ReferenceValue::Null; length],
-        };
-        let id: HeapId = self.current_id.into();
-        self.items.insert(id.clone(), heap_item.clone());
-        self.current_id += 1;
+        })
+    }
 
-        debug!("allocated {heap_item:?} with id {id:?}");
-        id
+    /// Allocates a shallow copy of the object or array at `id`, as `Object.clone` does
+    pub fn clone_item(&mut self, id: &HeapId) -> Result<HeapId> {
+        let item = match self.get(id)?.clone() {
+            HeapItem::Object(object) => HeapItem::Object(Object {
+                identity_hash: self.next_identity_hash(),
+                ..object
+            }),
+            HeapItem::ReferenceArray { class, values, .. } => HeapItem::ReferenceArray {
+                object_id: self.current_id.into(),
+                class,
+                values,
+            },
+            HeapItem::PrimitiveArray(array_type, values) => {
+                HeapItem::PrimitiveArray(array_type, values)
+            }
+            HeapItem::ClassMirror { class, .. } => bail!("mirror of {class:?} can't be cloned"),
+        };
+        Ok(self.insert(item))
     }
 
     pub fn allocate_primitive_array(
@@ -210,15 +348,104 @@ impl Heap {
         array_type: PrimitiveArrayType,
         values: Vec<PrimitiveArrayValue>,
     ) -> HeapId {
-        let heap_item = HeapItem::PrimitiveArray(array_type, values);
-        let id: HeapId = self.current_id.into();
-        self.items.insert(id.clone(), heap_item.clone());
-        self.current_id += 1;
+        self.insert(HeapItem::PrimitiveArray(array_type, values))
+    }
 
+    /// Ids are never reused, a stale id of a collected item can't alias a newer item
+    fn insert(&mut self, heap_item: HeapItem) -> HeapId {
+        let id: HeapId = self.current_id.into();
         debug!("allocated {heap_item:?} with id {id:?}");
+
+        self.items.insert(id.clone(), heap_item);
+        self.current_id += 1;
+        self.allocations += 1;
         id
     }
 
+    /// Marsaglia's xor-shift, like HotSpot, yields positive 31 bit values
+    fn next_identity_hash(&mut self) -> i32 {
+        let mut x = match self.identity_hash_state {
+            0 => 0x9e37_79b9,
+            state => state,
+        };
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.identity_hash_state = x;
+        (x >> 1) as i32
+    }
+
+    /// Objects keep the hash assigned on allocation, arrays hash their id as ids are never reused
+    pub fn identity_hash(&self, id: &HeapId) -> Result<i32> {
+        match self.get(id)? {
+            HeapItem::Object(object) | HeapItem::ClassMirror { object, .. } => {
+                Ok(object.identity_hash)
+            }
+            HeapItem::ReferenceArray { .. } | HeapItem::PrimitiveArray(_, _) => {
+                let mut hasher = DefaultHasher::new();
+                id.hash(&mut hasher);
+                Ok((hasher.finish() >> 33) as i32)
+            }
+        }
+    }
+
+    pub fn set_gc_threshold(&mut self, threshold: Option<usize>) {
+        self.gc_threshold = threshold;
+    }
+
+    pub fn should_collect(&self) -> bool {
+        self.gc_threshold
+            .is_some_and(|threshold| self.allocations >= threshold)
+    }
+
+    pub fn set_max_items(&mut self, max_items: Option<usize>) {
+        self.max_items = max_items;
+    }
+
+    /// Whether the heap holds as many items as it may
+    pub fn is_full(&self) -> bool {
+        self.max_items
+            .is_some_and(|max_items| self.items.len() >= max_items)
+    }
+
+    /// Mark-sweep collection, removes every item which isn't reachable from `roots`.
+    /// Returns the number of removed items.
+    pub fn collect(&mut self, roots: &[HeapId]) -> usize {
+        let mut marked = HashSet::new();
+        let mut pending = roots.to_vec();
+        while let Some(id) = pending.pop() {
+            if !marked.insert(id.clone()) {
+                continue;
+            }
+
+            match self.items.get(&id) {
+                Some(HeapItem::Object(object) | HeapItem::ClassMirror { object, .. }) => pending
+                    .extend(
+                        object
+                            .fields
+                            .values()
+                            .filter_map(|field| field.value.heap_id().ok())
+                            .cloned(),
+                    ),
+                Some(HeapItem::ReferenceArray { values, .. }) => {
+                    pending.extend(values.iter().filter_map(|v| v.heap_id().ok()).cloned())
+                }
+                Some(HeapItem::PrimitiveArray(_, _)) | None => {}
+            }
+        }
+
+        let count = self.items.len();
+        self.items.retain(|id, _| marked.contains(id));
+        self.allocations = 0;
+
+        let collected = count - self.items.len();
+        debug!(
+            "collected {collected} heap items, {} remaining",
+            self.items.len()
+        );
+        collected
+    }
+
     pub fn allocate_default_primitive_array(
         &mut self,
         array_type: PrimitiveArrayType,
@@ -233,7 +460,7 @@ impl Heap {
             .items
             .get_mut(object_id)
             .context(format!("unknown object with {object_id:?}"))?;
-        if let HeapItem::Object(object) = item {
+        if let Some(object) = item.object_mut() {
             let field = object
                 .fields
                 .get_mut(name)
@@ -252,7 +479,7 @@ impl Heap {
             .context(format!("unknown object with {id:?}"))?;
 
         match item {
-            HeapItem::Object(object) => object
+            HeapItem::Object(object) | HeapItem::ClassMirror { object, .. } => object
                 .fields
                 .get(name)
                 .context("no field with name '{name}' found")
@@ -261,6 +488,17 @@ impl Heap {
         }
     }
 
+    /// Summaries of all items on the heap, in the order they were allocated
+    pub fn summaries(&self) -> Result<Vec<HeapSummary>> {
+        let mut summaries = self
+            .items
+            .iter()
+            .map(|(id, item)| item.summary(id))
+            .collect::<Result<Vec<_>>>()?;
+        summaries.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(summaries)
+    }
+
     pub fn get(&self, id: &HeapId) -> Result<&HeapItem> {
         self.items.get(id).context("no heap item at id {id}")
     }
@@ -277,7 +515,13 @@ impl Heap {
             .context(format!("unknown object with {id:?}"))?;
 
         match arr {
-            HeapItem::ReferenceArray { values, .. } => values[index] = value,
+            HeapItem::ReferenceArray { values, .. } => {
+                let length = values.len();
+                *values.get_mut(index).ok_or(ArrayIndexOutOfBounds {
+                    index: index as i64,
+                    length,
+                })? = value
+            }
             _ => bail!("object at {id:?} is not a reference array, is {arr:?}"),
         }
 
@@ -296,8 +540,14 @@ impl Heap {
             .context(format!("unknown object with {id:?}"))?;
 
         match arr {
-            HeapItem::PrimitiveArray(_, values) => values[index] = value,
-            _ => bail!("object at {id:?} is not a reference array, is {arr:?}"),
+            HeapItem::PrimitiveArray(_, values) => {
+                let length = values.len();
+                *values.get_mut(index).ok_or(ArrayIndexOutOfBounds {
+                    index: index as i64,
+                    length,
+                })? = value
+            }
+            _ => bail!("object at {id:?} is not a primitive array, is {arr:?}"),
         }
 
         Ok(())
@@ -343,3 +593,145 @@ impl Heap {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(heap: &mut Heap) -> HeapId {
+        let fields = HashMap::from([(
+            "next".to_owned(),
+            InstanceField::new(0, FieldValue::Reference(ReferenceValue::Null)),
+        )]);
+        heap.allocate(ClassIdentifier::from_parts("", "Node"), fields)
+    }
+
+    fn link(heap: &mut Heap, from: &HeapId, to: &HeapId) {
+        let next = FieldValue::Reference(ReferenceValue::HeapItem(to.clone()));
+        heap.set_field(from, "next", next).unwrap();
+    }
+
+    #[test]
+    fn collect_unreachable_cycle() {
+        let mut heap = Heap::default();
+        let first = node(&mut heap);
+        let second = node(&mut heap);
+        link(&mut heap, &first, &second);
+        link(&mut heap, &second, &first);
+
+        assert_eq!(heap.collect(std::slice::from_ref(&first)), 0);
+        assert_eq!(heap.collect(&[]), 2);
+        assert!(heap.get(&first).is_err());
+        assert!(heap.get(&second).is_err());
+    }
+
+    #[test]
+    fn collect_keeps_reachable_items() {
+        let mut heap = Heap::default();
+        let array = heap.allocate_array(ClassIdentifier::from_parts("", "Node"), 1);
+        let object = node(&mut heap);
+        let garbage = node(&mut heap);
+        heap.store_into_reference_array(&array, 0, ReferenceValue::HeapItem(object.clone()))
+            .unwrap();
+
+        assert_eq!(heap.collect(std::slice::from_ref(&array)), 1);
+        assert!(heap.get(&array).is_ok());
+        assert!(heap.get(&object).is_ok());
+        assert!(heap.get(&garbage).is_err());
+        assert_ne!(node(&mut heap), garbage);
+    }
+
+    #[test]
+    fn store_out_of_bounds() {
+        let mut heap = Heap::default();
+        let array = heap.allocate_array(ClassIdentifier::from_parts("", "Node"), 1);
+        let err = heap
+            .store_into_reference_array(&array, 1, ReferenceValue::Null)
+            .unwrap_err();
+        let err = err.downcast_ref::<ArrayIndexOutOfBounds>().unwrap();
+        assert_eq!(err.to_string(), "Index 1 out of bounds for length 1");
+
+        let array = heap.allocate_default_primitive_array(PrimitiveArrayType::Int, 2);
+        let err = heap
+            .store_into_primitive_array(&array, 2, PrimitiveArrayValue::Int(1))
+            .unwrap_err();
+        assert!(err.downcast_ref::<ArrayIndexOutOfBounds>().is_some());
+    }
+
+    #[test]
+    fn identity_hash() {
+        let mut heap = Heap::default();
+        let first = node(&mut heap);
+        let second = node(&mut heap);
+        let array = heap.allocate_array(ClassIdentifier::from_parts("", "Node"), 1);
+
+        let hash = heap.identity_hash(&first).unwrap();
+        assert!(hash >= 0);
+        assert_eq!(heap.identity_hash(&first).unwrap(), hash);
+        assert_ne!(heap.identity_hash(&second).unwrap(), hash);
+        assert_eq!(
+            heap.identity_hash(&array).unwrap(),
+            heap.identity_hash(&array).unwrap()
+        );
+
+        let cloned = heap.get(&first).unwrap().clone();
+        assert_eq!(cloned.object().unwrap().identity_hash, hash);
+    }
+
+    #[test]
+    fn max_items() {
+        let mut heap = Heap::default();
+        let first = node(&mut heap);
+        assert!(!heap.is_full());
+
+        heap.set_max_items(Some(2));
+        node(&mut heap);
+        assert!(heap.is_full());
+
+        heap.collect(std::slice::from_ref(&first));
+        assert!(!heap.is_full());
+    }
+
+    #[test]
+    fn summaries() {
+        let mut heap = Heap::default();
+        let first = node(&mut heap);
+        let second = node(&mut heap);
+        link(&mut heap, &first, &second);
+        let array = heap.allocate_default_primitive_array(PrimitiveArrayType::Int, 3);
+
+        let summaries = heap.summaries().unwrap();
+        let ids: Vec<_> = summaries.iter().map(|summary| summary.id.clone()).collect();
+        assert_eq!(ids, [first.clone(), second.clone(), array.clone()]);
+
+        let summary = &summaries[0];
+        assert_eq!(summary.class, ClassIdentifier::from_parts("", "Node"));
+        assert_eq!(summary.length, None);
+        let [(name, FieldValue::Reference(ReferenceValue::HeapItem(next)))] =
+            summary.fields.as_slice()
+        else {
+            panic!("unexpected fields {:?}", summary.fields);
+        };
+        assert_eq!(name, "next");
+        assert_eq!(next, &second);
+
+        let summary = heap.get(&array).unwrap().summary(&array).unwrap();
+        assert!(summary.fields.is_empty());
+        assert_eq!(summary.length, Some(3));
+    }
+
+    #[test]
+    fn gc_threshold() {
+        let mut heap = Heap::default();
+        node(&mut heap);
+        assert!(!heap.should_collect());
+
+        heap.set_gc_threshold(Some(2));
+        assert!(!heap.should_collect());
+        node(&mut heap);
+        assert!(heap.should_collect());
+
+        heap.collect(&[]);
+        assert!(!heap.should_collect());
+    }
+}
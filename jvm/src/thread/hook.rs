@@ -0,0 +1,62 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use common::ClassIdentifier;
+
+use crate::thread::Instruction;
+
+/// Observes the execution of a thread
+pub trait ExecutionHook: Send {
+    /// Called with the instruction at `pc` of the current method in `class`, before executing it
+    fn before_instruction(&mut self, class: &ClassIdentifier, pc: usize, instruction: &Instruction);
+}
+
+/// Tallies the executed instructions per mnemonic, clones share their totals
+#[derive(Debug, Default, Clone)]
+pub struct InstructionCounter {
+    counts: Arc<Mutex<HashMap<&'static str, u64>>>,
+}
+
+impl InstructionCounter {
+    pub fn record(&self, instruction: &Instruction) {
+        if let (Ok(mut counts), Ok(mnemonic)) = (self.counts.lock(), instruction.mnemonic()) {
+            *counts.entry(mnemonic).or_default() += 1;
+        }
+    }
+
+    /// Number of executed instructions with `mnemonic`
+    pub fn count(&self, mnemonic: &str) -> u64 {
+        self.counts().get(mnemonic).copied().unwrap_or_default()
+    }
+
+    /// Number of executed instructions by mnemonic
+    pub fn counts(&self) -> HashMap<&'static str, u64> {
+        self.counts
+            .lock()
+            .map(|counts| counts.clone())
+            .unwrap_or_default()
+    }
+
+    /// Number of executed instructions
+    pub fn total(&self) -> u64 {
+        self.counts().values().sum()
+    }
+
+    /// One line per mnemonic with its count, starting with the most executed one
+    pub fn histogram(&self) -> String {
+        let mut counts: Vec<_> = self.counts().into_iter().collect();
+        counts.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then(a_name.cmp(b_name)));
+        counts
+            .iter()
+            .map(|(mnemonic, count)| format!("{mnemonic:<16}{count}\n"))
+            .collect()
+    }
+}
+
+impl ExecutionHook for InstructionCounter {
+    fn before_instruction(&mut self, _: &ClassIdentifier, _: usize, instruction: &Instruction) {
+        self.record(instruction);
+    }
+}
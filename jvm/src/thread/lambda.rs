@@ -0,0 +1,471 @@
+use std::collections::HashSet;
+
+use anyhow::{Result, bail};
+use common::ClassIdentifier;
+use parser::class::{
+    ClassFile,
+    access_flags::AccessFlag,
+    attribute::Attribute,
+    constant_pool::{ConstantPool, CpIndex, CpInfo, ReferenceKind},
+    descriptor::{BaseType, FieldType, MethodDescriptor, ReturnDescriptor},
+    field::{self, Field},
+    method::{self, Method},
+};
+
+use crate::thread::method_handle::MethodHandle;
+
+const ALOAD_0: u8 = 0x2a;
+const ILOAD: u8 = 0x15;
+const LLOAD: u8 = 0x16;
+const FLOAD: u8 = 0x17;
+const DLOAD: u8 = 0x18;
+const ALOAD: u8 = 0x19;
+const POP: u8 = 0x57;
+const POP2: u8 = 0x58;
+const DUP: u8 = 0x59;
+const I2L: u8 = 0x85;
+const I2F: u8 = 0x86;
+const I2D: u8 = 0x87;
+const L2F: u8 = 0x89;
+const L2D: u8 = 0x8a;
+const F2D: u8 = 0x8d;
+const IRETURN: u8 = 0xac;
+const LRETURN: u8 = 0xad;
+const FRETURN: u8 = 0xae;
+const DRETURN: u8 = 0xaf;
+const ARETURN: u8 = 0xb0;
+const RETURN: u8 = 0xb1;
+const GETFIELD: u8 = 0xb4;
+const INVOKEVIRTUAL: u8 = 0xb6;
+const INVOKESPECIAL: u8 = 0xb7;
+const INVOKESTATIC: u8 = 0xb8;
+const INVOKEINTERFACE: u8 = 0xb9;
+const NEW: u8 = 0xbb;
+const CHECKCAST: u8 = 0xc0;
+
+/// Spins the class behind a lambda the way `InnerClassLambdaMetafactory` does: it implements
+/// `interface` by storing the captured arguments in fields `arg$1`, `arg$2`, ... and forwarding
+/// calls of `sam_name` to `target`. Parameters and the result are cast, boxed, unboxed and widened
+/// between the types of `instantiated_descriptor` and those of `target`.
+pub fn class_file(
+    identifier: &ClassIdentifier,
+    interface: &ClassIdentifier,
+    sam_name: &str,
+    sam_descriptor: &MethodDescriptor,
+    instantiated_descriptor: &MethodDescriptor,
+    captured: &[FieldType],
+    target: &MethodHandle,
+) -> Result<ClassFile> {
+    let mut cp = ConstantPoolBuilder::default();
+    let this_class = cp.class(&identifier.with_slashes()?);
+    let super_class = cp.class("java/lang/Object");
+    let interface = cp.class(&interface.with_slashes()?);
+
+    let mut fields = Vec::new();
+    for (i, field_type) in captured.iter().enumerate() {
+        fields.push(Field {
+            access_flags: HashSet::from([field::AccessFlag::Private, field::AccessFlag::Final]),
+            name_index: cp.utf8(&format!("arg${}", i + 1)),
+            descriptor_index: cp.utf8(&field_type.descriptor()),
+            attributes: vec![],
+        });
+    }
+
+    let code = forwarding_code(
+        &mut cp,
+        this_class.clone(),
+        sam_descriptor,
+        instantiated_descriptor,
+        captured,
+        target,
+    )?;
+    let method = Method {
+        access_flags: HashSet::from([method::AccessFlag::Public]),
+        name_index: cp.utf8(sam_name),
+        descriptor_index: cp.utf8(sam_descriptor.raw()),
+        attributes: vec![code],
+    };
+
+    Ok(ClassFile {
+        minor_version: 0,
+        major_version: 61,
+        constant_pool: ConstantPool { infos: cp.infos },
+        access_flags: HashSet::from([AccessFlag::Final, AccessFlag::Super, AccessFlag::Synthetic]),
+        this_class,
+        super_class,
+        interfaces: vec![interface],
+        fields,
+        methods: vec![method],
+        attributes: vec![],
+    })
+}
+
+/// `Code` attribute loading the captured arguments followed by the parameters of the
+/// functional interface method onto the operand stack and invoking `target` with them
+fn forwarding_code(
+    cp: &mut ConstantPoolBuilder,
+    this_class: CpIndex,
+    sam_descriptor: &MethodDescriptor,
+    instantiated_descriptor: &MethodDescriptor,
+    captured: &[FieldType],
+    target: &MethodHandle,
+) -> Result<Attribute> {
+    let mut code = Vec::new();
+    let mut stack = 0;
+
+    let target_class = target.class.with_slashes()?;
+    let target_descriptor = target.method_descriptor()?;
+    let has_receiver = !matches!(
+        target.kind,
+        ReferenceKind::InvokeStatic | ReferenceKind::NewInvokeSpecial
+    );
+
+    let mut target_parameters = Vec::new();
+    if has_receiver {
+        target_parameters.push(FieldType::ObjectType {
+            class_name: target_class.clone(),
+        });
+    }
+    target_parameters.extend(target_descriptor.parameters.iter().cloned());
+
+    if captured.len() + sam_descriptor.parameters.len() != target_parameters.len() {
+        bail!(
+            "lambda with {} captured and {} parameters can't invoke {}{}, TODO: throw LambdaConversionException",
+            captured.len(),
+            sam_descriptor.parameters.len(),
+            target.name,
+            target_descriptor.raw()
+        );
+    }
+
+    if sam_descriptor.parameters.len() != instantiated_descriptor.parameters.len() {
+        bail!(
+            "lambda for {} can't be instantiated as {}, TODO: throw LambdaConversionException",
+            sam_descriptor.raw(),
+            instantiated_descriptor.raw()
+        );
+    }
+
+    if matches!(target.kind, ReferenceKind::NewInvokeSpecial) {
+        code.push(NEW);
+        code.extend(cp.class(&target_class).0.to_be_bytes());
+        code.push(DUP);
+        stack += 2;
+    }
+
+    let mut target_parameters = target_parameters.iter();
+    for (i, field_type) in captured.iter().enumerate() {
+        let field = cp.field_ref(
+            this_class.clone(),
+            &format!("arg${}", i + 1),
+            &field_type.descriptor(),
+        );
+        code.push(ALOAD_0);
+        code.push(GETFIELD);
+        code.extend(field.0.to_be_bytes());
+        stack += slots(field_type);
+
+        // captured arguments already have the type of the target parameter
+        target_parameters.next();
+    }
+
+    let mut local = 1;
+    let parameters = sam_descriptor
+        .parameters
+        .iter()
+        .zip(&instantiated_descriptor.parameters)
+        .zip(target_parameters);
+    for ((parameter, instantiated), target_parameter) in parameters {
+        let opcode = match parameter {
+            FieldType::BaseType(BaseType::Long) => LLOAD,
+            FieldType::BaseType(BaseType::Float) => FLOAD,
+            FieldType::BaseType(BaseType::Double) => DLOAD,
+            FieldType::BaseType(_) => ILOAD,
+            FieldType::ObjectType { .. } | FieldType::ComponentType(_) => ALOAD,
+        };
+        code.push(opcode);
+        code.push(u8::try_from(local)?);
+        local += slots(parameter);
+        stack += slots(parameter).max(slots(target_parameter));
+
+        adapt(&mut code, cp, parameter, instantiated)?;
+        adapt(&mut code, cp, instantiated, target_parameter)?;
+    }
+
+    let method = cp.method_ref(
+        &target_class,
+        &target.name,
+        target_descriptor.raw(),
+        target.is_interface,
+    );
+    let invoke = match target.kind {
+        ReferenceKind::InvokeStatic => INVOKESTATIC,
+        // private methods of the target class are called virtually, as with nestmates
+        ReferenceKind::InvokeVirtual | ReferenceKind::InvokeSpecial if target.is_interface => {
+            INVOKEINTERFACE
+        }
+        ReferenceKind::InvokeVirtual | ReferenceKind::InvokeSpecial => INVOKEVIRTUAL,
+        ReferenceKind::InvokeInterface => INVOKEINTERFACE,
+        ReferenceKind::NewInvokeSpecial => INVOKESPECIAL,
+        ref kind => bail!("TODO: lambda for method handle of kind {kind:?}"),
+    };
+    code.push(invoke);
+    code.extend(method.0.to_be_bytes());
+    if invoke == INVOKEINTERFACE {
+        let count = target_parameters_slots(has_receiver, &target_descriptor);
+        code.push(u8::try_from(count)?);
+        code.push(0);
+    }
+
+    let target_return = if matches!(target.kind, ReferenceKind::NewInvokeSpecial) {
+        ReturnDescriptor::FieldType(FieldType::ObjectType {
+            class_name: target_class,
+        })
+    } else {
+        target_descriptor.return_descriptor.clone()
+    };
+    let return_opcode = match (
+        &sam_descriptor.return_descriptor,
+        &instantiated_descriptor.return_descriptor,
+        &target_return,
+    ) {
+        (ReturnDescriptor::Void, _, ReturnDescriptor::Void) => RETURN,
+        (ReturnDescriptor::Void, _, ReturnDescriptor::FieldType(field_type)) => {
+            code.push(if slots(field_type) == 2 { POP2 } else { POP });
+            RETURN
+        }
+        (
+            ReturnDescriptor::FieldType(sam_return),
+            ReturnDescriptor::FieldType(instantiated),
+            ReturnDescriptor::FieldType(target_return),
+        ) => {
+            stack = stack.max(slots(target_return)).max(slots(sam_return));
+            adapt(&mut code, cp, target_return, instantiated)?;
+            adapt(&mut code, cp, instantiated, sam_return)?;
+            match sam_return {
+                FieldType::BaseType(BaseType::Long) => LRETURN,
+                FieldType::BaseType(BaseType::Float) => FRETURN,
+                FieldType::BaseType(BaseType::Double) => DRETURN,
+                FieldType::BaseType(_) => IRETURN,
+                FieldType::ObjectType { .. } | FieldType::ComponentType(_) => ARETURN,
+            }
+        }
+        (sam_return, _, target_return) => {
+            bail!(
+                "lambda can't return {target_return:?} as {sam_return:?}, TODO: throw LambdaConversionException"
+            )
+        }
+    };
+    code.push(return_opcode);
+
+    let exception_table = vec![];
+    let attributes = vec![];
+    // max_stack, max_locals, code_length, exception_table_length and attributes_count
+    let attribute_length = 12 + code.len() as u32;
+    Ok(Attribute::Code {
+        attribute_name_index: cp.utf8("Code"),
+        attribute_length,
+        max_stack: stack.max(1),
+        max_locals: local,
+        code,
+        exception_table,
+        attributes,
+    })
+}
+
+/// Converts the value of type `from` on top of the operand stack to `to` like
+/// `LambdaMetafactory` does: primitives are widened, boxed and unboxed and references are cast
+fn adapt(
+    code: &mut Vec<u8>,
+    cp: &mut ConstantPoolBuilder,
+    from: &FieldType,
+    to: &FieldType,
+) -> Result<()> {
+    match (from, to) {
+        (FieldType::BaseType(from), FieldType::BaseType(to)) => widen(code, from, to),
+        (FieldType::BaseType(primitive), _) => {
+            // valueOf shares the box cache of the interpreter
+            let (wrapper, _) = wrapper(primitive);
+            let descriptor = format!("({})L{wrapper};", from.descriptor());
+            let value_of = cp.method_ref(wrapper, "valueOf", &descriptor, false);
+            code.push(INVOKESTATIC);
+            code.extend(value_of.0.to_be_bytes());
+            Ok(())
+        }
+        (_, FieldType::BaseType(to)) => {
+            let wrapped = match from {
+                FieldType::ObjectType { class_name } => wrapped(class_name),
+                _ => None,
+            };
+            // references other than wrappers are unboxed as the wrapper of the target type
+            let primitive = match wrapped {
+                Some(primitive) => primitive,
+                None => {
+                    checkcast(code, cp, wrapper(to).0);
+                    to.clone()
+                }
+            };
+            let (wrapper, unbox) = wrapper(&primitive);
+            let descriptor = format!("(){}", FieldType::BaseType(primitive.clone()).descriptor());
+            let unbox = cp.method_ref(wrapper, unbox, &descriptor, false);
+            code.push(INVOKEVIRTUAL);
+            code.extend(unbox.0.to_be_bytes());
+            widen(code, &primitive, to)
+        }
+        (from, to) if from == to => Ok(()),
+        (_, FieldType::ObjectType { class_name }) if class_name == "java/lang/Object" => Ok(()),
+        (_, FieldType::ObjectType { class_name }) => {
+            checkcast(code, cp, class_name);
+            Ok(())
+        }
+        (_, array) => {
+            checkcast(code, cp, &array.descriptor());
+            Ok(())
+        }
+    }
+}
+
+/// Widening primitive conversion from `from` to `to`
+fn widen(code: &mut Vec<u8>, from: &BaseType, to: &BaseType) -> Result<()> {
+    let int_like = matches!(
+        from,
+        BaseType::Byte | BaseType::Short | BaseType::Char | BaseType::Int
+    );
+    let opcode = match (from, to) {
+        (from, to) if from == to => return Ok(()),
+        (BaseType::Byte, BaseType::Short) => return Ok(()),
+        (_, BaseType::Int) if int_like => return Ok(()),
+        (_, BaseType::Long) if int_like => I2L,
+        (_, BaseType::Float) if int_like => I2F,
+        (_, BaseType::Double) if int_like => I2D,
+        (BaseType::Long, BaseType::Float) => L2F,
+        (BaseType::Long, BaseType::Double) => L2D,
+        (BaseType::Float, BaseType::Double) => F2D,
+        (from, to) => {
+            bail!("lambda can't convert {from:?} to {to:?}, TODO: throw LambdaConversionException")
+        }
+    };
+    code.push(opcode);
+    Ok(())
+}
+
+fn checkcast(code: &mut Vec<u8>, cp: &mut ConstantPoolBuilder, class_name: &str) {
+    code.push(CHECKCAST);
+    code.extend(cp.class(class_name).0.to_be_bytes());
+}
+
+/// Wrapper class of `primitive` and the name of its method unboxing it
+fn wrapper(primitive: &BaseType) -> (&'static str, &'static str) {
+    match primitive {
+        BaseType::Byte => ("java/lang/Byte", "byteValue"),
+        BaseType::Char => ("java/lang/Character", "charValue"),
+        BaseType::Double => ("java/lang/Double", "doubleValue"),
+        BaseType::Float => ("java/lang/Float", "floatValue"),
+        BaseType::Int => ("java/lang/Integer", "intValue"),
+        BaseType::Long => ("java/lang/Long", "longValue"),
+        BaseType::Short => ("java/lang/Short", "shortValue"),
+        BaseType::Boolean => ("java/lang/Boolean", "booleanValue"),
+    }
+}
+
+/// Primitive type wrapped by instances of `class_name`
+fn wrapped(class_name: &str) -> Option<BaseType> {
+    [
+        BaseType::Byte,
+        BaseType::Char,
+        BaseType::Double,
+        BaseType::Float,
+        BaseType::Int,
+        BaseType::Long,
+        BaseType::Short,
+        BaseType::Boolean,
+    ]
+    .into_iter()
+    .find(|primitive| wrapper(primitive).0 == class_name)
+}
+
+fn target_parameters_slots(has_receiver: bool, descriptor: &MethodDescriptor) -> u16 {
+    u16::from(has_receiver) + descriptor.parameters.iter().map(slots).sum::<u16>()
+}
+
+fn slots(field_type: &FieldType) -> u16 {
+    match field_type {
+        FieldType::BaseType(BaseType::Long | BaseType::Double) => 2,
+        _ => 1,
+    }
+}
+
+struct ConstantPoolBuilder {
+    infos: Vec<CpInfo>,
+}
+
+impl Default for ConstantPoolBuilder {
+    fn default() -> Self {
+        // first item is reserved
+        Self {
+            infos: vec![CpInfo::Reserved],
+        }
+    }
+}
+
+impl ConstantPoolBuilder {
+    fn push(&mut self, info: CpInfo) -> CpIndex {
+        self.infos.push(info);
+        CpIndex((self.infos.len() - 1) as u16)
+    }
+
+    fn utf8(&mut self, value: &str) -> CpIndex {
+        let existing = self
+            .infos
+            .iter()
+            .position(|info| matches!(info, CpInfo::Utf8(content) if content == value));
+        match existing {
+            Some(index) => CpIndex(index as u16),
+            None => self.push(CpInfo::Utf8(value.to_string())),
+        }
+    }
+
+    fn class(&mut self, name: &str) -> CpIndex {
+        let name_index = self.utf8(name);
+        self.push(CpInfo::Class { name_index })
+    }
+
+    fn name_and_type(&mut self, name: &str, descriptor: &str) -> CpIndex {
+        let name_index = self.utf8(name);
+        let descriptor_index = self.utf8(descriptor);
+        self.push(CpInfo::NameAndType {
+            name_index,
+            descriptor_index,
+        })
+    }
+
+    fn field_ref(&mut self, class_index: CpIndex, name: &str, descriptor: &str) -> CpIndex {
+        let name_and_type_index = self.name_and_type(name, descriptor);
+        self.push(CpInfo::FieldRef {
+            class_index,
+            name_and_type_index,
+        })
+    }
+
+    fn method_ref(
+        &mut self,
+        class_name: &str,
+        name: &str,
+        descriptor: &str,
+        is_interface: bool,
+    ) -> CpIndex {
+        let class_index = self.class(class_name);
+        let name_and_type_index = self.name_and_type(name, descriptor);
+        if is_interface {
+            self.push(CpInfo::InterfaceMethodRef {
+                class_index,
+                name_and_type_index,
+            })
+        } else {
+            self.push(CpInfo::MethodRef {
+                class_index,
+                name_and_type_index,
+            })
+        }
+    }
+}
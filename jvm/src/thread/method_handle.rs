@@ -0,0 +1,55 @@
+use anyhow::{Result, bail};
+use common::ClassIdentifier;
+use parser::class::{
+    constant_pool::ReferenceKind,
+    descriptor::{FieldDescriptor, MethodDescriptor},
+};
+
+/// The field or method referred to by a `CONSTANT_MethodHandle`, together with the kind of
+/// access the handle performs on it
+#[derive(Debug, Clone)]
+pub struct MethodHandle {
+    pub kind: ReferenceKind,
+    pub class: ClassIdentifier,
+    pub name: String,
+    pub descriptor: String,
+    /// Whether the member is referenced through a `CONSTANT_InterfaceMethodref`
+    pub is_interface: bool,
+}
+
+impl MethodHandle {
+    /// Whether the handle reads or writes a field instead of invoking a method
+    pub fn is_field(&self) -> bool {
+        matches!(
+            self.kind,
+            ReferenceKind::GetField
+                | ReferenceKind::GetStatic
+                | ReferenceKind::PutField
+                | ReferenceKind::PutStatic
+        )
+    }
+
+    /// Whether the referenced member is static, so no receiver is involved
+    pub fn is_static(&self) -> bool {
+        matches!(
+            self.kind,
+            ReferenceKind::GetStatic | ReferenceKind::PutStatic | ReferenceKind::InvokeStatic
+        )
+    }
+
+    pub fn field_descriptor(&self) -> Result<FieldDescriptor> {
+        if !self.is_field() {
+            bail!("method handle of kind {:?} has no field", self.kind);
+        }
+
+        FieldDescriptor::new(&self.descriptor)
+    }
+
+    pub fn method_descriptor(&self) -> Result<MethodDescriptor> {
+        if self.is_field() {
+            bail!("method handle of kind {:?} has no method", self.kind);
+        }
+
+        MethodDescriptor::new(&self.descriptor)
+    }
+}
@@ -1,42 +1,108 @@
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, LowerExp};
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
 use std::thread::JoinHandle;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, anyhow, bail};
 use common::{ClassIdentifier, FieldValue, FrameValue, HeapId, ReferenceValue, ThreadId};
+pub use heap::HeapSummary;
+pub use hook::{ExecutionHook, InstructionCounter};
 use monitor::Monitors;
+use native::{NativeMethod, NativeRegistry};
+pub use output::{OutputSink, StdOutputSink, VecOutputSink};
 use parser::class::{
     ClassFile,
-    constant_pool::{CpIndex, CpInfo},
-    descriptor::{FieldDescriptor, FieldType, MethodDescriptor, ReturnDescriptor},
+    access_flags::AccessFlag,
+    constant_pool::{ConstantPool, CpIndex, CpInfo, ReferenceKind},
+    descriptor::{BaseType, FieldDescriptor, FieldType, MethodDescriptor, ReturnDescriptor},
     field::Field,
     method::Method,
 };
-use stack::{Stack, code::Code, instruction::Instruction};
+pub use stack::instruction::Instruction;
+use stack::{BacktraceFrame, Stack, StackOverflow};
 use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::loader::BootstrapClassLoader;
-use class::Class;
-use heap::{Heap, HeapItem, InstanceField, PrimitiveArrayType, PrimitiveArrayValue};
+use class::{Class, InitializationState, InitializerThrew};
+use heap::{
+    ArrayIndexOutOfBounds, Heap, HeapItem, InstanceField, PrimitiveArrayType, PrimitiveArrayValue,
+};
+use method_handle::MethodHandle;
 
 mod class;
 mod heap;
+mod hook;
+mod lambda;
+mod method_handle;
 mod monitor;
 mod native;
+mod output;
 mod stack;
+mod verifier;
+
+const MAIN_DESCRIPTOR: &str = "([Ljava/lang/String;)V";
+/// Frames beyond the stack's limit available to throw a `StackOverflowError`
+const RESERVED_FRAMES: usize = 16;
+/// Native stack reserved per frame, each one nests an invocation of the interpreter loop whose
+/// unoptimized build takes up more than 16 KiB
+const NATIVE_FRAME_SIZE: usize = 64 * 1024;
+/// Values of the `coder` field of `java.lang.String`
+const STRING_LATIN1: i32 = 0;
+const STRING_UTF16: i32 = 1;
+/// Bits of the `threadStatus` field of `java.lang.Thread`, as used by `VM.toThreadState`
+const THREAD_ALIVE: i32 = 0x1;
+const THREAD_TERMINATED: i32 = 0x2;
+const THREAD_RUNNABLE: i32 = 0x4 | THREAD_ALIVE;
+
+/// Thread started by `Thread.start`
+struct StartedThread {
+    handle: JoinHandle<()>,
+    /// The VM exits without waiting for daemon threads
+    daemon: bool,
+}
 
 pub struct JvmThread {
     name: String,
     class_loader: Arc<Mutex<BootstrapClassLoader>>,
     classes: Arc<Mutex<HashMap<ClassIdentifier, Class>>>,
-    heap: Arc<Mutex<Heap>>,
-    monitors: Arc<Mutex<Monitors>>,
+    /// Signalled whenever a class finishes initialization, successfully or not
+    class_initialized: Arc<Condvar>,
+    /// Reads share the lock, only allocations and writes take it exclusively
+    heap: Arc<RwLock<Heap>>,
+    monitors: Arc<Monitors>,
+    /// Interned strings by their contents
+    strings: Arc<Mutex<HashMap<String, HeapId>>>,
+    /// Boxes returned by `valueOf` of the wrapper classes for the values they cache
+    boxes: Arc<Mutex<HashMap<(ClassIdentifier, i64), HeapId>>>,
+    /// `java.lang.Class` objects by the class they represent
+    mirrors: Arc<Mutex<HashMap<ClassIdentifier, HeapId>>>,
+    /// Threads started by `Thread.start` by their `java.lang.Thread` object
+    threads: Arc<Mutex<HashMap<HeapId, StartedThread>>>,
+    /// Signalled whenever a thread is interrupted, waking up sleeping threads to check their
+    /// interrupt status
+    interrupts: Arc<(Mutex<()>, Condvar)>,
+    natives: Arc<Mutex<NativeRegistry>>,
+    /// Receives what is written to stdout and stderr
+    output: Arc<Mutex<Box<dyn OutputSink>>>,
+    /// Number of threads currently running code in their interpreter loop
+    executing_threads: Arc<AtomicUsize>,
 
     stack: Stack,
     creation_time: Instant,
     current_thread_object: Option<HeapId>,
     current_thread_id: Option<ThreadId>,
+    exception: Option<HeapId>,
+    execution_hook: Option<Box<dyn ExecutionHook>>,
+    /// Counts executed instructions if profiling, shared with all threads created from this one
+    instruction_counter: Option<InstructionCounter>,
+    /// Whether classes are verified when they are linked
+    verify_classes: bool,
+    /// Number of nested interpreter loops of this thread, e.g. for a `<clinit>` or a method
+    /// called by a native
+    execute_depth: usize,
 }
 
 impl JvmThread {
@@ -46,95 +112,338 @@ impl JvmThread {
             class_loader,
 
             classes: Arc::new(Mutex::new(HashMap::new())),
-            heap: Arc::new(Mutex::new(Heap::default())),
-            monitors: Arc::new(Mutex::new(Monitors::default())),
+            class_initialized: Arc::new(Condvar::new()),
+            heap: Arc::new(RwLock::new(Heap::default())),
+            monitors: Arc::new(Monitors::default()),
+            strings: Arc::new(Mutex::new(HashMap::new())),
+            boxes: Arc::new(Mutex::new(HashMap::new())),
+            mirrors: Arc::new(Mutex::new(HashMap::new())),
+            threads: Arc::new(Mutex::new(HashMap::new())),
+            interrupts: Arc::default(),
+            natives: Arc::new(Mutex::new(NativeRegistry::default())),
+            output: Arc::new(Mutex::new(Box::new(StdOutputSink))),
+            executing_threads: Arc::default(),
             stack: Stack::default(),
             creation_time: Instant::now(),
             current_thread_object: None,
             current_thread_id: None,
+            exception: None,
+            execution_hook: None,
+            instruction_counter: None,
+            verify_classes: false,
+            execute_depth: 0,
         }
     }
 
-    fn new(
-        name: String,
-        class_loader: Arc<Mutex<BootstrapClassLoader>>,
-        classes: Arc<Mutex<HashMap<ClassIdentifier, Class>>>,
-        heap: Arc<Mutex<Heap>>,
-        monitors: Arc<Mutex<Monitors>>,
-    ) -> Self {
+    /// A thread sharing classes, heap, monitors, strings and natives with this one
+    pub fn new_thread(&self, name: String) -> Self {
         Self {
             name,
-            class_loader,
-            classes,
-            heap,
-            monitors,
-            stack: Stack::default(),
+            class_loader: self.class_loader.clone(),
+            classes: self.classes.clone(),
+            class_initialized: self.class_initialized.clone(),
+            heap: self.heap.clone(),
+            monitors: self.monitors.clone(),
+            strings: self.strings.clone(),
+            boxes: self.boxes.clone(),
+            mirrors: self.mirrors.clone(),
+            threads: self.threads.clone(),
+            interrupts: self.interrupts.clone(),
+            natives: self.natives.clone(),
+            output: self.output.clone(),
+            executing_threads: self.executing_threads.clone(),
+            stack: Stack::new(self.stack.max_frames()),
             creation_time: Instant::now(),
             current_thread_object: None,
             current_thread_id: None,
+            exception: None,
+            execution_hook: None,
+            instruction_counter: self.instruction_counter.clone(),
+            verify_classes: self.verify_classes,
+            execute_depth: 0,
         }
     }
 
-    pub fn new_thread(&self, name: String) -> Self {
-        Self::new(
-            name,
-            self.class_loader.clone(),
-            self.classes.clone(),
-            self.heap.clone(),
-            self.monitors.clone(),
-        )
+    /// Registers `method` as the implementation of the native method `name` in `class_identifier`,
+    /// it is shared with all threads created from this one
+    pub fn register_native(
+        &self,
+        class_identifier: ClassIdentifier,
+        name: &str,
+        method: impl Fn(&mut JvmThread, Vec<FrameValue>) -> Result<Option<FrameValue>>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Result<()> {
+        let mut natives = self
+            .natives
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        natives.register(class_identifier, name, method);
+        Ok(())
     }
 
-    pub fn caller_class(&self) -> Result<&ClassIdentifier> {
-        self.stack.caller_class()
+    /// Sends what is written to stdout and stderr to `sink` instead of the host's streams, for
+    /// this thread and all threads sharing its heap
+    pub fn set_output_sink(&self, sink: impl OutputSink + 'static) -> Result<()> {
+        let mut output = self
+            .output
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        *output = Box::new(sink);
+        Ok(())
     }
 
-    pub fn run_with_class(mut thread: Self, main_class: ClassIdentifier) -> JoinHandle<Result<()>> {
-        std::thread::spawn(move || match thread.run_main(&main_class) {
-            Ok(_) => Ok(()),
-            Err(err) => Err(anyhow!(
-                "thread '{}' has crashed: {err:?} at\n{}",
-                thread.name,
-                thread.stack.stack_trace()
-            )),
-        })
+    /// Passes `bytes` written to the file descriptor `fd` to the output sink
+    fn write_output(&self, fd: i32, bytes: &[u8]) -> Result<()> {
+        let mut output = self
+            .output
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        output.write(fd, bytes);
+        Ok(())
+    }
+
+    /// Calls `hook` before every instruction this thread executes, replacing any previous hook.
+    /// Threads created from this one don't inherit it.
+    pub fn set_execution_hook(&mut self, hook: impl ExecutionHook + 'static) {
+        self.execution_hook = Some(Box::new(hook));
+    }
+
+    /// Verifies the code of every class linked afterwards by this thread and threads created from
+    /// it against its stack map frames, rejecting type-unsafe code before it is run
+    pub fn verify_classes(&mut self) {
+        self.verify_classes = true;
+    }
+
+    /// Starts counting the executed instructions of this thread and all threads created from it
+    /// afterwards
+    pub fn profile_instructions(&mut self) {
+        self.instruction_counter
+            .get_or_insert_with(InstructionCounter::default);
+    }
+
+    /// Executed instructions by mnemonic across all profiled threads, see
+    /// [`Self::profile_instructions`]
+    pub fn instruction_counts(&self) -> HashMap<&'static str, u64> {
+        self.instruction_counter
+            .as_ref()
+            .map(InstructionCounter::counts)
+            .unwrap_or_default()
     }
 
-    pub fn run_with_method(
+    /// [`Self::instruction_counts`] as a histogram, starting with the most executed instruction
+    pub fn instruction_histogram(&self) -> String {
+        self.instruction_counter
+            .as_ref()
+            .map(InstructionCounter::histogram)
+            .unwrap_or_default()
+    }
+
+    fn native_method(
+        &self,
+        class_identifier: &ClassIdentifier,
+        name: &str,
+    ) -> Result<Option<NativeMethod>> {
+        let natives = self
+            .natives
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        Ok(natives.get(class_identifier, name))
+    }
+
+    pub fn caller_class(&self) -> Result<&ClassIdentifier> {
+        self.stack.caller_class()
+    }
+
+    pub fn run_with_class(
         mut thread: Self,
-        class: ClassIdentifier,
-        name: String,
-        descriptor: String,
-    ) {
-        std::thread::spawn(
-            move || match thread.run_method(&class, &name, &descriptor) {
-                Ok(_) => {
-                    info!("thread '{}' has exited normally", thread.name)
-                }
+        main_class: ClassIdentifier,
+        args: Vec<String>,
+    ) -> Result<JoinHandle<Result<()>>> {
+        let handle =
+            thread
+                .native_thread()
+                .spawn(move || match thread.run_main(&main_class, args) {
+                    Ok(_) => Ok(()),
+                    Err(err) => Err(anyhow!(
+                        "thread '{}' has crashed: {err:?} at\n{}",
+                        thread.name,
+                        thread.stack.stack_trace()
+                    )),
+                })?;
+        Ok(handle)
+    }
+
+    /// Builder of the native thread running this thread, its stack fits `max_frames` frames
+    fn native_thread(&self) -> std::thread::Builder {
+        std::thread::Builder::new().stack_size(self.stack.max_frames() * NATIVE_FRAME_SIZE)
+    }
+
+    /// Runs the `run` method of the `java.lang.Thread` `thread_object` on a new thread. Once it
+    /// returns, the object is marked as terminated and threads waiting in `Thread.join` are
+    /// notified.
+    pub fn start_thread(&mut self, thread_object: &HeapId) -> Result<()> {
+        let name = self.heap_get_field(thread_object, "name")?;
+        let name = self.read_java_string(name.heap_id()?)?;
+        let class = self.heap_get(thread_object)?.class_identifier()?;
+        let thread_id = self.heap_get_field(thread_object, "tid")?.long()?;
+        let daemon = self.heap_get_field(thread_object, "daemon")?.int()? != 0;
+        self.heap_set_field(
+            thread_object,
+            "threadStatus",
+            FieldValue::Integer(THREAD_RUNNABLE),
+        )?;
+
+        let mut thread = self.new_thread(name);
+        thread.current_thread_object = Some(thread_object.clone());
+        thread.current_thread_id = Some(thread_id.into());
+        let this = FrameValue::Reference(ReferenceValue::HeapItem(thread_object.clone()));
+        let handle = thread.native_thread().spawn(move || {
+            match thread.run_method(&class, "run", "()V", vec![this]) {
+                Ok(_) => info!("thread '{}' has exited normally", thread.name),
                 Err(err) => error!("thread '{}' has crashed: {err:?}", thread.name),
-            },
-        );
+            }
+
+            if let Err(err) = thread.terminate() {
+                error!("thread '{}' failed to terminate: {err:?}", thread.name);
+            }
+        })?;
+
+        let mut threads = self
+            .threads
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        threads.retain(|_, thread| !thread.handle.is_finished());
+        threads.insert(thread_object.clone(), StartedThread { handle, daemon });
+        Ok(())
+    }
+
+    /// Marks the thread object of this thread as terminated and wakes up the threads joining it
+    fn terminate(&mut self) -> Result<()> {
+        let thread_object = self.thread_object().context("no current thread found")?;
+        let thread_id = self
+            .current_thread_id
+            .clone()
+            .context("how do we not have a thread id?")?;
+
+        self.enter_object_monitor(&thread_object, &thread_id)?;
+        self.heap_set_field(
+            &thread_object,
+            "threadStatus",
+            FieldValue::Integer(THREAD_TERMINATED),
+        )?;
+        self.monitors.notify_all(&thread_object, &thread_id)?;
+        self.exit_object_monitor(&thread_object, &thread_id)
+    }
+
+    /// Whether the `java.lang.Thread` `thread_object` has been started and not yet terminated
+    pub fn is_alive(&self, thread_object: &HeapId) -> Result<bool> {
+        let status = self.heap_get_field(thread_object, "threadStatus")?.int()?;
+        Ok(status & THREAD_ALIVE != 0)
+    }
+
+    /// Sets the interrupt status of the `java.lang.Thread` `thread_object`, a sleeping thread
+    /// wakes up and throws an `InterruptedException`
+    pub fn interrupt(&mut self, thread_object: &HeapId) -> Result<()> {
+        self.heap_set_field(thread_object, "interrupted", FieldValue::Integer(1))?;
+        self.notify_interrupt()
+    }
+
+    /// Wakes up all sleeping threads to check whether they have been interrupted
+    fn notify_interrupt(&self) -> Result<()> {
+        let (lock, condvar) = &*self.interrupts;
+        let _guard = lock.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        condvar.notify_all();
+        Ok(())
+    }
+
+    /// Sleeps for `duration` unless the current thread is or gets interrupted. In that case its
+    /// interrupt status is cleared and `false` returned.
+    fn sleep(&mut self, duration: Duration) -> Result<bool> {
+        let thread_object = self.thread_object().context("no current thread found")?;
+        let deadline = Instant::now() + duration;
+
+        let interrupts = self.interrupts.clone();
+        let (lock, condvar) = &*interrupts;
+        let mut guard = lock.lock().map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        loop {
+            if self.heap_get_field(&thread_object, "interrupted")?.int()? != 0 {
+                self.heap_set_field(&thread_object, "interrupted", FieldValue::Integer(0))?;
+                return Ok(false);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(true);
+            }
+
+            guard = condvar
+                .wait_timeout(guard, deadline - now)
+                .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+                .0;
+        }
+    }
+
+    /// Blocks until all non-daemon threads started by `Thread.start` have exited, including the
+    /// ones they start in the meantime
+    pub fn join_threads(&self) -> Result<()> {
+        loop {
+            let handles = {
+                let mut threads = self
+                    .threads
+                    .lock()
+                    .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+                let non_daemon: Vec<_> = threads
+                    .iter()
+                    .filter(|(_, thread)| !thread.daemon)
+                    .map(|(thread_object, _)| thread_object.clone())
+                    .collect();
+
+                non_daemon
+                    .iter()
+                    .filter_map(|thread_object| threads.remove(thread_object))
+                    .map(|thread| thread.handle)
+                    .collect::<Vec<_>>()
+            };
+            if handles.is_empty() {
+                return Ok(());
+            }
+
+            for handle in handles {
+                handle
+                    .join()
+                    .map_err(|err| anyhow!("thread error: {err:?}"))?;
+            }
+        }
     }
 
     #[instrument(name = "", skip_all, fields(t = self.name))]
-    fn run_main(&mut self, main_class: &ClassIdentifier) -> Result<()> {
-        self.initialize(&ClassIdentifier::new(
-            "java.lang".to_owned(),
-            "Class".to_owned(),
-        ))?;
-        self.initialize(&ClassIdentifier::new(
-            "java.lang".to_owned(),
-            "Object".to_owned(),
-        ))?;
-        self.initialize(&ClassIdentifier::new(
-            "java.lang".to_owned(),
-            "String".to_owned(),
-        ))?;
+    fn run_main(&mut self, main_class: &ClassIdentifier, args: Vec<String>) -> Result<()> {
+        self.initialize(&ClassIdentifier::from_parts("java.lang", "Class"))?;
+        self.initialize(&ClassIdentifier::from_parts("java.lang", "Object"))?;
+        self.initialize(&ClassIdentifier::from_parts("java.lang", "String"))?;
         let thread_object_heap_id =
             self.new_thread_object(self.name.to_string(), "system".to_string())?;
         self.current_thread_object = Some(thread_object_heap_id);
         self.initialize(main_class)?;
-        bail!("TODO: run_main")
+
+        let (_, main) = self
+            .resolve_method(main_class, "main", MAIN_DESCRIPTOR)
+            .context(format!(
+                "TODO: throw NoSuchMethodError, no main method found in {main_class:?}"
+            ))?;
+        if !main.is_static() {
+            bail!("TODO: throw NoSuchMethodError, main method of {main_class:?} isn't static");
+        }
+
+        let args = self.new_string_array(args)?;
+        self.run_method(
+            main_class,
+            "main",
+            MAIN_DESCRIPTOR,
+            vec![FrameValue::Reference(ReferenceValue::HeapItem(args))],
+        )
     }
 
     #[instrument(name = "", skip_all, fields(t = self.name))]
@@ -143,22 +452,96 @@ impl JvmThread {
         class_identifier: &ClassIdentifier,
         name: &str,
         descriptor: &str,
+        local_variables: Vec<FrameValue>,
     ) -> Result<()> {
-        let (_, method) = self.resolve_method(class_identifier, name, descriptor)?;
-        let class = self.class(class_identifier)?;
+        let (class_identifier, method) = self.resolve_method(class_identifier, name, descriptor)?;
+        let class = self.class(&class_identifier)?;
         let descriptor = class.method_descriptor(&method)?;
-        let code = method
-            .code()
-            .context(format!("method {name} has no code"))?;
+        let code = class.code(&method)?;
+        let object_ref = match local_variables.first() {
+            Some(FrameValue::Reference(ReferenceValue::HeapItem(heap_id)))
+                if !method.is_static() =>
+            {
+                Some(heap_id.clone())
+            }
+            _ => None,
+        };
         self.stack.push(
             name.to_string(),
             descriptor,
-            vec![],
-            Code::new(code.clone())?,
-            class_identifier.clone(),
-            None,
-        );
-        self.execute()
+            local_variables,
+            code,
+            class_identifier,
+            object_ref,
+        )?;
+        self.execute()?;
+
+        if let Some(exception) = self.exception.take() {
+            let identifier = self.heap_get(&exception)?.class_identifier()?;
+            bail!("uncaught exception {identifier:?}")
+        }
+
+        Ok(())
+    }
+
+    /// Calls the static method `name` of `class` with `args` on this thread and returns its
+    /// result, `None` for void methods. The class is initialized first like for `invokestatic`.
+    pub fn call_static(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+        args: Vec<FieldValue>,
+    ) -> Result<Option<FieldValue>> {
+        let class_identifier = ClassIdentifier::new(class)?;
+        let method_descriptor = MethodDescriptor::new(descriptor)?;
+        if method_descriptor.parameters.len() != args.len() {
+            bail!(
+                "{name}{descriptor} takes {} arguments, got {}",
+                method_descriptor.parameters.len(),
+                args.len()
+            );
+        }
+
+        // the entry frame and whatever a failed call left above it are dropped on every path,
+        // later calls on this thread would run on top of them otherwise
+        let depth = self.stack.depth();
+        self.stack.push_entry(class_identifier.clone())?;
+        let result = self.call_from_entry(&class_identifier, name, &method_descriptor, args);
+        self.stack.truncate(depth);
+
+        if let Some(exception) = self.exception.take() {
+            let identifier = self.heap_get(&exception)?.class_identifier()?;
+            bail!("uncaught exception {identifier:?}")
+        }
+
+        result
+    }
+
+    /// Invokes the static method for [`Self::call_static`] from its entry frame, `None` if the
+    /// method returns void or threw
+    fn call_from_entry(
+        &mut self,
+        class_identifier: &ClassIdentifier,
+        name: &str,
+        method_descriptor: &MethodDescriptor,
+        args: Vec<FieldValue>,
+    ) -> Result<Option<FieldValue>> {
+        // the arguments are on the stack before initializing runs any code, so they are roots
+        // if it collects garbage
+        for arg in args {
+            self.stack.push_operand(arg.into())?;
+        }
+        self.initialize(class_identifier)?;
+        self.invoke_static_method(class_identifier, name, method_descriptor.raw())?;
+        if self.exception.is_some() {
+            return Ok(None);
+        }
+
+        match method_descriptor.return_descriptor {
+            ReturnDescriptor::Void => Ok(None),
+            _ => Ok(Some(self.stack.pop_operand()?.try_into()?)),
+        }
     }
 
     pub fn creation_time(&self) -> &Instant {
@@ -176,7 +559,7 @@ impl JvmThread {
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         classes
             .get(identifier)
-            .context(format!("class {identifier:?} is not initialized"))
+            .context(format!("class {identifier:?} is not linked"))
             .cloned()
     }
 
@@ -193,38 +576,178 @@ impl JvmThread {
         self.class(self.stack.current_class()?)
     }
 
+    pub fn set_gc_threshold(&self, threshold: Option<usize>) -> Result<()> {
+        let mut heap = self
+            .heap
+            .write()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        heap.set_gc_threshold(threshold);
+        Ok(())
+    }
+
+    /// Limits the stack of this thread and the threads it creates afterwards to `max_frames`,
+    /// invoking a method on a full stack throws a `StackOverflowError`
+    pub fn set_max_stack_frames(&mut self, max_frames: usize) {
+        self.stack.set_max_frames(max_frames);
+    }
+
+    /// Limits the heap shared by all threads to `max_items`, allocating more throws an
+    /// `OutOfMemoryError`. Like HotSpot, which preallocates its errors, the error's class is
+    /// initialized up front, so throwing it doesn't depend on allocations succeeding.
+    pub fn set_max_heap_items(&mut self, max_items: Option<usize>) -> Result<()> {
+        if max_items.is_some() {
+            self.initialize(&ClassIdentifier::from_parts(
+                "java.lang",
+                "OutOfMemoryError",
+            ))?;
+        }
+
+        let mut heap = self
+            .heap
+            .write()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        heap.set_max_items(max_items);
+        Ok(())
+    }
+
+    /// Throws an `OutOfMemoryError` if the heap is still full after collecting garbage, returns
+    /// whether it did
+    fn throw_if_out_of_memory(&mut self) -> Result<bool> {
+        if !self.heap_is_full()? {
+            return Ok(false);
+        }
+
+        if self.may_collect_garbage() {
+            self.collect_garbage()?;
+        }
+        if !self.heap_is_full()? {
+            return Ok(false);
+        }
+
+        self.throw_new("OutOfMemoryError", "Java heap space".to_string())?;
+        Ok(true)
+    }
+
+    fn heap_is_full(&self) -> Result<bool> {
+        let heap = self
+            .heap
+            .read()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        Ok(heap.is_full())
+    }
+
+    /// Whether the interpreter may collect garbage, which is only the case if this is the only
+    /// thread running code and it isn't in a nested interpreter loop. Other threads' stacks
+    /// aren't roots and a nested loop runs while Rust code, like a native, may hold ids which
+    /// aren't on the stack.
+    fn may_collect_garbage(&self) -> bool {
+        self.execute_depth == 1 && self.executing_threads.load(Ordering::SeqCst) == 1
+    }
+
+    /// Collects all heap items unreachable from this thread's stack, the fields of all classes,
+    /// their mirrors, started threads and the string pool, see [`Self::may_collect_garbage`]
+    /// for when that is safe
+    fn collect_garbage(&mut self) -> Result<()> {
+        let mut roots = self.stack.references();
+        roots.extend(self.current_thread_object.clone());
+        roots.extend(self.exception.clone());
+        roots.extend(
+            self.strings
+                .lock()
+                .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+                .values()
+                .cloned(),
+        );
+        roots.extend(
+            self.boxes
+                .lock()
+                .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+                .values()
+                .cloned(),
+        );
+        roots.extend(
+            self.mirrors
+                .lock()
+                .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+                .values()
+                .cloned(),
+        );
+        roots.extend(
+            self.threads
+                .lock()
+                .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+                .keys()
+                .cloned(),
+        );
+        {
+            let classes = self
+                .classes
+                .lock()
+                .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+            for class in classes.values() {
+                roots.extend(class.references());
+            }
+        }
+
+        let mut heap = self
+            .heap
+            .write()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        let collected = heap.collect(&roots);
+        info!("garbage collection freed {collected} heap items");
+        Ok(())
+    }
+
     pub fn heap_get(&self, heap_id: &HeapId) -> Result<HeapItem> {
         let heap = self
             .heap
-            .lock()
+            .read()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         heap.get(heap_id).cloned()
     }
 
+    /// Summaries of all items on the shared heap, for inspecting a program while debugging
+    pub fn dump_heap(&self) -> Result<Vec<HeapSummary>> {
+        let heap = self
+            .heap
+            .read()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        heap.summaries()
+    }
+
+    /// Summary of the heap item at `heap_id`
+    pub fn heap_summary(&self, heap_id: &HeapId) -> Result<HeapSummary> {
+        let heap = self
+            .heap
+            .read()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        heap.get(heap_id)?.summary(heap_id)
+    }
+
     pub fn get_primitive_array(
         &self,
         id: &HeapId,
     ) -> Result<(PrimitiveArrayType, Vec<PrimitiveArrayValue>)> {
         let heap = self
             .heap
-            .lock()
+            .read()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         let (typ, arr) = heap.get_primitive_array(id)?;
         Ok((typ.clone(), arr.clone()))
     }
 
-    fn get_reference_array(&self, id: &HeapId) -> Result<Vec<ReferenceValue>> {
+    pub fn get_reference_array(&self, id: &HeapId) -> Result<Vec<ReferenceValue>> {
         let heap = self
             .heap
-            .lock()
+            .read()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         heap.get_reference_array(id).cloned()
     }
 
-    fn get_array_length(&self, id: &HeapId) -> Result<usize> {
+    pub fn get_array_length(&self, id: &HeapId) -> Result<usize> {
         let heap = self
             .heap
-            .lock()
+            .read()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         heap.get_array_length(id)
     }
@@ -236,7 +759,7 @@ impl JvmThread {
     ) -> Result<HeapId> {
         let mut heap = self
             .heap
-            .lock()
+            .write()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         Ok(heap.allocate_primitive_array(array_type, values))
     }
@@ -249,7 +772,7 @@ impl JvmThread {
     ) -> Result<()> {
         let mut heap = self
             .heap
-            .lock()
+            .write()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         heap.store_into_primitive_array(id, index, value)
     }
@@ -262,7 +785,7 @@ impl JvmThread {
     ) -> Result<()> {
         let mut heap = self
             .heap
-            .lock()
+            .write()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         heap.store_into_reference_array(id, index, value)
     }
@@ -274,7 +797,7 @@ impl JvmThread {
     ) -> Result<HeapId> {
         let mut heap = self
             .heap
-            .lock()
+            .write()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
 
         Ok(heap.allocate(class_identifier, fields))
@@ -283,15 +806,38 @@ impl JvmThread {
     pub fn allocate_array(&mut self, class: ClassIdentifier, length: usize) -> Result<HeapId> {
         let mut heap = self
             .heap
-            .lock()
+            .write()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         Ok(heap.allocate_array(class, length))
     }
 
+    /// Shallow copy of the object or array at `heap_id`, see [`Heap::clone_item`]
+    pub fn clone_heap_item(&mut self, heap_id: &HeapId) -> Result<HeapId> {
+        let mut heap = self
+            .heap
+            .write()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        heap.clone_item(heap_id)
+    }
+
+    /// Hash of `reference` which stays the same for the lifetime of the referenced object
+    pub fn identity_hash(&self, reference: &ReferenceValue) -> Result<i32> {
+        match reference {
+            ReferenceValue::HeapItem(heap_id) => {
+                let heap = self
+                    .heap
+                    .read()
+                    .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+                heap.identity_hash(heap_id)
+            }
+            ReferenceValue::Null => Ok(0),
+        }
+    }
+
     pub fn heap_get_field(&self, id: &HeapId, name: &str) -> Result<FieldValue> {
         let heap = self
             .heap
-            .lock()
+            .read()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         heap.get_field(id, name)
     }
@@ -304,7 +850,7 @@ impl JvmThread {
     ) -> Result<()> {
         let mut heap = self
             .heap
-            .lock()
+            .write()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         heap.set_field(object_id, name, value)
     }
@@ -316,7 +862,7 @@ impl JvmThread {
     ) -> Result<HeapId> {
         let mut heap = self
             .heap
-            .lock()
+            .write()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         Ok(heap.allocate_default_primitive_array(array_type, count))
     }
@@ -329,137 +875,317 @@ impl JvmThread {
         loader.load(identifier)
     }
 
-    fn enter_object_monitor(&mut self, heap_id: &HeapId, thread_id: &ThreadId) -> Result<bool> {
-        let mut monitors = self
-            .monitors
+    fn define(&self, identifier: ClassIdentifier, class_file: ClassFile) -> Result<()> {
+        let mut loader = self
+            .class_loader
             .lock()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
-        Ok(monitors.enter_object_monitor(heap_id, thread_id))
+        loader.define(identifier, class_file);
+        Ok(())
+    }
+
+    fn enter_object_monitor(&mut self, heap_id: &HeapId, thread_id: &ThreadId) -> Result<()> {
+        self.monitors.enter_object_monitor(heap_id, thread_id)
     }
 
     fn exit_object_monitor(&mut self, heap_id: &HeapId, thread_id: &ThreadId) -> Result<()> {
-        let mut monitors = self
-            .monitors
-            .lock()
-            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
-        monitors.exit_object_monitor(heap_id, thread_id)
+        self.monitors.exit_object_monitor(heap_id, thread_id)
     }
 
-    fn enter_class_monitor(
-        &mut self,
-        class_identifier: &ClassIdentifier,
-        thread_id: &ThreadId,
-    ) -> Result<bool> {
-        let mut monitors = self
-            .monitors
-            .lock()
-            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
-        Ok(monitors.enter_class_monitor(class_identifier, thread_id))
+    /// Waits on the monitor of `heap_id`, which has to be owned by this thread
+    pub fn monitor_wait(&mut self, heap_id: &HeapId, timeout: Option<Duration>) -> Result<()> {
+        let thread_id = self
+            .current_thread_id
+            .clone()
+            .context("how do we not have a thread id?")?;
+        self.monitors.wait(heap_id, &thread_id, timeout)
     }
 
-    fn exit_class_monitor(
-        &mut self,
-        class_identifier: &ClassIdentifier,
-        thread_id: &ThreadId,
-    ) -> Result<()> {
-        let mut monitors = self
-            .monitors
-            .lock()
-            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
-        monitors.exit_class_monitor(class_identifier, thread_id)
+    pub fn monitor_notify(&mut self, heap_id: &HeapId, all: bool) -> Result<()> {
+        let thread_id = self
+            .current_thread_id
+            .clone()
+            .context("how do we not have a thread id?")?;
+        if all {
+            self.monitors.notify_all(heap_id, &thread_id)
+        } else {
+            self.monitors.notify(heap_id, &thread_id)
+        }
     }
 
-    fn initialize(&mut self, identifier: &ClassIdentifier) -> Result<Class> {
-        if let Some(c) = self
-            .classes
-            .lock()
-            .map_err(|e| anyhow!("Lock poisoned: {}", e))?
-            .get(identifier)
-            .cloned()
-            && (c.initialized() || c.being_initialized())
-        {
-            return Ok(c.clone());
+    /// Links the class, which is loading it, preparing its static fields with their default
+    /// values and linking its super class, without running any of its code. If enabled, the
+    /// class is verified first, see [`Self::verify_classes`].
+    fn link(&mut self, identifier: &ClassIdentifier) -> Result<Class> {
+        if let Ok(class) = self.class(identifier) {
+            return Ok(class);
         }
 
-        info!("initializing {identifier:?}");
+        debug!("linking {identifier:?}");
         let class_file = self.load(identifier)?;
 
         let mut class = Class::new(identifier.clone(), class_file);
-        class.initializing();
-        self.initialize_static_fields(&mut class)?;
-
-        let class_identifier = ClassIdentifier::new("java.lang".to_owned(), "Class".to_owned());
-        if identifier != &class_identifier {
-            let class_class = self.class(&class_identifier)?;
-            for field in class_class.fields() {
-                if field.is_static() {
-                    continue;
-                }
-
-                let name = class_class.utf8(&field.name_index)?;
-                let descriptor = class_class.utf8(&field.descriptor_index)?;
-                class.set_class_field(name.to_string(), descriptor)?;
+        if self.verify_classes {
+            verifier::verify(&class)?;
+        }
+        for field in &class.fields().clone() {
+            if field.is_static() {
+                let name = class.utf8(&field.name_index)?.to_string();
+                let descriptor = FieldDescriptor::new(class.utf8(&field.descriptor_index)?)?;
+                class.set_static_field(&name, descriptor.into())?;
             }
         }
 
-        self.insert_class(identifier.clone(), class.clone())?;
+        // keep the class of another thread linking it at the same time, which may already be
+        // initializing it
+        let class = self
+            .classes
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+            .entry(identifier.clone())
+            .or_insert(class)
+            .clone();
 
         if class.has_super_class() {
-            let super_class_identifier = class.super_class()?;
-            self.initialize(&super_class_identifier)?;
+            self.link(&class.super_class()?)?;
         }
 
-        self.execute_clinit(&class)?;
-        if identifier == &ClassIdentifier::new("java.lang".to_owned(), "System".to_owned()) {
-            let (_, method) = self.resolve_method(identifier, "initPhase1", "()V")?;
-            let class = self.class(identifier)?;
-            let descriptor = class.method_descriptor(&method)?;
+        Ok(class)
+    }
 
-            let operands = self.stack.pop_operands(descriptor.parameters.len())?;
-            let code = method.code().context("method {method_name} has no code")?;
-            self.stack.push(
-                "initPhase1".to_string(),
-                descriptor,
-                operands,
-                Code::new(code.clone())?,
-                identifier.clone(),
-                None,
-            );
-            self.execute()?;
+    /// The `java.lang.Class` object of `identifier`, which is allocated on first use and the
+    /// same object afterwards
+    pub fn class_mirror(&mut self, identifier: &ClassIdentifier) -> Result<HeapId> {
+        if let Some(mirror) = self
+            .mirrors
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+            .get(identifier)
+        {
+            return Ok(mirror.clone());
+        }
+
+        let class_class = self.link(&ClassIdentifier::from_parts("java.lang", "Class"))?;
+        let fields = self.default_instance_fields(&class_class, 0)?;
+        let mirror = self
+            .heap
+            .write()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+            .allocate_class_mirror(identifier.clone(), fields);
+
+        // another thread may have allocated a mirror in the meantime, which wins
+        Ok(self
+            .mirrors
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+            .entry(identifier.clone())
+            .or_insert(mirror)
+            .clone())
+    }
+
+    /// Class represented by the `java.lang.Class` object `reference`
+    pub fn mirrored_class(&self, reference: &ReferenceValue) -> Result<ClassIdentifier> {
+        let heap = self
+            .heap
+            .read()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        heap.get(reference.heap_id()?)?.mirrored_class().cloned()
+    }
+
+    /// Class of the primitive type `name`, e.g. `int`. It has no class file, so one without any
+    /// members is defined on first use.
+    pub fn primitive_class(&mut self, name: &str) -> Result<ClassIdentifier> {
+        let identifier = ClassIdentifier::primitive(name)?;
+        if self.class(&identifier).is_err() {
+            let class_file = ClassFile {
+                minor_version: 0,
+                major_version: 61,
+                constant_pool: ConstantPool {
+                    infos: vec![
+                        CpInfo::Reserved,
+                        CpInfo::Utf8(name.to_string()),
+                        CpInfo::Class {
+                            name_index: CpIndex(1),
+                        },
+                    ],
+                },
+                access_flags: HashSet::from([
+                    AccessFlag::Public,
+                    AccessFlag::Final,
+                    AccessFlag::Abstract,
+                ]),
+                this_class: CpIndex(2),
+                super_class: CpIndex(0),
+                interfaces: vec![],
+                fields: vec![],
+                methods: vec![],
+                attributes: vec![],
+            };
+            self.define(identifier.clone(), class_file)?;
         }
+
+        self.initialize(&identifier)?;
+        Ok(identifier)
+    }
+
+    /// Links the class if needed and initializes it by running its static initializer, after
+    /// initializing its super class.
+    ///
+    /// Only one thread initializes a class, others block until it is done. A recursive request
+    /// of the initializing thread returns the class while it is still being initialized.
+    fn initialize(&mut self, identifier: &ClassIdentifier) -> Result<Class> {
+        self.link(identifier)?;
+
+        let thread_id = self.current_thread_id.clone();
+        let class = {
+            let mut classes = self
+                .classes
+                .lock()
+                .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+            loop {
+                let class = classes
+                    .get_mut(identifier)
+                    .context(format!("class {identifier:?} is not linked"))?;
+                match class.initialization_state() {
+                    InitializationState::Initialized => return Ok(class.clone()),
+                    InitializationState::BeingInitialized(owner) if owner == &thread_id => {
+                        return Ok(class.clone());
+                    }
+                    InitializationState::BeingInitialized(_) => {
+                        classes = self
+                            .class_initialized
+                            .wait(classes)
+                            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+                    }
+                    InitializationState::Erroneous => {
+                        bail!("class {identifier:?} is erroneous, TODO: throw NoClassDefFoundError")
+                    }
+                    InitializationState::Linked => {
+                        class.initializing(thread_id);
+                        break class.clone();
+                    }
+                }
+            }
+        };
+
+        info!("initializing {identifier:?}");
+        let result = self.run_initialization(class);
+
         let mut classes = self
             .classes
             .lock()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
-        classes
+        let class = classes
             .get_mut(identifier)
-            .context(format!("class {identifier:?} is not initialized"))?
-            .finished_initialization();
+            .context(format!("class {identifier:?} is not linked"))?;
+        if result.is_ok() {
+            class.finished_initialization();
+            info!("initialized {identifier:?}");
+        } else {
+            class.failed_initialization();
+        }
+        let class = class.clone();
+        self.class_initialized.notify_all();
 
-        info!("initialized {identifier:?}");
-        Ok(class)
+        result.map(|_| class)
     }
 
-    fn initialize_static_fields(&mut self, class: &mut Class) -> Result<()> {
-        for field in &class.fields().clone() {
-            if field.is_static() {
-                self.initialize_static_final_field(class, field)?;
-            }
+    fn run_initialization(&mut self, mut class: Class) -> Result<()> {
+        let identifier = class.identifier().clone();
+        self.initialize_constant_fields(&mut class)?;
+        self.insert_class(identifier.clone(), class.clone())?;
+
+        if class.has_super_class() {
+            let super_class_identifier = class.super_class()?;
+            self.initialize(&super_class_identifier)?;
+        }
+
+        self.execute_clinit(&class)?;
+        if let Some(exception) = self.exception.take() {
+            let error = self.initializer_error(exception)?;
+            self.throw(error);
+            return Err(InitializerThrew { class: identifier }.into());
+        }
+
+        if identifier == ClassIdentifier::from_parts("java.lang", "System") {
+            self.run_system_phase("initPhase1")?;
+            // TODO: run initPhase2(ZZ)I, which boots the module system. ModuleBootstrap reads the
+            // module descriptors from the run-time image, which needs the natives of the file
+            // system and of jimage.
+            self.run_system_phase("initPhase3")?;
+        }
+
+        Ok(())
+    }
+
+    /// The error thrown for `exception` escaping a static initializer, `exception` itself if it
+    /// is an `Error` and otherwise an `ExceptionInInitializerError` caused by it
+    fn initializer_error(&mut self, exception: HeapId) -> Result<HeapId> {
+        let exception_ref = ReferenceValue::HeapItem(exception.clone());
+        if self.is_instance(&exception_ref, "java/lang/Error")? {
+            return Ok(exception);
+        }
+
+        // creating the error may run code which collects garbage, the operand keeps the
+        // exception reachable meanwhile
+        self.stack
+            .push_operand(FrameValue::Reference(exception_ref.clone()))?;
+        let identifier = ClassIdentifier::from_parts("java.lang", "ExceptionInInitializerError");
+        let error = self.new_exception(&identifier, String::new());
+        self.stack.pop_operand()?;
+
+        let error = error?;
+        self.heap_set_field(
+            &error,
+            "detailMessage",
+            FieldValue::Reference(ReferenceValue::Null),
+        )?;
+        self.heap_set_field(&error, "cause", FieldValue::Reference(exception_ref))?;
+        Ok(error)
+    }
+
+    /// Runs the static bootstrap method `name()V` of `java.lang.System` like the JVM does after
+    /// creating it, if System declares it
+    fn run_system_phase(&mut self, name: &str) -> Result<()> {
+        let identifier = ClassIdentifier::from_parts("java.lang", "System");
+        let class = self.class(&identifier)?;
+        let Ok(method) = class.method(name, "()V") else {
+            debug!("skipping {name}, it doesn't exist in {identifier:?}");
+            return Ok(());
+        };
+
+        let descriptor = class.method_descriptor(method)?;
+        let code = class.code(method)?;
+        self.stack
+            .push(name.to_string(), descriptor, vec![], code, identifier, None)?;
+        self.execute()?;
+
+        if let Some(exception) = self.exception.take() {
+            let exception = self.heap_get(&exception)?.class_identifier()?;
+            bail!("{name} of java.lang.System threw {exception:?}");
         }
 
         Ok(())
     }
 
-    fn initialize_static_final_field(&mut self, class: &mut Class, field: &Field) -> Result<()> {
-        let name = class.utf8(&field.name_index)?.to_string();
+    /// Sets the static fields with a `ConstantValue` attribute to their constant
+    fn initialize_constant_fields(&mut self, class: &mut Class) -> Result<()> {
+        for field in &class.fields().clone() {
+            let Some(constant_value_index) = field.get_constant_value_index() else {
+                continue;
+            };
+
+            if !field.is_static() {
+                continue;
+            }
 
-        trace!("initializing field {name}");
+            let name = class.utf8(&field.name_index)?.to_string();
+            trace!("initializing field {name}");
 
-        let field_value = if let Some(constant_value_index) = field.get_constant_value_index() {
-            match class.cp_item(constant_value_index)? {
+            let field_value = match class.cp_item(constant_value_index)? {
                 CpInfo::String { string_index } => {
                     let value = class.utf8(string_index)?;
-                    let heap_id = self.new_string(value.to_string())?;
+                    let heap_id = self.intern_string(value.to_string())?;
                     FieldValue::Reference(ReferenceValue::HeapItem(heap_id))
                 }
                 CpInfo::Integer(val) => FieldValue::Integer(*val),
@@ -467,28 +1193,26 @@ impl JvmThread {
                 CpInfo::Float(val) => FieldValue::Float(*val),
                 CpInfo::Double(val) => FieldValue::Double(*val),
                 item => bail!("invalid constant pool item: {item:?}"),
-            }
-        } else {
-            FieldDescriptor::new(class.utf8(&field.descriptor_index)?)?.into()
-        };
+            };
+
+            class.set_static_field(&name, field_value)?;
+        }
 
-        class.set_static_field(&name, field_value)
+        Ok(())
     }
 
     fn execute_clinit(&mut self, class: &Class) -> Result<()> {
         if let Ok(clinit_method) = class.method("<clinit>", "()V") {
             let descriptor = class.method_descriptor(clinit_method)?;
-            let code = clinit_method
-                .code()
-                .context("no code found for <clinit> method")?;
+            let code = class.code(clinit_method)?;
             self.stack.push(
                 "<clinit>".to_string(),
                 descriptor,
                 vec![],
-                Code::new(code.clone())?,
+                code,
                 class.identifier().clone(),
                 None,
-            );
+            )?;
             info!("running <clinit> for {:?}", class.identifier());
             self.execute()?;
         }
@@ -496,8 +1220,23 @@ impl JvmThread {
         Ok(())
     }
 
-    #[instrument(level = "debug", name = "", skip(self), fields(c = %self.stack.current_class()?))]
+    /// Runs the current frame until it returns, keeping track of the nesting of interpreter
+    /// loops for [`Self::may_collect_garbage`]
     fn execute(&mut self) -> Result<()> {
+        self.execute_depth += 1;
+        if self.execute_depth == 1 {
+            self.executing_threads.fetch_add(1, Ordering::SeqCst);
+        }
+        let result = self.execute_frames();
+        if self.execute_depth == 1 {
+            self.executing_threads.fetch_sub(1, Ordering::SeqCst);
+        }
+        self.execute_depth -= 1;
+        result
+    }
+
+    #[instrument(level = "debug", name = "", skip(self), fields(c = %self.stack.current_class()?))]
+    fn execute_frames(&mut self) -> Result<()> {
         info!(
             "running {} {:?} in {:?}",
             self.stack.method_name()?,
@@ -505,17 +1244,47 @@ impl JvmThread {
             self.stack.current_class()?,
         );
         loop {
+            if self.may_collect_garbage()
+                && self
+                    .heap
+                    .read()
+                    .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+                    .should_collect()
+            {
+                self.collect_garbage()?;
+            }
+
             let instruction = self.stack.current_instruction()?;
             debug!("executing {instruction:?}");
-            match instruction {
+            if let Some(counter) = &self.instruction_counter {
+                counter.record(&instruction);
+            }
+            if let Some(hook) = self.execution_hook.as_mut() {
+                hook.before_instruction(
+                    self.stack.current_class()?,
+                    self.stack.pc()?,
+                    &instruction,
+                );
+            }
+            match *instruction.operation() {
                 Instruction::Ldc(ref index) | Instruction::LdcW(ref index) => {
                     self.ldc(index)?;
                 }
-                Instruction::InvokeVirtual(ref index) => self.invoke_virtual(index)?,
-                Instruction::InvokeStatic(ref index) => self.invoke_static(index)?,
+                Instruction::InvokeVirtual(ref index) => {
+                    let result = self.invoke_virtual(index);
+                    self.throw_stack_overflow(result)?
+                }
+                Instruction::InvokeStatic(ref index) => {
+                    let result = self.invoke_static(index);
+                    let result = self.throw_stack_overflow(result);
+                    self.throw_initializer_error(result)?
+                }
                 Instruction::Iconst(val) => self.stack.push_operand(FrameValue::Int(val.into()))?,
                 Instruction::Anewarray(ref index) => self.a_new_array(index)?,
-                Instruction::PutStatic(ref index) => self.put_static(index)?,
+                Instruction::PutStatic(ref index) => {
+                    let result = self.put_static(index);
+                    self.throw_initializer_error(result)?
+                }
                 Instruction::Return => {
                     self.handle_synchronized_return()?;
                     self.stack.pop()?;
@@ -529,10 +1298,16 @@ impl JvmThread {
                 Instruction::GetField(ref index) => self.get_field(index)?,
                 Instruction::Astore(index) => self.astore(index)?,
                 Instruction::IfNull(offset) => self.if_null(offset)?,
-                Instruction::New(ref index) => self.new_instruction(index)?,
+                Instruction::New(ref index) => {
+                    let result = self.new_instruction(index);
+                    self.throw_initializer_error(result)?
+                }
                 Instruction::Dup => self.dup()?,
                 Instruction::Dup2 => self.dup2()?,
-                Instruction::InvokeSpecial(ref index) => self.invoke_special(index)?,
+                Instruction::InvokeSpecial(ref index) => {
+                    let result = self.invoke_special(index);
+                    self.throw_stack_overflow(result)?
+                }
                 Instruction::Areturn => {
                     self.handle_synchronized_return()?;
                     let object_ref = self.stack.pop_operand()?;
@@ -541,15 +1316,18 @@ impl JvmThread {
                     self.stack.push_operand(object_ref)?;
                     break;
                 }
-                Instruction::Dreturn => {
+                Instruction::Freturn | Instruction::Dreturn => {
                     self.handle_synchronized_return()?;
-                    let double = self.stack.pop_operand()?;
+                    let value = self.stack.pop_operand()?;
                     self.stack.pop()?;
-                    info!("returning {double:?}");
-                    self.stack.push_operand(double)?;
+                    info!("returning {value:?}");
+                    self.stack.push_operand(value)?;
                     break;
                 }
-                Instruction::InvokeDynamic(ref index) => self.invoke_dynamic(index)?,
+                Instruction::InvokeDynamic(ref index) => {
+                    let result = self.invoke_dynamic(index);
+                    self.throw_stack_overflow(result)?
+                }
                 Instruction::IfNonNull(offset) => self.if_non_null(offset)?,
                 Instruction::Ireturn => {
                     self.handle_synchronized_return()?;
@@ -557,7 +1335,10 @@ impl JvmThread {
                     break;
                 }
                 Instruction::IfNe(offset) => self.if_ne(offset)?,
-                Instruction::GetStatic(ref index) => self.get_static(index)?,
+                Instruction::GetStatic(ref index) => {
+                    let result = self.get_static(index);
+                    self.throw_initializer_error(result)?
+                }
                 Instruction::PutField(ref index) => self.put_field(index)?,
                 Instruction::Iload(index) => self.iload(index)?,
                 Instruction::AconstNull => self
@@ -571,6 +1352,10 @@ impl JvmThread {
                 Instruction::Castore => self.castore()?,
                 Instruction::Bastore => self.bastore()?,
                 Instruction::Iastore => self.iastore()?,
+                Instruction::Lastore => self.lastore()?,
+                Instruction::Fastore => self.fastore()?,
+                Instruction::Dastore => self.dastore()?,
+                Instruction::Sastore => self.sastore()?,
                 Instruction::Sipush(value) => {
                     self.stack.push_operand(FrameValue::Int(value.into()))?
                 }
@@ -586,6 +1371,7 @@ impl JvmThread {
                 Instruction::Land => self.land()?,
                 Instruction::Ifeq(offset) => self.if_eq(offset)?,
                 Instruction::Goto(offset) => self.stack.offset_pc(offset as i32)?,
+                Instruction::GotoW(offset) => self.stack.offset_pc(offset)?,
                 Instruction::Ifgt(offset) => self.if_gt(offset)?,
                 Instruction::Fload0 => self.fload(0)?,
                 Instruction::Fload1 => self.fload(1)?,
@@ -598,6 +1384,8 @@ impl JvmThread {
                 Instruction::Fconst(val) => self.stack.push_operand(FrameValue::Float(val))?,
                 Instruction::Fcmpl => self.fcmpl()?,
                 Instruction::Fcmpg => self.fcmpg()?,
+                Instruction::Dcmpl => self.dcmpl()?,
+                Instruction::Dcmpg => self.dcmpg()?,
                 Instruction::Ifle(offset) => self.if_le(offset)?,
                 Instruction::Iflt(offset) => self.if_lt(offset)?,
                 Instruction::IfIcmpge(offset) => self.if_icmpge(offset)?,
@@ -605,10 +1393,22 @@ impl JvmThread {
                 Instruction::I2l => self.i2l()?,
                 Instruction::I2f => self.i2f()?,
                 Instruction::L2f => self.l2f()?,
+                Instruction::I2d => self.i2d()?,
+                Instruction::L2d => self.l2d()?,
+                Instruction::F2l => self.f2l()?,
+                Instruction::D2i => self.d2i()?,
+                Instruction::D2f => self.d2f()?,
                 Instruction::Fdiv => self.fdiv()?,
+                Instruction::Frem => self.frem()?,
+                Instruction::Fneg => self.fneg()?,
                 Instruction::F2d => self.f2d()?,
                 Instruction::F2i => self.f2i()?,
                 Instruction::Dadd => self.dadd()?,
+                Instruction::Dsub => self.dsub()?,
+                Instruction::Dmul => self.dmul()?,
+                Instruction::Ddiv => self.ddiv()?,
+                Instruction::Drem => self.drem()?,
+                Instruction::Dneg => self.dneg()?,
                 Instruction::Fadd => self.fadd()?,
                 Instruction::D2l => self.d2l()?,
                 Instruction::Lstore(index) => self.lstore(index)?,
@@ -633,6 +1433,12 @@ impl JvmThread {
                 Instruction::Lshl => self.lshl()?,
                 Instruction::Ishl => self.ishl()?,
                 Instruction::Baload => self.baload()?,
+                Instruction::Iaload => self.iaload()?,
+                Instruction::Laload => self.laload()?,
+                Instruction::Faload => self.faload()?,
+                Instruction::Daload => self.daload()?,
+                Instruction::Caload => self.caload()?,
+                Instruction::Saload => self.saload()?,
                 Instruction::Aaload => self.aaload()?,
                 Instruction::I2c => self.i2c()?,
                 Instruction::I2b => self.i2b()?,
@@ -660,19 +1466,53 @@ impl JvmThread {
                 Instruction::Lload2 => self.lload(2)?,
                 Instruction::Lload3 => self.lload(3)?,
                 Instruction::Lmul => self.lmul()?,
+                Instruction::Ldiv => self.ldiv()?,
+                Instruction::Lrem => self.lrem()?,
+                Instruction::Lneg => self.lneg()?,
+                Instruction::Ior => self.ior()?,
+                Instruction::Lor => self.lor()?,
+                Instruction::Lxor => self.lxor()?,
                 Instruction::Imul => self.imul()?,
                 Instruction::Fmul => self.fmul()?,
                 Instruction::InvokeInterface(ref index, count) => {
-                    self.invoke_interface(index, count)?
+                    let result = self.invoke_interface(index, count);
+                    self.throw_stack_overflow(result)?
                 }
                 Instruction::Pop => self.pop()?,
                 Instruction::Ixor => self.ixor()?,
                 Instruction::DupX1 => self.dup_x1()?,
+                Instruction::DupX2 => self.dup_x2()?,
+                Instruction::Dup2X1 => self.dup2_x1()?,
+                Instruction::Dup2X2 => self.dup2_x2()?,
+                Instruction::Pop2 => self.pop2()?,
+                Instruction::Swap => self.swap()?,
                 Instruction::MonitorEnter => self.monitor_enter()?,
                 Instruction::MonitorExit => self.monitor_exit()?,
+                Instruction::Athrow => self.athrow()?,
+                Instruction::Wide(_) => bail!("wide instructions cannot be nested"),
                 Instruction::Irem => self.irem()?,
                 Instruction::Idiv => self.idiv()?,
                 Instruction::Ineg => self.ineg()?,
+                Instruction::Nop => {}
+                Instruction::Dload(index) => self.dload(index)?,
+                Instruction::Dload0 => self.dload(0)?,
+                Instruction::Dload1 => self.dload(1)?,
+                Instruction::Dload2 => self.dload(2)?,
+                Instruction::Dload3 => self.dload(3)?,
+                Instruction::Dstore(index) => self.dstore(index)?,
+                Instruction::Dstore0 => self.dstore(0)?,
+                Instruction::Dstore1 => self.dstore(1)?,
+                Instruction::Dstore2 => self.dstore(2)?,
+                Instruction::Dstore3 => self.dstore(3)?,
+                Instruction::Fstore0 => self.fstore(0)?,
+                Instruction::Fstore1 => self.fstore(1)?,
+                Instruction::Fstore2 => self.fstore(2)?,
+                Instruction::Fstore3 => self.fstore(3)?,
+                Instruction::Fsub => self.fsub()?,
+                Instruction::I2s => self.i2s()?,
+                Instruction::Multianewarray(ref index, dimensions) => {
+                    self.multi_a_new_array(index, dimensions)?
+                }
                 Instruction::TableSwitch {
                     default,
                     low,
@@ -687,6 +1527,14 @@ impl JvmThread {
                 } => self.lookup_switch(default, offset_pairs)?,
             }
 
+            if let Some(exception) = self.exception.take() {
+                if self.handle_exception(exception)? {
+                    continue;
+                }
+
+                break;
+            }
+
             if !instruction.is_jump() {
                 self.stack.offset_pc(instruction.length() as i32)?;
             }
@@ -695,6 +1543,174 @@ impl JvmThread {
         Ok(())
     }
 
+    /// Throws `exception`, it is dispatched once the current instruction has been executed
+    pub fn throw(&mut self, exception: HeapId) {
+        self.exception = Some(exception);
+    }
+
+    /// Throws a new `java.lang` exception of class `name` with `message` as its detail message
+    pub fn throw_new(&mut self, name: &str, message: String) -> Result<()> {
+        let identifier = ClassIdentifier::from_parts("java.lang", name);
+        let exception = self.new_exception(&identifier, message)?;
+        self.throw(exception);
+        Ok(())
+    }
+
+    /// Throws a `StackOverflowError` if `result` failed because a frame was pushed onto a full
+    /// stack. Like with HotSpot's reserved stack zone, initializing the error's class may push
+    /// a few frames beyond the limit.
+    fn throw_stack_overflow(&mut self, result: Result<()>) -> Result<()> {
+        match result {
+            Err(err) if err.downcast_ref::<StackOverflow>().is_some() => {
+                let max_frames = self.stack.max_frames();
+                self.stack.set_max_frames(max_frames + RESERVED_FRAMES);
+                let identifier = ClassIdentifier::from_parts("java.lang", "StackOverflowError");
+                let error = self.new_exception(&identifier, String::new());
+                self.stack.set_max_frames(max_frames);
+
+                // HotSpot throws it without a message
+                let error = error?;
+                self.heap_set_field(
+                    &error,
+                    "detailMessage",
+                    FieldValue::Reference(ReferenceValue::Null),
+                )?;
+                self.throw(error);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Keeps the error pending if `result` failed because the static initializer of a class
+    /// threw, so it is thrown by the instruction which initialized the class
+    fn throw_initializer_error(&mut self, result: Result<()>) -> Result<()> {
+        match result {
+            Err(err) if err.downcast_ref::<InitializerThrew>().is_some() => {
+                if self.exception.is_none() {
+                    return Err(err);
+                }
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// Throws an `ArrayIndexOutOfBoundsException` if `result` failed because an array was
+    /// accessed outside of its bounds
+    fn throw_out_of_bounds(&mut self, result: Result<()>) -> Result<()> {
+        match result {
+            Err(err) => match err.downcast_ref::<ArrayIndexOutOfBounds>() {
+                Some(out_of_bounds) => {
+                    self.throw_new("ArrayIndexOutOfBoundsException", out_of_bounds.to_string())
+                }
+                None => Err(err),
+            },
+            ok => ok,
+        }
+    }
+
+    /// Index into an array of `length` elements, fails with [`ArrayIndexOutOfBounds`] if it is
+    /// negative or not smaller than `length`
+    fn array_index(index: i32, length: usize) -> Result<usize> {
+        match usize::try_from(index) {
+            Ok(i) if i < length => Ok(i),
+            _ => Err(ArrayIndexOutOfBounds {
+                index: index.into(),
+                length,
+            }
+            .into()),
+        }
+    }
+
+    /// Error for a negative `index` into the array at `heap_id`, the heap only checks the upper bound
+    fn negative_index(&self, heap_id: &HeapId, index: i32) -> Result<anyhow::Error> {
+        Ok(ArrayIndexOutOfBounds {
+            index: index.into(),
+            length: self.get_array_length(heap_id)?,
+        }
+        .into())
+    }
+
+    /// Stores `value` at `index` of the array at `heap_id`, throwing an
+    /// `ArrayIndexOutOfBoundsException` if it is out of bounds
+    fn store_primitive_element(
+        &mut self,
+        heap_id: &HeapId,
+        index: i32,
+        value: PrimitiveArrayValue,
+    ) -> Result<()> {
+        let result = match usize::try_from(index) {
+            Ok(index) => self.store_into_primitive_array(heap_id, index, value),
+            Err(_) => Err(self.negative_index(heap_id, index)?),
+        };
+        self.throw_out_of_bounds(result)
+    }
+
+    /// Reference array counterpart of [`Self::store_primitive_element`]
+    fn store_reference_element(
+        &mut self,
+        heap_id: &HeapId,
+        index: i32,
+        value: ReferenceValue,
+    ) -> Result<()> {
+        let result = match usize::try_from(index) {
+            Ok(index) => self.store_into_reference_array(heap_id, index, value),
+            Err(_) => Err(self.negative_index(heap_id, index)?),
+        };
+        self.throw_out_of_bounds(result)
+    }
+
+    /// Throws a `NullPointerException` for `action` attempted on `null`, e.g.
+    /// `Cannot read field "value"`
+    fn throw_null_pointer(&mut self, action: &str) -> Result<()> {
+        self.throw_new("NullPointerException", action.to_string())
+    }
+
+    fn athrow(&mut self) -> Result<()> {
+        let operand = self.stack.pop_operand()?;
+        let objectref = operand.reference()?;
+        if objectref.is_null() {
+            return self.throw_null_pointer("Cannot throw exception");
+        }
+
+        self.throw(objectref.heap_id()?.clone());
+        Ok(())
+    }
+
+    /// Transfers control to the first matching exception handler of the current frame.
+    /// If there is none, the frame is popped and the exception is rethrown in the caller.
+    fn handle_exception(&mut self, exception: HeapId) -> Result<bool> {
+        let exception_identifier = self.heap_get(&exception)?.class_identifier()?;
+        let current_class = self.current_class()?;
+
+        for handler in self.stack.exception_handlers()? {
+            if handler.catch_type != 0 {
+                let catch_type = current_class.class_identifier(&handler.catch_type.into())?;
+                if !self.is_subclass(&exception_identifier, &catch_type)? {
+                    continue;
+                }
+            }
+
+            debug!(
+                "caught {exception_identifier:?} in {}, continuing at {}",
+                self.stack.method_name()?,
+                handler.handler_pc
+            );
+            self.stack.enter_exception_handler(&handler, exception)?;
+            return Ok(true);
+        }
+
+        debug!(
+            "no handler for {exception_identifier:?} in {}",
+            self.stack.method_name()?
+        );
+        self.handle_synchronized_return()?;
+        self.stack.pop()?;
+        self.throw(exception);
+        Ok(false)
+    }
+
     fn lookup_switch(&mut self, default: i32, offset_pairs: &[(i32, i32)]) -> Result<()> {
         let key = self.stack.pop_operand()?.int()?;
 
@@ -728,30 +1744,44 @@ impl JvmThread {
 
     fn ineg(&mut self) -> Result<()> {
         let value = self.stack.pop_operand()?.int()?;
-        self.stack.push_operand(FrameValue::Int(-value))
+        self.stack
+            .push_operand(FrameValue::Int(value.wrapping_neg()))
+    }
+
+    /// Throws the `ArithmeticException` of an integer division by zero
+    fn throw_division_by_zero(&mut self) -> Result<()> {
+        self.throw_new("ArithmeticException", "/ by zero".to_string())
     }
 
     fn idiv(&mut self) -> Result<()> {
         let value2 = self.stack.pop_operand()?.int()?;
         let value1 = self.stack.pop_operand()?.int()?;
+        if value2 == 0 {
+            return self.throw_division_by_zero();
+        }
 
-        self.stack.push_operand(FrameValue::Int(value1 / value2))
+        self.stack
+            .push_operand(FrameValue::Int(value1.wrapping_div(value2)))
     }
 
     fn irem(&mut self) -> Result<()> {
         let value2 = self.stack.pop_operand()?.int()?;
         let value1 = self.stack.pop_operand()?.int()?;
         if value2 == 0 {
-            bail!("TODO: ArithmeticException")
+            return self.throw_division_by_zero();
         }
 
-        let result = value1 - (value1 / value2) * value2;
-        self.stack.push_operand(FrameValue::Int(result))
+        self.stack
+            .push_operand(FrameValue::Int(value1.wrapping_rem(value2)))
     }
 
     fn monitor_exit(&mut self) -> Result<()> {
         let operand = self.stack.pop_operand()?;
         let objectref = operand.reference()?;
+        if objectref.is_null() {
+            return self.throw_null_pointer("Cannot exit synchronized block");
+        }
+
         let heap_id = objectref.heap_id()?;
         let thread_id = self
             .current_thread_id
@@ -763,16 +1793,16 @@ impl JvmThread {
     fn monitor_enter(&mut self) -> Result<()> {
         let operand = self.stack.pop_operand()?;
         let objectref = operand.reference()?;
+        if objectref.is_null() {
+            return self.throw_null_pointer("Cannot enter synchronized block");
+        }
+
         let heap_id = objectref.heap_id()?;
         let thread_id = self
             .current_thread_id
             .clone()
             .context("how do we not have a thread id?")?;
-        if !self.enter_object_monitor(heap_id, &thread_id)? {
-            bail!("TODO: wait for monitor to be available")
-        }
-
-        Ok(())
+        self.enter_object_monitor(heap_id, &thread_id)
     }
 
     fn pop(&mut self) -> Result<()> {
@@ -800,29 +1830,24 @@ impl JvmThread {
         }
 
         let current_class = self.current_class()?;
+        let target = match current_class.cp_item(index)? {
+            CpInfo::Class { name_index } => current_class.utf8(name_index)?,
+            item => bail!("invalid checkcast type {item:?}"),
+        };
+        self.resolve_class_name(target)?;
 
-        match current_class.cp_item(index)? {
-            CpInfo::Class { name_index } => {
-                let identifier = ClassIdentifier::parse(current_class.utf8(name_index)?)?;
-                let class = self.resolve_class(&identifier)?;
-                let heap_item = self.heap_get(object_ref.heap_id()?)?.clone();
-                let object_identifier = heap_item.class_identifier()?;
-
-                if class.is_interface() {
-                    let object_ref_class = self.resolve_class(&object_identifier)?;
-                    if object_ref_class.implements(&identifier)? {
-                        return self.stack.push_operand(operand);
-                    }
-                }
-
-                if object_identifier == identifier {
-                    return self.stack.push_operand(operand);
-                }
-
-                bail!("TODO: return false?");
-            }
-            item => bail!("invalid instanceof type {item:?}"),
+        if self.is_instance(object_ref, target)? {
+            return self.stack.push_operand(operand);
         }
+
+        let object_identifier = self.heap_get(object_ref.heap_id()?)?.class_identifier()?;
+        self.throw_new(
+            "ClassCastException",
+            format!(
+                "class {object_identifier:?} cannot be cast to class {}",
+                target.replace('/', ".")
+            ),
+        )
     }
 
     fn instance_of(&mut self, index: &CpIndex) -> Result<()> {
@@ -834,45 +1859,69 @@ impl JvmThread {
         }
 
         let current_class = self.current_class()?;
+        let target = match current_class.cp_item(index)? {
+            CpInfo::Class { name_index } => current_class.utf8(name_index)?,
+            item => bail!("invalid instanceof type {item:?}"),
+        };
+        self.resolve_class_name(target)?;
 
-        let heap_item = self.heap_get(object_ref.heap_id()?)?.clone();
-        if heap_item.is_array() {
-            bail!("TOOD: instanceof for arrays")
-        }
-
-        match current_class.cp_item(index)? {
-            CpInfo::Class { name_index } => {
-                let identifier = ClassIdentifier::parse(current_class.utf8(name_index)?)?;
-                let class = self.resolve_class(&identifier)?;
-                let object_identifier = heap_item.class_identifier()?;
+        let is_instance = self.is_instance(object_ref, target)?;
+        self.stack.push_operand(FrameValue::Int(is_instance.into()))
+    }
 
-                if class.is_interface() {
-                    bail!("TODO: instanceof check with interface")
-                }
+    /// Checks if the non-null `object_ref` is an instance of `target`,
+    /// which is a class or array type as named in the constant pool
+    pub fn is_instance(&mut self, object_ref: &ReferenceValue, target: &str) -> Result<bool> {
+        self.is_instance_of(object_ref, &ClassIdentifier::new(target)?)
+    }
 
-                if object_identifier == identifier {
-                    return self.stack.push_operand(FrameValue::Int(1));
-                }
+    /// Checks if the non-null `object_ref` is an instance of the class or array type `target`
+    pub fn is_instance_of(
+        &mut self,
+        object_ref: &ReferenceValue,
+        target: &ClassIdentifier,
+    ) -> Result<bool> {
+        let class_identifier = self.heap_get(object_ref.heap_id()?)?.class_identifier()?;
+        self.is_subclass(&class_identifier, target)
+    }
 
-                if self.has_super_class(&class, &identifier)? {
-                    return self.stack.push_operand(FrameValue::Int(1));
-                }
+    /// Checks if `identifier` is `target` or extends/implements it, directly or transitively.
+    /// Arrays are subclasses of arrays with assignable components and of the types every
+    /// array implements, primitive classes only of themselves.
+    fn is_subclass(
+        &mut self,
+        identifier: &ClassIdentifier,
+        target: &ClassIdentifier,
+    ) -> Result<bool> {
+        if identifier == target {
+            return Ok(true);
+        }
+        if identifier.is_primitive() || target.is_primitive() {
+            return Ok(false);
+        }
 
-                self.stack.push_operand(FrameValue::Int(0))
-            }
-            item => bail!("invalid instanceof type {item:?}"),
+        if let Some(component) = identifier.component() {
+            return match target.component() {
+                Some(target_component) => self.is_subclass(&component, &target_component),
+                None => Ok(matches!(
+                    target.java_name().as_str(),
+                    "java.lang.Object" | "java.lang.Cloneable" | "java.io.Serializable"
+                )),
+            };
+        }
+        if target.is_array() {
+            return Ok(false);
         }
-    }
 
-    fn has_super_class(&mut self, class: &Class, identifier: &ClassIdentifier) -> Result<bool> {
-        if class.has_super_class() {
-            let super_class = class.super_class()?;
-            if &super_class == identifier {
+        let class = self.resolve_class(identifier)?;
+        for interface in class.super_interfaces()? {
+            if self.is_subclass(&interface, target)? {
                 return Ok(true);
             }
+        }
 
-            let class = self.resolve_class(&super_class)?;
-            return self.has_super_class(&class, identifier);
+        if class.has_super_class() {
+            return self.is_subclass(&class.super_class()?, target);
         }
 
         Ok(false)
@@ -884,13 +1933,14 @@ impl JvmThread {
         let arrayref = arrayref_operand.reference()?;
 
         if arrayref.is_null() {
-            bail!("TODO: throw NullPointerException")
+            return self.throw_null_pointer("Cannot load from byte/boolean array");
         }
 
         let (_, values) = self.get_primitive_array(arrayref.heap_id()?)?;
-        let array_value = values
-            .get(index as usize)
-            .context("no array value at index {index}")?;
+        let array_value = match Self::array_index(index, values.len()) {
+            Ok(index) => &values[index],
+            Err(err) => return self.throw_out_of_bounds(Err(err)),
+        };
 
         let value = match array_value {
             PrimitiveArrayValue::Boolean(val) => FrameValue::Int((*val).into()),
@@ -901,46 +1951,145 @@ impl JvmThread {
         self.stack.push_operand(value)
     }
 
-    fn aaload(&mut self) -> Result<()> {
+    /// Element at the operands of a primitive array load, `None` if it is out of bounds and an
+    /// `ArrayIndexOutOfBoundsException` has been thrown
+    fn primitive_array_load(&mut self) -> Result<Option<PrimitiveArrayValue>> {
         let index = self.stack.pop_operand()?.int()?;
         let arrayref_operand = self.stack.pop_operand()?;
         let arrayref = arrayref_operand.reference()?;
 
         if arrayref.is_null() {
-            bail!("TODO: throw NullPointerException")
+            self.throw_null_pointer("Cannot load from array")?;
+            return Ok(None);
         }
 
-        let values = self.get_reference_array(arrayref.heap_id()?)?;
-        let reference = values
-            .get(index as usize)
-            .context(format!("no array value at index {index}"))?;
-
-        self.stack
-            .push_operand(FrameValue::Reference(reference.clone()))
+        let (_, values) = self.get_primitive_array(arrayref.heap_id()?)?;
+        match Self::array_index(index, values.len()) {
+            Ok(index) => Ok(Some(values[index].clone())),
+            Err(err) => {
+                self.throw_out_of_bounds(Err(err))?;
+                Ok(None)
+            }
+        }
     }
 
-    fn array_length(&mut self) -> Result<()> {
-        let operand = self.stack.pop_operand()?;
-        let heap_id = operand.reference()?.heap_id()?;
-        let len = self.get_array_length(heap_id)?;
-        debug!("array_length {len}");
-        self.stack.push_operand(FrameValue::Int(len as i32))
+    fn iaload(&mut self) -> Result<()> {
+        let Some(value) = self.primitive_array_load()? else {
+            return Ok(());
+        };
+        let value = match value {
+            PrimitiveArrayValue::Int(val) => FrameValue::Int(val),
+            val => bail!("iaload array value must be int, is {val:?}"),
+        };
+
+        self.stack.push_operand(value)
     }
 
-    fn iushr(&mut self) -> Result<()> {
-        let value2 = self.stack.pop_operand()?.int()?;
-        let value1 = self.stack.pop_operand()?.int()?;
+    fn laload(&mut self) -> Result<()> {
+        let Some(value) = self.primitive_array_load()? else {
+            return Ok(());
+        };
+        let value = match value {
+            PrimitiveArrayValue::Long(val) => FrameValue::Long(val),
+            val => bail!("laload array value must be long, is {val:?}"),
+        };
 
-        let result = ((value1 as u32) >> (value2 & 31)) as i32;
-        self.stack.push_operand(FrameValue::Int(result))
+        self.stack.push_operand(value)
     }
 
-    fn lushr(&mut self) -> Result<()> {
-        let value2 = self.stack.pop_operand()?.int()?;
-        let value1 = self.stack.pop_operand()?.long()?;
+    fn faload(&mut self) -> Result<()> {
+        let Some(value) = self.primitive_array_load()? else {
+            return Ok(());
+        };
+        let value = match value {
+            PrimitiveArrayValue::Float(val) => FrameValue::Float(val),
+            val => bail!("faload array value must be float, is {val:?}"),
+        };
 
-        let result = ((value1 as u64) >> (value2 & 63)) as i64;
-        self.stack.push_operand(FrameValue::Long(result))
+        self.stack.push_operand(value)
+    }
+
+    fn daload(&mut self) -> Result<()> {
+        let Some(value) = self.primitive_array_load()? else {
+            return Ok(());
+        };
+        let value = match value {
+            PrimitiveArrayValue::Double(val) => FrameValue::Double(val),
+            val => bail!("daload array value must be double, is {val:?}"),
+        };
+
+        self.stack.push_operand(value)
+    }
+
+    fn caload(&mut self) -> Result<()> {
+        let Some(value) = self.primitive_array_load()? else {
+            return Ok(());
+        };
+        let value = match value {
+            PrimitiveArrayValue::Char(val) => FrameValue::Int(val.into()),
+            val => bail!("caload array value must be char, is {val:?}"),
+        };
+
+        self.stack.push_operand(value)
+    }
+
+    fn saload(&mut self) -> Result<()> {
+        let Some(value) = self.primitive_array_load()? else {
+            return Ok(());
+        };
+        let value = match value {
+            PrimitiveArrayValue::Short(val) => FrameValue::Int((val as i16).into()),
+            val => bail!("saload array value must be short, is {val:?}"),
+        };
+
+        self.stack.push_operand(value)
+    }
+
+    fn aaload(&mut self) -> Result<()> {
+        let index = self.stack.pop_operand()?.int()?;
+        let arrayref_operand = self.stack.pop_operand()?;
+        let arrayref = arrayref_operand.reference()?;
+
+        if arrayref.is_null() {
+            return self.throw_null_pointer("Cannot load from object array");
+        }
+
+        let values = self.get_reference_array(arrayref.heap_id()?)?;
+        let reference = match Self::array_index(index, values.len()) {
+            Ok(index) => &values[index],
+            Err(err) => return self.throw_out_of_bounds(Err(err)),
+        };
+
+        self.stack
+            .push_operand(FrameValue::Reference(reference.clone()))
+    }
+
+    fn array_length(&mut self) -> Result<()> {
+        let operand = self.stack.pop_operand()?;
+        if operand.reference()?.is_null() {
+            return self.throw_null_pointer("Cannot read the array length");
+        }
+
+        let heap_id = operand.reference()?.heap_id()?;
+        let len = self.get_array_length(heap_id)?;
+        debug!("array_length {len}");
+        self.stack.push_operand(FrameValue::Int(len as i32))
+    }
+
+    fn iushr(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.int()?;
+        let value1 = self.stack.pop_operand()?.int()?;
+
+        let result = ((value1 as u32) >> (value2 & 31)) as i32;
+        self.stack.push_operand(FrameValue::Int(result))
+    }
+
+    fn lushr(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.int()?;
+        let value1 = self.stack.pop_operand()?.long()?;
+
+        let result = ((value1 as u64) >> (value2 & 63)) as i64;
+        self.stack.push_operand(FrameValue::Long(result))
     }
 
     fn ishr(&mut self) -> Result<()> {
@@ -975,10 +2124,12 @@ impl JvmThread {
         self.stack.push_operand(FrameValue::Int(result))
     }
 
-    fn iinc(&mut self, index: usize, constant: i8) -> Result<()> {
+    fn iinc(&mut self, index: usize, constant: i16) -> Result<()> {
         let local_variable = self.stack.local_variable(index)?.int()?;
-        self.stack
-            .set_local_variable(index, FrameValue::Int(local_variable + constant as i32))
+        self.stack.set_local_variable(
+            index,
+            FrameValue::Int(local_variable.wrapping_add(constant.into())),
+        )
     }
 
     fn imul(&mut self) -> Result<()> {
@@ -1001,6 +2152,52 @@ impl JvmThread {
             .push_operand(FrameValue::Long(value1.wrapping_mul(value2)))
     }
 
+    fn ldiv(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.long()?;
+        let value1 = self.stack.pop_operand()?.long()?;
+        if value2 == 0 {
+            return self.throw_division_by_zero();
+        }
+
+        self.stack
+            .push_operand(FrameValue::Long(value1.wrapping_div(value2)))
+    }
+
+    fn lrem(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.long()?;
+        let value1 = self.stack.pop_operand()?.long()?;
+        if value2 == 0 {
+            return self.throw_division_by_zero();
+        }
+
+        self.stack
+            .push_operand(FrameValue::Long(value1.wrapping_rem(value2)))
+    }
+
+    fn lneg(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.long()?;
+        self.stack
+            .push_operand(FrameValue::Long(value.wrapping_neg()))
+    }
+
+    fn ior(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.int()?;
+        let value1 = self.stack.pop_operand()?.int()?;
+        self.stack.push_operand(FrameValue::Int(value1 | value2))
+    }
+
+    fn lor(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.long()?;
+        let value1 = self.stack.pop_operand()?.long()?;
+        self.stack.push_operand(FrameValue::Long(value1 | value2))
+    }
+
+    fn lxor(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.long()?;
+        let value1 = self.stack.pop_operand()?.long()?;
+        self.stack.push_operand(FrameValue::Long(value1 ^ value2))
+    }
+
     fn lcmp(&mut self) -> Result<()> {
         let value2 = self.stack.pop_operand()?.long()?;
         let value1 = self.stack.pop_operand()?.long()?;
@@ -1016,7 +2213,7 @@ impl JvmThread {
         self.stack.push_operand(value)
     }
 
-    fn lload(&mut self, index: u8) -> Result<()> {
+    fn lload(&mut self, index: u16) -> Result<()> {
         let value = self.stack.local_variable(index.into())?;
         if value.long().is_err() {
             bail!("lload can only load longs, is {value:?}")
@@ -1025,7 +2222,7 @@ impl JvmThread {
         self.stack.push_operand(value)
     }
 
-    fn lstore(&mut self, index: u8) -> Result<()> {
+    fn lstore(&mut self, index: u16) -> Result<()> {
         let value = self.stack.pop_operand()?;
         if value.long().is_err() {
             bail!("lstore can only store longs, is {value:?}")
@@ -1034,7 +2231,7 @@ impl JvmThread {
         self.stack.set_local_variable(index.into(), value)
     }
 
-    fn fstore(&mut self, index: u8) -> Result<()> {
+    fn fstore(&mut self, index: u16) -> Result<()> {
         let value = self.stack.pop_operand()?;
         if value.float().is_err() {
             bail!("fstore can only store floats, is {value:?}")
@@ -1043,6 +2240,30 @@ impl JvmThread {
         self.stack.set_local_variable(index.into(), value)
     }
 
+    fn dload(&mut self, index: u16) -> Result<()> {
+        let value = self.stack.local_variable(index.into())?;
+        if value.double().is_err() {
+            bail!("dload can only load doubles, is {value:?}")
+        }
+
+        self.stack.push_operand(value)
+    }
+
+    fn dstore(&mut self, index: u16) -> Result<()> {
+        let value = self.stack.pop_operand()?;
+        if value.double().is_err() {
+            bail!("dstore can only store doubles, is {value:?}")
+        }
+
+        self.stack.set_local_variable(index.into(), value)
+    }
+
+    fn i2s(&mut self) -> Result<()> {
+        let int = self.stack.pop_operand()?.int()?;
+        let short = int as i16;
+        self.stack.push_operand(FrameValue::Int(short as i32))
+    }
+
     fn i2c(&mut self) -> Result<()> {
         let int = self.stack.pop_operand()?.int()?;
         let char = int as u16;
@@ -1068,13 +2289,15 @@ impl JvmThread {
     fn iadd(&mut self) -> Result<()> {
         let value2 = self.stack.pop_operand()?.int()?;
         let value1 = self.stack.pop_operand()?.int()?;
-        self.stack.push_operand(FrameValue::Int(value1 + value2))
+        self.stack
+            .push_operand(FrameValue::Int(value1.wrapping_add(value2)))
     }
 
     fn ladd(&mut self) -> Result<()> {
         let value2 = self.stack.pop_operand()?.long()?;
         let value1 = self.stack.pop_operand()?.long()?;
-        self.stack.push_operand(FrameValue::Long(value1 + value2))
+        self.stack
+            .push_operand(FrameValue::Long(value1.wrapping_add(value2)))
     }
 
     fn dadd(&mut self) -> Result<()> {
@@ -1083,12 +2306,48 @@ impl JvmThread {
         self.stack.push_operand(FrameValue::Double(value1 + value2))
     }
 
+    fn dsub(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.double()?;
+        let value1 = self.stack.pop_operand()?.double()?;
+        self.stack.push_operand(FrameValue::Double(value1 - value2))
+    }
+
+    fn dmul(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.double()?;
+        let value1 = self.stack.pop_operand()?.double()?;
+        self.stack.push_operand(FrameValue::Double(value1 * value2))
+    }
+
+    fn ddiv(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.double()?;
+        let value1 = self.stack.pop_operand()?.double()?;
+        self.stack.push_operand(FrameValue::Double(value1 / value2))
+    }
+
+    /// Rust's `%` truncates like C's `fmod`, which is exactly what drem specifies
+    fn drem(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.double()?;
+        let value1 = self.stack.pop_operand()?.double()?;
+        self.stack.push_operand(FrameValue::Double(value1 % value2))
+    }
+
+    fn dneg(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.double()?;
+        self.stack.push_operand(FrameValue::Double(-value))
+    }
+
     fn fadd(&mut self) -> Result<()> {
         let value2 = self.stack.pop_operand()?.float()?;
         let value1 = self.stack.pop_operand()?.float()?;
         self.stack.push_operand(FrameValue::Float(value1 + value2))
     }
 
+    fn fsub(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.float()?;
+        let value1 = self.stack.pop_operand()?.float()?;
+        self.stack.push_operand(FrameValue::Float(value1 - value2))
+    }
+
     fn f2d(&mut self) -> Result<()> {
         let value = self.stack.pop_operand()?.float()?;
         self.stack.push_operand(FrameValue::Double(value.into()))
@@ -1105,6 +2364,18 @@ impl JvmThread {
         self.stack.push_operand(FrameValue::Float(value1 / value2))
     }
 
+    /// Rust's `%` truncates like C's `fmod`, which is exactly what frem specifies
+    fn frem(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.float()?;
+        let value1 = self.stack.pop_operand()?.float()?;
+        self.stack.push_operand(FrameValue::Float(value1 % value2))
+    }
+
+    fn fneg(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.float()?;
+        self.stack.push_operand(FrameValue::Float(-value))
+    }
+
     fn i2l(&mut self) -> Result<()> {
         let value = self.stack.pop_operand()?.int()?;
         self.stack.push_operand(FrameValue::Long(value.into()))
@@ -1120,6 +2391,33 @@ impl JvmThread {
         self.stack.push_operand(FrameValue::Float(value as f32))
     }
 
+    fn i2d(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.int()?;
+        self.stack.push_operand(FrameValue::Double(value.into()))
+    }
+
+    fn l2d(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.long()?;
+        self.stack.push_operand(FrameValue::Double(value as f64))
+    }
+
+    /// `as` saturates and maps NaN to 0, exactly like the JVM's narrowing conversion
+    fn f2l(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.float()?;
+        self.stack.push_operand(FrameValue::Long(value as i64))
+    }
+
+    /// `as` saturates and maps NaN to 0, exactly like the JVM's narrowing conversion
+    fn d2i(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.double()?;
+        self.stack.push_operand(FrameValue::Int(value as i32))
+    }
+
+    fn d2f(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.double()?;
+        self.stack.push_operand(FrameValue::Float(value as f32))
+    }
+
     fn fcmpl(&mut self) -> Result<()> {
         let value2 = self.stack.pop_operand()?.float()?;
         let value1 = self.stack.pop_operand()?.float()?;
@@ -1152,6 +2450,38 @@ impl JvmThread {
         self.stack.push_operand(value)
     }
 
+    fn dcmpl(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.double()?;
+        let value1 = self.stack.pop_operand()?.double()?;
+
+        let value = if value1 > value2 {
+            FrameValue::Int(1)
+        } else if value1 == value2 {
+            FrameValue::Int(0)
+        } else {
+            FrameValue::Int(-1)
+        };
+
+        self.stack.push_operand(value)
+    }
+
+    fn dcmpg(&mut self) -> Result<()> {
+        let value2 = self.stack.pop_operand()?.double()?;
+        let value1 = self.stack.pop_operand()?.double()?;
+
+        let value = if value1 > value2 {
+            FrameValue::Int(1)
+        } else if value1 == value2 {
+            FrameValue::Int(0)
+        } else if value1 < value2 {
+            FrameValue::Int(-1)
+        } else {
+            FrameValue::Int(1)
+        };
+
+        self.stack.push_operand(value)
+    }
+
     fn iand(&mut self) -> Result<()> {
         let value2 = self.stack.pop_operand()?.int()?;
         let value1 = self.stack.pop_operand()?.int()?;
@@ -1170,14 +2500,16 @@ impl JvmThread {
         let value2 = self.stack.pop_operand()?.int()?;
         let value1 = self.stack.pop_operand()?.int()?;
 
-        self.stack.push_operand(FrameValue::Int(value1 - value2))
+        self.stack
+            .push_operand(FrameValue::Int(value1.wrapping_sub(value2)))
     }
 
     fn lsub(&mut self) -> Result<()> {
         let value2 = self.stack.pop_operand()?.long()?;
         let value1 = self.stack.pop_operand()?.long()?;
 
-        self.stack.push_operand(FrameValue::Long(value1 - value2))
+        self.stack
+            .push_operand(FrameValue::Long(value1.wrapping_sub(value2)))
     }
 
     fn bastore(&mut self) -> Result<()> {
@@ -1188,8 +2520,11 @@ impl JvmThread {
         if !array_ref.is_reference() && self.is_array(&array_ref)? {
             bail!("arrayref has to be a reference to an array, is {array_ref:?}")
         }
+        if array_ref.reference()?.is_null() {
+            return self.throw_null_pointer("Cannot store to byte/boolean array");
+        }
 
-        let index = index.int()? as usize;
+        let index = index.int()?;
         let heap_id = array_ref.reference()?.heap_id()?;
         let (array_type, _) = self.get_primitive_array(heap_id)?;
 
@@ -1199,7 +2534,7 @@ impl JvmThread {
             _ => bail!("array type has to be bool or byte, is {array_type:?}"),
         };
 
-        self.store_into_primitive_array(heap_id, index, value)
+        self.store_primitive_element(heap_id, index, value)
     }
 
     fn iastore(&mut self) -> Result<()> {
@@ -1210,11 +2545,62 @@ impl JvmThread {
         if !array_ref.is_reference() && self.is_array(&array_ref)? {
             bail!("arrayref has to be a reference to an array, is {array_ref:?}")
         }
+        if array_ref.reference()?.is_null() {
+            return self.throw_null_pointer("Cannot store to int array");
+        }
 
-        let index = index.int()? as usize;
+        let index = index.int()?;
         let heap_id = array_ref.reference()?.heap_id()?;
 
-        self.store_into_primitive_array(heap_id, index, PrimitiveArrayValue::Int(value.int()?))
+        self.store_primitive_element(heap_id, index, PrimitiveArrayValue::Int(value.int()?))
+    }
+
+    fn primitive_array_store(
+        &mut self,
+        expected_type: PrimitiveArrayType,
+        value: PrimitiveArrayValue,
+    ) -> Result<()> {
+        let index = self.stack.pop_operand()?.int()?;
+        let array_ref = self.stack.pop_operand()?;
+        let array_ref = array_ref.reference()?;
+
+        if array_ref.is_null() {
+            return self.throw_null_pointer("Cannot store to array");
+        }
+
+        let heap_id = array_ref.heap_id()?;
+        let (array_type, _) = self.get_primitive_array(heap_id)?;
+        if array_type != expected_type {
+            bail!("array type has to be {expected_type:?}, is {array_type:?}")
+        }
+
+        self.store_primitive_element(heap_id, index, value)
+    }
+
+    fn lastore(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.long()?;
+        self.primitive_array_store(PrimitiveArrayType::Long, PrimitiveArrayValue::Long(value))
+    }
+
+    fn fastore(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.float()?;
+        self.primitive_array_store(PrimitiveArrayType::Float, PrimitiveArrayValue::Float(value))
+    }
+
+    fn dastore(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.double()?;
+        self.primitive_array_store(
+            PrimitiveArrayType::Double,
+            PrimitiveArrayValue::Double(value),
+        )
+    }
+
+    fn sastore(&mut self) -> Result<()> {
+        let value = self.stack.pop_operand()?.int()?;
+        self.primitive_array_store(
+            PrimitiveArrayType::Short,
+            PrimitiveArrayValue::Short(value as u16),
+        )
     }
 
     fn castore(&mut self) -> Result<()> {
@@ -1225,12 +2611,15 @@ impl JvmThread {
         if !array_ref.is_reference() && self.is_array(&array_ref)? {
             bail!("arrayref has to be a reference to an array, is {array_ref:?}")
         }
+        if array_ref.reference()?.is_null() {
+            return self.throw_null_pointer("Cannot store to char array");
+        }
 
-        let index = index.int()? as usize;
+        let index = index.int()?;
         let value = value.int()?;
         let heap_id = array_ref.reference()?.heap_id()?;
 
-        self.store_into_primitive_array(heap_id, index, PrimitiveArrayValue::Short(value as u16))
+        self.store_primitive_element(heap_id, index, PrimitiveArrayValue::Char(value as u16))
     }
 
     fn new_array(&mut self, atype: u8) -> Result<()> {
@@ -1241,6 +2630,10 @@ impl JvmThread {
             bail!("TODO: throw NegativeArraySizeException");
         }
 
+        if self.throw_if_out_of_memory()? {
+            return Ok(());
+        }
+
         let heap_id = self.allocate_default_primitive_array(array_type, count.try_into()?)?;
         self.stack
             .push_operand(FrameValue::Reference(ReferenceValue::HeapItem(heap_id)))
@@ -1262,15 +2655,18 @@ impl JvmThread {
         if !array_ref.is_reference() && self.is_array(&array_ref)? {
             bail!("arrayref has to be a reference to an array, is {array_ref:?}")
         }
+        if array_ref.reference()?.is_null() {
+            return self.throw_null_pointer("Cannot store to object array");
+        }
 
-        let index = index.int()? as usize;
+        let index = index.int()?;
         let value = value.reference()?.clone();
         let heap_id = array_ref.reference()?.heap_id()?;
 
-        self.store_into_reference_array(heap_id, index, value)
+        self.store_reference_element(heap_id, index, value)
     }
 
-    fn fload(&mut self, index: u8) -> Result<()> {
+    fn fload(&mut self, index: u16) -> Result<()> {
         let value = self.stack.local_variable(index.into())?;
         if value.float().is_err() {
             bail!("value has to be float, is {value:?}");
@@ -1278,7 +2674,7 @@ impl JvmThread {
         self.stack.push_operand(value)
     }
 
-    fn iload(&mut self, index: u8) -> Result<()> {
+    fn iload(&mut self, index: u16) -> Result<()> {
         let value = self.stack.local_variable(index.into())?;
 
         if value.int().is_err() {
@@ -1300,16 +2696,21 @@ impl JvmThread {
             bail!("object ref has to be reference but not array, is {object_ref:?}")
         }
 
+        if object_ref.reference()?.is_null() {
+            return self.throw_null_pointer(&format!("Cannot assign field \"{name}\""));
+        }
+
         let heap_id = object_ref.reference()?.heap_id()?;
         debug!("put field {name}: {value:?}");
-        self.heap_set_field(heap_id, &name, value.into())
+        self.heap_set_field(heap_id, &name, field_value(&descriptor, value)?)
     }
 
     fn get_static(&mut self, index: &CpIndex) -> Result<()> {
         let (class_identifier, name, descriptor) = self.field_ref(index)?;
-        self.resolve_field(&class_identifier, &name, descriptor.raw())?;
+        let (class_identifier, _) =
+            self.resolve_field(&class_identifier, &name, descriptor.raw())?;
 
-        let class = self.class(&class_identifier)?;
+        let class = self.initialize(&class_identifier)?;
         let field_value = class.get_static_field_value(&name)?;
 
         self.stack.push_operand(field_value.into())
@@ -1487,6 +2888,16 @@ impl JvmThread {
         let value = match current_class.cp_item(index)? {
             CpInfo::Long(value) => FrameValue::Long(*value),
             CpInfo::Double(value) => FrameValue::Double(*value),
+            CpInfo::Dynamic { .. } => return self.ldc_dynamic(&current_class, index, true),
+            info @ (CpInfo::Integer(_)
+            | CpInfo::Float(_)
+            | CpInfo::String { .. }
+            | CpInfo::Class { .. }
+            | CpInfo::MethodType { .. }
+            | CpInfo::MethodHandle { .. }) => bail!(
+                "TODO: throw VerifyError, ldc2_w of category 1 constant {info:?} at index \
+                {index:?}, it needs ldc or ldc_w"
+            ),
             info => bail!("item {info:?} at index {index:?} is not loadable"),
         };
 
@@ -1499,89 +2910,273 @@ impl JvmThread {
         let value = match current_class.cp_item(index)? {
             CpInfo::Class { name_index } => {
                 let name = current_class.utf8(name_index)?;
-                let identifier = ClassIdentifier::parse(name)?;
-                self.resolve_class(&identifier)?;
+                let identifier = ClassIdentifier::new(name)?;
+                self.resolve_class_name(name)?;
 
-                FrameValue::Reference(ReferenceValue::Class(identifier))
+                FrameValue::Reference(ReferenceValue::HeapItem(self.class_mirror(&identifier)?))
             }
             CpInfo::String { string_index } => {
                 let value = current_class.utf8(string_index)?;
-                let object_id = self.new_string(value.to_string())?;
+                let object_id = self.intern_string(value.to_string())?;
                 FrameValue::Reference(ReferenceValue::HeapItem(object_id))
             }
             CpInfo::Integer(value) => FrameValue::Int(*value),
             CpInfo::Float(value) => FrameValue::Float(*value),
+            CpInfo::MethodType { descriptor_index } => {
+                let descriptor = MethodDescriptor::new(current_class.utf8(descriptor_index)?)?;
+                return self.push_method_type(&descriptor);
+            }
+            CpInfo::MethodHandle { .. } => {
+                let method_handle = self.resolve_method_handle(&current_class, index)?;
+                return self.push_method_handle(current_class.identifier(), &method_handle);
+            }
+            CpInfo::Dynamic { .. } => return self.ldc_dynamic(&current_class, index, false),
+            info @ (CpInfo::Long(_) | CpInfo::Double(_)) => bail!(
+                "TODO: throw VerifyError, ldc of category 2 constant {info:?} at index \
+                {index:?}, it needs ldc2_w"
+            ),
             info => bail!("item {info:?} at index {index:?} is not loadable"),
         };
 
         self.stack.push_operand(value)
     }
 
-    fn invoke_virtual(&mut self, index: &CpIndex) -> Result<()> {
-        let (class_identifier, name, descriptor) = self.method_ref(index)?;
-        let (class_identifier, method) =
-            self.resolve_method(&class_identifier, &name, &descriptor)?;
-        let class = self.class(&class_identifier)?;
-
-        let method_descriptor = MethodDescriptor::new(class.utf8(&method.descriptor_index)?)?;
-        let operands = self
-            .stack
-            .pop_operands(method_descriptor.parameters.len() + 1)?;
-
-        let objectref = operands.first().context("no objectref found")?;
-        if objectref.reference()?.is_null() {
-            bail!("TODO: throw NullPointerException");
+    /// Pushes the dynamic constant at `index`, whose type has to be of category 2 for `ldc2_w`
+    /// and of category 1 for `ldc` and `ldc_w`. Like call sites, constants of the bootstrap
+    /// methods in `ConstantBootstraps` which need no `java.lang.invoke` are resolved without
+    /// running them.
+    fn ldc_dynamic(&mut self, class: &Class, index: &CpIndex, wide: bool) -> Result<()> {
+        let CpInfo::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } = class.cp_item(index)?
+        else {
+            bail!("no dynamic constant at index {index:?}")
+        };
+        let (name, descriptor) = class.name_and_type(name_and_type_index)?;
+        let field_type = FieldDescriptor::new(descriptor)?.field_type;
+        let category2 = matches!(
+            field_type,
+            FieldType::BaseType(BaseType::Long | BaseType::Double)
+        );
+        if category2 != wide {
+            let instruction = if wide { "ldc2_w" } else { "ldc" };
+            bail!(
+                "TODO: throw VerifyError, {instruction} of dynamic constant {name} with type \
+                {descriptor} at index {index:?}"
+            );
         }
 
-        let (class, method) = if method.is_private()
-            || class_identifier == ClassIdentifier::new("java.lang".to_owned(), "Class".to_owned())
-            || objectref.reference()?.is_class()
+        let bootstrap_method = class.bootstrap_method(bootstrap_method_attr_index.0)?;
+        let bootstrap_handle = self.method_handle(class, &bootstrap_method.method_ref)?;
+        if bootstrap_handle.class
+            != ClassIdentifier::from_parts("java.lang.invoke", "ConstantBootstraps")
         {
-            (class, method)
-        } else {
-            let objectref_identifier = match objectref.reference()? {
-                ReferenceValue::HeapItem(heap_id) => self.heap_get(heap_id)?.class_identifier()?,
-                ReferenceValue::Class(class_identifier) => class_identifier.clone(),
-                ReferenceValue::Null => bail!("reference is null"),
-            };
-            let class = self.class(&objectref_identifier)?;
-            let (class, method) = self.select_method(&class, &method, &name, &method_descriptor)?;
-            (class, method)
-        };
+            bail!(
+                "TODO: dynamic constants of {:?}.{}",
+                bootstrap_handle.class,
+                bootstrap_handle.name
+            );
+        }
+
+        let value = match bootstrap_handle.name.as_str() {
+            "nullConstant" => {
+                if matches!(field_type, FieldType::BaseType(_)) {
+                    bail!("TODO: throw IllegalArgumentException, {descriptor} is primitive");
+                }
+                FrameValue::Reference(ReferenceValue::Null)
+            }
+            // the name is the descriptor of the primitive type, e.g. `I`
+            "primitiveClass" => {
+                let primitive = self.field_type_class(&FieldDescriptor::new(name)?.field_type)?;
+                FrameValue::Reference(ReferenceValue::HeapItem(self.class_mirror(&primitive)?))
+            }
+            // the declaring class is the optional static argument, otherwise the type itself
+            "getStaticFinal" | "enumConstant" => {
+                let declaring_class = match bootstrap_method.arguments.first() {
+                    Some(argument) => class.class_identifier(argument)?,
+                    None => self.field_type_class(&field_type)?,
+                };
+                let declaring_class = self.initialize(&declaring_class)?;
+                declaring_class.get_static_field_value(name)?.into()
+            }
+            bootstrap_name => {
+                bail!("TODO: dynamic constants of ConstantBootstraps.{bootstrap_name}")
+            }
+        };
+
+        self.stack.push_operand(value)
+    }
+
+    /// Pushes a new `java.lang.invoke.MethodType` of `descriptor`
+    fn push_method_type(&mut self, descriptor: &MethodDescriptor) -> Result<()> {
+        let method_type = self.new_method_type(descriptor)?;
+        self.stack
+            .push_operand(FrameValue::Reference(ReferenceValue::HeapItem(method_type)))
+    }
+
+    /// Allocates a `java.lang.invoke.MethodType` of `descriptor` without running any of its
+    /// code, which needs most of `java.lang.invoke`.
+    ///
+    /// TODO: intern method types like `MethodType.makeImpl`
+    fn new_method_type(&mut self, descriptor: &MethodDescriptor) -> Result<HeapId> {
+        let return_type = match &descriptor.return_descriptor {
+            ReturnDescriptor::Void => self.primitive_class("void")?,
+            ReturnDescriptor::FieldType(field_type) => self.field_type_class(field_type)?,
+        };
+
+        let class_class = ClassIdentifier::from_parts("java.lang", "Class");
+        let parameter_types = self.allocate_array(class_class, descriptor.parameters.len())?;
+        for (i, parameter) in descriptor.parameters.iter().enumerate() {
+            let class = self.field_type_class(parameter)?;
+            let mirror = self.class_mirror(&class)?;
+            self.store_into_reference_array(&parameter_types, i, ReferenceValue::HeapItem(mirror))?;
+        }
+
+        let method_type_identifier = ClassIdentifier::from_parts("java.lang.invoke", "MethodType");
+        let class = self.resolve_class(&method_type_identifier)?;
+        let fields = self.default_instance_fields(&class, 0)?;
+        let method_type = self.allocate(method_type_identifier, fields)?;
+        let return_type = self.class_mirror(&return_type)?;
+        self.heap_set_field(
+            &method_type,
+            "rtype",
+            FieldValue::Reference(ReferenceValue::HeapItem(return_type)),
+        )?;
+        self.heap_set_field(
+            &method_type,
+            "ptypes",
+            FieldValue::Reference(ReferenceValue::HeapItem(parameter_types)),
+        )?;
+
+        Ok(method_type)
+    }
+
+    /// Pushes a `java.lang.invoke.MethodHandle` for `method_handle`, created like the JVM does
+    /// by calling `MethodHandleNatives.linkMethodHandleConstant` on behalf of `caller`
+    fn push_method_handle(
+        &mut self,
+        caller: &ClassIdentifier,
+        method_handle: &MethodHandle,
+    ) -> Result<()> {
+        let name = self.intern_string(method_handle.name.clone())?;
+        let caller = self.class_mirror(caller)?;
+        let class = self.class_mirror(&method_handle.class)?;
+        self.stack
+            .push_operand(FrameValue::Reference(ReferenceValue::HeapItem(caller)))?;
+        self.stack
+            .push_operand(FrameValue::Int(method_handle.kind.value().into()))?;
+        self.stack
+            .push_operand(FrameValue::Reference(ReferenceValue::HeapItem(class)))?;
+        self.stack
+            .push_operand(FrameValue::Reference(ReferenceValue::HeapItem(name)))?;
+        if method_handle.is_field() {
+            let field_type = method_handle.field_descriptor()?.field_type;
+            let class = self.field_type_class(&field_type)?;
+            let mirror = self.class_mirror(&class)?;
+            self.stack
+                .push_operand(FrameValue::Reference(ReferenceValue::HeapItem(mirror)))?;
+        } else {
+            self.push_method_type(&method_handle.method_descriptor()?)?;
+        }
+
+        self.invoke_static_method(
+            &ClassIdentifier::from_parts("java.lang.invoke", "MethodHandleNatives"),
+            "linkMethodHandleConstant",
+            "(Ljava/lang/Class;ILjava/lang/Class;Ljava/lang/String;Ljava/lang/Object;)Ljava/lang/invoke/MethodHandle;",
+        )
+    }
+
+    /// Resolved class of `field_type`
+    fn field_type_class(&mut self, field_type: &FieldType) -> Result<ClassIdentifier> {
+        if let FieldType::BaseType(base_type) = field_type {
+            return self.primitive_class(base_type.name());
+        }
+
+        let descriptor = field_type.descriptor();
+        self.resolve_class_name(&descriptor)?;
+        ClassIdentifier::new(&descriptor)
+    }
+
+    fn invoke_virtual(&mut self, index: &CpIndex) -> Result<()> {
+        let (class_identifier, name, descriptor) = self.method_ref(index)?;
+        self.invoke_virtual_method(&class_identifier, &name, &descriptor)
+    }
+
+    /// Invokes the method selected for the objectref below the arguments on the operand stack
+    fn invoke_virtual_method(
+        &mut self,
+        class_identifier: &ClassIdentifier,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<()> {
+        let (class_identifier, method) = self.resolve_method(class_identifier, name, descriptor)?;
+        let class = self.class(&class_identifier)?;
+
+        let method_descriptor = MethodDescriptor::new(class.utf8(&method.descriptor_index)?)?;
+        let operands = self
+            .stack
+            .pop_operands(method_descriptor.parameters.len() + 1)?;
+
+        let objectref = operands.first().context("no objectref found")?;
+        if objectref.reference()?.is_null() {
+            return self
+                .throw_null_pointer(&format!("Cannot invoke \"{class_identifier:?}.{name}()\""));
+        }
 
-        let method_name = class.method_name(&method)?.to_string();
+        let (class, method) = if method.is_private() {
+            (class, method)
+        } else {
+            let objectref_identifier = match objectref.reference()? {
+                ReferenceValue::HeapItem(heap_id) => match self.heap_get(heap_id)? {
+                    // arrays only inherit the methods of Object
+                    item if item.is_array() => ClassIdentifier::from_parts("java.lang", "Object"),
+                    item => item.class_identifier()?,
+                },
+                ReferenceValue::Null => bail!("reference is null"),
+            };
+            let class = self.class(&objectref_identifier)?;
+            let (class, method) = self.select_method(&class, &method, name, &method_descriptor)?;
+            (class, method)
+        };
 
-        let heap_id = objectref.reference()?.heap_id().ok();
+        self.invoke_selected_method(&class, &method, method_descriptor, operands)
+    }
+
+    /// Runs the instance `method` selected in `class`, with the objectref as first operand
+    fn invoke_selected_method(
+        &mut self,
+        class: &Class,
+        method: &Method,
+        method_descriptor: MethodDescriptor,
+        operands: Vec<FrameValue>,
+    ) -> Result<()> {
+        let method_name = class.method_name(method)?.to_string();
+        let heap_id = operands
+            .first()
+            .context("no objectref found")?
+            .reference()?
+            .heap_id()?
+            .clone();
 
         if method.is_synchronized() {
             let thread_id = self
                 .current_thread_id
                 .clone()
                 .context("how do we not have a thread id?")?;
-            if let Some(heap_id) = heap_id {
-                if !self.enter_object_monitor(heap_id, &thread_id)? {
-                    bail!("TODO: wait for monitor to be available")
-                }
-            } else {
-                let identifier = objectref.reference()?.class_identifier()?;
-                if !self.enter_class_monitor(identifier, &thread_id)? {
-                    bail!("TODO: wait for monitor to be available")
-                }
-            }
+            self.enter_object_monitor(&heap_id, &thread_id)?;
         }
 
-        if !method.is_native() {
-            let code = method
-                .code()
-                .context(format!("no code found for {name} method"))?;
+        if !method.is_native() && !native::is_intrinsic(class.identifier(), &method_name) {
+            let code = class.code(method)?;
             self.stack.push(
                 method_name,
                 method_descriptor,
-                operands.clone(),
-                Code::new(code.clone())?,
+                operands,
+                code,
                 class.identifier().clone(),
-                heap_id.cloned(),
-            );
+                Some(heap_id),
+            )?;
             self.execute()
         } else if let Some(return_value) =
             native::run(self, class.identifier(), &method_name, operands)?
@@ -1593,68 +3188,76 @@ impl JvmThread {
     }
 
     fn select_method(
-        &self,
+        &mut self,
         class: &Class,
         method: &Method,
         name: &str,
         method_descriptor: &MethodDescriptor,
     ) -> Result<(Class, Method)> {
-        if let Some(m) = class.overriden_method(method, name, method_descriptor)? {
-            return Ok((class.clone(), m));
-        } else if class.has_super_class() {
-            let super_class = self.class(&class.super_class()?)?;
-            return self.select_method(&super_class, method, name, method_descriptor);
+        let mut current = class.clone();
+        loop {
+            if let Some(m) = current.overriden_method(method, name, method_descriptor)? {
+                return Ok((current, m));
+            }
+
+            if !current.has_super_class() {
+                break;
+            }
+            current = self.class(&current.super_class()?)?;
         }
 
-        bail!("no method found")
+        let mut defaults = self.maximally_specific_methods(class, name, method_descriptor.raw())?;
+        defaults.retain(|(_, m)| !m.is_abstract());
+        match defaults.len() {
+            0 => bail!("no method found, TODO: throw AbstractMethodError"),
+            1 => Ok(defaults.remove(0)),
+            _ => bail!("multiple default methods found, TODO: throw IncompatibleClassChangeError"),
+        }
     }
 
     fn invoke_interface(&mut self, index: &CpIndex, _: u8) -> Result<()> {
         let (class_identifier, name, descriptor) = self.method_ref(index)?;
         let method = self.resolve_interface_method(&class_identifier, &name, &descriptor)?;
 
-        if method.is_synchronized() {
-            bail!("TODO: synchronized interface method")
-        }
-
-        if method.is_native() {
-            bail!("TODO: native interface method")
-        }
-
-        let interface_class = self.class(&class_identifier)?;
-        let method_descriptor = interface_class.method_descriptor(&method)?;
+        let method_descriptor = MethodDescriptor::new(&descriptor)?;
         let operands = self
             .stack
             .pop_operands(method_descriptor.parameters.len() + 1)?;
         let objectref = operands.first().context("no first operand")?.reference()?;
-        let heap_id = objectref.heap_id()?;
+        if objectref.is_null() {
+            return self
+                .throw_null_pointer(&format!("Cannot invoke \"{class_identifier:?}.{name}()\""));
+        }
+
         let class_identifier = self.class_identifier_from_reference(objectref)?;
         let class = self.class(&class_identifier)?;
         let (class, method) = self.select_method(&class, &method, &name, &method_descriptor)?;
-        let code = method
-            .code()
-            .context(format!("no code found for {name} method"))?;
-        self.stack.push(
-            name,
-            method_descriptor,
-            operands.clone(),
-            Code::new(code.clone())?,
-            class.identifier().clone(),
-            Some(heap_id.clone()),
-        );
-        self.execute()
+        self.invoke_selected_method(&class, &method, method_descriptor, operands)
     }
 
     fn invoke_static(&mut self, index: &CpIndex) -> Result<()> {
         let (class_identifier, name, descriptor) = self.method_ref(index)?;
+        self.invoke_static_method(&class_identifier, &name, &descriptor)
+    }
 
-        let (_, method) = self.resolve_method(&class_identifier, &name, &descriptor)?;
-        let class = self.class(&class_identifier)?;
+    /// Invokes the static method with the arguments on the operand stack
+    fn invoke_static_method(
+        &mut self,
+        class_identifier: &ClassIdentifier,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<()> {
+        let (declaring_class, method) = self.resolve_method(class_identifier, name, descriptor)?;
+        let class = self.class(&declaring_class)?;
 
         if !method.is_static() {
             bail!("method has to be static");
         }
 
+        if self.run_cached_value_of(&declaring_class, name, descriptor)? {
+            return Ok(());
+        }
+
         if method.is_abstract() {
             bail!("method cannot be static");
         }
@@ -1664,63 +3267,253 @@ impl JvmThread {
                 .current_thread_id
                 .clone()
                 .context("how do we not have a thread id?")?;
-            if !self.enter_class_monitor(&class_identifier, &thread_id)? {
-                bail!("TODO: wait for monitor to be available")
-            }
+            let mirror = self.class_mirror(&declaring_class)?;
+            self.enter_object_monitor(&mirror, &thread_id)?;
         }
 
         let descriptor = class.method_descriptor(&method)?;
 
         let operands = self.stack.pop_operands(descriptor.parameters.len())?;
         if method.is_native() {
-            if let Some(return_value) = native::run(self, class.identifier(), &name, operands)? {
+            if let Some(return_value) = native::run(self, class.identifier(), name, operands)? {
                 self.stack.push_operand(return_value)
             } else {
                 Ok(())
             }
         } else {
-            let code = method
-                .code()
-                .context(format!("no code found for {name} method"))?;
+            let code = class.code(&method)?;
             self.stack.push(
-                name,
+                name.to_string(),
                 descriptor,
                 operands,
-                Code::new(code.clone())?,
-                class_identifier,
+                code,
+                declaring_class,
                 None,
-            );
+            )?;
             self.execute()
         }
     }
 
+    /// Runs `valueOf` of the wrapper classes which cache the boxes of small values, returning
+    /// whether `name` is such a method. The JDK fills these caches in static initializers
+    /// reading saved system properties, which need a booted `System`, so the VM keeps them.
+    fn run_cached_value_of(
+        &mut self,
+        class_identifier: &ClassIdentifier,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<bool> {
+        if name != "valueOf" || class_identifier.package != "java.lang" {
+            return Ok(false);
+        }
+
+        let cached = match (class_identifier.name.as_str(), descriptor) {
+            ("Integer", "(I)Ljava/lang/Integer;")
+            | ("Long", "(J)Ljava/lang/Long;")
+            | ("Short", "(S)Ljava/lang/Short;")
+            | ("Byte", "(B)Ljava/lang/Byte;") => -128..=127,
+            ("Character", "(C)Ljava/lang/Character;") => 0..=127,
+            _ => return Ok(false),
+        };
+
+        let operand = self.stack.pop_operand()?;
+        let boxed = self.cached_box(class_identifier, operand, cached)?;
+        self.stack
+            .push_operand(FrameValue::Reference(ReferenceValue::HeapItem(boxed)))?;
+        Ok(true)
+    }
+
+    /// Boxes `operand` into an instance of the wrapper class `identifier`, values in `cached`
+    /// share one box
+    fn cached_box(
+        &mut self,
+        identifier: &ClassIdentifier,
+        operand: FrameValue,
+        cached: RangeInclusive<i64>,
+    ) -> Result<HeapId> {
+        let value = match operand {
+            FrameValue::Long(value) => value,
+            ref operand => operand.int()?.into(),
+        };
+        if !cached.contains(&value) {
+            return self.new_box(identifier, operand);
+        }
+
+        let key = (identifier.clone(), value);
+        let cached_box = self
+            .boxes
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+            .get(&key)
+            .cloned();
+        match cached_box {
+            Some(boxed) => Ok(boxed),
+            None => {
+                let boxed = self.new_box(identifier, operand)?;
+                Ok(self
+                    .boxes
+                    .lock()
+                    .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+                    .entry(key)
+                    .or_insert(boxed)
+                    .clone())
+            }
+        }
+    }
+
+    /// Boxes `value` like `valueOf` of its wrapper class does. Frame values don't tell ints
+    /// apart from the narrower types, so ints are always boxed as `java.lang.Integer`.
+    pub fn box_primitive(&mut self, value: FrameValue) -> Result<HeapId> {
+        match value {
+            FrameValue::Int(_) => {
+                let integer = ClassIdentifier::from_parts("java.lang", "Integer");
+                self.cached_box(&integer, value, -128..=127)
+            }
+            FrameValue::Long(_) => {
+                let long = ClassIdentifier::from_parts("java.lang", "Long");
+                self.cached_box(&long, value, -128..=127)
+            }
+            FrameValue::Float(_) => {
+                self.new_box(&ClassIdentifier::from_parts("java.lang", "Float"), value)
+            }
+            FrameValue::Double(_) => {
+                self.new_box(&ClassIdentifier::from_parts("java.lang", "Double"), value)
+            }
+            value => bail!("only primitives can be boxed, not {value:?}"),
+        }
+    }
+
+    /// Reads the primitive value of the wrapper object at `heap_id`
+    pub fn unbox(&self, heap_id: &HeapId) -> Result<FrameValue> {
+        let identifier = self.heap_get(heap_id)?.class_identifier()?;
+        let is_wrapper = identifier.package == "java.lang"
+            && matches!(
+                identifier.name.as_str(),
+                "Boolean"
+                    | "Character"
+                    | "Byte"
+                    | "Short"
+                    | "Integer"
+                    | "Long"
+                    | "Float"
+                    | "Double"
+            );
+        if !is_wrapper {
+            bail!("{identifier:?} is no wrapper class, TODO: throw ClassCastException");
+        }
+
+        Ok(self.heap_get_field(heap_id, "value")?.into())
+    }
+
+    /// Allocates an instance of the wrapper class `identifier` holding `value`
+    fn new_box(&mut self, identifier: &ClassIdentifier, value: FrameValue) -> Result<HeapId> {
+        let class = self.initialize(identifier)?;
+        let fields = self.default_instance_fields(&class, 0)?;
+        let boxed = self.allocate(identifier.clone(), fields)?;
+        self.heap_set_field(&boxed, "value", value.try_into()?)?;
+        Ok(boxed)
+    }
+
     fn a_new_array(&mut self, index: &CpIndex) -> Result<()> {
         let current_class = self.current_class()?;
         let array_class = current_class.class_identifier(index)?;
-        self.initialize(&array_class)?;
+        let CpInfo::Class { name_index } = current_class.cp_item(index)? else {
+            bail!("no class at index {index:?}")
+        };
+        self.resolve_class_name(current_class.utf8(name_index)?)?;
         let length = self.stack.pop_int()?;
+        if self.throw_if_out_of_memory()? {
+            return Ok(());
+        }
+
         let array = self.allocate_array(array_class, length as usize)?;
         let value = FrameValue::Reference(ReferenceValue::HeapItem(array));
         self.stack.push_operand(value)
     }
 
+    fn multi_a_new_array(&mut self, index: &CpIndex, dimensions: u8) -> Result<()> {
+        let current_class = self.current_class()?;
+        let array_class = current_class.class_identifier(index)?;
+        let CpInfo::Class { name_index } = current_class.cp_item(index)? else {
+            bail!("no class at index {index:?}")
+        };
+        self.resolve_class_name(current_class.utf8(name_index)?)?;
+
+        let counts = self
+            .stack
+            .pop_operands(dimensions.into())?
+            .iter()
+            .map(|count| count.int())
+            .collect::<Result<Vec<i32>>>()?;
+        if counts.iter().any(|count| *count < 0) {
+            bail!("TODO: throw NegativeArraySizeException");
+        }
+        if self.throw_if_out_of_memory()? {
+            return Ok(());
+        }
+
+        let array = self.allocate_multi_array(&array_class, &counts)?;
+        self.stack
+            .push_operand(FrameValue::Reference(ReferenceValue::HeapItem(array)))
+    }
+
+    /// Allocates an array of `array_class` with the lengths of its outer dimensions in `counts`,
+    /// inner dimensions without a length stay null
+    fn allocate_multi_array(
+        &mut self,
+        array_class: &ClassIdentifier,
+        counts: &[i32],
+    ) -> Result<HeapId> {
+        let component = array_class
+            .component()
+            .context(format!("{array_class:?} is no array class"))?;
+        let (count, inner_counts) = counts.split_first().context("no array dimensions")?;
+        let count = *count as usize;
+
+        if component.is_primitive() {
+            let array_type = PrimitiveArrayType::from_name(&component.name)?;
+            return self.allocate_default_primitive_array(array_type, count);
+        }
+
+        let array = self.allocate_array(component.clone(), count)?;
+        if !inner_counts.is_empty() {
+            for i in 0..count {
+                let inner = self.allocate_multi_array(&component, inner_counts)?;
+                self.store_into_reference_array(&array, i, ReferenceValue::HeapItem(inner))?;
+            }
+        }
+
+        Ok(array)
+    }
+
     fn put_static(&mut self, index: &CpIndex) -> Result<()> {
         let (identifier, name, descriptor) = self.field_ref(index)?;
 
-        self.resolve_field(&identifier, &name, descriptor.raw())?;
+        let (identifier, _) = self.resolve_field(&identifier, &name, descriptor.raw())?;
+        self.initialize(&identifier)?;
         let value = self.stack.pop_operand()?;
         debug!("put static field {name}: {value:?}");
+        self.set_static_field(&identifier, &name, field_value(&descriptor, value)?)
+    }
+
+    /// Sets the static field `name` of the initialized class `identifier`
+    pub fn set_static_field(
+        &self,
+        identifier: &ClassIdentifier,
+        name: &str,
+        value: FieldValue,
+    ) -> Result<()> {
         let mut classes = self
             .classes
             .lock()
             .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
         classes
-            .get_mut(&identifier)
+            .get_mut(identifier)
             .context(format!("class {identifier:?} is not initialized"))?
-            .set_static_field(&name, value.into())
+            .set_static_field(name, value)
     }
 
-    fn aload(&mut self, index: u8) -> Result<()> {
+    fn aload(&mut self, index: u16) -> Result<()> {
         let local_variable = self.stack.local_variable(index.into())?;
 
         if !local_variable.is_reference() {
@@ -1731,7 +3524,17 @@ impl JvmThread {
     }
 
     fn resolve_class(&mut self, identifier: &ClassIdentifier) -> Result<Class> {
-        self.initialize(identifier)
+        self.link(identifier)
+    }
+
+    /// Resolves the class named in the constant pool, arrays resolve their element class
+    fn resolve_class_name(&mut self, name: &str) -> Result<()> {
+        let element = ClassIdentifier::new(name)?.element();
+        if !element.is_primitive() {
+            self.resolve_class(&element)?;
+        }
+
+        Ok(())
     }
 
     fn get_field(&mut self, index: &CpIndex) -> Result<()> {
@@ -1743,18 +3546,14 @@ impl JvmThread {
             bail!("objectref has to be a reference but no array, is {object_ref:?}");
         }
 
-        // TODO: is this good? maybe classes should live on the heap as well?
-        if class_identifier == ClassIdentifier::new("java.lang".to_owned(), "Class".to_owned()) {
-            let identifier = self.class_identifier_from_reference(object_ref.reference()?)?;
-            let class = self.class(&identifier)?;
-            let field_value = class.get_class_field_value(&name)?;
-            self.stack.push_operand(field_value.into())
-        } else {
-            let heap_id = object_ref.reference()?.heap_id()?;
-            let field_value = self.heap_get_field(heap_id, &name)?;
-            debug!("get field {name}: {field_value:?}");
-            self.stack.push_operand(field_value.into())
+        if object_ref.reference()?.is_null() {
+            return self.throw_null_pointer(&format!("Cannot read field \"{name}\""));
         }
+
+        let heap_id = object_ref.reference()?.heap_id()?;
+        let field_value = self.heap_get_field(heap_id, &name)?;
+        debug!("get field {name}: {field_value:?}");
+        self.stack.push_operand(field_value.into())
     }
 
     fn class_identifier_from_reference(
@@ -1762,13 +3561,12 @@ impl JvmThread {
         reference: &ReferenceValue,
     ) -> Result<ClassIdentifier> {
         match reference {
-            ReferenceValue::Class(class_identifier) => Ok(class_identifier.clone()),
             ReferenceValue::HeapItem(heap_id) => self.heap_get(heap_id)?.class_identifier(),
             _ => bail!("no class identifier found for value {reference:?}"),
         }
     }
 
-    fn istore(&mut self, index: u8) -> Result<()> {
+    fn istore(&mut self, index: u16) -> Result<()> {
         let int = self.stack.pop_operand()?;
         if int.int().is_err() {
             bail!("istore value has to be int")
@@ -1777,7 +3575,7 @@ impl JvmThread {
         self.stack.set_local_variable(index.into(), int)
     }
 
-    fn astore(&mut self, index: u8) -> Result<()> {
+    fn astore(&mut self, index: u16) -> Result<()> {
         let objectref = self.stack.pop_operand()?;
         if !objectref.is_reference() {
             bail!("TODO: astore objectref has to be reference")
@@ -1815,7 +3613,11 @@ impl JvmThread {
     fn new_instruction(&mut self, index: &CpIndex) -> Result<()> {
         let current_class = self.current_class()?;
         let class_identifier = current_class.class_identifier(index)?;
-        let class = self.resolve_class(&class_identifier)?;
+        let class = self.initialize(&class_identifier)?;
+        if self.throw_if_out_of_memory()? {
+            return Ok(());
+        }
+
         let fields = self.default_instance_fields(&class, 0)?;
         let object_id = self.allocate(class.identifier().clone(), fields)?;
         self.stack
@@ -1853,101 +3655,677 @@ impl JvmThread {
         self.stack.push_operand(value1)
     }
 
-    fn invoke_special(&mut self, index: &CpIndex) -> Result<()> {
-        let (class_identifier, name, descriptor) = self.method_ref(index)?;
-        let (_, method) = self.resolve_method(&class_identifier, &name, &descriptor)?;
-        let class = self.initialize(&class_identifier)?;
-        let method_descriptor = class.method_descriptor(&method)?;
+    fn dup_x2(&mut self) -> Result<()> {
+        let value1 = self.pop_category1()?;
+        let values = self.pop_two_words()?;
+        self.stack.push_operand(value1.clone())?;
+        self.push_operands(values)?;
+        self.stack.push_operand(value1)
+    }
+
+    fn dup2_x1(&mut self) -> Result<()> {
+        let values1 = self.pop_two_words()?;
+        let value2 = self.pop_category1()?;
+        self.push_operands(values1.clone())?;
+        self.stack.push_operand(value2)?;
+        self.push_operands(values1)
+    }
+
+    fn dup2_x2(&mut self) -> Result<()> {
+        let values1 = self.pop_two_words()?;
+        let values2 = self.pop_two_words()?;
+        self.push_operands(values1.clone())?;
+        self.push_operands(values2)?;
+        self.push_operands(values1)
+    }
+
+    fn pop2(&mut self) -> Result<()> {
+        self.pop_two_words()?;
+        Ok(())
+    }
+
+    fn swap(&mut self) -> Result<()> {
+        let value1 = self.pop_category1()?;
+        let value2 = self.pop_category1()?;
+        self.stack.push_operand(value1)?;
+        self.stack.push_operand(value2)
+    }
 
-        if name != "<init>" || !method_descriptor.is_void() {
-            bail!("TODO: invokespecial for non instance initialization methods")
+    fn pop_category1(&mut self) -> Result<FrameValue> {
+        let value = self.stack.pop_operand()?;
+        if !value.is_category1() {
+            bail!("value has to be of computational type with category 1, is {value:?}");
         }
 
-        if class.contains_method(&method) {
-            if method.is_synchronized() {
-                bail!("TODO: invokespecial synchronized method")
-            }
+        Ok(value)
+    }
 
-            if method.is_native() {
-                bail!("TODO: invokespecial native method")
-            }
+    /// Pops either a single category 2 value or two category 1 values, returned in the
+    /// order they were pushed
+    fn pop_two_words(&mut self) -> Result<Vec<FrameValue>> {
+        let value1 = self.stack.pop_operand()?;
+        if !value1.is_category1() {
+            return Ok(vec![value1]);
+        }
 
-            let operands = self
-                .stack
-                .pop_operands(method_descriptor.parameters.len() + 1)?;
-            let code = method
-                .code()
-                .context(format!("no code found for {name} method"))?;
-            self.stack.push(
-                name.to_string(),
-                method_descriptor,
-                operands,
-                Code::new(code.clone())?,
-                class.identifier().clone(),
-                None,
-            );
-            self.execute()
-        } else {
-            bail!("TODO: invokespecial method lookup")
+        let value2 = self.pop_category1()?;
+        Ok(vec![value2, value1])
+    }
+
+    fn push_operands(&mut self, values: Vec<FrameValue>) -> Result<()> {
+        for value in values {
+            self.stack.push_operand(value)?;
         }
+
+        Ok(())
     }
 
-    fn invoke_dynamic(&mut self, index: &CpIndex) -> Result<()> {
+    fn invoke_special(&mut self, index: &CpIndex) -> Result<()> {
+        let (class_identifier, name, descriptor) = self.method_ref(index)?;
+        let (_, method) = self.resolve_method(&class_identifier, &name, &descriptor)?;
+        let class = self.initialize(&class_identifier)?;
+        if method.is_static() {
+            bail!("method has to be an instance method, TODO: throw IncompatibleClassChangeError");
+        }
+
+        // calls of super class methods start the lookup at the super class of the current class
         let current_class = self.current_class()?;
-        let (_, name_and_type_index) = if let CpInfo::InvokeDynamic {
-            bootstrap_method_attr_index,
-            name_and_type_index,
-        } = current_class.cp_item(index)?
+        let class = if name != "<init>"
+            && !class.is_interface()
+            && current_class.identifier() != class.identifier()
+            && current_class.has_super_class()
+            && self.is_subclass(current_class.identifier(), class.identifier())?
         {
-            (bootstrap_method_attr_index, name_and_type_index)
+            self.class(&current_class.super_class()?)?
         } else {
-            bail!("no invoke dynamic item at index {index:?}")
+            class
         };
 
-        let current_class = self.current_class()?;
-
-        let (_, descriptor) = current_class.name_and_type(name_and_type_index)?;
-        let method_descriptor = MethodDescriptor::new(descriptor)?;
-        if let ReturnDescriptor::FieldType(FieldType::ObjectType { class_name }) =
-            method_descriptor.return_descriptor
+        let method_descriptor = MethodDescriptor::new(&descriptor)?;
+        let operands = self
+            .stack
+            .pop_operands(method_descriptor.parameters.len() + 1)?;
+        if operands
+            .first()
+            .context("no objectref found")?
+            .reference()?
+            .is_null()
         {
-            self.initialize(&ClassIdentifier::parse(&class_name)?)?;
+            return self
+                .throw_null_pointer(&format!("Cannot invoke \"{class_identifier:?}.{name}()\""));
+        }
+
+        let (class, method) = self.select_special_method(&class, &name, &descriptor)?;
+        self.invoke_selected_method(&class, &method, method_descriptor, operands)
+    }
+
+    /// Selects the method `invokespecial` runs, which is declared in `class`, one of its super
+    /// classes or a default method of its superinterfaces (JVMS 6.5)
+    fn select_special_method(
+        &mut self,
+        class: &Class,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<(Class, Method)> {
+        let mut current = class.clone();
+        loop {
+            if let Ok(method) = current.method(name, descriptor)
+                && !method.is_static()
+            {
+                let method = method.clone();
+                if method.is_abstract() {
+                    bail!("method {name} is abstract, TODO: throw AbstractMethodError");
+                }
+                return Ok((current, method));
+            }
+
+            // the super class of an interface is Object, which is only considered below
+            if current.is_interface() || !current.has_super_class() {
+                break;
+            }
+            current = self.class(&current.super_class()?)?;
+        }
+
+        if class.is_interface() {
+            let object_class = self.class(&ClassIdentifier::from_parts("java.lang", "Object"))?;
+            if let Ok(object_method) = object_class.method(name, descriptor)
+                && object_method.is_public()
+                && !object_method.is_static()
+            {
+                let object_method = object_method.clone();
+                return Ok((object_class, object_method));
+            }
+        }
+
+        let mut defaults = self.maximally_specific_methods(class, name, descriptor)?;
+        defaults.retain(|(_, m)| !m.is_abstract());
+        match defaults.len() {
+            0 => bail!("no method found, TODO: throw AbstractMethodError"),
+            1 => Ok(defaults.remove(0)),
+            _ => bail!("multiple default methods found, TODO: throw IncompatibleClassChangeError"),
+        }
+    }
+
+    fn invoke_dynamic(&mut self, index: &CpIndex) -> Result<()> {
+        let current_class = self.current_class()?;
+        let (bootstrap_method_attr_index, name_and_type_index) = if let CpInfo::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } =
+            current_class.cp_item(index)?
+        {
+            (bootstrap_method_attr_index, name_and_type_index)
+        } else {
+            bail!("no invoke dynamic item at index {index:?}")
+        };
+
+        let (name, descriptor) = current_class.name_and_type(name_and_type_index)?;
+        let method_descriptor = MethodDescriptor::new(descriptor)?;
+        let bootstrap_method = current_class.bootstrap_method(bootstrap_method_attr_index.0)?;
+        // call sites of these bootstrap methods are linked without running them
+        let bootstrap_handle = self.method_handle(&current_class, &bootstrap_method.method_ref)?;
+
+        if bootstrap_handle.class
+            == ClassIdentifier::from_parts("java.lang.invoke", "LambdaMetafactory")
+        {
+            if bootstrap_handle.name != "metafactory" {
+                bail!("TODO: {} call sites", bootstrap_handle.name);
+            }
+
+            return self.metafactory(
+                &current_class,
+                index,
+                name,
+                &method_descriptor,
+                &bootstrap_method.arguments,
+            );
+        }
+
+        if bootstrap_handle.class
+            == ClassIdentifier::from_parts("java.lang.invoke", "StringConcatFactory")
+        {
+            let bootstrap_name = bootstrap_handle.name;
+            let (recipe, constants) = match bootstrap_name.as_str() {
+                "makeConcatWithConstants" => {
+                    let (recipe, constants) = bootstrap_method
+                        .arguments
+                        .split_first()
+                        .context("no recipe found for makeConcatWithConstants")?;
+                    (self.constant_string(&current_class, recipe)?, constants)
+                }
+                "makeConcat" => ("\u{1}".repeat(method_descriptor.parameters.len()), &[][..]),
+                _ => bail!("unknown string concatenation bootstrap method {bootstrap_name}"),
+            };
+
+            return self.concat(&current_class, &recipe, constants, &method_descriptor);
+        }
+
+        if let ReturnDescriptor::FieldType(FieldType::ObjectType { class_name }) =
+            method_descriptor.return_descriptor
+        {
+            self.initialize(&ClassIdentifier::new(&class_name)?)?;
         }
 
         for parameter in method_descriptor.parameters {
             if let FieldType::ObjectType { class_name } = parameter {
-                self.initialize(&ClassIdentifier::parse(&class_name)?)?;
+                self.initialize(&ClassIdentifier::new(&class_name)?)?;
             }
         }
 
-        let method_type_identifier =
-            ClassIdentifier::new("java.lang.invoke".to_owned(), "MethodType".to_owned());
+        let method_type_identifier = ClassIdentifier::from_parts("java.lang.invoke", "MethodType");
         let _class = self.resolve_class(&method_type_identifier)?;
         bail!("TODO: callsite resolution")
     }
 
+    /// Symbolic reference of the `CONSTANT_MethodHandle` at `index`
+    fn method_handle(&self, class: &Class, index: &CpIndex) -> Result<MethodHandle> {
+        let CpInfo::MethodHandle {
+            reference_kind,
+            reference_index,
+        } = class.cp_item(index)?
+        else {
+            bail!("no method handle at index {index:?}")
+        };
+
+        let reference = class.cp_item(reference_index)?;
+        let (class_index, name_and_type_index, is_interface) = match (reference_kind, reference) {
+            (
+                ReferenceKind::GetField
+                | ReferenceKind::GetStatic
+                | ReferenceKind::PutField
+                | ReferenceKind::PutStatic,
+                CpInfo::FieldRef {
+                    class_index,
+                    name_and_type_index,
+                },
+            )
+            | (
+                ReferenceKind::InvokeVirtual
+                | ReferenceKind::InvokeStatic
+                | ReferenceKind::InvokeSpecial
+                | ReferenceKind::NewInvokeSpecial,
+                CpInfo::MethodRef {
+                    class_index,
+                    name_and_type_index,
+                },
+            ) => (class_index, name_and_type_index, false),
+            (
+                ReferenceKind::InvokeStatic
+                | ReferenceKind::InvokeSpecial
+                | ReferenceKind::InvokeInterface,
+                CpInfo::InterfaceMethodRef {
+                    class_index,
+                    name_and_type_index,
+                },
+            ) => (class_index, name_and_type_index, true),
+            (kind, item) => bail!("method handle of kind {kind:?} can't refer to {item:?}"),
+        };
+
+        let (name, descriptor) = class.name_and_type(name_and_type_index)?;
+        let is_constructor = name == "<init>";
+        if is_constructor != matches!(reference_kind, ReferenceKind::NewInvokeSpecial)
+            || name == "<clinit>"
+        {
+            bail!("method handle of kind {reference_kind:?} can't refer to {name}");
+        }
+
+        Ok(MethodHandle {
+            kind: reference_kind.clone(),
+            class: class.class_identifier(class_index)?,
+            name: name.to_string(),
+            descriptor: descriptor.to_string(),
+            is_interface,
+        })
+    }
+
+    /// Resolves the `CONSTANT_MethodHandle` at `index` by resolving the field or method it
+    /// refers to, which has to match the kind of the handle
+    fn resolve_method_handle(&mut self, class: &Class, index: &CpIndex) -> Result<MethodHandle> {
+        let method_handle = self.method_handle(class, index)?;
+        let (class, name, descriptor) = (
+            &method_handle.class,
+            &method_handle.name,
+            &method_handle.descriptor,
+        );
+
+        let is_static = if method_handle.is_field() {
+            let descriptor = method_handle.field_descriptor()?;
+            self.resolve_field(class, name, descriptor.raw())?
+                .1
+                .is_static()
+        } else if method_handle.is_interface {
+            self.resolve_interface_method(class, name, descriptor)?
+                .is_static()
+        } else {
+            self.resolve_method(class, name, descriptor)?.1.is_static()
+        };
+
+        if is_static != method_handle.is_static() {
+            bail!(
+                "method handle of kind {:?} refers to {name}, TODO: throw IncompatibleClassChangeError",
+                method_handle.kind
+            );
+        }
+
+        Ok(method_handle)
+    }
+
+    /// Links a call site bootstrapped by `LambdaMetafactory.metafactory` and pushes the lambda
+    /// object capturing the operands of `descriptor`
+    fn metafactory(
+        &mut self,
+        current_class: &Class,
+        index: &CpIndex,
+        name: &str,
+        descriptor: &MethodDescriptor,
+        arguments: &[CpIndex],
+    ) -> Result<()> {
+        let [sam_method_type, implementation, instantiated_method_type] = arguments else {
+            bail!(
+                "metafactory expects 3 static arguments, got {}",
+                arguments.len()
+            );
+        };
+
+        let method_type = |index: &CpIndex| -> Result<MethodDescriptor> {
+            let CpInfo::MethodType { descriptor_index } = current_class.cp_item(index)? else {
+                bail!("no method type at index {index:?}")
+            };
+            MethodDescriptor::new(current_class.utf8(descriptor_index)?)
+        };
+        let sam_descriptor = method_type(sam_method_type)?;
+        // descriptor of the functional interface method with the type arguments of the call site
+        let instantiated_descriptor = method_type(instantiated_method_type)?;
+
+        let ReturnDescriptor::FieldType(FieldType::ObjectType { class_name }) =
+            &descriptor.return_descriptor
+        else {
+            bail!("lambda call site has to return an interface, is {descriptor:?}")
+        };
+        let interface = ClassIdentifier::new(class_name)?;
+
+        // every execution of a call site produces an instance of the same class
+        let caller = current_class.identifier();
+        let identifier = ClassIdentifier::from_parts(
+            &caller.package,
+            &format!("{}$$Lambda${}", caller.name, index.0),
+        );
+        if self.class(&identifier).is_err() {
+            let target = self.resolve_method_handle(current_class, implementation)?;
+            let class_file = lambda::class_file(
+                &identifier,
+                &interface,
+                name,
+                &sam_descriptor,
+                &instantiated_descriptor,
+                &descriptor.parameters,
+                &target,
+            )?;
+            self.define(identifier.clone(), class_file)?;
+        }
+
+        let class = self.initialize(&identifier)?;
+        let captured = self.stack.pop_operands(descriptor.parameters.len())?;
+        let fields = self.default_instance_fields(&class, 0)?;
+        let lambda = self.allocate(identifier, fields)?;
+        for (i, value) in captured.into_iter().enumerate() {
+            self.heap_set_field(&lambda, &format!("arg${}", i + 1), value.try_into()?)?;
+        }
+
+        self.stack
+            .push_operand(FrameValue::Reference(ReferenceValue::HeapItem(lambda)))
+    }
+
+    /// Pushes the concatenation of the operands of `method_descriptor` following `recipe`, in
+    /// which `\u{1}` stands for the next operand and `\u{2}` for the next of `constants`
+    fn concat(
+        &mut self,
+        class: &Class,
+        recipe: &str,
+        constants: &[CpIndex],
+        method_descriptor: &MethodDescriptor,
+    ) -> Result<()> {
+        let operands = self
+            .stack
+            .pop_operands(method_descriptor.parameters.len())?;
+        let mut operands = method_descriptor.parameters.iter().zip(operands);
+        let mut constants = constants.iter();
+
+        let mut result = String::new();
+        for c in recipe.chars() {
+            match c {
+                '\u{1}' => {
+                    let (parameter, operand) = operands
+                        .next()
+                        .context("recipe has more arguments than operands")?;
+                    let Some(string) = self.concat_operand_string(parameter, operand)? else {
+                        return Ok(());
+                    };
+                    result.push_str(&string);
+                }
+                '\u{2}' => {
+                    let index = constants.next().context("recipe has more constants")?;
+                    result.push_str(&self.constant_string(class, index)?);
+                }
+                c => result.push(c),
+            }
+        }
+
+        let string = self.new_string(result)?;
+        self.stack
+            .push_operand(FrameValue::Reference(ReferenceValue::HeapItem(string)))
+    }
+
+    /// String conversion of an operand of type `field_type` as done by string concatenation,
+    /// `None` if the object's `toString` threw an exception
+    fn concat_operand_string(
+        &mut self,
+        field_type: &FieldType,
+        operand: FrameValue,
+    ) -> Result<Option<String>> {
+        Ok(Some(match field_type {
+            FieldType::BaseType(BaseType::Boolean) => (operand.int()? != 0).to_string(),
+            FieldType::BaseType(BaseType::Char) => char::from_u32(operand.int()? as u16 as u32)
+                .unwrap_or(char::REPLACEMENT_CHARACTER)
+                .to_string(),
+            FieldType::BaseType(BaseType::Float) => floating_point_string(operand.float()?),
+            FieldType::BaseType(BaseType::Double) => floating_point_string(operand.double()?),
+            FieldType::BaseType(BaseType::Long) => operand.long()?.to_string(),
+            FieldType::BaseType(_) => operand.int()?.to_string(),
+            FieldType::ObjectType { .. } | FieldType::ComponentType(_) => {
+                let reference = operand.reference()?;
+                let ReferenceValue::HeapItem(heap_id) = reference else {
+                    if reference.is_null() {
+                        return Ok(Some("null".to_string()));
+                    }
+                    bail!("TODO: string conversion of {reference:?}");
+                };
+
+                let string = ClassIdentifier::from_parts("java.lang", "String");
+                if self.heap_get(heap_id)?.class_identifier()? == string {
+                    return self.read_java_string(heap_id).map(Some);
+                }
+
+                self.stack.push_operand(operand)?;
+                let object = ClassIdentifier::from_parts("java.lang", "Object");
+                self.invoke_virtual_method(&object, "toString", "()Ljava/lang/String;")?;
+                // nothing was returned, the exception is dispatched after the instruction
+                if self.exception.is_some() {
+                    return Ok(None);
+                }
+
+                match self.stack.pop_operand()?.reference()? {
+                    ReferenceValue::HeapItem(heap_id) => self.read_java_string(heap_id)?,
+                    _ => "null".to_string(),
+                }
+            }
+        }))
+    }
+
+    fn constant_string(&self, class: &Class, index: &CpIndex) -> Result<String> {
+        Ok(match class.cp_item(index)? {
+            CpInfo::String { string_index } => class.utf8(string_index)?.to_string(),
+            CpInfo::Integer(value) => value.to_string(),
+            CpInfo::Long(value) => value.to_string(),
+            CpInfo::Float(value) => floating_point_string(*value),
+            CpInfo::Double(value) => floating_point_string(*value),
+            item => bail!("TODO: string conversion of constant {item:?}"),
+        })
+    }
+
     pub fn new_string(&mut self, value: String) -> Result<HeapId> {
-        let string_identifier = ClassIdentifier::new("java.lang".to_owned(), "String".to_owned());
-        let class = self.resolve_class(&string_identifier)?;
+        let string_identifier = ClassIdentifier::from_parts("java.lang", "String");
+        let class = self.initialize(&string_identifier)?;
 
         let fields = self.default_instance_fields(&class, 0)?;
         let object_id = self.allocate(class.identifier().clone(), fields)?;
-        let bytes = value
-            .into_bytes()
-            .iter()
-            .map(|b| PrimitiveArrayValue::Byte(*b))
-            .collect();
+        self.write_java_string(&object_id, &value)?;
+        Ok(object_id)
+    }
+
+    /// Sets the contents of the `java.lang.String` at `heap_id` to `value`, using the compact
+    /// LATIN1 coder if possible and UTF16 otherwise
+    pub fn write_java_string(&mut self, heap_id: &HeapId, value: &str) -> Result<()> {
+        let (coder, bytes) = match value
+            .chars()
+            .map(|c| u8::try_from(u32::from(c)))
+            .collect::<Result<Vec<u8>, _>>()
+        {
+            Ok(latin1) => (STRING_LATIN1, latin1),
+            Err(_) => {
+                let bytes = value.encode_utf16().flat_map(u16::to_ne_bytes).collect();
+                (STRING_UTF16, bytes)
+            }
+        };
+
+        let bytes = bytes.into_iter().map(PrimitiveArrayValue::Byte).collect();
         let heap_item = self.allocate_primitive_array(PrimitiveArrayType::Byte, bytes)?;
         let byte_array = FrameValue::Reference(ReferenceValue::HeapItem(heap_item));
-        self.heap_set_field(&object_id, "value", byte_array.into())?;
+        self.heap_set_field(heap_id, "value", byte_array.try_into()?)?;
+        self.heap_set_field(heap_id, "coder", FieldValue::Integer(coder))
+    }
+
+    /// Returns the pooled string with the contents `value`, allocating it if there is none yet
+    pub fn intern_string(&mut self, value: String) -> Result<HeapId> {
+        if let Some(heap_id) = self
+            .strings
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+            .get(&value)
+        {
+            return Ok(heap_id.clone());
+        }
+
+        // allocating can run class initializers which intern strings themselves,
+        // so the pool must not be locked meanwhile
+        let heap_id = self.new_string(value.clone())?;
+        let mut strings = self
+            .strings
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+        Ok(strings.entry(value).or_insert(heap_id).clone())
+    }
+
+    /// Reads the contents of the `java.lang.String` at `heap_id`, decoding its value according
+    /// to its coder
+    pub fn read_java_string(&self, heap_id: &HeapId) -> Result<String> {
+        let byte_value = self.heap_get_field(heap_id, "value")?;
+        let (_, primitive_array) = self.get_primitive_array(byte_value.heap_id()?)?;
+        let bytes: Vec<u8> = primitive_array
+            .iter()
+            .map(|p| p.byte())
+            .collect::<Result<Vec<u8>>>()?;
+
+        match self.heap_get_field(heap_id, "coder")?.int()? {
+            STRING_LATIN1 => Ok(bytes.into_iter().map(char::from).collect()),
+            STRING_UTF16 => {
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_ne_bytes([unit[0], unit[1]]))
+                    .collect();
+                Ok(String::from_utf16(&units)?)
+            }
+            coder => bail!("invalid string coder {coder}"),
+        }
+    }
+
+    /// Allocates an exception of class `identifier` with `message` as its detail message
+    pub fn new_exception(
+        &mut self,
+        identifier: &ClassIdentifier,
+        message: String,
+    ) -> Result<HeapId> {
+        // initializing may run code which collects garbage, so the class is initialized before
+        // allocating anything only held here
+        let class = self.initialize(identifier)?;
+        let throwable = self.initialize(&ClassIdentifier::from_parts("java.lang", "Throwable"))?;
+        let message = self.new_string(message)?;
+
+        let fields = self.default_instance_fields(&class, 0)?;
+        let object_id = self.allocate(class.identifier().clone(), fields)?;
+        // no constructor runs, so the fields Throwable's field initializers set are filled in
+        // here, otherwise addSuppressed and initCause treat the exception as disabled or caused
+        self.heap_set_field(
+            &object_id,
+            "detailMessage",
+            FieldValue::Reference(ReferenceValue::HeapItem(message)),
+        )?;
+        self.heap_set_field(
+            &object_id,
+            "cause",
+            FieldValue::Reference(ReferenceValue::HeapItem(object_id.clone())),
+        )?;
+        self.heap_set_field(
+            &object_id,
+            "stackTrace",
+            throwable.get_static_field_value("UNASSIGNED_STACK")?,
+        )?;
+        self.heap_set_field(
+            &object_id,
+            "suppressedExceptions",
+            throwable.get_static_field_value("SUPPRESSED_SENTINEL")?,
+        )?;
+        self.fill_in_stack_trace(&object_id)?;
         Ok(object_id)
     }
 
+    /// Records the current stack in the `backtrace` of `throwable` as an `Object[]` of
+    /// `StackTraceElement`s, which `StackTraceElement.initStackTraceElements` copies from
+    pub fn fill_in_stack_trace(&mut self, throwable: &HeapId) -> Result<()> {
+        let frames = self.stack.backtrace(throwable);
+        self.initialize(&ClassIdentifier::from_parts(
+            "java.lang",
+            "StackTraceElement",
+        ))?;
+
+        let object_identifier = ClassIdentifier::from_parts("java.lang", "Object");
+        let backtrace = self.allocate_array(object_identifier, frames.len())?;
+        for (i, frame) in frames.iter().enumerate() {
+            let element = self.new_stack_trace_element(frame)?;
+            self.store_into_reference_array(&backtrace, i, ReferenceValue::HeapItem(element))?;
+        }
+
+        self.heap_set_field(
+            throwable,
+            "backtrace",
+            FieldValue::Reference(ReferenceValue::HeapItem(backtrace)),
+        )?;
+        self.heap_set_field(throwable, "depth", FieldValue::Integer(frames.len() as i32))
+    }
+
+    fn new_stack_trace_element(&mut self, frame: &BacktraceFrame) -> Result<HeapId> {
+        let identifier = ClassIdentifier::from_parts("java.lang", "StackTraceElement");
+        let class = self.initialize(&identifier)?;
+        let declaring_class_object = self.class_mirror(&frame.class)?;
+        let file_name = match self.class(&frame.class)?.source_file()? {
+            Some(file_name) => ReferenceValue::HeapItem(self.new_string(file_name.to_string())?),
+            None => ReferenceValue::Null,
+        };
+        let declaring_class = self.new_string(format!("{:?}", frame.class))?;
+        let method_name = self.new_string(frame.method_name.clone())?;
+
+        let fields = self.default_instance_fields(&class, 0)?;
+        let element = self.allocate(identifier, fields)?;
+        self.heap_set_field(
+            &element,
+            "declaringClassObject",
+            FieldValue::Reference(ReferenceValue::HeapItem(declaring_class_object)),
+        )?;
+        self.heap_set_field(
+            &element,
+            "declaringClass",
+            FieldValue::Reference(ReferenceValue::HeapItem(declaring_class)),
+        )?;
+        self.heap_set_field(
+            &element,
+            "methodName",
+            FieldValue::Reference(ReferenceValue::HeapItem(method_name)),
+        )?;
+        self.heap_set_field(&element, "fileName", FieldValue::Reference(file_name))?;
+        // -1 marks an unknown line number
+        let line_number = frame.line_number.map_or(-1, i32::from);
+        self.heap_set_field(&element, "lineNumber", FieldValue::Integer(line_number))?;
+
+        Ok(element)
+    }
+
+    /// Allocates a `String[]` holding a new string for each of `values`
+    pub fn new_string_array(&mut self, values: Vec<String>) -> Result<HeapId> {
+        let string_identifier = ClassIdentifier::from_parts("java.lang", "String");
+        let array = self.allocate_array(string_identifier, values.len())?;
+        for (i, value) in values.into_iter().enumerate() {
+            let string = self.new_string(value)?;
+            self.store_into_reference_array(&array, i, ReferenceValue::HeapItem(string))?;
+        }
+
+        Ok(array)
+    }
+
     fn new_thread_object(&mut self, name: String, thread_group_name: String) -> Result<HeapId> {
         let name_string = self.new_string(name)?;
         let thread_group = self.new_thread_group_object(thread_group_name)?;
-        let thread_identifier = ClassIdentifier::new("java.lang".to_owned(), "Thread".to_owned());
-        let class = self.resolve_class(&thread_identifier)?;
+        let thread_identifier = ClassIdentifier::from_parts("java.lang", "Thread");
+        let class = self.initialize(&thread_identifier)?;
 
         let fields = self.default_instance_fields(&class, 0)?;
         let object_id = self.allocate(class.identifier().clone(), fields)?;
@@ -1962,18 +4340,20 @@ impl JvmThread {
             FieldValue::Reference(ReferenceValue::HeapItem(thread_group)),
         )?;
         self.heap_set_field(&object_id, "priority", FieldValue::Integer(1))?;
+        self.heap_set_field(
+            &object_id,
+            "threadStatus",
+            FieldValue::Integer(THREAD_RUNNABLE),
+        )?;
 
         let thread_id = class.get_static_field_value("threadSeqNumber")?.long()?;
         self.heap_set_field(&object_id, "tid", FieldValue::Long(thread_id))?;
 
-        let mut classes = self
-            .classes
-            .lock()
-            .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
-        classes
-            .get_mut(&thread_identifier)
-            .context(format!("class {thread_identifier:?} is not initialized"))?
-            .set_static_field("threadSeqNumber", FieldValue::Long(thread_id + 1))?;
+        self.set_static_field(
+            &thread_identifier,
+            "threadSeqNumber",
+            FieldValue::Long(thread_id + 1),
+        )?;
 
         self.current_thread_id = Some(thread_id.into());
         Ok(object_id)
@@ -1981,9 +4361,8 @@ impl JvmThread {
 
     fn new_thread_group_object(&mut self, name: String) -> Result<HeapId> {
         let name_string = self.new_string(name)?;
-        let thread_identifier =
-            ClassIdentifier::new("java.lang".to_owned(), "ThreadGroup".to_owned());
-        let class = self.resolve_class(&thread_identifier)?;
+        let thread_identifier = ClassIdentifier::from_parts("java.lang", "ThreadGroup");
+        let class = self.initialize(&thread_identifier)?;
 
         let fields = self.default_instance_fields(&class, 0)?;
         let object_id = self.allocate(class.identifier().clone(), fields)?;
@@ -2001,7 +4380,18 @@ impl JvmThread {
         class: &Class,
         mut offset: i64,
     ) -> Result<HashMap<String, InstanceField>> {
+        // fields of superclasses come first, so they have the same offset in every subclass
         let mut fields = HashMap::new();
+        if class.has_super_class() {
+            let super_class = self.initialize(&class.super_class()?)?;
+            fields = self.default_instance_fields(&super_class, offset)?;
+            offset = fields
+                .values()
+                .map(|field| field.offset() + 1)
+                .max()
+                .unwrap_or(offset);
+        }
+
         for field in class.fields() {
             if field.is_static() {
                 continue;
@@ -2016,12 +4406,6 @@ impl JvmThread {
             offset += 1;
         }
 
-        if class.has_super_class() {
-            let super_class = self.initialize(&class.super_class()?)?;
-            let super_class_fields = self.default_instance_fields(&super_class, offset)?;
-            fields.extend(super_class_fields);
-        }
-
         Ok(fields)
     }
 
@@ -2033,18 +4417,29 @@ impl JvmThread {
     ) -> Result<(ClassIdentifier, Method)> {
         let class = self.initialize(class)?;
 
-        if let Ok(m) = class.method(name, descriptor) {
-            if class.is_method_signature_polymorphic(m)? {
-                bail!("TODO: method is signature polymorphic");
+        let mut current = class.clone();
+        loop {
+            if let Ok(m) = current.method(name, descriptor) {
+                if current.is_method_signature_polymorphic(m)? {
+                    bail!("TODO: method is signature polymorphic");
+                }
+
+                return Ok((current.identifier().clone(), m.clone()));
             }
 
-            Ok((class.identifier().clone(), m.clone()))
-        } else {
-            let super_class = class
-                .super_class()
-                .context("method not found, maybe check interfaces?")?;
-            self.resolve_method(&super_class.clone(), name, descriptor)
+            if !current.has_super_class() {
+                break;
+            }
+            current = self.initialize(&current.super_class()?)?;
         }
+
+        let (interface, method) = self
+            .resolve_superinterface_method(&class, name, descriptor)?
+            .context(format!(
+                "method {name} not found in {:?}",
+                class.identifier()
+            ))?;
+        Ok((interface.identifier().clone(), method))
     }
 
     fn resolve_interface_method(
@@ -2066,10 +4461,7 @@ impl JvmThread {
             return Ok(m.clone());
         }
 
-        let object_class = self.class(&ClassIdentifier::new(
-            "java.lang".to_owned(),
-            "Object".to_owned(),
-        ))?;
+        let object_class = self.class(&ClassIdentifier::from_parts("java.lang", "Object"))?;
         if let Ok(object_method) = object_class.method(name, descriptor)
             && object_method.is_public()
             && !object_method.is_static()
@@ -2077,31 +4469,112 @@ impl JvmThread {
             return Ok(object_method.clone());
         }
 
-        for super_interface in class.super_interfaces()? {
-            if let Ok(method) = self.resolve_interface_method(&super_interface, name, descriptor) {
-                return Ok(method.clone());
+        if let Some((_, method)) = self.resolve_superinterface_method(&class, name, descriptor)? {
+            return Ok(method);
+        }
+
+        bail!("method {name} not found, TODO: throw NoSuchMethodError")
+    }
+
+    /// Looks up `name` in the superinterfaces of `class`, preferring the only maximally-specific
+    /// non-abstract method and falling back to an arbitrary one (JVMS 5.4.3.3 and 5.4.3.4).
+    fn resolve_superinterface_method(
+        &mut self,
+        class: &Class,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Option<(Class, Method)>> {
+        let methods = self.maximally_specific_methods(class, name, descriptor)?;
+        let non_abstract: Vec<_> = methods.iter().filter(|(_, m)| !m.is_abstract()).collect();
+        if let [(interface, method)] = non_abstract.as_slice() {
+            return Ok(Some((interface.clone(), method.clone())));
+        }
+
+        Ok(methods.into_iter().next())
+    }
+
+    /// Collects the non-private, non-static methods named `name` declared in the superinterfaces of
+    /// `class` for which no subinterface declares a matching method as well.
+    fn maximally_specific_methods(
+        &mut self,
+        class: &Class,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<Vec<(Class, Method)>> {
+        let mut candidates = Vec::new();
+        self.superinterface_methods(class, name, descriptor, &mut candidates)?;
+
+        let mut methods = Vec::new();
+        for (interface, method) in &candidates {
+            let mut is_overridden = false;
+            for (other, _) in &candidates {
+                if other.identifier() != interface.identifier()
+                    && self.is_subclass(other.identifier(), interface.identifier())?
+                {
+                    is_overridden = true;
+                    break;
+                }
+            }
+
+            if !is_overridden {
+                methods.push((interface.clone(), method.clone()));
+            }
+        }
+
+        Ok(methods)
+    }
+
+    fn superinterface_methods(
+        &mut self,
+        class: &Class,
+        name: &str,
+        descriptor: &str,
+        methods: &mut Vec<(Class, Method)>,
+    ) -> Result<()> {
+        for identifier in class.super_interfaces()? {
+            let interface = self.resolve_class(&identifier)?;
+            if let Ok(m) = interface.method(name, descriptor)
+                && !m.is_private()
+                && !m.is_static()
+                && !methods.iter().any(|(c, _)| c.identifier() == &identifier)
+            {
+                methods.push((interface.clone(), m.clone()));
             }
+
+            self.superinterface_methods(&interface, name, descriptor, methods)?;
         }
 
-        bail!("TODO: 5.4.3.4 interface method resolution")
+        if class.has_super_class() {
+            let super_class = self.resolve_class(&class.super_class()?)?;
+            self.superinterface_methods(&super_class, name, descriptor, methods)?;
+        }
+
+        Ok(())
     }
 
+    /// Resolves the field in `class`, its superinterfaces or its superclasses, together with
+    /// the class which declares it
     fn resolve_field(
         &mut self,
         class: &ClassIdentifier,
         name: &str,
         descriptor: &str,
-    ) -> Result<Field> {
-        let class = self.initialize(class)?;
-
+    ) -> Result<(ClassIdentifier, Field)> {
+        let class = self.resolve_class(class)?;
         if let Ok(f) = class.field(name, descriptor) {
-            Ok(f.clone())
-        } else {
-            let super_class = class
-                .super_class()
-                .context("field not found, maybe check interfaces?")?;
-            self.resolve_field(&super_class, name, descriptor)
+            return Ok((class.identifier().clone(), f.clone()));
+        }
+
+        for interface in class.super_interfaces()? {
+            if let Ok(resolved) = self.resolve_field(&interface, name, descriptor) {
+                return Ok(resolved);
+            }
+        }
+
+        if !class.has_super_class() {
+            bail!("field {name} not found, TODO: throw NoSuchFieldError");
         }
+        self.resolve_field(&class.super_class()?, name, descriptor)
     }
 
     fn field_ref(&self, index: &CpIndex) -> Result<(ClassIdentifier, String, FieldDescriptor)> {
@@ -2132,7 +4605,11 @@ impl JvmThread {
                 class_index,
                 name_and_type_index,
             } => {
-                let class_identifier = current_class.class_identifier(class_index)?;
+                let mut class_identifier = current_class.class_identifier(class_index)?;
+                // arrays only inherit the methods of Object, like `clone` on an `[I`
+                if class_identifier.is_array() {
+                    class_identifier = ClassIdentifier::from_parts("java.lang", "Object");
+                }
                 let (name, descriptor) = current_class.name_and_type(name_and_type_index)?;
                 Ok((class_identifier, name.to_string(), descriptor.to_string()))
             }
@@ -2153,7 +4630,8 @@ impl JvmThread {
                 .clone()
                 .context("how do we not have a thread id?")?;
             if method.is_static() {
-                self.exit_class_monitor(current_class.identifier(), &thread_id)
+                let mirror = self.class_mirror(current_class.identifier())?;
+                self.exit_object_monitor(&mirror, &thread_id)
             } else {
                 let heap_id = self
                     .stack
@@ -2166,3 +4644,2196 @@ impl JvmThread {
         }
     }
 }
+
+/// `value` as the value of a field with `descriptor`, whose type it has to have
+fn field_value(descriptor: &FieldDescriptor, value: FrameValue) -> Result<FieldValue> {
+    let matches_type = match &descriptor.field_type {
+        FieldType::BaseType(BaseType::Long) => matches!(value, FrameValue::Long(_)),
+        FieldType::BaseType(BaseType::Float) => matches!(value, FrameValue::Float(_)),
+        FieldType::BaseType(BaseType::Double) => matches!(value, FrameValue::Double(_)),
+        FieldType::BaseType(_) => matches!(value, FrameValue::Int(_)),
+        FieldType::ObjectType { .. } | FieldType::ComponentType(_) => value.is_reference(),
+    };
+    if !matches_type {
+        bail!(
+            "cannot store {value:?} in field of type {}",
+            descriptor.raw()
+        );
+    }
+
+    value.try_into()
+}
+
+/// Formats `value` like `Float.toString` and `Double.toString`
+fn floating_point_string<T: Copy + Debug + LowerExp + Into<f64>>(value: T) -> String {
+    let double: f64 = value.into();
+    if double.is_nan() {
+        return "NaN".to_string();
+    }
+
+    if double.is_infinite() {
+        return if double > 0.0 {
+            "Infinity"
+        } else {
+            "-Infinity"
+        }
+        .to_string();
+    }
+
+    if double == 0.0 || (1e-3..1e7).contains(&double.abs()) {
+        return format!("{value:?}");
+    }
+
+    let scientific = format!("{value:e}");
+    match scientific.split_once('e') {
+        Some((mantissa, exponent)) if mantissa.contains('.') => format!("{mantissa}E{exponent}"),
+        Some((mantissa, exponent)) => format!("{mantissa}.0E{exponent}"),
+        None => scientific,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use parser::class::attribute::{Attribute, BootStrapMethod};
+
+    use crate::{
+        directory::DirectoryClassSource, jdk::Jdk, loader::ReadClass, thread::stack::code::Code,
+    };
+
+    use super::*;
+
+    /// Reads the classes compiled from the sources in `testdata`
+    /// (`javac --release 17 -d testdata testdata/*.java`)
+    fn test_data() -> DirectoryClassSource {
+        DirectoryClassSource::new(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata"))
+    }
+
+    pub(super) fn thread() -> JvmThread {
+        let sources: Vec<Box<dyn ReadClass>> =
+            vec![Box::new(test_data()), Box::new(Jdk::new().unwrap())];
+        let class_loader = Arc::new(Mutex::new(BootstrapClassLoader::new(sources)));
+        let mut thread = JvmThread::default("test".to_string(), class_loader);
+        thread
+            .initialize(&ClassIdentifier::from_parts("java.lang", "Class"))
+            .unwrap();
+        thread
+    }
+
+    fn run_static(thread: &mut JvmThread, class: &str, name: &str) -> Result<()> {
+        thread.run_method(&ClassIdentifier::new(class)?, name, "()V", vec![])
+    }
+
+    fn static_int(thread: &JvmThread, class: &str, name: &str) -> i32 {
+        thread
+            .class(&ClassIdentifier::new(class).unwrap())
+            .unwrap()
+            .get_static_field_value(name)
+            .unwrap()
+            .int()
+            .unwrap()
+    }
+
+    fn static_string(thread: &JvmThread, class: &str, name: &str) -> String {
+        let value = thread
+            .class(&ClassIdentifier::new(class).unwrap())
+            .unwrap()
+            .get_static_field_value(name)
+            .unwrap()
+            .reference()
+            .unwrap();
+        thread.read_java_string(value.heap_id().unwrap()).unwrap()
+    }
+
+    /// A thread without any class sources, executing a single empty frame
+    pub(super) fn frame_thread() -> JvmThread {
+        let class_loader = Arc::new(Mutex::new(BootstrapClassLoader::new(vec![])));
+        let mut thread = JvmThread::default("test".to_string(), class_loader);
+        let code = Code::new(Attribute::Code {
+            attribute_name_index: 0u16.into(),
+            attribute_length: 0,
+            max_stack: 0,
+            max_locals: 0,
+            code: vec![],
+            exception_table: vec![],
+            attributes: vec![],
+        })
+        .unwrap();
+        thread
+            .stack
+            .push(
+                "test".to_string(),
+                MethodDescriptor::new("()V").unwrap(),
+                vec![],
+                Arc::new(code),
+                ClassIdentifier::from_parts("", "Test"),
+                None,
+            )
+            .unwrap();
+        thread
+    }
+
+    /// Pushes `operands` onto the frame of `thread`, runs `op` and pops the operands it leaves
+    /// behind, the bottom one first
+    fn run_op(
+        thread: &mut JvmThread,
+        op: fn(&mut JvmThread) -> Result<()>,
+        operands: &[FrameValue],
+    ) -> Result<Vec<FrameValue>> {
+        for operand in operands {
+            thread.stack.push_operand(operand.clone())?;
+        }
+        op(thread)?;
+
+        let mut results = Vec::new();
+        while let Ok(value) = thread.stack.pop_operand() {
+            results.push(value);
+        }
+        results.reverse();
+        Ok(results)
+    }
+
+    #[test]
+    fn double_arithmetic() {
+        let mut thread = frame_thread();
+        let mut double_op = |op, operands: &[f64]| {
+            let operands: Vec<_> = operands.iter().copied().map(FrameValue::Double).collect();
+            let mut results = run_op(&mut thread, op, &operands).unwrap();
+            results.pop().unwrap().double().unwrap()
+        };
+        assert_eq!(double_op(JvmThread::dsub, &[1.5, 3.0]), -1.5);
+        assert_eq!(double_op(JvmThread::dmul, &[1.5, -3.0]), -4.5);
+        assert_eq!(double_op(JvmThread::ddiv, &[3.0, 2.0]), 1.5);
+        assert_eq!(double_op(JvmThread::ddiv, &[1.0, 0.0]), f64::INFINITY);
+        assert_eq!(double_op(JvmThread::ddiv, &[-1.0, 0.0]), f64::NEG_INFINITY);
+        assert!(double_op(JvmThread::ddiv, &[0.0, 0.0]).is_nan());
+        assert_eq!(double_op(JvmThread::drem, &[5.5, 2.0]), 1.5);
+        assert_eq!(double_op(JvmThread::drem, &[-5.5, 2.0]), -1.5);
+        assert_eq!(double_op(JvmThread::drem, &[5.5, -2.0]), 1.5);
+        assert!(double_op(JvmThread::drem, &[1.0, 0.0]).is_nan());
+        assert_eq!(double_op(JvmThread::dneg, &[2.0]), -2.0);
+        assert!(double_op(JvmThread::dneg, &[0.0]).is_sign_negative());
+    }
+
+    #[test]
+    fn long_arithmetic() {
+        let mut thread = frame_thread();
+        let mut long_op = |op, operands: &[i64]| {
+            let operands: Vec<_> = operands.iter().copied().map(FrameValue::Long).collect();
+            let mut results = run_op(&mut thread, op, &operands).unwrap();
+            results.pop().unwrap().long().unwrap()
+        };
+        assert_eq!(long_op(JvmThread::ldiv, &[7, -2]), -3);
+        assert_eq!(long_op(JvmThread::ldiv, &[i64::MIN, -1]), i64::MIN);
+        assert_eq!(long_op(JvmThread::lrem, &[-7, 2]), -1);
+        assert_eq!(long_op(JvmThread::lrem, &[i64::MIN, -1]), 0);
+        assert_eq!(long_op(JvmThread::lneg, &[i64::MIN]), i64::MIN);
+        assert_eq!(long_op(JvmThread::lor, &[0b1010, 0b0110]), 0b1110);
+        assert_eq!(long_op(JvmThread::lxor, &[0b1010, 0b0110]), 0b1100);
+    }
+
+    #[test]
+    fn int_division() {
+        let mut thread = frame_thread();
+        let mut int_op = |op, operands: &[i32]| {
+            let operands: Vec<_> = operands.iter().copied().map(FrameValue::Int).collect();
+            let mut results = run_op(&mut thread, op, &operands).unwrap();
+            results.pop().unwrap().int().unwrap()
+        };
+        assert_eq!(int_op(JvmThread::idiv, &[7, -2]), -3);
+        assert_eq!(int_op(JvmThread::idiv, &[i32::MIN, -1]), i32::MIN);
+        assert_eq!(int_op(JvmThread::irem, &[-7, 2]), -1);
+        assert_eq!(int_op(JvmThread::irem, &[i32::MIN, -1]), 0);
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let mut thread = thread();
+        run_static(&mut thread, "Division", "run").unwrap();
+        assert_eq!(static_int(&thread, "Division", "caught"), 4);
+    }
+
+    #[test]
+    fn float_arithmetic() {
+        let mut thread = frame_thread();
+        let mut float_op = |op, operands: &[f32]| {
+            let operands: Vec<_> = operands.iter().copied().map(FrameValue::Float).collect();
+            let mut results = run_op(&mut thread, op, &operands).unwrap();
+            results.pop().unwrap().float().unwrap()
+        };
+        assert_eq!(float_op(JvmThread::frem, &[5.5, 2.0]), 1.5);
+        assert_eq!(float_op(JvmThread::frem, &[-5.5, 2.0]), -1.5);
+        assert_eq!(float_op(JvmThread::frem, &[5.5, -2.0]), 1.5);
+        assert_eq!(float_op(JvmThread::frem, &[-5.5, -2.0]), -1.5);
+        assert!(float_op(JvmThread::frem, &[1.0, 0.0]).is_nan());
+        assert_eq!(float_op(JvmThread::fneg, &[2.0]), -2.0);
+        assert!(float_op(JvmThread::fneg, &[-0.0]).is_sign_positive());
+    }
+
+    #[test]
+    fn double_comparison() {
+        use FrameValue::Double;
+
+        let mut thread = frame_thread();
+        let mut double_cmp = |op, value1, value2| {
+            let mut results = run_op(&mut thread, op, &[Double(value1), Double(value2)]).unwrap();
+            results.pop().unwrap().int().unwrap()
+        };
+        for op in [JvmThread::dcmpl, JvmThread::dcmpg] {
+            assert_eq!(double_cmp(op, 2.0, 1.0), 1);
+            assert_eq!(double_cmp(op, 1.0, 1.0), 0);
+            assert_eq!(double_cmp(op, 1.0, 2.0), -1);
+            assert_eq!(double_cmp(op, 0.0, -0.0), 0);
+        }
+
+        assert_eq!(double_cmp(JvmThread::dcmpl, f64::NAN, 1.0), -1);
+        assert_eq!(double_cmp(JvmThread::dcmpl, 1.0, f64::NAN), -1);
+        assert_eq!(double_cmp(JvmThread::dcmpg, f64::NAN, 1.0), 1);
+        assert_eq!(double_cmp(JvmThread::dcmpg, 1.0, f64::NAN), 1);
+    }
+
+    #[test]
+    fn numeric_conversions() {
+        let mut thread = frame_thread();
+        let mut convert = |op, value| run_op(&mut thread, op, &[value]).unwrap().pop().unwrap();
+
+        let mut d2i = |value| {
+            convert(JvmThread::d2i, FrameValue::Double(value))
+                .int()
+                .unwrap()
+        };
+        assert_eq!(d2i(f64::NAN), 0);
+        assert_eq!(d2i(1e20), i32::MAX);
+        assert_eq!(d2i(-1e20), i32::MIN);
+        assert_eq!(d2i(f64::INFINITY), i32::MAX);
+        assert_eq!(d2i(-2.9), -2);
+
+        let mut f2l = |value| {
+            convert(JvmThread::f2l, FrameValue::Float(value))
+                .long()
+                .unwrap()
+        };
+        assert_eq!(f2l(f32::NAN), 0);
+        assert_eq!(f2l(1e30), i64::MAX);
+        assert_eq!(f2l(f32::NEG_INFINITY), i64::MIN);
+        assert_eq!(f2l(2.9), 2);
+
+        let mut d2f = |value| {
+            convert(JvmThread::d2f, FrameValue::Double(value))
+                .float()
+                .unwrap()
+        };
+        assert!(d2f(f64::NAN).is_nan());
+        assert_eq!(d2f(1e300), f32::INFINITY);
+        assert_eq!(d2f(-1e300), f32::NEG_INFINITY);
+        assert!(d2f(-1e-300).is_sign_negative());
+
+        let i2d = convert(JvmThread::i2d, FrameValue::Int(i32::MIN));
+        assert_eq!(i2d.double().unwrap(), -2147483648.0);
+        let l2d = convert(JvmThread::l2d, FrameValue::Long(i64::MAX));
+        assert_eq!(l2d.double().unwrap(), 9.223372036854776e18);
+    }
+
+    #[test]
+    fn primitive_array_loads() {
+        let mut thread = frame_thread();
+        let mut array_load = |op, array_type, values, index| {
+            let heap_id = thread.allocate_primitive_array(array_type, values)?;
+            let arrayref = FrameValue::Reference(ReferenceValue::HeapItem(heap_id));
+            let mut results = run_op(&mut thread, op, &[arrayref, FrameValue::Int(index)])?;
+            results.pop().context("no value loaded")
+        };
+
+        let int = array_load(
+            JvmThread::iaload,
+            PrimitiveArrayType::Int,
+            vec![PrimitiveArrayValue::Int(1), PrimitiveArrayValue::Int(-2)],
+            1,
+        );
+        assert_eq!(int.unwrap().int().unwrap(), -2);
+
+        let long = array_load(
+            JvmThread::laload,
+            PrimitiveArrayType::Long,
+            vec![PrimitiveArrayValue::Long(i64::MAX)],
+            0,
+        );
+        assert_eq!(long.unwrap().long().unwrap(), i64::MAX);
+
+        let float = array_load(
+            JvmThread::faload,
+            PrimitiveArrayType::Float,
+            vec![PrimitiveArrayValue::Float(1.5)],
+            0,
+        );
+        assert_eq!(float.unwrap().float().unwrap(), 1.5);
+
+        let double = array_load(
+            JvmThread::daload,
+            PrimitiveArrayType::Double,
+            vec![PrimitiveArrayValue::Double(-2.5)],
+            0,
+        );
+        assert_eq!(double.unwrap().double().unwrap(), -2.5);
+
+        let char = array_load(
+            JvmThread::caload,
+            PrimitiveArrayType::Char,
+            vec![PrimitiveArrayValue::Char(0xffff)],
+            0,
+        );
+        assert_eq!(char.unwrap().int().unwrap(), 0xffff);
+
+        let short = array_load(
+            JvmThread::saload,
+            PrimitiveArrayType::Short,
+            vec![PrimitiveArrayValue::Short(0xffff)],
+            0,
+        );
+        assert_eq!(short.unwrap().int().unwrap(), -1);
+    }
+
+    #[test]
+    fn primitive_array_load_null() {
+        let mut thread = thread();
+        let object = ClassIdentifier::from_parts("java.lang", "Object");
+        thread.stack.push_entry(object).unwrap();
+        thread
+            .stack
+            .push_operand(FrameValue::Reference(ReferenceValue::Null))
+            .unwrap();
+        thread.stack.push_operand(FrameValue::Int(0)).unwrap();
+
+        thread.iaload().unwrap();
+        assert_eq!(
+            pending_exception(&mut thread).as_deref(),
+            Some("java.lang.NullPointerException")
+        );
+    }
+
+    fn array_store(
+        op: fn(&mut JvmThread) -> Result<()>,
+        array_type: PrimitiveArrayType,
+        index: i32,
+        value: FrameValue,
+    ) -> Result<PrimitiveArrayValue> {
+        let mut thread = frame_thread();
+        let heap_id = thread.allocate_default_primitive_array(array_type, 2)?;
+        let arrayref = FrameValue::Reference(ReferenceValue::HeapItem(heap_id.clone()));
+        run_op(&mut thread, op, &[arrayref, FrameValue::Int(index), value])?;
+        let (_, values) = thread.get_primitive_array(&heap_id)?;
+        Ok(values[index as usize].clone())
+    }
+
+    #[test]
+    fn primitive_array_stores() {
+        let long = array_store(
+            JvmThread::lastore,
+            PrimitiveArrayType::Long,
+            1,
+            FrameValue::Long(i64::MIN),
+        );
+        assert!(matches!(long, Ok(PrimitiveArrayValue::Long(i64::MIN))));
+
+        let float = array_store(
+            JvmThread::fastore,
+            PrimitiveArrayType::Float,
+            0,
+            FrameValue::Float(1.5),
+        );
+        assert!(matches!(float, Ok(PrimitiveArrayValue::Float(1.5))));
+
+        let double = array_store(
+            JvmThread::dastore,
+            PrimitiveArrayType::Double,
+            1,
+            FrameValue::Double(-2.5),
+        );
+        assert!(matches!(double, Ok(PrimitiveArrayValue::Double(-2.5))));
+
+        let short = array_store(
+            JvmThread::sastore,
+            PrimitiveArrayType::Short,
+            0,
+            FrameValue::Int(0x12345),
+        );
+        assert!(matches!(short, Ok(PrimitiveArrayValue::Short(0x2345))));
+    }
+
+    #[test]
+    fn primitive_array_store_type_mismatch() {
+        let res = array_store(
+            JvmThread::dastore,
+            PrimitiveArrayType::Float,
+            0,
+            FrameValue::Double(1.0),
+        );
+        assert!(res.is_err());
+
+        let res = array_store(
+            JvmThread::fastore,
+            PrimitiveArrayType::Float,
+            0,
+            FrameValue::Double(1.0),
+        );
+        assert!(res.is_err());
+    }
+
+    fn stack_op(op: fn(&mut JvmThread) -> Result<()>, operands: &[FrameValue]) -> Result<String> {
+        let results = run_op(&mut frame_thread(), op, operands)?;
+        let values: Vec<_> = results
+            .into_iter()
+            .map(|value| match value {
+                FrameValue::Int(val) => format!("i{val}"),
+                FrameValue::Long(val) => format!("l{val}"),
+                value => format!("{value:?}"),
+            })
+            .collect();
+        Ok(values.join(" "))
+    }
+
+    #[test]
+    fn stack_manipulation() {
+        use FrameValue::{Int, Long};
+
+        assert_eq!(
+            stack_op(JvmThread::swap, &[Int(1), Int(2)]).unwrap(),
+            "i2 i1"
+        );
+        assert!(stack_op(JvmThread::swap, &[Int(1), Long(2)]).is_err());
+
+        assert_eq!(stack_op(JvmThread::pop2, &[Int(1), Long(2)]).unwrap(), "i1");
+        assert_eq!(
+            stack_op(JvmThread::pop2, &[Int(1), Int(2), Int(3)]).unwrap(),
+            "i1"
+        );
+        assert!(stack_op(JvmThread::pop2, &[Long(1), Int(2)]).is_err());
+
+        let dup_x2 = stack_op(JvmThread::dup_x2, &[Int(3), Int(2), Int(1)]).unwrap();
+        assert_eq!(dup_x2, "i1 i3 i2 i1");
+        let dup_x2 = stack_op(JvmThread::dup_x2, &[Long(2), Int(1)]).unwrap();
+        assert_eq!(dup_x2, "i1 l2 i1");
+        assert!(stack_op(JvmThread::dup_x2, &[Int(2), Long(1)]).is_err());
+
+        let dup2_x1 = stack_op(JvmThread::dup2_x1, &[Int(3), Int(2), Int(1)]).unwrap();
+        assert_eq!(dup2_x1, "i2 i1 i3 i2 i1");
+        let dup2_x1 = stack_op(JvmThread::dup2_x1, &[Int(2), Long(1)]).unwrap();
+        assert_eq!(dup2_x1, "l1 i2 l1");
+        assert!(stack_op(JvmThread::dup2_x1, &[Long(2), Long(1)]).is_err());
+
+        let dup2_x2 = stack_op(JvmThread::dup2_x2, &[Int(4), Int(3), Int(2), Int(1)]).unwrap();
+        assert_eq!(dup2_x2, "i2 i1 i4 i3 i2 i1");
+        let dup2_x2 = stack_op(JvmThread::dup2_x2, &[Int(3), Int(2), Long(1)]).unwrap();
+        assert_eq!(dup2_x2, "l1 i3 i2 l1");
+        let dup2_x2 = stack_op(JvmThread::dup2_x2, &[Long(3), Int(2), Int(1)]).unwrap();
+        assert_eq!(dup2_x2, "i2 i1 l3 i2 i1");
+        let dup2_x2 = stack_op(JvmThread::dup2_x2, &[Long(2), Long(1)]).unwrap();
+        assert_eq!(dup2_x2, "l1 l2 l1");
+    }
+
+    #[test]
+    fn const_instructions() {
+        let mut thread = thread();
+        run_static(&mut thread, "ConstInstructions", "run").unwrap();
+        let class = thread
+            .class(&ClassIdentifier::new("ConstInstructions").unwrap())
+            .unwrap();
+        let value = |name: &str| class.get_static_field_value(name).unwrap();
+
+        assert!(matches!(value("intMinusOne"), FieldValue::Integer(-1)));
+        assert!(matches!(value("bytePush"), FieldValue::Integer(-100)));
+        assert!(matches!(value("shortPush"), FieldValue::Integer(-1000)));
+        assert!(matches!(value("longZero"), FieldValue::Long(0)));
+        assert!(matches!(value("longOne"), FieldValue::Long(1)));
+        assert!(matches!(value("floatZero"), FieldValue::Float(0.0)));
+        assert!(matches!(value("floatOne"), FieldValue::Float(1.0)));
+        assert!(matches!(value("floatTwo"), FieldValue::Float(2.0)));
+        assert!(matches!(value("doubleZero"), FieldValue::Double(0.0)));
+        assert!(matches!(value("doubleOne"), FieldValue::Double(1.0)));
+    }
+
+    #[test]
+    fn missing_instructions() {
+        let mut thread = thread();
+        run_static(&mut thread, "Instructions", "run").unwrap();
+        let class = thread
+            .class(&ClassIdentifier::new("Instructions").unwrap())
+            .unwrap();
+        let value = |name: &str| class.get_static_field_value(name).unwrap();
+
+        assert!(matches!(value("doubleSum"), FieldValue::Double(7.5)));
+        assert!(matches!(value("floatDifference"), FieldValue::Float(3.5)));
+        assert!(matches!(
+            value("shortOverflow"),
+            FieldValue::Integer(-25536)
+        ));
+        assert!(matches!(
+            value("intOverflow"),
+            FieldValue::Integer(i32::MIN)
+        ));
+        assert!(matches!(value("longOverflow"), FieldValue::Long(i64::MAX)));
+        assert!(matches!(value("returnedFloat"), FieldValue::Float(1.5)));
+        assert!(matches!(value("intArrayLength"), FieldValue::Integer(2)));
+        assert!(matches!(value("intInnerLength"), FieldValue::Integer(3)));
+        assert!(matches!(value("intElement"), FieldValue::Integer(7)));
+        assert!(matches!(value("stringInnerLength"), FieldValue::Integer(4)));
+        assert!(matches!(value("stringElementNull"), FieldValue::Integer(1)));
+        assert!(matches!(value("partialInnerNull"), FieldValue::Integer(1)));
+    }
+
+    #[test]
+    fn athrow_caught_in_same_method() {
+        let mut thread = thread();
+        run_static(&mut thread, "Exceptions", "catchInSameMethod").unwrap();
+        assert_eq!(static_int(&thread, "Exceptions", "result"), 1);
+    }
+
+    #[test]
+    fn athrow_caught_in_caller() {
+        let mut thread = thread();
+        run_static(&mut thread, "Exceptions", "catchInCaller").unwrap();
+        assert_eq!(static_int(&thread, "Exceptions", "result"), 2);
+    }
+
+    #[test]
+    fn vm_exception_suppression() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        run_static(&mut thread, "Exceptions", "suppressInVmException").unwrap();
+        assert_eq!(static_int(&thread, "Exceptions", "result"), 1);
+
+        let caught = thread
+            .class(&ClassIdentifier::new("Exceptions").unwrap())
+            .unwrap()
+            .get_static_field_value("caught")
+            .unwrap()
+            .reference()
+            .unwrap();
+        let suppressed = thread
+            .heap_get_field(caught.heap_id().unwrap(), "suppressedExceptions")
+            .unwrap()
+            .reference()
+            .unwrap();
+        let size = thread
+            .heap_get_field(suppressed.heap_id().unwrap(), "size")
+            .unwrap();
+        assert_eq!(size.int().unwrap(), 1);
+    }
+
+    #[test]
+    fn instance_of() {
+        let mut thread = thread();
+        run_static(&mut thread, "InstanceOf", "run").unwrap();
+
+        assert_eq!(static_int(&thread, "InstanceOf", "intArrayIsObject"), 1);
+        assert_eq!(static_int(&thread, "InstanceOf", "intArrayIsLongArray"), 0);
+        assert_eq!(
+            static_int(&thread, "InstanceOf", "stringArrayIsObjectArray"),
+            1
+        );
+        assert_eq!(
+            static_int(&thread, "InstanceOf", "stringArrayIsIntArray"),
+            0
+        );
+        assert_eq!(
+            static_int(&thread, "InstanceOf", "implementsTransitively"),
+            1
+        );
+        assert_eq!(static_int(&thread, "InstanceOf", "implementsUnrelated"), 0);
+    }
+
+    #[test]
+    fn check_cast() {
+        let mut thread = thread();
+        run_static(&mut thread, "CheckCast", "run").unwrap();
+
+        assert_eq!(static_int(&thread, "CheckCast", "upcast"), 1);
+        assert_eq!(static_int(&thread, "CheckCast", "arrayUpcast"), 1);
+        assert_eq!(static_int(&thread, "CheckCast", "failedCast"), 1);
+        assert_eq!(static_int(&thread, "CheckCast", "failedArrayCast"), 1);
+        assert_eq!(static_int(&thread, "CheckCast", "nullCast"), 1);
+    }
+
+    #[test]
+    fn default_methods() {
+        let mut thread = thread();
+        run_static(&mut thread, "DefaultMethods", "run").unwrap();
+
+        assert_eq!(static_int(&thread, "DefaultMethods", "viaInterface"), 1);
+        assert_eq!(static_int(&thread, "DefaultMethods", "viaClass"), 1);
+        assert_eq!(static_int(&thread, "DefaultMethods", "overridden"), 3);
+        assert_eq!(static_int(&thread, "DefaultMethods", "mostSpecific"), 2);
+    }
+
+    #[test]
+    fn wait_notify() {
+        let mut waiter = thread();
+        waiter.current_thread_id = Some(1.into());
+        waiter
+            .initialize(&ClassIdentifier::new("WaitNotify").unwrap())
+            .unwrap();
+        let mut notifier = waiter.new_thread("notifier".to_string());
+        notifier.current_thread_id = Some(2.into());
+
+        let waiting = std::thread::spawn(move || {
+            run_static(&mut waiter, "WaitNotify", "waitUntilReady").unwrap();
+            waiter
+        });
+        std::thread::sleep(Duration::from_millis(100));
+        run_static(&mut notifier, "WaitNotify", "notifyReady").unwrap();
+
+        let waiter = waiting.join().unwrap();
+        assert_eq!(static_int(&waiter, "WaitNotify", "result"), 1);
+    }
+
+    #[test]
+    fn synchronized_counter() {
+        let mut first = thread();
+        first.current_thread_id = Some(1.into());
+        first
+            .initialize(&ClassIdentifier::new("SynchronizedCounter").unwrap())
+            .unwrap();
+        let mut second = first.new_thread("second".to_string());
+        second.current_thread_id = Some(2.into());
+
+        let counting = std::thread::spawn(move || {
+            run_static(&mut second, "SynchronizedCounter", "count").unwrap();
+        });
+        run_static(&mut first, "SynchronizedCounter", "count").unwrap();
+        counting.join().unwrap();
+
+        assert_eq!(
+            static_int(&first, "SynchronizedCounter", "staticCounter"),
+            200
+        );
+        assert_eq!(
+            static_int(&first, "SynchronizedCounter", "objectCounter"),
+            200
+        );
+    }
+
+    #[test]
+    fn synchronized_on_class() {
+        let mut first = thread();
+        first.current_thread_id = Some(1.into());
+        first
+            .initialize(&ClassIdentifier::new("ClassLock").unwrap())
+            .unwrap();
+        let mut second = first.new_thread("second".to_string());
+        second.current_thread_id = Some(2.into());
+
+        let counting = std::thread::spawn(move || {
+            run_static(&mut second, "ClassLock", "count").unwrap();
+        });
+        run_static(&mut first, "ClassLock", "count").unwrap();
+        counting.join().unwrap();
+
+        assert_eq!(static_int(&first, "ClassLock", "counter"), 400);
+    }
+
+    /// A thread with a `java.lang.Thread` object, as natives of `Thread` need one
+    pub(super) fn main_thread() -> JvmThread {
+        let mut thread = thread();
+        let main = thread
+            .new_thread_object("main".to_string(), "system".to_string())
+            .unwrap();
+        thread.current_thread_object = Some(main);
+        thread
+    }
+
+    /// `java.lang.Thread` object running `target` once started, built without running one of
+    /// Thread's constructors as they initialize System, which isn't possible yet
+    fn unstarted_thread(thread: &mut JvmThread, name: &str, target: &str) -> HeapId {
+        let thread_class = ClassIdentifier::from_parts("java.lang", "Thread");
+        let class = thread.initialize(&thread_class).unwrap();
+        let fields = thread.default_instance_fields(&class, 0).unwrap();
+        let thread_object = thread.allocate(thread_class, fields).unwrap();
+
+        let name = thread.new_string(name.to_string()).unwrap();
+        let main = thread.thread_object().unwrap();
+        let group = thread.heap_get_field(&main, "group").unwrap();
+        let target = ClassIdentifier::new(target).unwrap();
+        let class = thread.initialize(&target).unwrap();
+        let fields = thread.default_instance_fields(&class, 0).unwrap();
+        let target = thread.allocate(target, fields).unwrap();
+
+        // monitors tell their owners apart by the thread id
+        let mut classes = thread.classes.lock().unwrap();
+        let thread_class = classes
+            .get_mut(&ClassIdentifier::from_parts("java.lang", "Thread"))
+            .unwrap();
+        let tid = thread_class
+            .get_static_field_value("threadSeqNumber")
+            .unwrap()
+            .long()
+            .unwrap();
+        thread_class
+            .set_static_field("threadSeqNumber", FieldValue::Long(tid + 1))
+            .unwrap();
+        drop(classes);
+
+        for (field, value) in [
+            (
+                "name",
+                FieldValue::Reference(ReferenceValue::HeapItem(name)),
+            ),
+            ("group", group),
+            (
+                "target",
+                FieldValue::Reference(ReferenceValue::HeapItem(target)),
+            ),
+            ("tid", FieldValue::Long(tid)),
+        ] {
+            thread.heap_set_field(&thread_object, field, value).unwrap();
+        }
+
+        thread_object
+    }
+
+    #[test]
+    fn thread_join() {
+        let mut thread = main_thread();
+        let main = thread.thread_object().unwrap();
+        assert!(thread.is_alive(&main).unwrap());
+
+        let child = unstarted_thread(&mut thread, "child", "ThreadJoin$Flag");
+        assert!(!thread.is_alive(&child).unwrap());
+
+        thread
+            .run_method(
+                &ClassIdentifier::new("ThreadJoin").unwrap(),
+                "startAndJoin",
+                "(Ljava/lang/Thread;)V",
+                vec![FrameValue::Reference(ReferenceValue::HeapItem(child))],
+            )
+            .unwrap();
+        assert_eq!(static_int(&thread, "ThreadJoin", "flag"), 1);
+        assert_eq!(static_int(&thread, "ThreadJoin", "aliveAfterJoin"), 0);
+        thread.join_threads().unwrap();
+    }
+
+    #[test]
+    fn daemon_threads() {
+        let mut thread = main_thread();
+        let daemon = unstarted_thread(&mut thread, "daemon", "Daemons$Forever");
+        thread
+            .heap_set_field(&daemon, "daemon", FieldValue::Integer(1))
+            .unwrap();
+        let finishing = unstarted_thread(&mut thread, "finishing", "Daemons$Finishing");
+
+        thread
+            .run_method(
+                &ClassIdentifier::new("Daemons").unwrap(),
+                "start",
+                "(Ljava/lang/Thread;Ljava/lang/Thread;)V",
+                vec![
+                    FrameValue::Reference(ReferenceValue::HeapItem(daemon.clone())),
+                    FrameValue::Reference(ReferenceValue::HeapItem(finishing.clone())),
+                ],
+            )
+            .unwrap();
+        thread.join_threads().unwrap();
+
+        assert_eq!(static_int(&thread, "Daemons", "finished"), 1);
+        assert!(!thread.is_alive(&finishing).unwrap());
+        assert!(thread.is_alive(&daemon).unwrap());
+    }
+
+    #[test]
+    fn sleep() {
+        let mut thread = main_thread();
+        let start = Instant::now();
+        run_static(&mut thread, "Sleep", "sleepBriefly").unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(12));
+        assert_eq!(static_int(&thread, "Sleep", "slept"), 1);
+
+        run_static(&mut thread, "Sleep", "interruptSelf").unwrap();
+        assert_eq!(static_int(&thread, "Sleep", "interruptedBySelf"), 1);
+        assert_eq!(static_int(&thread, "Sleep", "clearedByInterrupted"), 1);
+        assert_eq!(static_int(&thread, "Sleep", "clearedByThrow"), 1);
+    }
+
+    #[test]
+    fn interrupt_sleeping_thread() {
+        let mut sleeping = main_thread();
+        sleeping
+            .initialize(&ClassIdentifier::new("Sleep").unwrap())
+            .unwrap();
+        let sleeping_object = sleeping.thread_object().unwrap();
+        let mut interrupting = sleeping.new_thread("interrupting".to_string());
+
+        let start = Instant::now();
+        let sleeping = std::thread::spawn(move || {
+            run_static(&mut sleeping, "Sleep", "sleepUntilInterrupted").unwrap();
+            sleeping
+        });
+        std::thread::sleep(Duration::from_millis(50));
+        interrupting.interrupt(&sleeping_object).unwrap();
+
+        let sleeping = sleeping.join().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(static_int(&sleeping, "Sleep", "interruptedByOther"), 1);
+    }
+
+    #[test]
+    fn invoke_special() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        run_static(&mut thread, "SuperCalls", "run").unwrap();
+        assert_eq!(static_int(&thread, "SuperCalls", "superCall"), 3);
+        assert_eq!(static_int(&thread, "SuperCalls", "defaultCall"), 101);
+        assert_eq!(static_int(&thread, "SuperCalls", "synchronizedCall"), 11);
+        assert_eq!(static_int(&thread, "SuperCalls", "nativeCall"), 1);
+
+        run_static(&mut thread, "PrivateCalls", "run").unwrap();
+        assert_eq!(static_int(&thread, "PrivateCalls", "result"), 5);
+    }
+
+    #[test]
+    fn interface_natives() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        run_static(&mut thread, "InterfaceNatives", "run").unwrap();
+        assert_eq!(static_int(&thread, "InterfaceNatives", "stableHash"), 1);
+        assert_eq!(
+            static_int(&thread, "InterfaceNatives", "synchronizedCall"),
+            2
+        );
+    }
+
+    #[test]
+    fn object_clone() {
+        let mut thread = thread();
+        run_static(&mut thread, "Cloning", "run").unwrap();
+        for name in [
+            "distinct",
+            "sameValue",
+            "sameName",
+            "arrayDistinct",
+            "arrayCopied",
+            "notCloneable",
+        ] {
+            assert_eq!(static_int(&thread, "Cloning", name), 1, "{name}");
+        }
+    }
+
+    #[test]
+    fn math_functions() {
+        let mut thread = thread();
+        run_static(&mut thread, "MathFunctions", "run").unwrap();
+        let class = thread
+            .class(&ClassIdentifier::new("MathFunctions").unwrap())
+            .unwrap();
+        let double = |name: &str| {
+            let value = class.get_static_field_value(name).unwrap();
+            FrameValue::from(value).double().unwrap()
+        };
+        assert_eq!(double("sqrt"), std::f64::consts::SQRT_2);
+        assert_eq!(double("pow"), 1024.0);
+        assert_eq!(double("sin"), 1.0);
+        assert_eq!(double("log"), 1.0);
+        assert_eq!(double("absNegativeZero"), f64::INFINITY);
+        assert_eq!(static_int(&thread, "MathFunctions", "absMin"), i32::MIN);
+        let abs_min_long = class.get_static_field_value("absMinLong").unwrap();
+        assert_eq!(abs_min_long.long().unwrap(), i64::MIN);
+    }
+
+    #[test]
+    fn garbage_collection_roots() {
+        let mut thread = thread();
+        thread.set_gc_threshold(Some(10)).unwrap();
+        // the argument is only held by Rust code while <clinit> collects garbage
+        let string = thread.new_string("hello".to_string()).unwrap();
+        let length = thread
+            .call_static(
+                "GcRoots",
+                "length",
+                "(Ljava/lang/String;)I",
+                vec![FieldValue::Reference(ReferenceValue::HeapItem(string))],
+            )
+            .unwrap();
+        assert_eq!(length.unwrap().int().unwrap(), 5);
+
+        // nothing is collected while another thread runs code, as its stack isn't a root
+        let unreachable = thread.new_string("unreachable".to_string()).unwrap();
+        thread.executing_threads.fetch_add(1, Ordering::SeqCst);
+        thread
+            .call_static("GcRoots", "allocate", "()V", vec![])
+            .unwrap();
+        assert!(thread.heap_get(&unreachable).is_ok());
+
+        thread.executing_threads.fetch_sub(1, Ordering::SeqCst);
+        thread
+            .call_static("GcRoots", "allocate", "()V", vec![])
+            .unwrap();
+        assert!(thread.heap_get(&unreachable).is_err());
+    }
+
+    #[test]
+    fn out_of_memory() {
+        let mut thread = main_thread();
+        for class in ["java.lang.System", "OutOfMemory"] {
+            thread
+                .initialize(&ClassIdentifier::new(class).unwrap())
+                .unwrap();
+        }
+        thread.collect_garbage().unwrap();
+        let used = thread.dump_heap().unwrap().len();
+        thread.set_max_heap_items(Some(used + 200)).unwrap();
+
+        run_static(&mut thread, "OutOfMemory", "run").unwrap();
+        let allocated = static_int(&thread, "OutOfMemory", "allocated");
+        assert!((100..200).contains(&allocated), "{allocated}");
+        assert_eq!(
+            static_string(&thread, "OutOfMemory", "message"),
+            "Java heap space"
+        );
+    }
+
+    #[test]
+    fn stack_overflow() {
+        let mut thread = thread();
+        thread.set_max_stack_frames(100);
+        run_static(&mut thread, "Recursion", "run").unwrap();
+        // `run` and the frame overflowing the stack aren't part of the recursion
+        assert_eq!(static_int(&thread, "Recursion", "caught"), 99);
+        assert_eq!(static_int(&thread, "Recursion", "withoutMessage"), 1);
+        assert_eq!(static_int(&thread, "Recursion", "unwound"), 10);
+    }
+
+    #[test]
+    fn stack_overflow_default_limit() {
+        let mut thread = thread();
+        // the test harness' thread has too small a stack for the default limit
+        let caught = thread
+            .native_thread()
+            .spawn(move || {
+                run_static(&mut thread, "Recursion", "run").unwrap();
+                static_int(&thread, "Recursion", "caught")
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(caught, stack::DEFAULT_MAX_FRAMES as i32 - 1);
+    }
+
+    #[test]
+    fn overridden_object_methods() {
+        let mut thread = thread();
+        run_static(&mut thread, "HashKeys", "run").unwrap();
+        assert_eq!(static_int(&thread, "HashKeys", "found"), 70);
+        assert_eq!(static_int(&thread, "HashKeys", "replaced"), 84);
+        assert_eq!(static_int(&thread, "HashKeys", "size"), 10);
+        assert_eq!(static_int(&thread, "HashKeys", "setSize"), 2);
+        assert_eq!(static_int(&thread, "HashKeys", "overriddenHash"), 2);
+        assert_eq!(static_int(&thread, "HashKeys", "identityHash"), 1);
+        assert_eq!(static_int(&thread, "HashKeys", "overriddenToString"), 1);
+    }
+
+    #[test]
+    fn string_hashes() {
+        let mut thread = main_thread();
+        run_static(&mut thread, "StringHashes", "run").unwrap();
+        assert_eq!(static_int(&thread, "StringHashes", "hello"), 99162322);
+        assert_eq!(
+            static_int(&thread, "StringHashes", "throughObject"),
+            99162322
+        );
+        assert_eq!(static_int(&thread, "StringHashes", "utf16"), 394284424);
+        assert_eq!(static_int(&thread, "StringHashes", "empty"), 0);
+        assert_eq!(static_int(&thread, "StringHashes", "found"), 7);
+    }
+
+    #[test]
+    fn system_properties() {
+        let mut thread = main_thread();
+        run_static(&mut thread, "SystemProperties", "run").unwrap();
+        assert!(!static_string(&thread, "SystemProperties", "osName").is_empty());
+        assert!(!static_string(&thread, "SystemProperties", "javaHome").is_empty());
+        let user_dir = std::env::current_dir().unwrap();
+        assert_eq!(
+            static_string(&thread, "SystemProperties", "userDir"),
+            user_dir.to_string_lossy()
+        );
+        assert_eq!(
+            static_string(&thread, "SystemProperties", "lineSeparator"),
+            if cfg!(windows) { "\r\n" } else { "\n" }
+        );
+    }
+
+    #[test]
+    fn system_bootstrap() {
+        let mut thread = main_thread();
+        run_static(&mut thread, "SystemBootstrap", "run").unwrap();
+        for name in [
+            "out",
+            "err",
+            "in",
+            "systemClassLoader",
+            "contextClassLoader",
+        ] {
+            assert_eq!(static_int(&thread, "SystemBootstrap", name), 1, "{name}");
+        }
+    }
+
+    #[test]
+    fn output_sink() {
+        let mut thread = main_thread();
+        let sink = VecOutputSink::default();
+        thread.set_output_sink(sink.clone()).unwrap();
+        run_static(&mut thread, "Printing", "run").unwrap();
+
+        assert_eq!(sink.bytes(1), b"Hello, World!\n42\n");
+        assert_eq!(sink.bytes(2), b"oops");
+    }
+
+    #[test]
+    fn superclass_fields_first() {
+        let mut thread = thread();
+        let mut fields = |name| {
+            let class = thread
+                .initialize(&ClassIdentifier::from_parts("java.util", name))
+                .unwrap();
+            thread.default_instance_fields(&class, 0).unwrap()
+        };
+        let abstract_list = fields("AbstractList");
+        let array_list = fields("ArrayList");
+
+        // inherited fields have the same offset in every subclass
+        assert_eq!(
+            array_list["modCount"].offset(),
+            abstract_list["modCount"].offset()
+        );
+        assert!(array_list["size"].offset() > array_list["modCount"].offset());
+    }
+
+    #[test]
+    fn initializer_errors() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        run_static(&mut thread, "InitializerErrors", "run").unwrap();
+        assert_eq!(static_int(&thread, "InitializerErrors", "caught"), 3);
+        assert_eq!(static_int(&thread, "InitializerErrors", "causeKept"), 1);
+        assert_eq!(static_int(&thread, "InitializerErrors", "errorKept"), 1);
+    }
+
+    #[test]
+    fn initialization_cycle() {
+        let mut thread = thread();
+        run_static(&mut thread, "InitCycle", "run").unwrap();
+
+        assert_eq!(static_int(&thread, "InitCycle", "a"), 11);
+        assert_eq!(static_int(&thread, "InitCycle", "b"), 10);
+    }
+
+    #[test]
+    fn concurrent_initialization() {
+        let mut first = thread();
+        first.current_thread_id = Some(1.into());
+        first
+            .initialize(&ClassIdentifier::new("InitCycle").unwrap())
+            .unwrap();
+        let mut second = first.new_thread("second".to_string());
+        second.current_thread_id = Some(2.into());
+
+        let reading = std::thread::spawn(move || {
+            run_static(&mut second, "InitCycle", "readSlow").unwrap();
+            static_int(&second, "InitCycle", "slow")
+        });
+        run_static(&mut first, "InitCycle", "readSlow").unwrap();
+
+        assert_eq!(reading.join().unwrap(), 2000);
+        assert_eq!(static_int(&first, "InitCycle", "slow"), 2000);
+    }
+
+    #[test]
+    fn intern_strings() {
+        let mut thread = thread();
+        run_static(&mut thread, "Intern", "run").unwrap();
+
+        assert_eq!(static_int(&thread, "Intern", "sameLiteral"), 1);
+        assert_eq!(static_int(&thread, "Intern", "constantField"), 1);
+        assert_eq!(static_int(&thread, "Intern", "interned"), 1);
+    }
+
+    #[test]
+    fn identity_hash_code() {
+        let mut thread = thread();
+        run_static(&mut thread, "IdentityHash", "run").unwrap();
+
+        assert_eq!(static_int(&thread, "IdentityHash", "stable"), 1);
+        assert_eq!(static_int(&thread, "IdentityHash", "distinct"), 1);
+    }
+
+    #[test]
+    fn class_mirrors() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        run_static(&mut thread, "ClassMirrors", "run").unwrap();
+
+        assert_eq!(static_int(&thread, "ClassMirrors", "sameObject"), 1);
+        assert_eq!(static_int(&thread, "ClassMirrors", "sameClass"), 1);
+        assert_eq!(static_int(&thread, "ClassMirrors", "sameHash"), 1);
+        assert_eq!(static_int(&thread, "ClassMirrors", "locked"), 1);
+
+        let identifier = ClassIdentifier::new("ClassMirrors").unwrap();
+        let mirror = thread.class_mirror(&identifier).unwrap();
+        assert_eq!(thread.class_mirror(&identifier).unwrap(), mirror);
+        assert_eq!(
+            thread
+                .mirrored_class(&ReferenceValue::HeapItem(mirror))
+                .unwrap(),
+            identifier
+        );
+    }
+
+    #[test]
+    fn identity_hash_code_is_stable() {
+        let mut thread = thread();
+        let object = thread.new_string("hash".to_string()).unwrap();
+        let system = ClassIdentifier::from_parts("java.lang", "System");
+
+        let mut identity_hash_code = || {
+            let operands = vec![FrameValue::Reference(ReferenceValue::HeapItem(
+                object.clone(),
+            ))];
+            native::run(&mut thread, &system, "identityHashCode", operands)
+                .unwrap()
+                .unwrap()
+                .int()
+                .unwrap()
+        };
+        assert_eq!(identity_hash_code(), identity_hash_code());
+    }
+
+    fn arraycopy(
+        thread: &mut JvmThread,
+        src: &HeapId,
+        src_pos: i32,
+        dest: &HeapId,
+        dest_pos: i32,
+        length: i32,
+    ) {
+        let system = ClassIdentifier::from_parts("java.lang", "System");
+        let reference = |id: &HeapId| FrameValue::Reference(ReferenceValue::HeapItem(id.clone()));
+        let operands = vec![
+            reference(src),
+            FrameValue::Int(src_pos),
+            reference(dest),
+            FrameValue::Int(dest_pos),
+            FrameValue::Int(length),
+        ];
+        native::run(thread, &system, "arraycopy", operands).unwrap();
+    }
+
+    /// Allocates a `String[]` filled with `values`
+    fn string_array(thread: &mut JvmThread, values: &[&str]) -> HeapId {
+        let values = values.iter().map(|value| value.to_string()).collect();
+        thread.new_string_array(values).unwrap()
+    }
+
+    fn string_array_values(thread: &JvmThread, array: &HeapId) -> Vec<String> {
+        thread
+            .get_reference_array(array)
+            .unwrap()
+            .iter()
+            .map(|value| match value {
+                ReferenceValue::Null => "null".to_string(),
+                value => thread.read_java_string(value.heap_id().unwrap()).unwrap(),
+            })
+            .collect()
+    }
+
+    fn pending_exception(thread: &mut JvmThread) -> Option<String> {
+        let exception = thread.exception.take()?;
+        Some(format!(
+            "{:?}",
+            thread
+                .heap_get(&exception)
+                .unwrap()
+                .class_identifier()
+                .unwrap()
+        ))
+    }
+
+    #[test]
+    fn arraycopy_reference_array() {
+        let mut thread = thread();
+        let src = string_array(&mut thread, &["a", "b", "c"]);
+        let dest = thread
+            .allocate_array(ClassIdentifier::new("java/lang/Object").unwrap(), 4)
+            .unwrap();
+
+        arraycopy(&mut thread, &src, 1, &dest, 0, 2);
+        assert_eq!(pending_exception(&mut thread), None);
+        assert_eq!(
+            string_array_values(&thread, &dest),
+            ["b", "c", "null", "null"]
+        );
+    }
+
+    #[test]
+    fn arraycopy_overlapping() {
+        let mut thread = thread();
+        let array = string_array(&mut thread, &["a", "b", "c", "d"]);
+
+        arraycopy(&mut thread, &array, 0, &array, 1, 3);
+        assert_eq!(string_array_values(&thread, &array), ["a", "a", "b", "c"]);
+
+        arraycopy(&mut thread, &array, 1, &array, 0, 3);
+        assert_eq!(string_array_values(&thread, &array), ["a", "b", "c", "c"]);
+    }
+
+    #[test]
+    fn arraycopy_out_of_bounds() {
+        let mut thread = thread();
+        let src = string_array(&mut thread, &["a", "b"]);
+        let dest = string_array(&mut thread, &["c", "d"]);
+
+        arraycopy(&mut thread, &src, 1, &dest, 0, 2);
+        assert_eq!(
+            pending_exception(&mut thread).as_deref(),
+            Some("java.lang.ArrayIndexOutOfBoundsException")
+        );
+        arraycopy(&mut thread, &src, 0, &dest, -1, 1);
+        assert_eq!(
+            pending_exception(&mut thread).as_deref(),
+            Some("java.lang.ArrayIndexOutOfBoundsException")
+        );
+        assert_eq!(string_array_values(&thread, &dest), ["c", "d"]);
+    }
+
+    #[test]
+    fn arraycopy_store_check() {
+        let mut thread = thread();
+        let src = string_array(&mut thread, &["a"]);
+        let dest = thread
+            .allocate_array(ClassIdentifier::new("java/lang/Integer").unwrap(), 1)
+            .unwrap();
+
+        arraycopy(&mut thread, &src, 0, &dest, 0, 1);
+        assert_eq!(
+            pending_exception(&mut thread).as_deref(),
+            Some("java.lang.ArrayStoreException")
+        );
+
+        // the components of a String[][] are String[]
+        let nested = thread
+            .allocate_array(ClassIdentifier::new("[Ljava/lang/String;").unwrap(), 1)
+            .unwrap();
+        let objects = thread
+            .allocate_array(ClassIdentifier::new("java/lang/Object").unwrap(), 2)
+            .unwrap();
+        let strings = string_array(&mut thread, &["a"]);
+        for (i, value) in [strings, dest].into_iter().enumerate() {
+            let value = ReferenceValue::HeapItem(value);
+            thread
+                .store_into_reference_array(&objects, i, value)
+                .unwrap();
+        }
+        arraycopy(&mut thread, &objects, 0, &nested, 0, 1);
+        assert_eq!(pending_exception(&mut thread), None);
+        arraycopy(&mut thread, &objects, 1, &nested, 0, 1);
+        assert_eq!(
+            pending_exception(&mut thread).as_deref(),
+            Some("java.lang.ArrayStoreException")
+        );
+    }
+
+    fn unsafe_call(
+        thread: &mut JvmThread,
+        name: &str,
+        operands: Vec<FrameValue>,
+    ) -> Option<FrameValue> {
+        let class = ClassIdentifier::from_parts("jdk.internal.misc", "Unsafe");
+        let mut unsafe_operands = vec![FrameValue::Reference(ReferenceValue::Null)];
+        unsafe_operands.extend(operands);
+        native::run(thread, &class, name, unsafe_operands).unwrap()
+    }
+
+    #[test]
+    fn unsafe_field_access() {
+        let mut thread = thread();
+        let identifier = ClassIdentifier::new("UnsafeAccess").unwrap();
+        let class = thread.resolve_class(&identifier).unwrap();
+        let fields = thread.default_instance_fields(&class, 0).unwrap();
+        let object = thread.allocate(identifier.clone(), fields).unwrap();
+        let object = FrameValue::Reference(ReferenceValue::HeapItem(object));
+        let mirror = thread.class_mirror(&identifier).unwrap();
+
+        let mut offset = |name: &str| {
+            let name = thread.new_string(name.to_string()).unwrap();
+            let operands = vec![
+                FrameValue::Reference(ReferenceValue::HeapItem(mirror.clone())),
+                FrameValue::Reference(ReferenceValue::HeapItem(name)),
+            ];
+            unsafe_call(&mut thread, "objectFieldOffset1", operands).unwrap()
+        };
+        let long_offset = offset("longValue");
+        let int_offset = offset("intValue");
+
+        let put = vec![
+            object.clone(),
+            long_offset.clone(),
+            FrameValue::Long(i64::MIN),
+        ];
+        assert!(unsafe_call(&mut thread, "putLong", put).is_none());
+        let get = vec![object.clone(), long_offset];
+        let value = unsafe_call(&mut thread, "getLong", get).unwrap();
+        assert_eq!(value.long().unwrap(), i64::MIN);
+
+        let put = vec![object.clone(), int_offset.clone(), FrameValue::Int(7)];
+        unsafe_call(&mut thread, "putIntVolatile", put);
+        let value = unsafe_call(&mut thread, "getInt", vec![object, int_offset]).unwrap();
+        assert_eq!(value.int().unwrap(), 7);
+    }
+
+    #[test]
+    fn unsafe_array_access() {
+        let mut thread = thread();
+        let array = string_array(&mut thread, &["a", "b", "c"]);
+        let array_class = thread
+            .class_mirror(&ClassIdentifier::new("[Ljava/lang/String;").unwrap())
+            .unwrap();
+        let array_class = FrameValue::Reference(ReferenceValue::HeapItem(array_class));
+
+        let base = unsafe_call(&mut thread, "arrayBaseOffset0", vec![array_class.clone()])
+            .unwrap()
+            .int()
+            .unwrap();
+        let scale = unsafe_call(&mut thread, "arrayIndexScale0", vec![array_class])
+            .unwrap()
+            .int()
+            .unwrap();
+        assert!(scale > 0 && scale & (scale - 1) == 0);
+        let offset = FrameValue::Long(base as i64 + 2 * scale as i64);
+
+        let value = thread.new_string("d".to_string()).unwrap();
+        let value = FrameValue::Reference(ReferenceValue::HeapItem(value));
+        let array = FrameValue::Reference(ReferenceValue::HeapItem(array.clone()));
+        let put = vec![array.clone(), offset.clone(), value.clone()];
+        unsafe_call(&mut thread, "putReferenceRelease", put);
+
+        let get = vec![array, offset];
+        let read = unsafe_call(&mut thread, "getReferenceAcquire", get).unwrap();
+        assert_eq!(read.reference().unwrap(), value.reference().unwrap());
+    }
+
+    #[test]
+    fn registered_native() {
+        let mut thread = thread();
+        thread
+            .register_native(
+                ClassIdentifier::new("NativeCall").unwrap(),
+                "answer",
+                |_, operands| {
+                    let value = operands.first().context("no operand")?.int()?;
+                    Ok(Some(FrameValue::Int(value * 2)))
+                },
+            )
+            .unwrap();
+
+        run_static(&mut thread, "NativeCall", "run").unwrap();
+        assert_eq!(static_int(&thread, "NativeCall", "result"), 42);
+    }
+
+    #[test]
+    fn store_into_unused_local() {
+        let mut thread = thread();
+        run_static(&mut thread, "Locals", "run").unwrap();
+        assert_eq!(static_int(&thread, "Locals", "result"), 42);
+    }
+
+    #[test]
+    fn null_access() {
+        let mut thread = thread();
+        run_static(&mut thread, "NullAccess", "run").unwrap();
+        assert_eq!(static_int(&thread, "NullAccess", "caught"), 6);
+
+        let exception = thread
+            .class(&ClassIdentifier::new("NullAccess").unwrap())
+            .unwrap()
+            .get_static_field_value("exception")
+            .unwrap()
+            .reference()
+            .unwrap();
+        let message = thread
+            .heap_get_field(exception.heap_id().unwrap(), "detailMessage")
+            .unwrap()
+            .reference()
+            .unwrap();
+        assert_eq!(
+            thread.read_java_string(message.heap_id().unwrap()).unwrap(),
+            "Cannot invoke \"NullAccess.value()\""
+        );
+    }
+
+    #[test]
+    fn boxing() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        run_static(&mut thread, "Boxing", "run").unwrap();
+        assert_eq!(static_int(&thread, "Boxing", "smallSame"), 1);
+        assert_eq!(static_int(&thread, "Boxing", "largeSame"), 0);
+        assert_eq!(static_int(&thread, "Boxing", "sum"), 42);
+        assert_eq!(static_int(&thread, "Boxing", "primitive"), 1);
+        assert_eq!(static_int(&thread, "Boxing", "wrapperPrimitive"), 0);
+        assert_eq!(static_int(&thread, "Boxing", "sameClass"), 0);
+    }
+
+    #[test]
+    fn box_primitives() {
+        let mut thread = thread();
+        let int = thread.box_primitive(FrameValue::Int(42)).unwrap();
+        assert_eq!(
+            format!(
+                "{:?}",
+                thread.heap_get(&int).unwrap().class_identifier().unwrap()
+            ),
+            "java.lang.Integer"
+        );
+        assert_eq!(
+            thread.heap_get_field(&int, "value").unwrap().int().unwrap(),
+            42
+        );
+        assert_eq!(thread.box_primitive(FrameValue::Int(42)).unwrap(), int);
+        assert_eq!(thread.unbox(&int).unwrap().int().unwrap(), 42);
+
+        let double = thread.box_primitive(FrameValue::Double(2.5)).unwrap();
+        assert_eq!(
+            format!(
+                "{:?}",
+                thread
+                    .heap_get(&double)
+                    .unwrap()
+                    .class_identifier()
+                    .unwrap()
+            ),
+            "java.lang.Double"
+        );
+        assert_eq!(thread.unbox(&double).unwrap().double().unwrap(), 2.5);
+
+        let long = thread.box_primitive(FrameValue::Long(1 << 40)).unwrap();
+        assert_eq!(thread.unbox(&long).unwrap().long().unwrap(), 1 << 40);
+
+        let string = thread.new_string("no box".to_string()).unwrap();
+        assert!(thread.unbox(&string).is_err());
+        assert!(
+            thread
+                .box_primitive(FrameValue::Reference(ReferenceValue::Null))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn java_strings() {
+        let mut thread = thread();
+        let coder = |thread: &JvmThread, string: &HeapId| {
+            thread
+                .heap_get_field(string, "coder")
+                .unwrap()
+                .int()
+                .unwrap()
+        };
+
+        let ascii = thread.new_string("ascii".to_string()).unwrap();
+        assert_eq!(coder(&thread, &ascii), STRING_LATIN1);
+        assert_eq!(thread.read_java_string(&ascii).unwrap(), "ascii");
+
+        let latin1 = thread.new_string("grün".to_string()).unwrap();
+        assert_eq!(coder(&thread, &latin1), STRING_LATIN1);
+        let value = thread.heap_get_field(&latin1, "value").unwrap();
+        let (_, bytes) = thread
+            .get_primitive_array(value.heap_id().unwrap())
+            .unwrap();
+        assert_eq!(bytes.len(), 4);
+        assert_eq!(thread.read_java_string(&latin1).unwrap(), "grün");
+
+        let utf16 = thread.new_string("snow ☃ 𝄞".to_string()).unwrap();
+        assert_eq!(coder(&thread, &utf16), STRING_UTF16);
+        assert_eq!(thread.read_java_string(&utf16).unwrap(), "snow ☃ 𝄞");
+
+        thread.write_java_string(&utf16, "ascii again").unwrap();
+        assert_eq!(coder(&thread, &utf16), STRING_LATIN1);
+        assert_eq!(thread.read_java_string(&utf16).unwrap(), "ascii again");
+    }
+
+    #[test]
+    fn unicode_strings() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        run_static(&mut thread, "Unicode", "run").unwrap();
+        assert_eq!(static_int(&thread, "Unicode", "length"), 6);
+        assert_eq!(static_int(&thread, "Unicode", "snowman"), 0x2603);
+        assert_eq!(
+            static_string(&thread, "Unicode", "concatenated"),
+            "snow \u{2603}!"
+        );
+        assert_eq!(
+            static_string(&thread, "Unicode", "fromChars"),
+            "g\u{fc}\u{2603}"
+        );
+    }
+
+    #[test]
+    fn class_names() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        run_static(&mut thread, "ClassNames", "run").unwrap();
+        assert_eq!(
+            static_string(&thread, "ClassNames", "name"),
+            "java.util.Map$Entry"
+        );
+        assert_eq!(static_string(&thread, "ClassNames", "simpleName"), "Entry");
+        assert_eq!(
+            static_string(&thread, "ClassNames", "canonicalName"),
+            "java.util.Map.Entry"
+        );
+        assert_eq!(
+            static_string(&thread, "ClassNames", "arrayName"),
+            "[[Ljava.lang.String;"
+        );
+        assert_eq!(static_int(&thread, "ClassNames", "sameArrayClass"), 1);
+    }
+
+    #[test]
+    fn array_index_out_of_bounds() {
+        let mut thread = thread();
+        run_static(&mut thread, "ArrayBounds", "run").unwrap();
+        assert_eq!(static_int(&thread, "ArrayBounds", "caught"), 7);
+        assert_eq!(
+            static_string(&thread, "ArrayBounds", "message"),
+            "Index 5 out of bounds for length 2"
+        );
+    }
+
+    /// Records the pc and op code of every executed instruction
+    struct Recorder(Arc<Mutex<Vec<(usize, u8)>>>);
+
+    impl ExecutionHook for Recorder {
+        fn before_instruction(
+            &mut self,
+            _: &ClassIdentifier,
+            pc: usize,
+            instruction: &Instruction,
+        ) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((pc, instruction.op_code().unwrap()));
+        }
+    }
+
+    #[test]
+    fn execution_hook() {
+        let mut thread = thread();
+        let executed = Arc::new(Mutex::new(Vec::new()));
+        thread.set_execution_hook(Recorder(executed.clone()));
+        run_static(&mut thread, "Locals", "run").unwrap();
+
+        let executed = executed.lock().unwrap().clone();
+        assert_eq!(
+            executed,
+            vec![
+                // run: bipush, bipush, invokestatic
+                (0, 0x10),
+                (2, 0x10),
+                (4, 0xb8),
+                // store: iload_0, iload_1, iadd, istore, iload, ireturn
+                (0, 0x1a),
+                (1, 0x1b),
+                (2, 0x60),
+                (3, 0x36),
+                (5, 0x15),
+                (7, 0xac),
+                // run: putstatic, return
+                (7, 0xb3),
+                (10, 0xb1),
+            ]
+        );
+    }
+
+    #[test]
+    fn instruction_counter() {
+        let mut thread = thread();
+        let counter = InstructionCounter::default();
+        thread.set_execution_hook(counter.clone());
+        run_static(&mut thread, "Locals", "run").unwrap();
+
+        assert_eq!(counter.count("bipush"), 2);
+        assert_eq!(counter.count("iadd"), 1);
+        assert_eq!(counter.total(), 11);
+    }
+
+    #[test]
+    fn verified_classes() {
+        let mut thread = thread();
+        thread.verify_classes();
+        thread.current_thread_id = Some(1.into());
+        for class in ["Loop", "Instructions", "Exceptions", "SynchronizedCounter"] {
+            thread.link(&ClassIdentifier::new(class).unwrap()).unwrap();
+        }
+
+        run_static(&mut thread, "Instructions", "run").unwrap();
+        run_static(&mut thread, "Exceptions", "catchInCaller").unwrap();
+        assert_eq!(static_int(&thread, "Exceptions", "result"), 2);
+    }
+
+    #[test]
+    fn instruction_profile() {
+        let mut thread = thread();
+        thread.profile_instructions();
+        let mut other = thread.new_thread("other".to_string());
+        run_static(&mut thread, "Loop", "run").unwrap();
+        run_static(&mut other, "Loop", "run").unwrap();
+        assert_eq!(static_int(&thread, "Loop", "result"), 4950);
+
+        let counts = thread.instruction_counts();
+        assert_eq!(counts["iinc"], 200);
+        assert_eq!(counts["goto"], 200);
+        // loaded by both the loop condition and its body
+        assert!(
+            thread
+                .instruction_histogram()
+                .starts_with("iload_1         402\n")
+        );
+    }
+
+    #[test]
+    fn code_decoded_once() {
+        let mut thread = thread();
+        run_static(&mut thread, "Loop", "run").unwrap();
+        let identifier = ClassIdentifier::new("Loop").unwrap();
+        let (_, method) = thread.resolve_method(&identifier, "run", "()V").unwrap();
+        let code = thread.class(&identifier).unwrap().code(&method).unwrap();
+
+        run_static(&mut thread, "Loop", "run").unwrap();
+        let reused = thread.class(&identifier).unwrap().code(&method).unwrap();
+        assert!(Arc::ptr_eq(&code, &reused));
+    }
+
+    #[test]
+    fn concurrent_heap_access() {
+        let thread = thread();
+        let string = ClassIdentifier::from_parts("java.lang", "String");
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let mut thread = thread.new_thread(format!("worker {i}"));
+                let string = string.clone();
+                std::thread::spawn(move || {
+                    let array = thread.allocate_array(string, 100).unwrap();
+                    for j in 0..100 {
+                        let value = thread.new_string(format!("{i}-{j}")).unwrap();
+                        let value = ReferenceValue::HeapItem(value);
+                        thread.store_into_reference_array(&array, j, value).unwrap();
+                    }
+
+                    for (j, value) in thread
+                        .get_reference_array(&array)
+                        .unwrap()
+                        .iter()
+                        .enumerate()
+                    {
+                        let value = thread.read_java_string(value.heap_id().unwrap()).unwrap();
+                        assert_eq!(value, format!("{i}-{j}"));
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn call_static() {
+        let mut thread = thread();
+        let args = vec![FieldValue::Integer(3), FieldValue::Integer(7)];
+        let max = thread
+            .call_static("java.lang.Math", "max", "(II)I", args)
+            .unwrap();
+        assert_eq!(max.unwrap().int().unwrap(), 7);
+        assert!(thread.stack.method_name().is_err());
+
+        let err = thread
+            .call_static("java.lang.Math", "max", "(II)I", vec![])
+            .unwrap_err();
+        assert_eq!(err.to_string(), "max(II)I takes 2 arguments, got 0");
+    }
+
+    #[test]
+    fn call_static_failing() {
+        let mut thread = thread();
+        let depth = thread.stack.depth();
+        let args = vec![FieldValue::Integer(3)];
+        thread
+            .call_static("java.lang.Math", "missing", "(I)I", args)
+            .unwrap_err();
+        assert_eq!(thread.stack.depth(), depth);
+
+        let args = vec![FieldValue::Integer(-3)];
+        let abs = thread
+            .call_static("java.lang.Math", "abs", "(I)I", args)
+            .unwrap();
+        assert_eq!(abs.unwrap().int().unwrap(), 3);
+        assert_eq!(thread.stack.depth(), depth);
+    }
+
+    #[test]
+    fn crash_stack_trace() {
+        let mut thread = thread();
+        run_static(&mut thread, "Crash", "outer").unwrap_err();
+
+        let stack_trace = thread.stack.stack_trace();
+        assert_eq!(stack_trace, "Crash.inner::5\nCrash.outer::9\n");
+    }
+
+    #[test]
+    fn main_args() {
+        let mut thread = thread();
+        let args = vec!["first".to_string(), "second".to_string()];
+        thread
+            .run_main(&ClassIdentifier::new("Args").unwrap(), args)
+            .unwrap();
+
+        assert_eq!(static_int(&thread, "Args", "count"), 2);
+        assert_eq!(static_string(&thread, "Args", "first"), "first");
+        assert_eq!(static_string(&thread, "Args", "second"), "second");
+    }
+
+    #[test]
+    fn lambda() {
+        let mut thread = thread();
+        run_static(&mut thread, "Lambdas", "run").unwrap();
+
+        assert_eq!(static_int(&thread, "Lambdas", "ran"), 2);
+    }
+
+    #[test]
+    fn capturing_lambda() {
+        let mut thread = thread();
+        run_static(&mut thread, "Lambdas", "runCapturing").unwrap();
+
+        assert_eq!(static_int(&thread, "Lambdas", "captured"), 42);
+    }
+
+    #[test]
+    fn method_references() {
+        let mut thread = thread();
+        run_static(&mut thread, "Lambdas", "runMethodReferences").unwrap();
+
+        assert_eq!(static_int(&thread, "Lambdas", "ran"), 1);
+        assert_eq!(static_int(&thread, "Lambdas", "constructed"), 40);
+        assert_eq!(static_int(&thread, "Lambdas", "combined"), 7);
+    }
+
+    #[test]
+    fn adapted_method_references() {
+        let mut thread = thread();
+        run_static(&mut thread, "Lambdas", "runAdapted").unwrap();
+
+        assert_eq!(static_int(&thread, "Lambdas", "absolute"), 5);
+        assert_eq!(static_int(&thread, "Lambdas", "unboxed"), -8);
+        let widened = thread
+            .class(&ClassIdentifier::new("Lambdas").unwrap())
+            .unwrap()
+            .get_static_field_value("widened")
+            .unwrap();
+        assert!(matches!(widened, FieldValue::Long(42)));
+    }
+
+    #[test]
+    fn resolve_static_method_handle() {
+        let mut thread = thread();
+        let class = thread
+            .initialize(&ClassIdentifier::new("Lambdas").unwrap())
+            .unwrap();
+        let bootstrap_method = class.bootstrap_method(0).unwrap();
+        let method_handle = thread
+            .resolve_method_handle(&class, &bootstrap_method.arguments[1])
+            .unwrap();
+
+        assert!(matches!(method_handle.kind, ReferenceKind::InvokeStatic));
+        assert_eq!(
+            method_handle.class,
+            ClassIdentifier::new("Lambdas").unwrap()
+        );
+        assert_eq!(method_handle.name, "lambda$run$0");
+        assert_eq!(method_handle.method_descriptor().unwrap().raw(), "()V");
+        assert!(method_handle.is_static());
+        assert!(!method_handle.is_field());
+    }
+
+    #[test]
+    fn linking_does_not_initialize() {
+        let mut thread = thread();
+        run_static(&mut thread, "Linking", "createArray").unwrap();
+        run_static(&mut thread, "Linking", "loadClass").unwrap();
+        assert_eq!(static_int(&thread, "Linking", "initialized"), 0);
+
+        run_static(&mut thread, "Linking", "callStatic").unwrap();
+        assert_eq!(static_int(&thread, "Linking", "initialized"), 1);
+    }
+
+    #[test]
+    fn negative_constants() {
+        let mut thread = thread();
+        run_static(&mut thread, "Constants", "load").unwrap();
+        assert_eq!(static_int(&thread, "Constants", "NEGATIVE_INT"), -100000);
+        assert_eq!(static_int(&thread, "Constants", "loadedInt"), -100000);
+
+        let class = thread
+            .class(&ClassIdentifier::new("Constants").unwrap())
+            .unwrap();
+        for name in ["NEGATIVE_LONG", "loadedLong"] {
+            let value = class.get_static_field_value(name).unwrap();
+            assert_eq!(value.long().unwrap(), -5000000000);
+        }
+    }
+
+    #[test]
+    fn ldc_of_wrong_category() {
+        let mut thread = frame_thread();
+        let mut run = |descriptor: &str, code: Vec<u8>| {
+            let class = verifier::tests::class(descriptor, code, vec![]);
+            let identifier = class.identifier().clone();
+            thread.insert_class(identifier.clone(), class).unwrap();
+            thread
+                .run_method(&identifier, "run", descriptor, vec![])
+                .unwrap_err()
+                .to_string()
+        };
+
+        // ldc #7, dreturn
+        let err = run("()D", vec![0x12, 0x07, 0xaf]);
+        assert!(
+            err.contains("ldc of category 2 constant Double(1.5)"),
+            "{err}"
+        );
+        // ldc2_w #9, lreturn
+        let err = run("()J", vec![0x14, 0x00, 0x09, 0xad]);
+        assert!(
+            err.contains("ldc2_w of category 1 constant Integer(42)"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn dynamic_constants() {
+        let utf8 = |value: &str| CpInfo::Utf8(value.to_string());
+        let name_and_type = |name: u16, descriptor: u16| CpInfo::NameAndType {
+            name_index: name.into(),
+            descriptor_index: descriptor.into(),
+        };
+        let bootstrap = |method_ref: u16| CpInfo::MethodHandle {
+            reference_kind: ReferenceKind::InvokeStatic,
+            reference_index: method_ref.into(),
+        };
+        let method_ref = |name_and_type: u16| CpInfo::MethodRef {
+            class_index: 11u16.into(),
+            name_and_type_index: name_and_type.into(),
+        };
+        let dynamic = |bootstrap_method: u16, name_and_type: u16| CpInfo::Dynamic {
+            bootstrap_method_attr_index: bootstrap_method.into(),
+            name_and_type_index: name_and_type.into(),
+        };
+        let lookup = "Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/Class;";
+        // the constant pool of `verifier::tests::class` continues at index 10
+        let constants = vec![
+            utf8("java/lang/invoke/ConstantBootstraps"),
+            CpInfo::Class {
+                name_index: 10u16.into(),
+            },
+            utf8("primitiveClass"),
+            utf8(&format!("({lookup})Ljava/lang/Class;")),
+            name_and_type(12, 13),
+            method_ref(14),
+            bootstrap(15),
+            utf8("I"),
+            utf8("Ljava/lang/Class;"),
+            name_and_type(17, 18),
+            // #20, `int.class`
+            dynamic(0, 19),
+            utf8("getStaticFinal"),
+            utf8(&format!("({lookup}Ljava/lang/Class;)Ljava/lang/Object;")),
+            name_and_type(21, 22),
+            method_ref(23),
+            bootstrap(24),
+            utf8("java/lang/Long"),
+            CpInfo::Class {
+                name_index: 26u16.into(),
+            },
+            utf8("MAX_VALUE"),
+            utf8("J"),
+            name_and_type(28, 29),
+            // #31, `Long.MAX_VALUE`
+            dynamic(1, 30),
+            utf8("BootstrapMethods"),
+        ];
+        let bootstrap_methods = Attribute::BootstrapMethods {
+            attribute_name_index: 32u16.into(),
+            attribute_length: 0,
+            methods: vec![
+                BootStrapMethod {
+                    method_ref: 16u16.into(),
+                    arguments: vec![],
+                },
+                BootStrapMethod {
+                    method_ref: 25u16.into(),
+                    arguments: vec![27u16.into()],
+                },
+            ],
+        };
+
+        let run = |thread: &mut JvmThread, descriptor: &str, code: Vec<u8>| {
+            let class = verifier::tests::class_with(
+                descriptor,
+                code,
+                vec![],
+                constants.clone(),
+                vec![bootstrap_methods.clone()],
+            );
+            verifier::verify(&class)?;
+            thread.insert_class(class.identifier().clone(), class)?;
+            thread.call_static("Test", "run", descriptor, vec![])
+        };
+
+        let mut thread = thread();
+        // ldc #20, areturn
+        let int = run(&mut thread, "()Ljava/lang/Class;", vec![0x12, 20, 0xb0]);
+        let int = FrameValue::from(int.unwrap().unwrap());
+        assert_eq!(
+            thread.mirrored_class(int.reference().unwrap()).unwrap(),
+            ClassIdentifier::primitive("int").unwrap()
+        );
+        // ldc2_w #31, lreturn
+        let max = run(&mut thread, "()J", vec![0x14, 0, 31, 0xad]);
+        assert_eq!(max.unwrap().unwrap().long().unwrap(), i64::MAX);
+
+        // ldc #31, lreturn
+        let err = run(&mut thread, "()J", vec![0x12, 31, 0xad]).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("ldc of category 2 dynamic constant"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn increments() {
+        let mut thread = thread();
+        run_static(&mut thread, "Increments", "run").unwrap();
+        assert_eq!(static_int(&thread, "Increments", "wrapped"), i32::MIN);
+        assert_eq!(static_int(&thread, "Increments", "wrappedDown"), i32::MAX);
+        assert_eq!(static_int(&thread, "Increments", "wideIncrement"), 1005);
+        assert_eq!(static_int(&thread, "Increments", "wideDecrement"), -29995);
+    }
+
+    #[test]
+    fn wide_fields() {
+        let mut thread = thread();
+        run_static(&mut thread, "WideFields", "run").unwrap();
+        let class = thread
+            .class(&ClassIdentifier::new("WideFields").unwrap())
+            .unwrap();
+        let value = |name: &str| class.get_static_field_value(name).unwrap();
+
+        assert!(matches!(value("staticLong"), FieldValue::Long(10000000002)));
+        assert!(matches!(value("staticDouble"), FieldValue::Double(2.5)));
+        assert!(matches!(value("chained"), FieldValue::Long(7)));
+        assert!(matches!(value("longResult"), FieldValue::Long(10000000009)));
+    }
+
+    #[test]
+    fn field_value_type() {
+        let long = FieldDescriptor::new("J").unwrap();
+        assert!(matches!(
+            field_value(&long, FrameValue::Long(1)).unwrap(),
+            FieldValue::Long(1)
+        ));
+        assert!(field_value(&long, FrameValue::Int(1)).is_err());
+        assert!(field_value(&long, FrameValue::Reserved).is_err());
+
+        let double = FieldDescriptor::new("D").unwrap();
+        assert!(field_value(&double, FrameValue::Long(1)).is_err());
+        let object = FieldDescriptor::new("Ljava/lang/Object;").unwrap();
+        assert!(field_value(&object, FrameValue::Reference(ReferenceValue::Null)).is_ok());
+    }
+
+    #[test]
+    fn double_constants() {
+        let mut thread = thread();
+        run_static(&mut thread, "Constants", "load").unwrap();
+
+        let class = thread
+            .class(&ClassIdentifier::new("Constants").unwrap())
+            .unwrap();
+        // from the ConstantValue attribute and from ldc2_w
+        let ratio = class.get_static_field_value("RATIO").unwrap();
+        assert!(matches!(ratio, FieldValue::Double(1.25)), "{ratio:?}");
+        let loaded = class.get_static_field_value("loadedDouble").unwrap();
+        assert!(matches!(loaded, FieldValue::Double(-2.5)), "{loaded:?}");
+    }
+
+    #[test]
+    fn stack_trace() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        run_static(&mut thread, "StackTraces", "capture").unwrap();
+        let identifier = ClassIdentifier::new("StackTraces").unwrap();
+        let caught = thread
+            .class(&identifier)
+            .unwrap()
+            .get_static_field_value("caught")
+            .unwrap()
+            .reference()
+            .unwrap();
+        let depth = thread.heap_get_field(caught.heap_id().unwrap(), "depth");
+        assert_eq!(depth.unwrap().int().unwrap(), 2);
+
+        // materialize the stack trace like `Throwable.getStackTrace` does
+        let (_, method) = thread
+            .resolve_method(&identifier, "capture", "()V")
+            .unwrap();
+        let code = thread.class(&identifier).unwrap().code(&method).unwrap();
+        thread
+            .stack
+            .push(
+                "capture".to_string(),
+                MethodDescriptor::new("()V").unwrap(),
+                vec![],
+                code,
+                identifier,
+                None,
+            )
+            .unwrap();
+        thread
+            .stack
+            .push_operand(FrameValue::Reference(caught))
+            .unwrap();
+        thread.stack.push_operand(FrameValue::Int(2)).unwrap();
+        thread
+            .invoke_static_method(
+                &ClassIdentifier::from_parts("java.lang", "StackTraceElement"),
+                "of",
+                "(Ljava/lang/Throwable;I)[Ljava/lang/StackTraceElement;",
+            )
+            .unwrap();
+
+        let trace = thread.stack.pop_operand().unwrap();
+        let trace = thread
+            .get_reference_array(trace.reference().unwrap().heap_id().unwrap())
+            .unwrap();
+        let field = |element: &ReferenceValue, name| {
+            thread
+                .heap_get_field(element.heap_id().unwrap(), name)
+                .unwrap()
+        };
+        let method_names: Vec<String> = trace
+            .iter()
+            .map(|element| {
+                let method_name = field(element, "methodName").reference().unwrap();
+                thread
+                    .read_java_string(method_name.heap_id().unwrap())
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(method_names, vec!["thrower", "capture"]);
+        assert_eq!(field(&trace[0], "lineNumber").int().unwrap(), 5);
+        let file_name = field(&trace[0], "fileName").reference().unwrap();
+        assert_eq!(
+            thread
+                .read_java_string(file_name.heap_id().unwrap())
+                .unwrap(),
+            "StackTraces.java"
+        );
+    }
+
+    #[test]
+    fn ldc_method_type() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        let identifier = ClassIdentifier::new("Lambdas").unwrap();
+        let class = thread.initialize(&identifier).unwrap();
+        let (_, method) = thread.resolve_method(&identifier, "run", "()V").unwrap();
+        thread
+            .stack
+            .push(
+                "run".to_string(),
+                MethodDescriptor::new("()V").unwrap(),
+                vec![],
+                class.code(&method).unwrap(),
+                identifier,
+                None,
+            )
+            .unwrap();
+
+        // the functional interface method type of the first lambda, `()V`
+        let index = &class.bootstrap_method(0).unwrap().arguments[0];
+        thread.ldc(index).unwrap();
+
+        let method_type = thread.stack.pop_operand().unwrap();
+        let method_type = method_type.reference().unwrap().heap_id().unwrap();
+        assert_eq!(
+            thread
+                .heap_get(method_type)
+                .unwrap()
+                .class_identifier()
+                .unwrap(),
+            ClassIdentifier::from_parts("java.lang.invoke", "MethodType")
+        );
+        let rtype = thread.heap_get_field(method_type, "rtype").unwrap();
+        assert_eq!(
+            thread.mirrored_class(&rtype.reference().unwrap()).unwrap(),
+            ClassIdentifier::primitive("void").unwrap()
+        );
+    }
+
+    #[test]
+    fn string_concatenation() {
+        let mut thread = thread();
+        run_static(&mut thread, "Concat", "run").unwrap();
+
+        assert_eq!(static_string(&thread, "Concat", "described"), "3 apples");
+    }
+
+    #[test]
+    fn string_concatenation_conversions() {
+        let mut thread = thread();
+        run_static(&mut thread, "Concat", "runMixed").unwrap();
+
+        assert_eq!(
+            static_string(&thread, "Concat", "mixed"),
+            "c=true, 5, 1.0E10, null, (1, 2)"
+        );
+    }
+
+    #[test]
+    fn string_concatenation_throwing_to_string() {
+        let mut thread = thread();
+        thread.current_thread_id = Some(1.into());
+        run_static(&mut thread, "Concat", "runThrowing").unwrap();
+
+        assert_eq!(static_string(&thread, "Concat", "caught"), "no string");
+    }
+
+    #[test]
+    fn main_sentinel() {
+        let mut thread = thread();
+        let main_class = ClassIdentifier::new("MainSentinel").unwrap();
+        thread.run_main(&main_class, vec![]).unwrap();
+        assert_eq!(static_int(&thread, "MainSentinel", "sentinel"), 42);
+    }
+
+    #[test]
+    fn missing_main() {
+        for class in ["MainSentinel$NoMain", "MainSentinel$InstanceMain"] {
+            let mut thread = thread();
+            let main_class = ClassIdentifier::new(class).unwrap();
+            let err = thread.run_main(&main_class, vec![]).unwrap_err();
+            assert!(err.to_string().contains("NoSuchMethodError"), "{err:?}");
+        }
+    }
+}
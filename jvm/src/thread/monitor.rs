@@ -1,23 +1,32 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
 
-use anyhow::{Result, bail};
-use common::{ClassIdentifier, HeapId, ThreadId};
+use anyhow::{Context, Result, anyhow, bail};
+use common::{HeapId, ThreadId};
 use tracing::info;
 
-#[derive(Debug)]
-pub struct Monitor {
+#[derive(Debug, Default)]
+struct Monitor {
     entry_count: u64,
     owner: Option<ThreadId>,
+    /// Threads blocked on this monitor, either to enter it or in its wait set
+    blocked: u64,
+    /// Tickets of the threads in the wait set which haven't been notified yet, in the order they
+    /// started waiting
+    waiting: VecDeque<u64>,
+    /// Tickets of notified threads which haven't woken up yet, so a notification can't be taken
+    /// by a thread which started waiting after it
+    notified: HashSet<u64>,
+    /// Ticket of the next thread starting to wait
+    next_ticket: u64,
+    /// Signalled whenever the monitor is released or notified
+    condvar: Arc<Condvar>,
 }
 
 impl Monitor {
-    fn new(thread_id: ThreadId) -> Self {
-        Self {
-            entry_count: 1,
-            owner: Some(thread_id),
-        }
-    }
-
     fn owned_by(&self, thread_id: &ThreadId) -> bool {
         if let Some(owner) = &self.owner {
             owner == thread_id
@@ -25,95 +34,224 @@ impl Monitor {
             false
         }
     }
+
+    fn try_enter(&mut self, thread_id: &ThreadId, entry_count: u64) -> bool {
+        if self.owner.is_none() {
+            self.owner = Some(thread_id.clone());
+            self.entry_count = entry_count;
+        } else if self.owned_by(thread_id) {
+            self.entry_count += entry_count;
+        } else {
+            return false;
+        }
+
+        true
+    }
 }
 
+type MonitorMap = HashMap<HeapId, Monitor>;
+
 #[derive(Debug, Default)]
 pub struct Monitors {
-    object_monitors: HashMap<HeapId, Monitor>,
-    class_monitors: HashMap<ClassIdentifier, Monitor>,
+    monitors: Mutex<MonitorMap>,
 }
 
 impl Monitors {
-    pub fn enter_object_monitor(&mut self, heap_id: &HeapId, thread_id: &ThreadId) -> bool {
-        if let Some(monitor) = self.object_monitors.get_mut(heap_id) {
-            if monitor.owned_by(thread_id) {
-                monitor.entry_count += 1;
-            } else {
-                return false;
-            }
-        } else {
-            let monitor = Monitor::new(thread_id.clone());
-            self.object_monitors.insert(heap_id.clone(), monitor);
-        }
-
+    /// Blocks until `thread_id` owns the monitor of `heap_id`
+    pub fn enter_object_monitor(&self, heap_id: &HeapId, thread_id: &ThreadId) -> Result<()> {
+        self.acquire(self.lock()?, heap_id, thread_id, 1)?;
         info!("entered monitor for {heap_id:?} with thread {thread_id:?}");
+        Ok(())
+    }
 
-        true
+    pub fn exit_object_monitor(&self, heap_id: &HeapId, thread_id: &ThreadId) -> Result<()> {
+        self.exit(heap_id, thread_id)
     }
 
-    pub fn enter_class_monitor(
-        &mut self,
-        class_identifier: &ClassIdentifier,
+    /// Releases the monitor of `heap_id` until another thread notifies it or `timeout` has passed,
+    /// afterwards the monitor is reacquired with the same entry count as before.
+    pub fn wait(
+        &self,
+        heap_id: &HeapId,
         thread_id: &ThreadId,
-    ) -> bool {
-        if let Some(monitor) = self.class_monitors.get_mut(class_identifier) {
-            if monitor.owned_by(thread_id) {
-                monitor.entry_count += 1;
+        timeout: Option<Duration>,
+    ) -> Result<()> {
+        let mut monitors = self.lock()?;
+        let monitor = monitors
+            .get_mut(heap_id)
+            .filter(|monitor| monitor.owned_by(thread_id))
+            .context("TODO: throw IllegalMonitorStateException")?;
+
+        let entry_count = monitor.entry_count;
+        monitor.owner = None;
+        monitor.entry_count = 0;
+        let ticket = monitor.next_ticket;
+        monitor.next_ticket += 1;
+        monitor.waiting.push_back(ticket);
+        monitor.blocked += 1;
+        monitor.condvar.notify_all();
+        let condvar = monitor.condvar.clone();
+        info!("thread {thread_id:?} is waiting on {heap_id:?}");
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let monitor = monitors.get_mut(heap_id).context("monitor vanished")?;
+            if monitor.notified.remove(&ticket) {
+                break;
+            }
+
+            if let Some(deadline) = deadline {
+                let now = Instant::now();
+                if now >= deadline {
+                    monitor.waiting.retain(|waiting| *waiting != ticket);
+                    break;
+                }
+
+                monitors = condvar
+                    .wait_timeout(monitors, deadline - now)
+                    .map_err(|e| anyhow!("Lock poisoned: {}", e))?
+                    .0;
             } else {
-                return false;
+                monitors = condvar
+                    .wait(monitors)
+                    .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
             }
-        } else {
-            let monitor = Monitor::new(thread_id.clone());
-            self.class_monitors
-                .insert(class_identifier.clone(), monitor);
         }
 
-        info!("entered monitor for {class_identifier:?} with thread {thread_id:?}");
+        monitors
+            .get_mut(heap_id)
+            .context("monitor vanished")?
+            .blocked -= 1;
+        self.acquire(monitors, heap_id, thread_id, entry_count)?;
+        info!("thread {thread_id:?} stopped waiting on {heap_id:?}");
+        Ok(())
+    }
 
-        true
+    /// Wakes up a single thread waiting on the monitor of `heap_id`
+    pub fn notify(&self, heap_id: &HeapId, thread_id: &ThreadId) -> Result<()> {
+        self.notify_waiting(heap_id, thread_id, false)
     }
 
-    pub fn exit_object_monitor(&mut self, heap_id: &HeapId, thread_id: &ThreadId) -> Result<()> {
-        if let Some(monitor) = self.object_monitors.get_mut(heap_id) {
-            if monitor.owned_by(thread_id) {
-                monitor.entry_count -= 1;
-                if monitor.entry_count == 0 {
-                    monitor.owner = None;
-                    info!("thread {thread_id:?} is no longer the owner of {heap_id:?}");
-                }
+    /// Wakes up all threads waiting on the monitor of `heap_id`
+    pub fn notify_all(&self, heap_id: &HeapId, thread_id: &ThreadId) -> Result<()> {
+        self.notify_waiting(heap_id, thread_id, true)
+    }
 
-                self.object_monitors.remove(heap_id);
-                info!("exited monitor for {heap_id:?} with thread {thread_id:?}");
-                Ok(())
-            } else {
-                bail!("TODO: IllegalMonitorAccessException");
+    fn notify_waiting(&self, heap_id: &HeapId, thread_id: &ThreadId, all: bool) -> Result<()> {
+        let mut monitors = self.lock()?;
+        let monitor = monitors
+            .get_mut(heap_id)
+            .filter(|monitor| monitor.owned_by(thread_id))
+            .context("TODO: throw IllegalMonitorStateException")?;
+
+        let count = if all { monitor.waiting.len() } else { 1 };
+        for _ in 0..count {
+            if let Some(ticket) = monitor.waiting.pop_front() {
+                monitor.notified.insert(ticket);
             }
-        } else {
-            bail!("no monitor found for {heap_id:?}");
         }
+        monitor.condvar.notify_all();
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, MonitorMap>> {
+        self.monitors
+            .lock()
+            .map_err(|e| anyhow!("Lock poisoned: {}", e))
     }
 
-    pub fn exit_class_monitor(
-        &mut self,
-        class_identifier: &ClassIdentifier,
+    /// Blocks until `thread_id` owns the monitor, entering it `entry_count` times
+    fn acquire(
+        &self,
+        mut monitors: MutexGuard<'_, MonitorMap>,
+        heap_id: &HeapId,
         thread_id: &ThreadId,
+        entry_count: u64,
     ) -> Result<()> {
-        if let Some(monitor) = self.class_monitors.get_mut(class_identifier) {
-            if monitor.owned_by(thread_id) {
-                monitor.entry_count -= 1;
-                if monitor.entry_count == 0 {
-                    monitor.owner = None;
-                    info!("thread {thread_id:?} is no longer the owner of {class_identifier:?}");
-                }
+        loop {
+            let monitor = monitors.entry(heap_id.clone()).or_default();
+            if monitor.try_enter(thread_id, entry_count) {
+                return Ok(());
+            }
 
-                self.class_monitors.remove(class_identifier);
-                info!("exited monitor for {class_identifier:?} with thread {thread_id:?}");
-                Ok(())
-            } else {
-                bail!("TODO: IllegalMonitorAccessException");
+            monitor.blocked += 1;
+            let condvar = monitor.condvar.clone();
+            monitors = condvar
+                .wait(monitors)
+                .map_err(|e| anyhow!("Lock poisoned: {}", e))?;
+            monitors
+                .get_mut(heap_id)
+                .context("monitor vanished")?
+                .blocked -= 1;
+        }
+    }
+
+    fn exit(&self, heap_id: &HeapId, thread_id: &ThreadId) -> Result<()> {
+        let mut monitors = self.lock()?;
+        let monitor = monitors
+            .get_mut(heap_id)
+            .context(format!("no monitor found for {heap_id:?}"))?;
+        if !monitor.owned_by(thread_id) {
+            bail!("TODO: throw IllegalMonitorStateException");
+        }
+
+        monitor.entry_count -= 1;
+        if monitor.entry_count == 0 {
+            monitor.owner = None;
+            monitor.condvar.notify_all();
+            info!("thread {thread_id:?} is no longer the owner of {heap_id:?}");
+
+            if monitor.blocked == 0 {
+                monitors.remove(heap_id);
             }
-        } else {
-            bail!("no monitor found for {class_identifier:?}");
+        }
+
+        info!("exited monitor for {heap_id:?} with thread {thread_id:?}");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    /// A thread waiting after a notification mustn't take it from the notified thread. As the
+    /// notified thread usually wakes up first, this is repeated a couple of times.
+    #[test]
+    fn notification_isnt_taken_by_later_waiter() {
+        let monitors = Arc::new(Monitors::default());
+        for i in 0..50 {
+            let heap_id = HeapId::from(i);
+            let (sender, receiver) = mpsc::channel();
+
+            let waiter = {
+                let monitors = monitors.clone();
+                let heap_id = heap_id.clone();
+                std::thread::spawn(move || {
+                    let thread_id = ThreadId::from(1);
+                    monitors.enter_object_monitor(&heap_id, &thread_id).unwrap();
+                    sender.send(()).unwrap();
+                    monitors.wait(&heap_id, &thread_id, None).unwrap();
+                    monitors.exit_object_monitor(&heap_id, &thread_id).unwrap();
+                    sender.send(()).unwrap();
+                })
+            };
+
+            receiver.recv().unwrap();
+            // only succeeds once the waiter released the monitor by waiting
+            let thread_id = ThreadId::from(2);
+            monitors.enter_object_monitor(&heap_id, &thread_id).unwrap();
+            monitors.notify(&heap_id, &thread_id).unwrap();
+            monitors
+                .wait(&heap_id, &thread_id, Some(Duration::from_millis(1)))
+                .unwrap();
+            monitors.exit_object_monitor(&heap_id, &thread_id).unwrap();
+
+            let woken_up = receiver.recv_timeout(Duration::from_secs(5));
+            assert!(woken_up.is_ok(), "notification was lost in round {i}");
+            waiter.join().unwrap();
         }
     }
 }
@@ -3,6 +3,10 @@ use common::{ClassIdentifier, FrameValue, ReferenceValue};
 
 use crate::thread::JvmThread;
 
+const ACC_PUBLIC: i32 = 0x0001;
+const ACC_FINAL: i32 = 0x0010;
+const ACC_ABSTRACT: i32 = 0x0400;
+
 pub fn run(
     jvm: &mut JvmThread,
     name: &str,
@@ -10,17 +14,32 @@ pub fn run(
 ) -> Result<Option<FrameValue>> {
     match name {
         "registerNatives" => Ok(None),
-        "initClassName" => {
-            if let FrameValue::Reference(ReferenceValue::Class(identifier)) =
-                operands.first().context("no operands provided")?
-            {
-                let object_id = jvm.new_string(format!("{identifier:?}").to_string())?;
+        "initClassName" | "getName" => {
+            let name = class_operand(jvm, &operands, 0)?.java_name();
+            let object_id = jvm.new_string(name)?;
+            Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(
+                object_id,
+            ))))
+        }
+        "getSimpleName" => {
+            let name = class_operand(jvm, &operands, 0)?.simple_name();
+            let object_id = jvm.new_string(name)?;
+            Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(
+                object_id,
+            ))))
+        }
+        "getCanonicalName" => match class_operand(jvm, &operands, 0)?.canonical_name() {
+            Some(name) => {
+                let object_id = jvm.new_string(name)?;
                 Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(
                     object_id,
                 ))))
-            } else {
-                bail!("first operand has to be a reference")
             }
+            None => Ok(Some(FrameValue::Reference(ReferenceValue::Null))),
+        },
+        "isArray" => {
+            let is_array = class_operand(jvm, &operands, 0)?.is_array();
+            Ok(Some(FrameValue::Int(is_array.into())))
         }
         "desiredAssertionStatus0" => Ok(Some(FrameValue::Int(0))),
         "getPrimitiveClass" => {
@@ -32,41 +51,9 @@ pub fn run(
                 bail!("no reference found, instead: {operand:?}")
             };
 
-            let value_heap_item = jvm.heap_get_field(heap_id, "value")?;
-            let (_, primitive_array) = jvm.get_primitive_array(value_heap_item.heap_id()?)?;
-
-            let bytes: Vec<u8> = primitive_array
-                .iter()
-                .map(|p| p.byte())
-                .collect::<Result<Vec<u8>>>()?;
-            let name = String::from_utf8(bytes)?;
-            match name.as_str() {
-                "int" => Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                    ClassIdentifier::new("java.lang".to_owned(), "Integer".to_owned()),
-                )))),
-                "boolean" => Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                    ClassIdentifier::new("java.lang".to_owned(), "Boolean".to_owned()),
-                )))),
-                "byte" => Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                    ClassIdentifier::new("java.lang".to_owned(), "Byte".to_owned()),
-                )))),
-                "short" => Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                    ClassIdentifier::new("java.lang".to_owned(), "Short".to_owned()),
-                )))),
-                "char" => Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                    ClassIdentifier::new("java.lang".to_owned(), "Character".to_owned()),
-                )))),
-                "double" => Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                    ClassIdentifier::new("java.lang".to_owned(), "Double".to_owned()),
-                )))),
-                "long" => Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                    ClassIdentifier::new("java.lang".to_owned(), "Long".to_owned()),
-                )))),
-                "float" => Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                    ClassIdentifier::new("java.lang".to_owned(), "Float".to_owned()),
-                )))),
-                _ => bail!("invalid primitive class name: '{name}'"),
-            }
+            let name = jvm.read_java_string(heap_id)?;
+            let class = jvm.primitive_class(&name)?;
+            Ok(Some(class_value(jvm, &class)?))
         }
         "forName0" => {
             let heap_id = operands
@@ -74,41 +61,113 @@ pub fn run(
                 .context("no first operand")?
                 .reference()?
                 .heap_id()?;
-            let byte_value = jvm.heap_get_field(heap_id, "value")?;
-            let (_, primitive_array) = jvm.get_primitive_array(byte_value.heap_id()?)?;
-            let bytes: Vec<u8> = primitive_array
-                .iter()
-                .map(|p| p.byte())
-                .collect::<Result<Vec<u8>>>()?;
-            let name = String::from_utf8(bytes)?;
-            Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                ClassIdentifier::parse(&name)?,
-            ))))
+            let name = jvm.read_java_string(heap_id)?;
+            Ok(Some(class_value(jvm, &ClassIdentifier::new(&name)?)?))
         }
         "isPrimitive" => {
-            let value = match format!(
-                "{:?}",
-                operands
-                    .first()
-                    .context("no first operand")?
-                    .reference()?
-                    .class_identifier()?
-            )
-            .as_str()
-            {
-                "java.lang.Byte" => FrameValue::Int(1),
-                "java.lang.Character" => FrameValue::Int(1),
-                "java.lang.Double" => FrameValue::Int(1),
-                "java.lang.Float" => FrameValue::Int(1),
-                "java.lang.Integer" => FrameValue::Int(1),
-                "java.lang.Long" => FrameValue::Int(1),
-                "java.lang.Short" => FrameValue::Int(1),
-                "java.lang.Boolean" => FrameValue::Int(1),
-                "java.lang.Void" => FrameValue::Int(1),
-                _ => FrameValue::Int(0),
+            let is_primitive = class_operand(jvm, &operands, 0)?.is_primitive();
+            Ok(Some(FrameValue::Int(is_primitive.into())))
+        }
+        "isAssignableFrom" => {
+            let target = class_operand(jvm, &operands, 0)?;
+            let class = match operands.get(1).context("no class operand")?.reference()? {
+                ReferenceValue::Null => {
+                    jvm.throw_null_pointer("Cannot invoke \"Class.isAssignableFrom()\"")?;
+                    return Ok(None);
+                }
+                reference => jvm.mirrored_class(reference)?,
+            };
+            let assignable = is_assignable(jvm, &target, &class)?;
+            Ok(Some(FrameValue::Int(assignable.into())))
+        }
+        "isInstance" => {
+            let target = class_operand(jvm, &operands, 0)?;
+            let is_instance = match operands.get(1).context("no object operand")?.reference()? {
+                ReferenceValue::Null => false,
+                _ if target.is_primitive() => false,
+                object => jvm.is_instance_of(object, &target)?,
+            };
+            Ok(Some(FrameValue::Int(is_instance.into())))
+        }
+        "getSuperclass" => {
+            let identifier = class_operand(jvm, &operands, 0)?;
+            if identifier.is_primitive() {
+                return Ok(Some(FrameValue::Reference(ReferenceValue::Null)));
+            }
+            if identifier.is_array() {
+                let object = ClassIdentifier::from_parts("java.lang", "Object");
+                return Ok(Some(class_value(jvm, &object)?));
+            }
+
+            let class = jvm.resolve_class(&identifier)?;
+            if class.is_interface() || !class.has_super_class() {
+                Ok(Some(FrameValue::Reference(ReferenceValue::Null)))
+            } else {
+                Ok(Some(class_value(jvm, &class.super_class()?)?))
+            }
+        }
+        "getInterfaces0" => {
+            let identifier = class_operand(jvm, &operands, 0)?;
+            let interfaces = if identifier.is_primitive() {
+                vec![]
+            } else if identifier.is_array() {
+                vec![
+                    ClassIdentifier::from_parts("java.lang", "Cloneable"),
+                    ClassIdentifier::from_parts("java.io", "Serializable"),
+                ]
+            } else {
+                jvm.resolve_class(&identifier)?.super_interfaces()?
             };
-            Ok(Some(value))
+
+            let class = ClassIdentifier::from_parts("java.lang", "Class");
+            let array = jvm.allocate_array(class, interfaces.len())?;
+            for (i, interface) in interfaces.into_iter().enumerate() {
+                let mirror = jvm.class_mirror(&interface)?;
+                jvm.store_into_reference_array(&array, i, ReferenceValue::HeapItem(mirror))?;
+            }
+            Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(array))))
         }
         _ => bail!("TODO"),
     }
 }
+
+/// Access flags of `class` as `Reflection.getClassAccessFlags` reports them, arrays and
+/// primitive classes are public, abstract and final
+pub(super) fn access_flags(jvm: &mut JvmThread, class: &ClassIdentifier) -> Result<i32> {
+    if class.is_primitive() || class.is_array() {
+        return Ok(ACC_PUBLIC | ACC_FINAL | ACC_ABSTRACT);
+    }
+
+    Ok(jvm.resolve_class(class)?.access_flags().into())
+}
+
+pub(super) fn class_operand(
+    jvm: &JvmThread,
+    operands: &[FrameValue],
+    index: usize,
+) -> Result<ClassIdentifier> {
+    let reference = operands
+        .get(index)
+        .context(format!("no operand at {index}"))?
+        .reference()?;
+    jvm.mirrored_class(reference)
+}
+
+/// Reference to the `java.lang.Class` object of `class`
+fn class_value(jvm: &mut JvmThread, class: &ClassIdentifier) -> Result<FrameValue> {
+    let mirror = jvm.class_mirror(class)?;
+    Ok(FrameValue::Reference(ReferenceValue::HeapItem(mirror)))
+}
+
+/// Checks if values of `class` can be assigned to `target`, primitive classes only to themselves
+fn is_assignable(
+    jvm: &mut JvmThread,
+    target: &ClassIdentifier,
+    class: &ClassIdentifier,
+) -> Result<bool> {
+    if target.is_primitive() || class.is_primitive() {
+        return Ok(target == class);
+    }
+
+    jvm.is_subclass(class, target)
+}
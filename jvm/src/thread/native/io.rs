@@ -0,0 +1,151 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use common::{FrameValue, ReferenceValue};
+
+use crate::thread::JvmThread;
+
+/// Bits of `FileSystem.getBooleanAttributes`
+const BA_EXISTS: i32 = 0x01;
+const BA_REGULAR: i32 = 0x02;
+const BA_DIRECTORY: i32 = 0x04;
+const BA_HIDDEN: i32 = 0x08;
+
+pub fn run_file_descriptor(name: &str) -> Result<Option<FrameValue>> {
+    match name {
+        "initIDs" => Ok(None),
+        // handles are only used on windows
+        "getHandle" => Ok(Some(FrameValue::Long(-1))),
+        "getAppend" => Ok(Some(FrameValue::Int(0))),
+        _ => bail!("TODO"),
+    }
+}
+
+pub fn run_file_input_stream(name: &str) -> Result<Option<FrameValue>> {
+    match name {
+        "initIDs" => Ok(None),
+        _ => bail!("TODO"),
+    }
+}
+
+pub fn run_unix_file_system(
+    jvm: &mut JvmThread,
+    name: &str,
+    operands: Vec<FrameValue>,
+) -> Result<Option<FrameValue>> {
+    match name {
+        "initIDs" => Ok(None),
+        "canonicalize0" => {
+            let path = operands
+                .get(1)
+                .context("no 'path' operand found")?
+                .reference()?;
+            let path = jvm.read_java_string(path.heap_id()?)?;
+            // paths which don't exist can't be resolved, they are kept as they are
+            let canonical = std::fs::canonicalize(&path)
+                .map_or(path, |canonical| canonical.to_string_lossy().to_string());
+            let canonical = jvm.new_string(canonical)?;
+            Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(
+                canonical,
+            ))))
+        }
+        "getBooleanAttributes0" => {
+            let path = file_path(jvm, &operands)?;
+            let attributes = match std::fs::metadata(&path) {
+                Ok(metadata) => {
+                    let hidden = path
+                        .file_name()
+                        .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+                    BA_EXISTS
+                        | if metadata.is_file() { BA_REGULAR } else { 0 }
+                        | if metadata.is_dir() { BA_DIRECTORY } else { 0 }
+                        | if hidden { BA_HIDDEN } else { 0 }
+                }
+                Err(_) => 0,
+            };
+            Ok(Some(FrameValue::Int(attributes)))
+        }
+        _ => bail!("TODO"),
+    }
+}
+
+/// Path of the `java.io.File` which is the second operand, after the file system
+fn file_path(jvm: &JvmThread, operands: &[FrameValue]) -> Result<PathBuf> {
+    let file = operands
+        .get(1)
+        .context("no 'f' operand found")?
+        .reference()?;
+    let path = jvm.heap_get_field(file.heap_id()?, "path")?.reference()?;
+    Ok(PathBuf::from(jvm.read_java_string(path.heap_id()?)?))
+}
+
+pub fn run_file_output_stream(
+    jvm: &mut JvmThread,
+    name: &str,
+    operands: Vec<FrameValue>,
+) -> Result<Option<FrameValue>> {
+    match name {
+        "initIDs" => Ok(None),
+        "writeBytes" => {
+            let fd = fd(jvm, &operands)?;
+            let bytes = operands
+                .get(1)
+                .context("no 'b' operand found")?
+                .reference()?;
+            let off = operands.get(2).context("no 'off' operand found")?.int()?;
+            let len = operands.get(3).context("no 'len' operand found")?.int()?;
+            if bytes.is_null() {
+                jvm.throw_null_pointer("Cannot read the array length")?;
+                return Ok(None);
+            }
+
+            let (_, values) = jvm.get_primitive_array(bytes.heap_id()?)?;
+            if off < 0 || len < 0 || off as usize + len as usize > values.len() {
+                jvm.throw_new(
+                    "IndexOutOfBoundsException",
+                    format!(
+                        "Range [{off}, {}) out of bounds for length {}",
+                        off as i64 + len as i64,
+                        values.len()
+                    ),
+                )?;
+                return Ok(None);
+            }
+
+            let bytes = values[off as usize..(off + len) as usize]
+                .iter()
+                .map(|value| value.byte())
+                .collect::<Result<Vec<u8>>>()?;
+            write(jvm, fd, &bytes)?;
+            Ok(None)
+        }
+        "write" => {
+            let fd = fd(jvm, &operands)?;
+            let b = operands.get(1).context("no 'b' operand found")?.int()?;
+            write(jvm, fd, &[b as u8])?;
+            Ok(None)
+        }
+        _ => bail!("TODO"),
+    }
+}
+
+/// File descriptor of the `FileOutputStream` which is the first operand
+fn fd(jvm: &JvmThread, operands: &[FrameValue]) -> Result<i32> {
+    let stream = operands
+        .first()
+        .context("no 'this' operand found")?
+        .reference()?;
+    let descriptor = jvm.heap_get_field(stream.heap_id()?, "fd")?.reference()?;
+    if descriptor.is_null() {
+        bail!("TODO: throw IOException, stream closed");
+    }
+
+    jvm.heap_get_field(descriptor.heap_id()?, "fd")?.int()
+}
+
+fn write(jvm: &JvmThread, fd: i32, bytes: &[u8]) -> Result<()> {
+    match fd {
+        1 | 2 => jvm.write_output(fd, bytes),
+        _ => bail!("TODO: writing to file descriptor {fd}"),
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::{Context, Result, bail};
+use common::{FrameValue, ReferenceValue};
+
+use crate::thread::JvmThread;
+
+pub fn run_boot_loader(name: &str) -> Result<Option<FrameValue>> {
+    match name {
+        // TODO: the VM doesn't keep track of modules yet
+        "setBootLoaderUnnamedModule0" => Ok(None),
+        _ => bail!("TODO"),
+    }
+}
+
+/// The VM implements the natives of the JDK libraries itself, so every library counts as built
+/// into the VM and loading one always succeeds
+pub fn run_native_libraries(
+    jvm: &mut JvmThread,
+    name: &str,
+    operands: Vec<FrameValue>,
+) -> Result<Option<FrameValue>> {
+    match name {
+        "findBuiltinLib" => {
+            let file_name = operands
+                .first()
+                .context("no library name operand")?
+                .reference()?
+                .heap_id()?;
+            let file_name = jvm.read_java_string(file_name)?;
+            let library = file_name
+                .strip_prefix(std::env::consts::DLL_PREFIX)
+                .and_then(|name| name.strip_suffix(std::env::consts::DLL_SUFFIX))
+                .unwrap_or(&file_name);
+            let library = jvm.new_string(library.to_string())?;
+            Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(
+                library,
+            ))))
+        }
+        "load" => Ok(Some(FrameValue::Int(1))),
+        "unload" => Ok(None),
+        _ => bail!("TODO"),
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::{Context, Result, bail};
+use common::FrameValue;
+
+/// Natives of `java.lang.StrictMath`, which the JDK implements with fdlibm. Rust's `f64` methods
+/// use the platform's libm, so their results may differ in the last bit.
+pub fn run(name: &str, operands: Vec<FrameValue>) -> Result<Option<FrameValue>> {
+    let a = operands.first().context("no operand")?.double()?;
+    let result = match name {
+        "sin" => a.sin(),
+        "cos" => a.cos(),
+        "tan" => a.tan(),
+        "asin" => a.asin(),
+        "acos" => a.acos(),
+        "atan" => a.atan(),
+        "log" => a.ln(),
+        "log10" => a.log10(),
+        "sqrt" => a.sqrt(),
+        "sinh" => a.sinh(),
+        "cosh" => a.cosh(),
+        "tanh" => a.tanh(),
+        "expm1" => a.exp_m1(),
+        "log1p" => a.ln_1p(),
+        "IEEEremainder" | "atan2" => {
+            let b = operands.get(1).context("no second operand")?.double()?;
+            if name == "atan2" {
+                a.atan2(b)
+            } else {
+                ieee_remainder(a, b)
+            }
+        }
+        _ => bail!("TODO"),
+    };
+
+    Ok(Some(FrameValue::Double(result)))
+}
+
+/// Remainder of `x / p` with the quotient rounded to the nearest even integer, unlike `%` which
+/// truncates it. Follows `__ieee754_remainder` of fdlibm.
+fn ieee_remainder(x: f64, p: f64) -> f64 {
+    if x.is_nan() || p.is_nan() || x.is_infinite() || p == 0.0 {
+        return f64::NAN;
+    }
+
+    let p = p.abs();
+    // reducing by 2p first keeps the parity of the quotient, which decides ties
+    let mut r = if p <= f64::MAX / 2.0 { x % (p + p) } else { x }.abs();
+    if p < 2.0 * f64::MIN_POSITIVE {
+        if r + r > p {
+            r -= p;
+            if r + r >= p {
+                r -= p;
+            }
+        }
+    } else {
+        let half = 0.5 * p;
+        if r > half {
+            r -= p;
+            if r >= half {
+                r -= p;
+            }
+        }
+    }
+
+    if x.is_sign_negative() { -r } else { r }
+}
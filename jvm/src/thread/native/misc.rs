@@ -1,6 +1,8 @@
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use common::FrameValue;
 
+use crate::thread::JvmThread;
+
 pub fn run_cds(name: &str) -> Result<Option<FrameValue>> {
     match name {
         "isDumpingClassList0" => Ok(Some(FrameValue::Int(0))),
@@ -19,3 +21,36 @@ pub fn run_vm(name: &str) -> Result<Option<FrameValue>> {
         _ => bail!("TODO"),
     }
 }
+
+pub fn run_scoped_memory_access(name: &str) -> Result<Option<FrameValue>> {
+    match name {
+        "registerNatives" => Ok(None),
+        _ => bail!("TODO"),
+    }
+}
+
+pub fn run_signal(
+    jvm: &mut JvmThread,
+    name: &str,
+    operands: Vec<FrameValue>,
+) -> Result<Option<FrameValue>> {
+    match name {
+        "findSignal0" => {
+            let signal = operands
+                .first()
+                .context("no signal name operand")?
+                .reference()?
+                .heap_id()?;
+            let number = match jvm.read_java_string(signal)?.as_str() {
+                "HUP" => 1,
+                "INT" => 2,
+                "TERM" => 15,
+                _ => -1,
+            };
+            Ok(Some(FrameValue::Int(number)))
+        }
+        // TODO: install handlers, until then every signal keeps its default handler
+        "handle0" => Ok(Some(FrameValue::Long(0))),
+        _ => bail!("TODO"),
+    }
+}
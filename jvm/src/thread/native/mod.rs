@@ -1,5 +1,5 @@
 use common::{FrameValue, ReferenceValue};
-use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
 use common::ClassIdentifier;
@@ -8,13 +8,28 @@ use tracing::{info, warn};
 use crate::thread::JvmThread;
 
 mod class;
+mod io;
+mod loader;
+mod math;
 mod misc;
+mod props;
 mod reflect;
+mod registry;
 mod runtime;
 mod system;
 mod thread;
+mod throwable;
 mod r#unsafe;
 
+pub use registry::{NativeMethod, NativeRegistry};
+
+/// Checks if the VM runs the method natively, although the JDK implements it in Java. The names
+/// of classes are kept by the VM, not in the reflection data the JDK would compute them from.
+pub fn is_intrinsic(class_identifier: &ClassIdentifier, name: &str) -> bool {
+    *class_identifier == ClassIdentifier::from_parts("java.lang", "Class")
+        && matches!(name, "getName" | "getSimpleName" | "getCanonicalName")
+}
+
 pub fn run(
     jvm: &mut JvmThread,
     class_identifier: &ClassIdentifier,
@@ -26,15 +41,31 @@ pub fn run(
         class_identifier, operands
     );
 
+    if let Some(method) = jvm.native_method(class_identifier, name)? {
+        return method(jvm, operands);
+    }
+
     match format!("{:?}", class_identifier).as_str() {
         "java.lang.Class" => class::run(jvm, name, operands),
         "java.lang.Runtime" => runtime::run(name),
+        "java.lang.StrictMath" => math::run(name, operands),
         "jdk.internal.misc.Unsafe" => r#unsafe::run(jvm, name, operands),
         "java.lang.Thread" => thread::run(jvm, name, operands),
         "java.lang.System" => system::run(jvm, name, operands),
+        "jdk.internal.util.SystemProps$Raw" => props::run(jvm, name),
         "jdk.internal.misc.CDS" => misc::run_cds(name),
         "jdk.internal.misc.VM" => misc::run_vm(name),
-        "jdk.internal.reflect.Reflection" => reflect::run(jvm, name),
+        "jdk.internal.misc.ScopedMemoryAccess" => misc::run_scoped_memory_access(name),
+        "jdk.internal.misc.Signal" => misc::run_signal(jvm, name, operands),
+        "java.io.FileDescriptor" => io::run_file_descriptor(name),
+        "java.io.FileInputStream" => io::run_file_input_stream(name),
+        "java.io.UnixFileSystem" => io::run_unix_file_system(jvm, name, operands),
+        "java.io.FileOutputStream" => io::run_file_output_stream(jvm, name, operands),
+        "jdk.internal.loader.BootLoader" => loader::run_boot_loader(name),
+        "jdk.internal.loader.NativeLibraries" => loader::run_native_libraries(jvm, name, operands),
+        "jdk.internal.reflect.Reflection" => reflect::run(jvm, name, operands),
+        "java.lang.Throwable" => throwable::run_throwable(jvm, name, operands),
+        "java.lang.StackTraceElement" => throwable::run_stack_trace_element(jvm, name, operands),
         "java.lang.Object" => match name {
             "getClass" => {
                 let heap_id = operands
@@ -42,25 +73,82 @@ pub fn run(
                     .context("operands are empty")?
                     .reference()?
                     .heap_id()?;
-                let heap_item = jvm.heap_get(heap_id)?;
-                let class_identifier = heap_item.class_identifier()?;
-                Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                    class_identifier.clone(),
+                let class_identifier = jvm.heap_get(heap_id)?.class_identifier()?;
+                let mirror = jvm.class_mirror(&class_identifier)?;
+                Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(
+                    mirror,
                 ))))
             }
+            "clone" => {
+                let heap_id = operands
+                    .first()
+                    .context("operands are empty")?
+                    .reference()?
+                    .heap_id()?;
+                let item = jvm.heap_get(heap_id)?;
+                let class_identifier = item.class_identifier()?;
+                let cloneable = ClassIdentifier::from_parts("java.lang", "Cloneable");
+                if !item.is_array() && !jvm.is_subclass(&class_identifier, &cloneable)? {
+                    jvm.throw_new("CloneNotSupportedException", class_identifier.java_name())?;
+                    return Ok(None);
+                }
+
+                let clone = jvm.clone_heap_item(heap_id)?;
+                Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(clone))))
+            }
             "hashCode" => {
-                // TODO: this is ultra wrong, need to hash values not references
-                let mut hasher = DefaultHasher::new();
-                operands
+                let reference = operands
+                    .first()
+                    .context("operands are empty")?
+                    .reference()?;
+                Ok(Some(FrameValue::Int(jvm.identity_hash(reference)?)))
+            }
+            "wait" => {
+                let heap_id = operands
+                    .first()
+                    .context("operands are empty")?
+                    .reference()?
+                    .heap_id()?;
+                let timeout = operands.get(1).context("no timeout operand")?.long()?;
+                let timeout = match timeout {
+                    0 => None,
+                    1.. => Some(Duration::from_millis(timeout as u64)),
+                    _ => bail!("negative timeout, TODO: throw IllegalArgumentException"),
+                };
+                jvm.monitor_wait(heap_id, timeout)?;
+                Ok(None)
+            }
+            "notify" | "notifyAll" => {
+                let heap_id = operands
+                    .first()
+                    .context("operands are empty")?
+                    .reference()?
+                    .heap_id()?;
+                jvm.monitor_notify(heap_id, name == "notifyAll")?;
+                Ok(None)
+            }
+            _ => bail!("TODO"),
+        },
+        "java.lang.String" => match name {
+            "intern" => {
+                let heap_id = operands
                     .first()
                     .context("operands are empty")?
                     .reference()?
-                    .hash(&mut hasher);
-                let value = hasher.finish();
-                Ok(Some(FrameValue::Int(value as i32)))
+                    .heap_id()?;
+                let value = jvm.read_java_string(heap_id)?;
+                let interned = jvm.intern_string(value)?;
+                Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(
+                    interned,
+                ))))
             }
             _ => bail!("TODO"),
         },
+        "java.lang.StringUTF16" => match name {
+            // strings are decoded with the byte order of the host as well
+            "isBigEndian" => Ok(Some(FrameValue::Int(cfg!(target_endian = "big").into()))),
+            _ => bail!("TODO"),
+        },
         "java.security.AccessController" => match name {
             // TODO: this will be used at some point
             "getStackAccessControlContext" => Ok(Some(FrameValue::Reference(ReferenceValue::Null))),
@@ -73,6 +161,16 @@ pub fn run(
                 std::thread::park();
                 Ok(None)
             }
+            "refersTo0" => {
+                let reference = operands
+                    .first()
+                    .context("operands are empty")?
+                    .reference()?
+                    .heap_id()?;
+                let object = operands.get(1).context("no object operand")?.reference()?;
+                let referent = jvm.heap_get_field(reference, "referent")?.reference()?;
+                Ok(Some(FrameValue::Int((&referent == object).into())))
+            }
             _ => bail!("TODO"),
         },
         "java.lang.ClassLoader" => match name {
@@ -80,48 +178,567 @@ pub fn run(
             "registerNatives" => Ok(None),
             _ => bail!("TODO"),
         },
+        "java.lang.invoke.MethodHandleNatives" => match name {
+            "registerNatives" => Ok(None),
+            _ => bail!("TODO"),
+        },
         "java.lang.Float" => match name {
-            "floatToRawIntBits" => {
+            "floatToRawIntBits" | "floatToIntBits" => {
                 let float = operands
                     .first()
                     .context("no float to convert to int")?
                     .float()?;
-                Ok(Some(FrameValue::Int(float as i32)))
+                let bits = if name == "floatToIntBits" && float.is_nan() {
+                    f32::NAN.to_bits()
+                } else {
+                    float.to_bits()
+                };
+                Ok(Some(FrameValue::Int(bits as i32)))
+            }
+            "intBitsToFloat" => {
+                let int = operands
+                    .first()
+                    .context("no int to convert to float")?
+                    .int()?;
+                Ok(Some(FrameValue::Float(f32::from_bits(int as u32))))
             }
             _ => bail!("TODO"),
         },
         "java.lang.Double" => match name {
-            "doubleToRawLongBits" => {
+            "doubleToRawLongBits" | "doubleToLongBits" => {
                 let double = operands
                     .first()
                     .context("no double to convert to long")?
                     .double()?;
-                Ok(Some(FrameValue::Long(double as i64)))
+                let bits = if name == "doubleToLongBits" && double.is_nan() {
+                    f64::NAN.to_bits()
+                } else {
+                    double.to_bits()
+                };
+                Ok(Some(FrameValue::Long(bits as i64)))
             }
             "longBitsToDouble" => {
                 let long = operands
                     .first()
                     .context("no long to convert to double")?
                     .long()?;
-                Ok(Some(FrameValue::Double(long as f64)))
-            }
-            _ => bail!("TODO"),
-        },
-        "jdk.internal.util.SystemProps$Raw" => match name {
-            "platformProperties" => {
-                let string_class =
-                    ClassIdentifier::new("java.lang".to_owned(), "String".to_owned());
-                let array = jvm.allocate_array(string_class, 39)?;
-                Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(array))))
-            }
-            "vmProperties" => {
-                let string_class =
-                    ClassIdentifier::new("java.lang".to_owned(), "String".to_owned());
-                let array = jvm.allocate_array(string_class, 0)?;
-                Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(array))))
+                Ok(Some(FrameValue::Double(f64::from_bits(long as u64))))
             }
             _ => bail!("TODO"),
         },
         _ => bail!("native method {name} on {class_identifier:?} not implemented",),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use common::FieldValue;
+
+    use super::*;
+    use crate::thread::{
+        heap::{InstanceField, PrimitiveArrayType, PrimitiveArrayValue},
+        tests::{frame_thread, main_thread, thread},
+    };
+
+    fn call(
+        jvm: &mut JvmThread,
+        class: &str,
+        name: &str,
+        operands: Vec<FrameValue>,
+    ) -> Result<Option<FrameValue>> {
+        run(jvm, &ClassIdentifier::new(class)?, name, operands)
+    }
+
+    fn class(jvm: &mut JvmThread, class: &str) -> FrameValue {
+        mirror(jvm, &ClassIdentifier::new(class).unwrap())
+    }
+
+    fn mirror(jvm: &mut JvmThread, class: &ClassIdentifier) -> FrameValue {
+        FrameValue::Reference(ReferenceValue::HeapItem(jvm.class_mirror(class).unwrap()))
+    }
+
+    fn mirrored(jvm: &JvmThread, class: &FrameValue) -> ClassIdentifier {
+        jvm.mirrored_class(class.reference().unwrap()).unwrap()
+    }
+
+    fn string(jvm: &mut JvmThread, value: &str) -> FrameValue {
+        let heap_id = jvm.new_string(value.to_string()).unwrap();
+        FrameValue::Reference(ReferenceValue::HeapItem(heap_id))
+    }
+
+    #[test]
+    fn class_natives() {
+        let mut jvm = thread();
+        let int = string(&mut jvm, "int");
+        let primitive = call(&mut jvm, "java/lang/Class", "getPrimitiveClass", vec![int]);
+        let primitive = primitive.unwrap().unwrap();
+        assert_eq!(
+            mirrored(&jvm, &primitive),
+            ClassIdentifier::primitive("int").unwrap()
+        );
+
+        let is_primitive = call(&mut jvm, "java/lang/Class", "isPrimitive", vec![primitive]);
+        assert_eq!(is_primitive.unwrap().unwrap().int().unwrap(), 1);
+        let operands = vec![class(&mut jvm, "java/lang/Integer")];
+        let is_primitive = call(&mut jvm, "java/lang/Class", "isPrimitive", operands);
+        assert_eq!(is_primitive.unwrap().unwrap().int().unwrap(), 0);
+        let operands = vec![class(&mut jvm, "java/lang/String")];
+        let is_primitive = call(&mut jvm, "java/lang/Class", "isPrimitive", operands);
+        assert_eq!(is_primitive.unwrap().unwrap().int().unwrap(), 0);
+    }
+
+    #[test]
+    fn class_name_natives() {
+        let mut jvm = thread();
+        let mut name = |method: &str, class: ClassIdentifier| {
+            let operands = vec![mirror(&mut jvm, &class)];
+            let name = call(&mut jvm, "java/lang/Class", method, operands).unwrap();
+            match name.unwrap().reference().unwrap() {
+                ReferenceValue::HeapItem(heap_id) => Some(jvm.read_java_string(heap_id).unwrap()),
+                _ => None,
+            }
+        };
+
+        let entry = ClassIdentifier::new("java/util/Map$Entry").unwrap();
+        assert_eq!(
+            name("initClassName", entry.clone()).unwrap(),
+            "java.util.Map$Entry"
+        );
+        assert_eq!(name("getSimpleName", entry.clone()).unwrap(), "Entry");
+        assert_eq!(
+            name("getCanonicalName", entry).unwrap(),
+            "java.util.Map.Entry"
+        );
+
+        let string = ClassIdentifier::new("java/lang/String").unwrap();
+        let strings = ClassIdentifier::array(&string, 1);
+        assert_eq!(
+            name("getName", strings.clone()).unwrap(),
+            "[Ljava.lang.String;"
+        );
+        assert_eq!(name("getSimpleName", strings).unwrap(), "String[]");
+        let anonymous = ClassIdentifier::new("Main$1").unwrap();
+        assert!(name("getCanonicalName", anonymous).is_none());
+
+        let mut is_array = |class: ClassIdentifier| {
+            let operands = vec![mirror(&mut jvm, &class)];
+            let is_array = call(&mut jvm, "java/lang/Class", "isArray", operands).unwrap();
+            is_array.unwrap().int().unwrap()
+        };
+        assert_eq!(is_array(ClassIdentifier::array(&string, 2)), 1);
+        assert_eq!(is_array(string), 0);
+    }
+
+    #[test]
+    fn class_hierarchy_natives() {
+        let mut jvm = thread();
+        let mut assignable = |target: &str, class_name: &str| {
+            let operands = vec![class(&mut jvm, target), class(&mut jvm, class_name)];
+            let result = call(&mut jvm, "java/lang/Class", "isAssignableFrom", operands);
+            result.unwrap().unwrap().int().unwrap()
+        };
+        assert_eq!(assignable("java/lang/Object", "java/lang/String"), 1);
+        assert_eq!(assignable("java/lang/String", "java/lang/Object"), 0);
+        assert_eq!(assignable("java/lang/CharSequence", "java/lang/String"), 1);
+        assert_eq!(assignable("java/lang/String", "java/lang/String"), 1);
+        assert_eq!(assignable("int", "int"), 1);
+        assert_eq!(assignable("java/lang/Integer", "int"), 0);
+        assert_eq!(assignable("java/lang/Object", "int"), 0);
+
+        let value = string(&mut jvm, "value");
+        let mut is_instance = |target: &str, object: FrameValue| {
+            let operands = vec![class(&mut jvm, target), object];
+            let result = call(&mut jvm, "java/lang/Class", "isInstance", operands);
+            result.unwrap().unwrap().int().unwrap()
+        };
+        assert_eq!(is_instance("java/lang/Object", value.clone()), 1);
+        assert_eq!(is_instance("java/lang/Comparable", value.clone()), 1);
+        assert_eq!(is_instance("java/lang/Integer", value), 0);
+        let null = FrameValue::Reference(ReferenceValue::Null);
+        assert_eq!(is_instance("java/lang/Object", null), 0);
+
+        let mut super_class = |class_name: &str| {
+            let operands = vec![class(&mut jvm, class_name)];
+            let result = call(&mut jvm, "java/lang/Class", "getSuperclass", operands);
+            let reference = result.unwrap().unwrap().reference().unwrap().clone();
+            (!reference.is_null()).then(|| jvm.mirrored_class(&reference).unwrap())
+        };
+        assert_eq!(
+            super_class("java/lang/Integer"),
+            Some(ClassIdentifier::new("java/lang/Number").unwrap())
+        );
+        assert!(super_class("java/lang/Object").is_none());
+        assert!(super_class("java/lang/Runnable").is_none());
+        assert!(super_class("int").is_none());
+
+        let string = class(&mut jvm, "java/lang/String");
+        let interfaces = call(&mut jvm, "java/lang/Class", "getInterfaces0", vec![string]);
+        let interfaces = interfaces.unwrap().unwrap();
+        let interfaces: Vec<_> = jvm
+            .get_reference_array(interfaces.reference().unwrap().heap_id().unwrap())
+            .unwrap()
+            .iter()
+            .map(|interface| jvm.mirrored_class(interface).unwrap())
+            .collect();
+        assert!(interfaces.contains(&ClassIdentifier::new("java/lang/CharSequence").unwrap()));
+        assert!(interfaces.contains(&ClassIdentifier::new("java/io/Serializable").unwrap()));
+    }
+
+    #[test]
+    fn runtime_natives() {
+        let mut jvm = frame_thread();
+        let processors = call(&mut jvm, "java/lang/Runtime", "availableProcessors", vec![]);
+        assert!(processors.unwrap().unwrap().int().unwrap() > 0);
+        let max_memory = call(&mut jvm, "java/lang/Runtime", "maxMemory", vec![]);
+        assert!(max_memory.unwrap().unwrap().long().unwrap() > 0);
+    }
+
+    #[test]
+    fn system_natives() {
+        let mut jvm = frame_thread();
+        let nano_time = call(&mut jvm, "java/lang/System", "nanoTime", vec![]);
+        assert!(nano_time.unwrap().unwrap().long().unwrap() >= 0);
+        let register = call(&mut jvm, "java/lang/System", "registerNatives", vec![]);
+        assert!(register.unwrap().is_none());
+    }
+
+    #[test]
+    fn misc_natives() {
+        let mut jvm = frame_thread();
+        let sharing = call(
+            &mut jvm,
+            "jdk/internal/misc/CDS",
+            "isSharingEnabled0",
+            vec![],
+        );
+        assert_eq!(sharing.unwrap().unwrap().int().unwrap(), 0);
+        let seed = call(
+            &mut jvm,
+            "jdk/internal/misc/CDS",
+            "getRandomSeedForDumping",
+            vec![],
+        );
+        assert_eq!(seed.unwrap().unwrap().long().unwrap(), 0);
+        let initialize = call(&mut jvm, "jdk/internal/misc/VM", "initialize", vec![]);
+        assert!(initialize.unwrap().is_none());
+    }
+
+    #[test]
+    fn reflection_natives() {
+        let mut jvm = thread();
+        // String calls into Integer, which asks for its caller
+        let string = ClassIdentifier::from_parts("java.lang", "String");
+        jvm.stack.push_entry(string.clone()).unwrap();
+        let integer = ClassIdentifier::from_parts("java.lang", "Integer");
+        jvm.stack.push_entry(integer).unwrap();
+
+        let caller = call(
+            &mut jvm,
+            "jdk/internal/reflect/Reflection",
+            "getCallerClass",
+            vec![],
+        );
+        assert_eq!(mirrored(&jvm, &caller.unwrap().unwrap()), string);
+    }
+
+    #[test]
+    fn thread_natives() {
+        let mut jvm = main_thread();
+        let register = call(&mut jvm, "java/lang/Thread", "registerNatives", vec![]);
+        assert!(register.unwrap().is_none());
+        let current = call(&mut jvm, "java/lang/Thread", "currentThread", vec![]);
+        let current = current.unwrap().unwrap();
+        let main = jvm.thread_object().unwrap();
+        assert_eq!(current.reference().unwrap().heap_id().unwrap(), &main);
+    }
+
+    #[test]
+    fn object_natives() {
+        let mut jvm = thread();
+        let value = string(&mut jvm, "value");
+        let object_class = call(&mut jvm, "java/lang/Object", "getClass", vec![value]);
+        assert_eq!(
+            object_class.unwrap().unwrap().reference().unwrap(),
+            class(&mut jvm, "java/lang/String").reference().unwrap()
+        );
+
+        let ints = jvm
+            .allocate_primitive_array(PrimitiveArrayType::Int, vec![PrimitiveArrayValue::Int(0)])
+            .unwrap();
+        let ints = FrameValue::Reference(ReferenceValue::HeapItem(ints));
+        let array_class = call(&mut jvm, "java/lang/Object", "getClass", vec![ints]);
+        let array_class = array_class.unwrap().unwrap();
+        assert_eq!(
+            array_class.reference().unwrap(),
+            class(&mut jvm, "[I").reference().unwrap()
+        );
+        let is_primitive = call(
+            &mut jvm,
+            "java/lang/Class",
+            "isPrimitive",
+            vec![array_class],
+        );
+        assert_eq!(is_primitive.unwrap().unwrap().int().unwrap(), 0);
+
+        let strings = jvm
+            .allocate_array(ClassIdentifier::new("[Ljava/lang/String;").unwrap(), 1)
+            .unwrap();
+        let strings = FrameValue::Reference(ReferenceValue::HeapItem(strings));
+        let array_class = call(&mut jvm, "java/lang/Object", "getClass", vec![strings]);
+        let array_class = array_class.unwrap().unwrap();
+        let identifier = mirrored(&jvm, &array_class);
+        assert_eq!(identifier.array_dimensions, 2);
+        assert_eq!(identifier.java_name(), "[[Ljava.lang.String;");
+    }
+
+    #[test]
+    fn string_natives() {
+        let mut jvm = thread();
+        let first = string(&mut jvm, "value");
+        let second = string(&mut jvm, "value");
+        let first = call(&mut jvm, "java/lang/String", "intern", vec![first]);
+        let second = call(&mut jvm, "java/lang/String", "intern", vec![second]);
+        assert_eq!(
+            first.unwrap().unwrap().reference().unwrap(),
+            second.unwrap().unwrap().reference().unwrap()
+        );
+    }
+
+    #[test]
+    fn security_natives() {
+        let mut jvm = frame_thread();
+        let context = call(
+            &mut jvm,
+            "java/security/AccessController",
+            "getStackAccessControlContext",
+            vec![],
+        );
+        assert!(context.unwrap().unwrap().reference().unwrap().is_null());
+        let register = call(&mut jvm, "java/lang/ClassLoader", "registerNatives", vec![]);
+        assert!(register.unwrap().is_none());
+    }
+
+    #[test]
+    fn number_natives() {
+        let mut jvm = frame_thread();
+        let mut number = |class: &str, name: &str, operand: FrameValue| {
+            call(&mut jvm, class, name, vec![operand]).unwrap().unwrap()
+        };
+
+        let bits = number(
+            "java/lang/Float",
+            "floatToRawIntBits",
+            FrameValue::Float(1.0),
+        );
+        assert_eq!(bits.int().unwrap(), 0x3f800000);
+        let bits = number("java/lang/Float", "floatToIntBits", FrameValue::Float(-2.5));
+        assert_eq!(bits.int().unwrap(), 0xc0200000u32 as i32);
+        let float = number(
+            "java/lang/Float",
+            "intBitsToFloat",
+            FrameValue::Int(0x3f800000),
+        );
+        assert_eq!(float.float().unwrap(), 1.0);
+
+        let bits = number(
+            "java/lang/Double",
+            "doubleToRawLongBits",
+            FrameValue::Double(1.0),
+        );
+        assert_eq!(bits.long().unwrap(), 0x3ff0000000000000);
+        let bits = number(
+            "java/lang/Double",
+            "doubleToLongBits",
+            FrameValue::Double(-0.0),
+        );
+        assert_eq!(bits.long().unwrap(), i64::MIN);
+        let double = number(
+            "java/lang/Double",
+            "longBitsToDouble",
+            FrameValue::Long(0x4004000000000000),
+        );
+        assert_eq!(double.double().unwrap(), 2.5);
+    }
+
+    #[test]
+    fn strict_math_natives() {
+        let mut jvm = frame_thread();
+        let mut math = |name: &str, operands: &[f64]| {
+            let operands = operands.iter().copied().map(FrameValue::Double).collect();
+            let result = call(&mut jvm, "java/lang/StrictMath", name, operands);
+            result.unwrap().unwrap().double().unwrap()
+        };
+
+        assert_eq!(math("sqrt", &[2.0]), std::f64::consts::SQRT_2);
+        assert!(math("sqrt", &[-1.0]).is_nan());
+        assert_eq!(math("cos", &[0.0]), 1.0);
+        assert_eq!(math("log10", &[1000.0]), 3.0);
+        assert_eq!(math("atan2", &[1.0, 1.0]), std::f64::consts::FRAC_PI_4);
+
+        assert_eq!(math("IEEEremainder", &[5.0, 2.0]), 1.0);
+        assert_eq!(math("IEEEremainder", &[7.0, 2.0]), -1.0);
+        assert_eq!(math("IEEEremainder", &[-7.0, 2.0]), 1.0);
+        assert_eq!(math("IEEEremainder", &[11.0, 3.0]), -1.0);
+        assert_eq!(math("IEEEremainder", &[3.0, f64::INFINITY]), 3.0);
+        assert!(math("IEEEremainder", &[-4.0, 2.0]).is_sign_negative());
+        assert!(math("IEEEremainder", &[1.0, 0.0]).is_nan());
+    }
+
+    #[test]
+    fn nan_bits() {
+        let mut jvm = frame_thread();
+        let float_nan = f32::from_bits(0x7f800001);
+        let double_nan = f64::from_bits(0x7ff0000000000001);
+        let mut number = |class: &str, name: &str, operand: FrameValue| {
+            call(&mut jvm, class, name, vec![operand]).unwrap().unwrap()
+        };
+
+        let raw = number(
+            "java/lang/Float",
+            "floatToRawIntBits",
+            FrameValue::Float(float_nan),
+        );
+        assert_eq!(raw.int().unwrap(), 0x7f800001);
+        let bits = number(
+            "java/lang/Float",
+            "floatToIntBits",
+            FrameValue::Float(float_nan),
+        );
+        assert_eq!(bits.int().unwrap(), 0x7fc00000);
+
+        let operand = FrameValue::Double(double_nan);
+        let raw = number("java/lang/Double", "doubleToRawLongBits", operand.clone());
+        assert_eq!(raw.long().unwrap(), 0x7ff0000000000001);
+        let bits = number("java/lang/Double", "doubleToLongBits", operand);
+        assert_eq!(bits.long().unwrap(), 0x7ff8000000000000);
+    }
+
+    #[test]
+    fn system_props_natives() {
+        let mut jvm = thread();
+        let mut properties = |name: &str| {
+            let array = call(&mut jvm, "jdk/internal/util/SystemProps$Raw", name, vec![]);
+            let array = array.unwrap().unwrap().reference().unwrap().clone();
+            let values = jvm.get_reference_array(array.heap_id().unwrap()).unwrap();
+            values
+                .iter()
+                .map(|value| match value {
+                    ReferenceValue::HeapItem(heap_id) => {
+                        Some(jvm.read_java_string(heap_id).unwrap())
+                    }
+                    ReferenceValue::Null => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let platform = properties("platformProperties");
+        assert_eq!(platform.len(), 39);
+        assert!(platform[21].is_some(), "os.name");
+        assert!(platform[36].is_some(), "user.dir");
+        assert_eq!(platform[10], None, "ftp.nonProxyHosts");
+
+        let vm = properties("vmProperties");
+        assert_eq!(vm.len() % 2, 0);
+        assert_eq!(vm[0].as_deref(), Some("java.home"));
+        assert!(vm.iter().all(Option::is_some));
+    }
+
+    /// A `FileOutputStream` whose `FileDescriptor` has the file descriptor `fd`
+    fn file_output_stream(jvm: &mut JvmThread, fd: i32) -> FrameValue {
+        let fields = HashMap::from([(
+            "fd".to_string(),
+            InstanceField::new(0, FieldValue::Integer(fd)),
+        )]);
+        let descriptor = ClassIdentifier::from_parts("java.io", "FileDescriptor");
+        let descriptor = jvm.allocate(descriptor, fields).unwrap();
+        let descriptor = FieldValue::Reference(ReferenceValue::HeapItem(descriptor));
+        let fields = HashMap::from([("fd".to_string(), InstanceField::new(0, descriptor))]);
+        let stream = ClassIdentifier::from_parts("java.io", "FileOutputStream");
+        let stream = jvm.allocate(stream, fields).unwrap();
+        FrameValue::Reference(ReferenceValue::HeapItem(stream))
+    }
+
+    #[test]
+    fn io_natives() {
+        let mut jvm = frame_thread();
+        let handle = call(
+            &mut jvm,
+            "java/io/FileDescriptor",
+            "getHandle",
+            vec![FrameValue::Int(1)],
+        );
+        assert_eq!(handle.unwrap().unwrap().long().unwrap(), -1);
+        let append = call(
+            &mut jvm,
+            "java/io/FileDescriptor",
+            "getAppend",
+            vec![FrameValue::Int(1)],
+        );
+        assert_eq!(append.unwrap().unwrap().int().unwrap(), 0);
+
+        let bytes = jvm
+            .allocate_primitive_array(
+                PrimitiveArrayType::Byte,
+                vec![PrimitiveArrayValue::Byte(0); 2],
+            )
+            .unwrap();
+        let bytes = FrameValue::Reference(ReferenceValue::HeapItem(bytes));
+        let write = |jvm: &mut JvmThread, fd: i32, off: i32, len: i32| {
+            let stream = file_output_stream(jvm, fd);
+            let operands = vec![
+                stream,
+                bytes.clone(),
+                FrameValue::Int(off),
+                FrameValue::Int(len),
+                FrameValue::Int(0),
+            ];
+            call(jvm, "java/io/FileOutputStream", "writeBytes", operands)
+        };
+
+        assert!(write(&mut jvm, 1, 2, 0).unwrap().is_none());
+        assert!(jvm.exception.is_none());
+        // files aren't supported yet
+        assert!(write(&mut jvm, 3, 0, 0).is_err());
+    }
+
+    #[test]
+    fn io_natives_out_of_bounds() {
+        let mut jvm = thread();
+        let stream = file_output_stream(&mut jvm, 1);
+        let bytes = jvm
+            .allocate_primitive_array(
+                PrimitiveArrayType::Byte,
+                vec![PrimitiveArrayValue::Byte(0); 2],
+            )
+            .unwrap();
+        let bytes = FrameValue::Reference(ReferenceValue::HeapItem(bytes));
+        let operands = vec![
+            stream,
+            bytes,
+            FrameValue::Int(1),
+            FrameValue::Int(2),
+            FrameValue::Int(0),
+        ];
+        let result = call(&mut jvm, "java/io/FileOutputStream", "writeBytes", operands);
+        assert!(result.unwrap().is_none());
+
+        let exception = jvm.exception.take().unwrap();
+        let identifier = jvm
+            .heap_get(&exception)
+            .unwrap()
+            .class_identifier()
+            .unwrap();
+        assert_eq!(
+            format!("{identifier:?}"),
+            "java.lang.IndexOutOfBoundsException"
+        );
+    }
+
+    #[test]
+    fn unknown_native() {
+        let mut jvm = frame_thread();
+        assert!(call(&mut jvm, "Unknown", "method", vec![]).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use std::{env, fs, path::Path};
+
+use anyhow::{Result, bail};
+use common::{ClassIdentifier, FrameValue, ReferenceValue};
+
+use crate::thread::JvmThread;
+
+/// Length of the array returned by `platformProperties`, `SystemProps$Raw.FIXED_LENGTH`
+const PLATFORM_PROPERTIES: usize = 39;
+
+// indices into the platform properties, the `_*_NDX` constants of `SystemProps$Raw`
+const DISPLAY_COUNTRY: usize = 0;
+const DISPLAY_LANGUAGE: usize = 1;
+const FILE_ENCODING: usize = 4;
+const FILE_SEPARATOR: usize = 5;
+const FORMAT_COUNTRY: usize = 6;
+const FORMAT_LANGUAGE: usize = 7;
+const JAVA_IO_TMPDIR: usize = 18;
+const LINE_SEPARATOR: usize = 19;
+const OS_ARCH: usize = 20;
+const OS_NAME: usize = 21;
+const OS_VERSION: usize = 22;
+const PATH_SEPARATOR: usize = 23;
+const SUN_ARCH_DATA_MODEL: usize = 28;
+const SUN_CPU_ENDIAN: usize = 29;
+const SUN_IO_UNICODE_ENCODING: usize = 31;
+const SUN_JNU_ENCODING: usize = 32;
+const SUN_OS_PATCH_LEVEL: usize = 33;
+const USER_DIR: usize = 36;
+const USER_HOME: usize = 37;
+const USER_NAME: usize = 38;
+
+pub fn run(jvm: &mut JvmThread, name: &str) -> Result<Option<FrameValue>> {
+    let properties = match name {
+        "platformProperties" => platform_properties(),
+        "vmProperties" => vm_properties()
+            .into_iter()
+            .flat_map(|(key, value)| [Some(key.to_string()), Some(value)])
+            .collect(),
+        _ => bail!("TODO"),
+    };
+
+    let string_class = ClassIdentifier::from_parts("java.lang", "String");
+    let array = jvm.allocate_array(string_class, properties.len())?;
+    for (i, property) in properties.into_iter().enumerate() {
+        if let Some(property) = property {
+            let string = jvm.new_string(property)?;
+            jvm.store_into_reference_array(&array, i, ReferenceValue::HeapItem(string))?;
+        }
+    }
+
+    Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(array))))
+}
+
+/// Properties of the host, unset ones stay `null` like the JDK expects
+fn platform_properties() -> Vec<Option<String>> {
+    let mut properties = vec![None; PLATFORM_PROPERTIES];
+    let mut set = |index: usize, value: &str| properties[index] = Some(value.to_string());
+
+    let (language, country) = locale();
+    set(DISPLAY_LANGUAGE, &language);
+    set(FORMAT_LANGUAGE, &language);
+    set(DISPLAY_COUNTRY, &country);
+    set(FORMAT_COUNTRY, &country);
+    set(FILE_ENCODING, "UTF-8");
+    set(SUN_JNU_ENCODING, "UTF-8");
+
+    set(FILE_SEPARATOR, std::path::MAIN_SEPARATOR_STR);
+    set(PATH_SEPARATOR, if cfg!(windows) { ";" } else { ":" });
+    set(LINE_SEPARATOR, if cfg!(windows) { "\r\n" } else { "\n" });
+    set(JAVA_IO_TMPDIR, &env::temp_dir().to_string_lossy());
+
+    set(OS_NAME, os_name());
+    set(OS_ARCH, os_arch());
+    let os_version = fs::read_to_string("/proc/sys/kernel/osrelease");
+    set(
+        OS_VERSION,
+        os_version.as_deref().unwrap_or("unknown").trim(),
+    );
+    set(SUN_OS_PATCH_LEVEL, "unknown");
+    set(SUN_ARCH_DATA_MODEL, &usize::BITS.to_string());
+    if cfg!(target_endian = "little") {
+        set(SUN_CPU_ENDIAN, "little");
+        set(SUN_IO_UNICODE_ENCODING, "UnicodeLittle");
+    } else {
+        set(SUN_CPU_ENDIAN, "big");
+        set(SUN_IO_UNICODE_ENCODING, "UnicodeBig");
+    }
+
+    if let Ok(dir) = env::current_dir() {
+        set(USER_DIR, &dir.to_string_lossy());
+    }
+    let home = env::var("HOME").or_else(|_| env::var("USERPROFILE"));
+    set(USER_HOME, home.as_deref().unwrap_or("?"));
+    let user = env::var("USER").or_else(|_| env::var("USERNAME"));
+    set(USER_NAME, user.as_deref().unwrap_or("?"));
+
+    properties
+}
+
+/// Language and country of the locale in `LC_ALL` or `LANG`, e.g. `en_US.UTF-8`
+fn locale() -> (String, String) {
+    let locale = env::var("LC_ALL")
+        .or_else(|_| env::var("LANG"))
+        .unwrap_or_default();
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    if locale.is_empty() || locale == "C" || locale == "POSIX" {
+        return ("en".to_string(), String::new());
+    }
+
+    let (language, country) = locale.split_once('_').unwrap_or((locale, ""));
+    (language.to_string(), country.to_string())
+}
+
+/// `os.name` as the JDK reports it
+fn os_name() -> &'static str {
+    match env::consts::OS {
+        "linux" => "Linux",
+        "macos" => "Mac OS X",
+        "windows" => "Windows",
+        "freebsd" => "FreeBSD",
+        os => os,
+    }
+}
+
+/// `os.arch` as the JDK reports it
+fn os_arch() -> &'static str {
+    match env::consts::ARCH {
+        "x86_64" => "amd64",
+        "x86" => "i386",
+        arch => arch,
+    }
+}
+
+/// Properties the VM defines itself, returned to the JDK as key/value pairs
+fn vm_properties() -> Vec<(&'static str, String)> {
+    let java_home = env::current_exe()
+        .ok()
+        .as_deref()
+        .and_then(Path::parent)
+        .map_or_else(|| ".".to_string(), |dir| dir.to_string_lossy().to_string());
+
+    vec![
+        ("java.home", java_home),
+        (
+            "java.vm.specification.name",
+            "Java Virtual Machine Specification".to_string(),
+        ),
+        (
+            "java.vm.specification.vendor",
+            "Oracle Corporation".to_string(),
+        ),
+        ("java.vm.specification.version", "17".to_string()),
+        ("java.vm.name", "atria".to_string()),
+        ("java.vm.vendor", "atria".to_string()),
+        ("java.vm.version", env!("CARGO_PKG_VERSION").to_string()),
+        ("java.vm.info", "interpreted mode".to_string()),
+        ("java.library.path", String::new()),
+        ("sun.boot.library.path", String::new()),
+    ]
+}
@@ -1,16 +1,26 @@
 use anyhow::{Result, bail};
 use common::{FrameValue, ReferenceValue};
 
+use super::class;
 use crate::thread::JvmThread;
 
-pub fn run(jvm: &mut JvmThread, name: &str) -> Result<Option<FrameValue>> {
+pub fn run(
+    jvm: &mut JvmThread,
+    name: &str,
+    operands: Vec<FrameValue>,
+) -> Result<Option<FrameValue>> {
     match name {
         "getCallerClass" => {
-            let caller_class = jvm.caller_class()?;
-            Ok(Some(FrameValue::Reference(ReferenceValue::Class(
-                caller_class.clone(),
+            let caller_class = jvm.caller_class()?.clone();
+            let mirror = jvm.class_mirror(&caller_class)?;
+            Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(
+                mirror,
             ))))
         }
+        "getClassAccessFlags" => {
+            let class = class::class_operand(jvm, &operands, 0)?;
+            Ok(Some(FrameValue::Int(class::access_flags(jvm, &class)?)))
+        }
         _ => bail!("TODO"),
     }
 }
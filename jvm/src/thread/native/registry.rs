@@ -0,0 +1,36 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use common::{ClassIdentifier, FrameValue};
+
+use crate::thread::JvmThread;
+
+pub type NativeMethod =
+    Arc<dyn Fn(&mut JvmThread, Vec<FrameValue>) -> Result<Option<FrameValue>> + Send + Sync>;
+
+/// Native methods registered at runtime, these take precedence over the built-in ones
+#[derive(Default)]
+pub struct NativeRegistry {
+    methods: HashMap<(ClassIdentifier, String), NativeMethod>,
+}
+
+impl NativeRegistry {
+    pub fn register(
+        &mut self,
+        class_identifier: ClassIdentifier,
+        name: &str,
+        method: impl Fn(&mut JvmThread, Vec<FrameValue>) -> Result<Option<FrameValue>>
+        + Send
+        + Sync
+        + 'static,
+    ) {
+        self.methods
+            .insert((class_identifier, name.to_string()), Arc::new(method));
+    }
+
+    pub fn get(&self, class_identifier: &ClassIdentifier, name: &str) -> Option<NativeMethod> {
+        self.methods
+            .get(&(class_identifier.clone(), name.to_string()))
+            .cloned()
+    }
+}
@@ -1,11 +1,9 @@
-use common::FrameValue;
-use std::hash::Hash;
-use std::hash::Hasher;
-use std::{hash::DefaultHasher, time::Instant};
+use common::{ClassIdentifier, FieldValue, FrameValue, ReferenceValue};
+use std::time::Instant;
 
 use anyhow::{Context, Result, bail};
 
-use crate::thread::JvmThread;
+use crate::thread::{JvmThread, heap::HeapItem};
 
 pub fn run(
     jvm: &mut JvmThread,
@@ -20,43 +18,117 @@ pub fn run(
             Ok(Some(FrameValue::Long(elapsed as i64)))
         }
         "identityHashCode" => {
-            let mut hasher = DefaultHasher::new();
-            operands
+            let reference = operands
                 .first()
                 .context("operands are empty")?
-                .reference()?
-                .hash(&mut hasher);
-            let value = hasher.finish();
-            Ok(Some(FrameValue::Int(value as i32)))
+                .reference()?;
+            Ok(Some(FrameValue::Int(jvm.identity_hash(reference)?)))
         }
-        "arraycopy" => {
-            let src = operands
+        "setIn0" | "setOut0" | "setErr0" => {
+            let stream = operands.first().context("no stream operand")?.reference()?;
+            let field = match name {
+                "setIn0" => "in",
+                "setOut0" => "out",
+                _ => "err",
+            };
+            let system = ClassIdentifier::from_parts("java.lang", "System");
+            jvm.set_static_field(&system, field, FieldValue::Reference(stream.clone()))?;
+            Ok(None)
+        }
+        "mapLibraryName" => {
+            let library = operands
                 .first()
-                .context("no src operand")?
-                .reference()?
-                .heap_id()?;
+                .context("no library name operand")?
+                .reference()?;
+            if library.is_null() {
+                jvm.throw_null_pointer("library name is null")?;
+                return Ok(None);
+            }
+
+            let library = jvm.read_java_string(library.heap_id()?)?;
+            let file_name = format!(
+                "{}{library}{}",
+                std::env::consts::DLL_PREFIX,
+                std::env::consts::DLL_SUFFIX
+            );
+            let file_name = jvm.new_string(file_name)?;
+            Ok(Some(FrameValue::Reference(ReferenceValue::HeapItem(
+                file_name,
+            ))))
+        }
+        "arraycopy" => {
+            let src = operands.first().context("no src operand")?.reference()?;
             let src_pos = operands.get(1).context("no src_pos operand")?.int()?;
-            let dest = operands
-                .get(2)
-                .context("no dest operand")?
-                .reference()?
-                .heap_id()?;
-            let mut dest_pos = operands.get(3).context("no dest_pos operand")?.int()?;
+            let dest = operands.get(2).context("no dest operand")?.reference()?;
+            let dest_pos = operands.get(3).context("no dest_pos operand")?.int()?;
             let length = operands.get(4).context("no length operand")?.int()?;
 
-            if let Ok((_, src_arr)) = jvm.get_primitive_array(src) {
-                for i in src_pos..src_pos + length {
-                    let val = src_arr
-                        .get(i as usize)
-                        .context("TODO: throw IndexOutOfBoundsException")?;
-                    jvm.store_into_primitive_array(dest, dest_pos as usize, val.clone())?;
-                    dest_pos += 1;
-                }
+            if src.is_null() || dest.is_null() {
+                jvm.throw_null_pointer("arraycopy: array is null")?;
+                return Ok(None);
+            }
 
+            let (src, dest) = (src.heap_id()?, dest.heap_id()?);
+            let (src_item, dest_item) = (jvm.heap_get(src)?, jvm.heap_get(dest)?);
+            if !src_item.is_array() || !dest_item.is_array() {
+                jvm.throw_new("ArrayStoreException", "arraycopy: not an array".to_string())?;
                 return Ok(None);
             }
 
-            bail!("arraycopy for reference array");
+            for (pos, array) in [(src_pos, src), (dest_pos, dest)] {
+                let array_length = jvm.get_array_length(array)?;
+                if pos < 0 || length < 0 || pos as usize + length as usize > array_length {
+                    jvm.throw_new(
+                        "ArrayIndexOutOfBoundsException",
+                        format!(
+                            "arraycopy: range [{pos}, {}) out of bounds for length {array_length}",
+                            pos as i64 + length as i64
+                        ),
+                    )?;
+                    return Ok(None);
+                }
+            }
+
+            let (src_pos, dest_pos, length) =
+                (src_pos as usize, dest_pos as usize, length as usize);
+
+            // the source is copied out of the heap first, so overlapping ranges behave like memmove
+            match (&src_item, &dest_item) {
+                (
+                    HeapItem::PrimitiveArray(src_type, src_arr),
+                    HeapItem::PrimitiveArray(dest_type, _),
+                ) if src_type == dest_type => {
+                    for (i, val) in src_arr[src_pos..src_pos + length].iter().enumerate() {
+                        jvm.store_into_primitive_array(dest, dest_pos + i, val.clone())?;
+                    }
+                }
+                (
+                    HeapItem::ReferenceArray { values, .. },
+                    HeapItem::ReferenceArray { class, .. },
+                ) => {
+                    for (i, value) in values[src_pos..src_pos + length].iter().enumerate() {
+                        if !value.is_null() && !jvm.is_instance_of(value, class)? {
+                            jvm.throw_new(
+                                "ArrayStoreException",
+                                format!(
+                                    "arraycopy: element type mismatch, can't store into {class:?}[]"
+                                ),
+                            )?;
+                            return Ok(None);
+                        }
+
+                        jvm.store_into_reference_array(dest, dest_pos + i, value.clone())?;
+                    }
+                }
+                _ => {
+                    jvm.throw_new(
+                        "ArrayStoreException",
+                        "arraycopy: type mismatch".to_string(),
+                    )?;
+                }
+            }
+
+            Ok(None)
         }
 
         _ => bail!("TODO"),
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use anyhow::{Context, Result, bail};
 use common::{FrameValue, ReferenceValue};
 
@@ -17,38 +19,42 @@ pub fn run(
             let objectref = operands.first().context("no first operand")?;
             let priority = operands.get(1).context("no second operand")?;
             let heap_id = objectref.reference()?.heap_id()?;
-            jvm.heap_set_field(heap_id, "priority", priority.clone().into())?;
+            jvm.heap_set_field(heap_id, "priority", priority.clone().try_into()?)?;
             Ok(None)
         }
         "start0" => {
             let objectref = operands
                 .first()
                 .context("no first operand, no thread to start")?;
-            let heap_id = objectref.reference()?.heap_id()?;
-            let heap_item = jvm.heap_get(heap_id)?;
-            let object = heap_item.object()?;
-            let class_identifier = object.class();
-
-            let name = jvm.heap_get_field(heap_id, "name")?;
-            let byte_value = jvm.heap_get_field(name.heap_id()?, "value")?;
-            let (_, primitive_array) = jvm.get_primitive_array(byte_value.heap_id()?)?;
-            let bytes: Vec<u8> = primitive_array
-                .iter()
-                .map(|p| p.byte())
-                .collect::<Result<Vec<u8>>>()?;
-            let name = String::from_utf8(bytes)?;
-
-            let new_thread = jvm.new_thread(name.to_string());
-
-            JvmThread::run_with_method(
-                new_thread,
-                class_identifier.clone(),
-                "run".to_string(),
-                "()V".to_string(),
-            );
+            jvm.start_thread(objectref.reference()?.heap_id()?)?;
+            Ok(None)
+        }
+        "isAlive" => {
+            let objectref = operands.first().context("no first operand")?;
+            let is_alive = jvm.is_alive(objectref.reference()?.heap_id()?)?;
+            Ok(Some(FrameValue::Int(is_alive.into())))
+        }
+        "sleep" => {
+            let millis = operands.first().context("no first operand")?.long()?;
+            let Ok(millis) = u64::try_from(millis) else {
+                jvm.throw_new(
+                    "IllegalArgumentException",
+                    "timeout value is negative".to_string(),
+                )?;
+                return Ok(None);
+            };
 
+            if !jvm.sleep(Duration::from_millis(millis))? {
+                jvm.throw_new("InterruptedException", "sleep interrupted".to_string())?;
+            }
+            Ok(None)
+        }
+        "interrupt0" => {
+            jvm.notify_interrupt()?;
             Ok(None)
         }
+        // only needed on Windows, where interrupts are also signalled by an event
+        "clearInterruptEvent" => Ok(None),
         _ => bail!("TODO"),
     }
 }
@@ -0,0 +1,64 @@
+use anyhow::{Context, Result, bail};
+use common::FrameValue;
+
+use crate::thread::JvmThread;
+
+pub fn run_throwable(
+    jvm: &mut JvmThread,
+    name: &str,
+    operands: Vec<FrameValue>,
+) -> Result<Option<FrameValue>> {
+    match name {
+        "fillInStackTrace" => {
+            let throwable = operands
+                .first()
+                .context("no 'this' operand found")?
+                .reference()?;
+            jvm.fill_in_stack_trace(throwable.heap_id()?)?;
+            Ok(Some(FrameValue::Reference(throwable.clone())))
+        }
+        _ => bail!("TODO"),
+    }
+}
+
+pub fn run_stack_trace_element(
+    jvm: &mut JvmThread,
+    name: &str,
+    operands: Vec<FrameValue>,
+) -> Result<Option<FrameValue>> {
+    match name {
+        "initStackTraceElements" => {
+            let elements = operands
+                .first()
+                .context("no 'elements' operand found")?
+                .reference()?;
+            let throwable = operands
+                .get(1)
+                .context("no 'x' operand found")?
+                .reference()?;
+            if elements.is_null() || throwable.is_null() {
+                jvm.throw_null_pointer("Cannot initialize stack trace elements")?;
+                return Ok(None);
+            }
+
+            let backtrace = jvm.heap_get_field(throwable.heap_id()?, "backtrace")?;
+            let backtrace = jvm.get_reference_array(backtrace.heap_id()?)?;
+            let elements = jvm.get_reference_array(elements.heap_id()?)?;
+            for (element, recorded) in elements.iter().zip(backtrace) {
+                for field in [
+                    "declaringClassObject",
+                    "declaringClass",
+                    "methodName",
+                    "fileName",
+                    "lineNumber",
+                ] {
+                    let value = jvm.heap_get_field(recorded.heap_id()?, field)?;
+                    jvm.heap_set_field(element.heap_id()?, field, value)?;
+                }
+            }
+
+            Ok(None)
+        }
+        _ => bail!("TODO"),
+    }
+}
@@ -1,9 +1,14 @@
-use crate::thread::heap::HeapItem;
+use crate::thread::heap::{HeapItem, PrimitiveArrayValue};
 use anyhow::{Context, Result, bail};
-use common::{FieldValue, FrameValue};
+use common::{FieldValue, FrameValue, HeapId};
 
 use crate::thread::JvmThread;
 
+/// Offset of the first array element, distinct from field offsets which start at 0
+const ARRAY_BASE_OFFSET: i64 = 16;
+/// Elements aren't laid out in memory, so every element type has the same scale
+const ARRAY_INDEX_SCALE: i64 = 1;
+
 pub fn run(
     jvm: &mut JvmThread,
     name: &str,
@@ -12,19 +17,21 @@ pub fn run(
     match name {
         "registerNatives" => Ok(None),
         "storeFence" => Ok(None),
-        "arrayBaseOffset0" => Ok(Some(FrameValue::Int(0))),
-        "arrayIndexScale0" => Ok(Some(FrameValue::Int(0))),
+        "arrayBaseOffset0" => Ok(Some(FrameValue::Int(ARRAY_BASE_OFFSET as i32))),
+        "arrayIndexScale0" => Ok(Some(FrameValue::Int(ARRAY_INDEX_SCALE as i32))),
+        "ensureClassInitialized0" => {
+            let class = operands.get(1).context("no class operand found")?;
+            let class = jvm.mirrored_class(class.reference()?)?;
+            if !class.is_primitive() && !class.is_array() {
+                jvm.initialize(&class)?;
+            }
+            Ok(None)
+        }
         "objectFieldOffset1" => {
             let class = operands.get(1).context("no class operand found")?;
             let name = operands.get(2).context("no String operand found")?;
-            let byte_value = jvm.heap_get_field(name.reference()?.heap_id()?, "value")?;
-            let (_, primitive_array) = jvm.get_primitive_array(byte_value.heap_id()?)?;
-            let bytes: Vec<u8> = primitive_array
-                .iter()
-                .map(|p| p.byte())
-                .collect::<Result<Vec<u8>>>()?;
-            let name = String::from_utf8(bytes)?;
-            let class = jvm.class(class.reference()?.class_identifier()?)?;
+            let name = jvm.read_java_string(name.reference()?.heap_id()?)?;
+            let class = jvm.class(&jvm.mirrored_class(class.reference()?)?)?;
             let offset = jvm
                 .default_instance_fields(&class, 0)?
                 .get(&name)
@@ -104,8 +111,16 @@ pub fn run(
 
             let object = jvm.heap_get(heap_id)?;
 
-            if object.is_array() {
-                jvm.store_into_reference_array(heap_id, offset as usize, x.clone())?;
+            if let HeapItem::ReferenceArray { values, .. } = object {
+                let index = array_index(offset)?;
+                let value = values
+                    .get(index)
+                    .context(format!("no value at offset {offset}"))?;
+                if value != expected {
+                    return Ok(Some(FrameValue::Int(0)));
+                }
+
+                jvm.store_into_reference_array(heap_id, index, x.clone())?;
                 return Ok(Some(FrameValue::Int(1)));
             }
 
@@ -121,31 +136,108 @@ pub fn run(
 
             bail!("no field with offset '{offset}' found");
         }
-        "getReferenceVolatile" => {
-            let object = operands.get(1).context("no 'object' operand found")?;
-            let offset = operands
-                .get(2)
-                .context("no 'offset' operand found")?
-                .long()?;
-            let heap_id = object.reference()?.heap_id()?;
-            let object = jvm.heap_get(heap_id)?;
+        "getInt"
+        | "getIntVolatile"
+        | "getIntAcquire"
+        | "getLong"
+        | "getLongVolatile"
+        | "getLongAcquire"
+        | "getReference"
+        | "getReferenceVolatile"
+        | "getReferenceAcquire" => Ok(Some(get(jvm, &operands)?)),
+        "putInt"
+        | "putIntVolatile"
+        | "putIntRelease"
+        | "putLong"
+        | "putLongVolatile"
+        | "putLongRelease"
+        | "putReference"
+        | "putReferenceVolatile"
+        | "putReferenceRelease" => {
+            let x = operands.get(3).context("no 'x' operand found")?;
+            put(jvm, &operands, x.clone())?;
+            Ok(None)
+        }
+        _ => bail!("TODO"),
+    }
+}
 
-            if let HeapItem::ReferenceArray { values, .. } = object {
-                let value = values.get(offset as usize).context("no value at offset")?;
-                return Ok(Some(FrameValue::Reference(value.clone())));
+/// Reads the field or array element at the offset of the object in `operands`
+fn get(jvm: &mut JvmThread, operands: &[FrameValue]) -> Result<FrameValue> {
+    let (heap_id, offset) = location(operands)?;
+
+    match jvm.heap_get(heap_id)? {
+        HeapItem::ReferenceArray { values, .. } => values
+            .get(array_index(offset)?)
+            .context(format!("no value at offset {offset}"))
+            .map(|value| FrameValue::Reference(value.clone())),
+        HeapItem::PrimitiveArray(_, values) => {
+            match values
+                .get(array_index(offset)?)
+                .context(format!("no value at offset {offset}"))?
+            {
+                PrimitiveArrayValue::Int(value) => Ok(FrameValue::Int(*value)),
+                PrimitiveArrayValue::Long(value) => Ok(FrameValue::Long(*value)),
+                value => bail!("TODO: unsafe access of {value:?}"),
             }
+        }
+        HeapItem::Object(_) | HeapItem::ClassMirror { .. } => {
+            let name = field_name(jvm, heap_id, offset)?;
+            Ok(jvm.heap_get_field(heap_id, &name)?.into())
+        }
+    }
+}
 
-            let class = jvm.class(&object.class_identifier()?)?;
+/// Writes `x` into the field or array element at the offset of the object in `operands`
+fn put(jvm: &mut JvmThread, operands: &[FrameValue], x: FrameValue) -> Result<()> {
+    let (heap_id, offset) = location(operands)?;
 
-            for (name, field) in jvm.default_instance_fields(&class, 0)? {
-                if field.offset() == offset {
-                    let field_value = jvm.heap_get_field(heap_id, &name)?;
-                    return Ok(Some(field_value.into()));
-                }
-            }
+    match (jvm.heap_get(heap_id)?, x) {
+        (HeapItem::ReferenceArray { .. }, FrameValue::Reference(value)) => {
+            jvm.store_into_reference_array(heap_id, array_index(offset)?, value)
+        }
+        (HeapItem::PrimitiveArray(_, _), FrameValue::Int(value)) => jvm.store_into_primitive_array(
+            heap_id,
+            array_index(offset)?,
+            PrimitiveArrayValue::Int(value),
+        ),
+        (HeapItem::PrimitiveArray(_, _), FrameValue::Long(value)) => jvm
+            .store_into_primitive_array(
+                heap_id,
+                array_index(offset)?,
+                PrimitiveArrayValue::Long(value),
+            ),
+        (HeapItem::Object(_) | HeapItem::ClassMirror { .. }, x) => {
+            let name = field_name(jvm, heap_id, offset)?;
+            jvm.heap_set_field(heap_id, &name, x.try_into()?)
+        }
+        (item, x) => bail!("can't put {x:?} into {item:?}"),
+    }
+}
 
-            bail!("no field with offset '{offset}' found");
+/// Object and offset operands, the first operand is the `Unsafe` instance
+fn location(operands: &[FrameValue]) -> Result<(&HeapId, i64)> {
+    let object = operands.get(1).context("no 'object' operand found")?;
+    let offset = operands
+        .get(2)
+        .context("no 'offset' operand found")?
+        .long()?;
+    Ok((object.reference()?.heap_id()?, offset))
+}
+
+fn field_name(jvm: &mut JvmThread, heap_id: &HeapId, offset: i64) -> Result<String> {
+    let object = jvm.heap_get(heap_id)?;
+    let class = jvm.class(&object.class_identifier()?)?;
+    for (name, field) in jvm.default_instance_fields(&class, 0)? {
+        if field.offset() == offset {
+            return Ok(name);
         }
-        _ => bail!("TODO"),
     }
+
+    bail!("no field with offset '{offset}' found");
+}
+
+fn array_index(offset: i64) -> Result<usize> {
+    let index = (offset - ARRAY_BASE_OFFSET) / ARRAY_INDEX_SCALE;
+    usize::try_from(index).context(format!("invalid array offset {offset}"))
 }
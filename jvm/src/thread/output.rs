@@ -0,0 +1,61 @@
+use std::{
+    collections::HashMap,
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+/// Receives the bytes written to the standard streams by `FileOutputStream`
+pub trait OutputSink: Send {
+    /// Called with the `bytes` written to the file descriptor `fd`, 1 for stdout and 2 for stderr
+    fn write(&mut self, fd: i32, bytes: &[u8]);
+}
+
+impl<T: OutputSink + ?Sized> OutputSink for Box<T> {
+    fn write(&mut self, fd: i32, bytes: &[u8]) {
+        (**self).write(fd, bytes);
+    }
+}
+
+/// Writes to the stdout and stderr of the host process
+#[derive(Debug, Default)]
+pub struct StdOutputSink;
+
+impl OutputSink for StdOutputSink {
+    fn write(&mut self, fd: i32, bytes: &[u8]) {
+        // like PrintStream, write errors are swallowed
+        let _ = match fd {
+            2 => {
+                let mut stderr = std::io::stderr().lock();
+                stderr.write_all(bytes).and_then(|_| stderr.flush())
+            }
+            _ => {
+                let mut stdout = std::io::stdout().lock();
+                stdout.write_all(bytes).and_then(|_| stdout.flush())
+            }
+        };
+    }
+}
+
+/// Keeps the written bytes per file descriptor, clones share them
+#[derive(Debug, Default, Clone)]
+pub struct VecOutputSink {
+    written: Arc<Mutex<HashMap<i32, Vec<u8>>>>,
+}
+
+impl VecOutputSink {
+    /// All bytes written to `fd` so far
+    pub fn bytes(&self, fd: i32) -> Vec<u8> {
+        self.written
+            .lock()
+            .map(|written| written.get(&fd).cloned().unwrap_or_default())
+            .unwrap_or_default()
+    }
+}
+
+impl OutputSink for VecOutputSink {
+    fn write(&mut self, fd: i32, bytes: &[u8]) {
+        if let Ok(mut written) = self.written.lock() {
+            written.entry(fd).or_default().extend_from_slice(bytes);
+        }
+    }
+}
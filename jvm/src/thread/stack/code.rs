@@ -1,10 +1,18 @@
-use anyhow::{Result, bail};
-use parser::class::attribute::Attribute;
+use anyhow::{Context, Result, bail};
+use parser::class::attribute::{Attribute, ExceptionHandler, StackMapTableEntry};
+
+use crate::thread::stack::instruction::Instruction;
 
 #[derive(Debug)]
 pub struct Code {
     instructions: Vec<u8>,
+    /// Instructions decoded up front by their pc, `None` for pcs inside of an instruction
+    decoded: Vec<Option<Instruction>>,
+    /// Pc of the first instruction that couldn't be decoded, from there on instructions are
+    /// decoded when they are executed
+    decoded_until: usize,
     max_locals: u16,
+    exception_table: Vec<ExceptionHandler>,
     attributes: Vec<Attribute>,
 }
 
@@ -13,13 +21,18 @@ impl Code {
         if let Attribute::Code {
             max_locals,
             code,
+            exception_table,
             attributes,
             ..
         } = attribute
         {
+            let (decoded, decoded_until) = decode(&code);
             Ok(Self {
                 instructions: code,
+                decoded,
+                decoded_until,
                 max_locals,
+                exception_table,
                 attributes,
             })
         } else {
@@ -31,10 +44,45 @@ impl Code {
         &self.instructions
     }
 
+    /// The instruction starting at `pc`
+    pub fn instruction(&self, pc: usize) -> Result<Instruction> {
+        if pc < self.decoded_until {
+            return self.decoded[pc]
+                .clone()
+                .context(format!("pc {pc} is inside of an instruction"));
+        }
+
+        let bytes = self
+            .instructions
+            .get(pc..)
+            .context(format!("pc {pc} is out of bounds"))?;
+        Instruction::new(bytes, pc)
+    }
+
     pub fn max_locals(&self) -> u16 {
         self.max_locals
     }
 
+    /// Entries of the `StackMapTable` attribute, empty if the code has none
+    pub fn stack_map_table(&self) -> &[StackMapTableEntry] {
+        self.attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::StackMapTable { entries, .. } => Some(entries.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Exception handlers whose range covers `pc`, in the order they have to be searched
+    pub fn exception_handlers(&self, pc: u16) -> Vec<ExceptionHandler> {
+        self.exception_table
+            .iter()
+            .filter(|handler| handler.start_pc <= pc && pc < handler.end_pc)
+            .cloned()
+            .collect()
+    }
+
     pub fn line_number(&self, pc: u16) -> Option<u16> {
         let mut res = None;
         for attribute in &self.attributes {
@@ -53,3 +101,71 @@ impl Code {
         res
     }
 }
+
+/// Decodes the instructions of `code` up to the first one that can't be decoded, whose pc is
+/// returned alongside
+fn decode(code: &[u8]) -> (Vec<Option<Instruction>>, usize) {
+    let mut decoded = vec![None; code.len()];
+    let mut pc = 0;
+    while pc < code.len() {
+        // an unsupported instruction must only fail once it's executed
+        let Ok(instruction) = Instruction::new(&code[pc..], pc) else {
+            break;
+        };
+        let length = instruction.length();
+        decoded[pc] = Some(instruction);
+        pc += length;
+    }
+
+    (decoded, pc.min(code.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(bytes: Vec<u8>) -> Code {
+        Code::new(Attribute::Code {
+            attribute_name_index: 0u16.into(),
+            attribute_length: 0,
+            max_stack: 0,
+            max_locals: 0,
+            code: bytes,
+            exception_table: vec![],
+            attributes: vec![],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn decodes_up_front() {
+        // bipush 5, goto -2, return
+        let code = code(vec![0x10, 0x05, 0xa7, 0xff, 0xfe, 0xb1]);
+        assert_eq!(code.decoded_until, 6);
+        assert!(matches!(
+            code.instruction(0).unwrap(),
+            Instruction::Bipush(5)
+        ));
+        assert!(matches!(
+            code.instruction(2).unwrap(),
+            Instruction::Goto(-2)
+        ));
+        assert!(matches!(code.instruction(5).unwrap(), Instruction::Return));
+    }
+
+    #[test]
+    fn rejects_pc_inside_of_instruction() {
+        let code = code(vec![0x10, 0x05, 0xb1]);
+        let err = code.instruction(1).unwrap_err();
+        assert!(err.to_string().contains("inside of an instruction"));
+    }
+
+    #[test]
+    fn decodes_unsupported_instructions_when_executed() {
+        // return, jsr
+        let code = code(vec![0xb1, 0xa8, 0x00, 0x00]);
+        assert_eq!(code.decoded_until, 1);
+        assert!(matches!(code.instruction(0).unwrap(), Instruction::Return));
+        assert!(code.instruction(1).is_err());
+    }
+}
@@ -13,12 +13,223 @@ fn offset(bytes: &[u8]) -> Result<i16> {
     Ok((byte1 as i16) << 8 | byte2 as i16)
 }
 
+fn wide_offset(bytes: &[u8]) -> Result<i32> {
+    let bytes = bytes.get(1..5).context("premature end of code")?;
+    Ok(i32::from_be_bytes(bytes.try_into()?))
+}
+
 fn short(bytes: &[u8]) -> Result<u16> {
     let byte1 = *bytes.get(1).context("premature end of code")?;
     let byte2 = *bytes.get(2).context("premature end of code")?;
     Ok((byte1 as u16) << 8 | byte2 as u16)
 }
 
+/// Mnemonics of the instructions, indexed by their op code
+const MNEMONICS: [&str; 202] = [
+    "nop",
+    "aconst_null",
+    "iconst_m1",
+    "iconst_0",
+    "iconst_1",
+    "iconst_2",
+    "iconst_3",
+    "iconst_4",
+    "iconst_5",
+    "lconst_0",
+    "lconst_1",
+    "fconst_0",
+    "fconst_1",
+    "fconst_2",
+    "dconst_0",
+    "dconst_1",
+    "bipush",
+    "sipush",
+    "ldc",
+    "ldc_w",
+    "ldc2_w",
+    "iload",
+    "lload",
+    "fload",
+    "dload",
+    "aload",
+    "iload_0",
+    "iload_1",
+    "iload_2",
+    "iload_3",
+    "lload_0",
+    "lload_1",
+    "lload_2",
+    "lload_3",
+    "fload_0",
+    "fload_1",
+    "fload_2",
+    "fload_3",
+    "dload_0",
+    "dload_1",
+    "dload_2",
+    "dload_3",
+    "aload_0",
+    "aload_1",
+    "aload_2",
+    "aload_3",
+    "iaload",
+    "laload",
+    "faload",
+    "daload",
+    "aaload",
+    "baload",
+    "caload",
+    "saload",
+    "istore",
+    "lstore",
+    "fstore",
+    "dstore",
+    "astore",
+    "istore_0",
+    "istore_1",
+    "istore_2",
+    "istore_3",
+    "lstore_0",
+    "lstore_1",
+    "lstore_2",
+    "lstore_3",
+    "fstore_0",
+    "fstore_1",
+    "fstore_2",
+    "fstore_3",
+    "dstore_0",
+    "dstore_1",
+    "dstore_2",
+    "dstore_3",
+    "astore_0",
+    "astore_1",
+    "astore_2",
+    "astore_3",
+    "iastore",
+    "lastore",
+    "fastore",
+    "dastore",
+    "aastore",
+    "bastore",
+    "castore",
+    "sastore",
+    "pop",
+    "pop2",
+    "dup",
+    "dup_x1",
+    "dup_x2",
+    "dup2",
+    "dup2_x1",
+    "dup2_x2",
+    "swap",
+    "iadd",
+    "ladd",
+    "fadd",
+    "dadd",
+    "isub",
+    "lsub",
+    "fsub",
+    "dsub",
+    "imul",
+    "lmul",
+    "fmul",
+    "dmul",
+    "idiv",
+    "ldiv",
+    "fdiv",
+    "ddiv",
+    "irem",
+    "lrem",
+    "frem",
+    "drem",
+    "ineg",
+    "lneg",
+    "fneg",
+    "dneg",
+    "ishl",
+    "lshl",
+    "ishr",
+    "lshr",
+    "iushr",
+    "lushr",
+    "iand",
+    "land",
+    "ior",
+    "lor",
+    "ixor",
+    "lxor",
+    "iinc",
+    "i2l",
+    "i2f",
+    "i2d",
+    "l2i",
+    "l2f",
+    "l2d",
+    "f2i",
+    "f2l",
+    "f2d",
+    "d2i",
+    "d2l",
+    "d2f",
+    "i2b",
+    "i2c",
+    "i2s",
+    "lcmp",
+    "fcmpl",
+    "fcmpg",
+    "dcmpl",
+    "dcmpg",
+    "ifeq",
+    "ifne",
+    "iflt",
+    "ifge",
+    "ifgt",
+    "ifle",
+    "if_icmpeq",
+    "if_icmpne",
+    "if_icmplt",
+    "if_icmpge",
+    "if_icmpgt",
+    "if_icmple",
+    "if_acmpeq",
+    "if_acmpne",
+    "goto",
+    "jsr",
+    "ret",
+    "tableswitch",
+    "lookupswitch",
+    "ireturn",
+    "lreturn",
+    "freturn",
+    "dreturn",
+    "areturn",
+    "return",
+    "getstatic",
+    "putstatic",
+    "getfield",
+    "putfield",
+    "invokevirtual",
+    "invokespecial",
+    "invokestatic",
+    "invokeinterface",
+    "invokedynamic",
+    "new",
+    "newarray",
+    "anewarray",
+    "arraylength",
+    "athrow",
+    "checkcast",
+    "instanceof",
+    "monitorenter",
+    "monitorexit",
+    "wide",
+    "multianewarray",
+    "ifnull",
+    "ifnonnull",
+    "goto_w",
+    "jsr_w",
+];
+
 #[derive(Debug, Clone)]
 pub enum Instruction {
     Iconst(i8),
@@ -28,14 +239,16 @@ pub enum Instruction {
     InvokeVirtual(CpIndex),
     InvokeStatic(CpIndex),
     Anewarray(CpIndex),
-    Aload(u8),
+    Aload(u16),
     Pop,
+    Pop2,
+    Swap,
     Aload0,
     Aload1,
     Aload2,
     Aload3,
     GetField(CpIndex),
-    Astore(u8),
+    Astore(u16),
     IfNull(i16),
     Areturn,
     Dreturn,
@@ -50,26 +263,31 @@ pub enum Instruction {
     GetStatic(CpIndex),
     LdcW(CpIndex),
     PutField(CpIndex),
-    Iload(u8),
+    Iload(u16),
     Iload0,
     Iload1,
     Iload2,
     Iload3,
     AconstNull,
     Aastore,
-    Bipush(u8),
+    Bipush(i8),
     Newarray(u8),
     Castore,
     Bastore,
     Iastore,
-    Sipush(u16),
+    Lastore,
+    Fastore,
+    Dastore,
+    Sastore,
+    Sipush(i16),
     Lreturn,
-    Istore(u8),
+    Istore(u16),
     Isub,
     Lsub,
     Iand,
     Ifeq(i16),
     Goto(i16),
+    GotoW(i32),
     Ifgt(i16),
     Fload0,
     Fload1,
@@ -78,28 +296,42 @@ pub enum Instruction {
     Fconst(f32),
     Fcmpg,
     Fcmpl,
+    Dcmpl,
+    Dcmpg,
     Ifle(i16),
     Iflt(i16),
     IfIcmpge(i16),
     Dconst(f64),
     I2l,
     I2f,
+    I2d,
     L2f,
+    L2d,
+    F2l,
+    D2i,
+    D2f,
     Fdiv,
+    Frem,
+    Fneg,
     F2d,
     F2i,
     Dadd,
+    Dsub,
+    Dmul,
+    Ddiv,
+    Drem,
+    Dneg,
     Fadd,
     D2l,
-    Lstore(u8),
-    Fstore(u8),
-    Lload(u8),
-    Fload(u8),
+    Lstore(u16),
+    Fstore(u16),
+    Lload(u16),
+    Fload(u16),
     Ldc2W(CpIndex),
     Lcmp,
     L2i,
     IfIcmplt(i16),
-    Iinc(u8, i8),
+    Iinc(u16, i16),
     Iushr,
     Lushr,
     Ifge(i16),
@@ -112,6 +344,12 @@ pub enum Instruction {
     Land,
     Ixor,
     Baload,
+    Iaload,
+    Laload,
+    Faload,
+    Daload,
+    Caload,
+    Saload,
     I2c,
     I2b,
     IfIcmpne(i16),
@@ -138,20 +376,54 @@ pub enum Instruction {
     Lload2,
     Lload3,
     Lmul,
+    Ldiv,
+    Lrem,
+    Lneg,
+    Ior,
+    Lor,
+    Lxor,
     Fmul,
     Idiv,
     Ladd,
     Imul,
     InvokeInterface(CpIndex, u8),
     DupX1,
+    DupX2,
+    Dup2X1,
+    Dup2X2,
     Aaload,
     Lshl,
     Ishl,
     MonitorEnter,
     MonitorExit,
+    Athrow,
     Irem,
     Ineg,
+    Nop,
+    Dload(u16),
+    Dload0,
+    Dload1,
+    Dload2,
+    Dload3,
+    Dstore(u16),
+    Dstore0,
+    Dstore1,
+    Dstore2,
+    Dstore3,
+    Fstore0,
+    Fstore1,
+    Fstore2,
+    Fstore3,
+    Fsub,
+    I2s,
+    Freturn,
+    /// Creates an array with the dimensions popped from the stack, which can be less than the
+    /// dimensions of the array class
+    Multianewarray(CpIndex, u8),
+    /// An instruction with 16 bit operands, introduced by the `wide` prefix
+    Wide(Box<Instruction>),
     TableSwitch {
+        /// Padding before the operands
         skip: usize,
         default: i32,
         low: i32,
@@ -159,6 +431,7 @@ pub enum Instruction {
         jump_offsets: Vec<i32>,
     },
     LookupSwitch {
+        /// Padding before the operands
         skip: usize,
         default: i32,
         offset_pairs: Vec<(i32, i32)>,
@@ -168,6 +441,7 @@ pub enum Instruction {
 impl Instruction {
     pub fn new(bytes: &[u8], pc: usize) -> Result<Self> {
         Ok(match bytes.first().context("premature end of code")? {
+            0x0 => Instruction::Nop,
             0x1 => Instruction::AconstNull,
             0x2 => Instruction::Iconst(-1),
             0x3 => Instruction::Iconst(0),
@@ -178,12 +452,13 @@ impl Instruction {
             0x8 => Instruction::Iconst(5),
             0x9 => Instruction::Lconst(0),
             0xa => Instruction::Lconst(1),
-            0x10 => Instruction::Bipush(*bytes.get(1).context("premature end of code")?),
-            0x11 => Instruction::Sipush(short(bytes)?),
+            0x10 => Instruction::Bipush(*bytes.get(1).context("premature end of code")? as i8),
+            0x11 => Instruction::Sipush(short(bytes)? as i16),
             0x12 => Instruction::Ldc((*bytes.get(1).context("premature end of code")?).into()),
             0x13 => Instruction::LdcW(cp_index(bytes)?),
-            0x17 => Instruction::Fload(*bytes.get(1).context("premature end of code")?),
-            0x19 => Instruction::Aload(*bytes.get(1).context("premature end of code")?),
+            0x17 => Instruction::Fload((*bytes.get(1).context("premature end of code")?).into()),
+            0x18 => Instruction::Dload((*bytes.get(1).context("premature end of code")?).into()),
+            0x19 => Instruction::Aload((*bytes.get(1).context("premature end of code")?).into()),
             0x1e => Instruction::Lload0,
             0x1f => Instruction::Lload1,
             0x20 => Instruction::Lload2,
@@ -194,8 +469,8 @@ impl Instruction {
             0xe => Instruction::Dconst(0.0),
             0xf => Instruction::Dconst(1.0),
             0x14 => Instruction::Ldc2W(cp_index(bytes)?),
-            0x15 => Instruction::Iload(*bytes.get(1).context("premature end of code")?),
-            0x16 => Instruction::Lload(*bytes.get(1).context("premature end of code")?),
+            0x15 => Instruction::Iload((*bytes.get(1).context("premature end of code")?).into()),
+            0x16 => Instruction::Lload((*bytes.get(1).context("premature end of code")?).into()),
             0x1a => Instruction::Iload0,
             0x1b => Instruction::Iload1,
             0x1c => Instruction::Iload2,
@@ -204,16 +479,27 @@ impl Instruction {
             0x23 => Instruction::Fload1,
             0x24 => Instruction::Fload2,
             0x25 => Instruction::Fload3,
+            0x26 => Instruction::Dload0,
+            0x27 => Instruction::Dload1,
+            0x28 => Instruction::Dload2,
+            0x29 => Instruction::Dload3,
             0x2a => Instruction::Aload0,
             0x2b => Instruction::Aload1,
             0x2c => Instruction::Aload2,
             0x2d => Instruction::Aload3,
             0x32 => Instruction::Aaload,
+            0x2e => Instruction::Iaload,
+            0x2f => Instruction::Laload,
+            0x30 => Instruction::Faload,
+            0x31 => Instruction::Daload,
             0x33 => Instruction::Baload,
-            0x36 => Instruction::Istore(*bytes.get(1).context("premature end of code")?),
-            0x37 => Instruction::Lstore(*bytes.get(1).context("premature end of code")?),
-            0x38 => Instruction::Fstore(*bytes.get(1).context("premature end of code")?),
-            0x3a => Instruction::Astore(*bytes.get(1).context("premature end of code")?),
+            0x34 => Instruction::Caload,
+            0x35 => Instruction::Saload,
+            0x36 => Instruction::Istore((*bytes.get(1).context("premature end of code")?).into()),
+            0x37 => Instruction::Lstore((*bytes.get(1).context("premature end of code")?).into()),
+            0x38 => Instruction::Fstore((*bytes.get(1).context("premature end of code")?).into()),
+            0x39 => Instruction::Dstore((*bytes.get(1).context("premature end of code")?).into()),
+            0x3a => Instruction::Astore((*bytes.get(1).context("premature end of code")?).into()),
             0x3b => Instruction::Istore0,
             0x3c => Instruction::Istore1,
             0x3d => Instruction::Istore2,
@@ -222,31 +508,59 @@ impl Instruction {
             0x40 => Instruction::Lstore1,
             0x41 => Instruction::Lstore2,
             0x42 => Instruction::Lstore3,
+            0x43 => Instruction::Fstore0,
+            0x44 => Instruction::Fstore1,
+            0x45 => Instruction::Fstore2,
+            0x46 => Instruction::Fstore3,
+            0x47 => Instruction::Dstore0,
+            0x48 => Instruction::Dstore1,
+            0x49 => Instruction::Dstore2,
+            0x4a => Instruction::Dstore3,
             0x4b => Instruction::Astore0,
             0x4c => Instruction::Astore1,
             0x4d => Instruction::Astore2,
             0x4e => Instruction::Astore3,
             0x4f => Instruction::Iastore,
+            0x50 => Instruction::Lastore,
+            0x51 => Instruction::Fastore,
+            0x52 => Instruction::Dastore,
             0x53 => Instruction::Aastore,
             0x54 => Instruction::Bastore,
             0x55 => Instruction::Castore,
+            0x56 => Instruction::Sastore,
             0x57 => Instruction::Pop,
+            0x58 => Instruction::Pop2,
             0x59 => Instruction::Dup,
             0x5a => Instruction::DupX1,
+            0x5b => Instruction::DupX2,
             0x5c => Instruction::Dup2,
+            0x5d => Instruction::Dup2X1,
+            0x5e => Instruction::Dup2X2,
+            0x5f => Instruction::Swap,
             0x60 => Instruction::Iadd,
             0x61 => Instruction::Ladd,
             0x62 => Instruction::Fadd,
             0x63 => Instruction::Dadd,
             0x64 => Instruction::Isub,
             0x65 => Instruction::Lsub,
+            0x66 => Instruction::Fsub,
+            0x67 => Instruction::Dsub,
             0x68 => Instruction::Imul,
             0x69 => Instruction::Lmul,
             0x6a => Instruction::Fmul,
+            0x6b => Instruction::Dmul,
             0x6c => Instruction::Idiv,
+            0x6d => Instruction::Ldiv,
             0x6e => Instruction::Fdiv,
+            0x6f => Instruction::Ddiv,
             0x70 => Instruction::Irem,
+            0x71 => Instruction::Lrem,
+            0x72 => Instruction::Frem,
+            0x73 => Instruction::Drem,
             0x74 => Instruction::Ineg,
+            0x75 => Instruction::Lneg,
+            0x76 => Instruction::Fneg,
+            0x77 => Instruction::Dneg,
             0x78 => Instruction::Ishl,
             0x79 => Instruction::Lshl,
             0x7a => Instruction::Ishr,
@@ -255,23 +569,34 @@ impl Instruction {
             0x7d => Instruction::Lushr,
             0x7e => Instruction::Iand,
             0x7f => Instruction::Land,
+            0x80 => Instruction::Ior,
+            0x81 => Instruction::Lor,
             0x82 => Instruction::Ixor,
+            0x83 => Instruction::Lxor,
             0x84 => Instruction::Iinc(
-                *bytes.get(1).context("premature end of code")?,
-                *bytes.get(2).context("premature end of code")? as i8,
+                (*bytes.get(1).context("premature end of code")?).into(),
+                (*bytes.get(2).context("premature end of code")? as i8).into(),
             ),
             0x85 => Instruction::I2l,
             0x86 => Instruction::I2f,
+            0x87 => Instruction::I2d,
             0x88 => Instruction::L2i,
             0x89 => Instruction::L2f,
+            0x8a => Instruction::L2d,
             0x8b => Instruction::F2i,
+            0x8c => Instruction::F2l,
             0x8d => Instruction::F2d,
+            0x8e => Instruction::D2i,
             0x8f => Instruction::D2l,
+            0x90 => Instruction::D2f,
             0x91 => Instruction::I2b,
             0x92 => Instruction::I2c,
+            0x93 => Instruction::I2s,
             0x94 => Instruction::Lcmp,
             0x95 => Instruction::Fcmpl,
             0x96 => Instruction::Fcmpg,
+            0x97 => Instruction::Dcmpl,
+            0x98 => Instruction::Dcmpg,
             0x99 => Instruction::Ifeq(offset(bytes)?),
             0x9a => Instruction::IfNe(offset(bytes)?),
             0x9b => Instruction::Iflt(offset(bytes)?),
@@ -287,10 +612,12 @@ impl Instruction {
             0xa5 => Instruction::IfAcmpeq(offset(bytes)?),
             0xa6 => Instruction::IfAcmpne(offset(bytes)?),
             0xa7 => Instruction::Goto(offset(bytes)?),
+            0xa8 | 0xa9 | 0xc9 => bail!("jsr/ret not supported (pre-1.6 class file)"),
             0xaa => table_switch(&bytes[1..], pc + 1)?,
             0xab => lookup_switch(&bytes[1..], pc + 1)?,
             0xac => Instruction::Ireturn,
             0xad => Instruction::Lreturn,
+            0xae => Instruction::Freturn,
             0xaf => Instruction::Dreturn,
             0xb0 => Instruction::Areturn,
             0xb1 => Instruction::Return,
@@ -310,12 +637,19 @@ impl Instruction {
             0xbc => Instruction::Newarray(*bytes.get(1).context("premature end of code")?),
             0xbd => Instruction::Anewarray(cp_index(bytes)?),
             0xbe => Instruction::ArrayLength,
+            0xbf => Instruction::Athrow,
             0xc0 => Instruction::Checkcast(cp_index(bytes)?),
             0xc2 => Instruction::MonitorEnter,
             0xc3 => Instruction::MonitorExit,
             0xc1 => Instruction::Instanceof(cp_index(bytes)?),
+            0xc4 => wide(bytes)?,
+            0xc5 => Instruction::Multianewarray(
+                cp_index(bytes)?,
+                *bytes.get(3).context("premature end of code")?,
+            ),
             0xc6 => Instruction::IfNull(offset(bytes)?),
             0xc7 => Instruction::IfNonNull(offset(bytes)?),
+            0xc8 => Instruction::GotoW(wide_offset(bytes)?),
             op_code => bail!("unknown instruction: 0x{op_code:x}"),
         })
     }
@@ -352,6 +686,10 @@ impl Instruction {
             Self::Castore => 1,
             Self::Bastore => 1,
             Self::Iastore => 1,
+            Self::Lastore => 1,
+            Self::Fastore => 1,
+            Self::Dastore => 1,
+            Self::Sastore => 1,
             Self::Sipush(_) => 3,
             Self::Lreturn => 1,
             Self::Istore(_) => 2,
@@ -360,9 +698,12 @@ impl Instruction {
             Self::Iand => 1,
             Self::Ifeq(_) => 3,
             Self::Goto(_) => 3,
+            Self::GotoW(_) => 5,
             Self::Ifgt(_) => 3,
             Self::Fconst(_) => 1,
             Self::Fcmpl => 1,
+            Self::Dcmpl => 1,
+            Self::Dcmpg => 1,
             Self::Ifle(_) => 3,
             Self::Iflt(_) => 3,
             Self::IfIcmpge(_) => 3,
@@ -370,9 +711,21 @@ impl Instruction {
             Self::I2l => 1,
             Self::I2f => 1,
             Self::L2f => 1,
+            Self::I2d => 1,
+            Self::L2d => 1,
+            Self::F2l => 1,
+            Self::D2i => 1,
+            Self::D2f => 1,
             Self::Fdiv => 1,
+            Self::Frem => 1,
+            Self::Fneg => 1,
             Self::F2d => 1,
             Self::Dadd => 1,
+            Self::Dsub => 1,
+            Self::Dmul => 1,
+            Self::Ddiv => 1,
+            Self::Drem => 1,
+            Self::Dneg => 1,
             Self::Fadd => 1,
             Self::D2l => 1,
             Self::Lstore(_) => 2,
@@ -404,6 +757,12 @@ impl Instruction {
             Self::ArrayLength => 1,
             Self::Ishr => 1,
             Self::Baload => 1,
+            Self::Iaload => 1,
+            Self::Laload => 1,
+            Self::Faload => 1,
+            Self::Daload => 1,
+            Self::Caload => 1,
+            Self::Saload => 1,
             Self::I2c => 1,
             Self::I2b => 1,
             Self::IfIcmpne(_) => 3,
@@ -429,6 +788,12 @@ impl Instruction {
             Self::Lload2 => 1,
             Self::Lload3 => 1,
             Self::Lmul => 1,
+            Self::Ldiv => 1,
+            Self::Lrem => 1,
+            Self::Lneg => 1,
+            Self::Ior => 1,
+            Self::Lor => 1,
+            Self::Lxor => 1,
             Self::Imul => 1,
             Self::Lshr => 1,
             Self::Land => 1,
@@ -438,26 +803,278 @@ impl Instruction {
             Self::Pop => 1,
             Self::Ixor => 1,
             Self::DupX1 => 1,
+            Self::DupX2 => 1,
+            Self::Dup2X1 => 1,
+            Self::Dup2X2 => 1,
+            Self::Pop2 => 1,
+            Self::Swap => 1,
             Self::Aaload => 1,
             Self::Dreturn => 1,
             Self::Lshl => 1,
             Self::Ishl => 1,
             Self::MonitorEnter => 1,
             Self::MonitorExit => 1,
+            Self::Athrow => 1,
             Self::Irem => 1,
             Self::Ineg => 1,
             Self::Fload(_) => 2,
             Self::Fcmpg => 1,
             Self::F2i => 1,
             Self::Fmul => 1,
+            Self::Nop => 1,
+            Self::Dload(_) => 2,
+            Self::Dload0 => 1,
+            Self::Dload1 => 1,
+            Self::Dload2 => 1,
+            Self::Dload3 => 1,
+            Self::Dstore(_) => 2,
+            Self::Dstore0 => 1,
+            Self::Dstore1 => 1,
+            Self::Dstore2 => 1,
+            Self::Dstore3 => 1,
+            Self::Fstore0 => 1,
+            Self::Fstore1 => 1,
+            Self::Fstore2 => 1,
+            Self::Fstore3 => 1,
+            Self::Fsub => 1,
+            Self::I2s => 1,
+            Self::Freturn => 1,
+            Self::Multianewarray(_, _) => 4,
+            Self::Wide(instruction) => match **instruction {
+                Self::Iinc(_, _) => 6,
+                _ => 4,
+            },
             Self::TableSwitch {
                 skip, jump_offsets, ..
-            } => skip + 12 + jump_offsets.len() * 4,
+            } => 1 + skip + 12 + jump_offsets.len() * 4,
             Self::Idiv => 1,
             Self::IfAcmpeq(_) => 3,
             Self::LookupSwitch {
                 skip, offset_pairs, ..
-            } => skip + 4 + offset_pairs.len() * 8,
+            } => 1 + skip + 8 + offset_pairs.len() * 8,
+        }
+    }
+
+    /// The op code this instruction was decoded from, `wide` for widened instructions
+    pub fn op_code(&self) -> Result<u8> {
+        let op_code = match self {
+            Self::Nop => 0x0,
+            Self::AconstNull => 0x1,
+            Self::Iconst(-1) => 0x2,
+            Self::Iconst(0) => 0x3,
+            Self::Iconst(1) => 0x4,
+            Self::Iconst(2) => 0x5,
+            Self::Iconst(3) => 0x6,
+            Self::Iconst(4) => 0x7,
+            Self::Iconst(5) => 0x8,
+            Self::Lconst(0) => 0x9,
+            Self::Lconst(1) => 0xa,
+            Self::Fconst(value) if *value == 0.0 => 0xb,
+            Self::Fconst(value) if *value == 1.0 => 0xc,
+            Self::Fconst(value) if *value == 2.0 => 0xd,
+            Self::Dconst(value) if *value == 0.0 => 0xe,
+            Self::Dconst(value) if *value == 1.0 => 0xf,
+            Self::Bipush(..) => 0x10,
+            Self::Sipush(..) => 0x11,
+            Self::Ldc(..) => 0x12,
+            Self::LdcW(..) => 0x13,
+            Self::Ldc2W(..) => 0x14,
+            Self::Iload(..) => 0x15,
+            Self::Lload(..) => 0x16,
+            Self::Fload(..) => 0x17,
+            Self::Dload(..) => 0x18,
+            Self::Aload(..) => 0x19,
+            Self::Iload0 => 0x1a,
+            Self::Iload1 => 0x1b,
+            Self::Iload2 => 0x1c,
+            Self::Iload3 => 0x1d,
+            Self::Lload0 => 0x1e,
+            Self::Lload1 => 0x1f,
+            Self::Lload2 => 0x20,
+            Self::Lload3 => 0x21,
+            Self::Fload0 => 0x22,
+            Self::Fload1 => 0x23,
+            Self::Fload2 => 0x24,
+            Self::Fload3 => 0x25,
+            Self::Dload0 => 0x26,
+            Self::Dload1 => 0x27,
+            Self::Dload2 => 0x28,
+            Self::Dload3 => 0x29,
+            Self::Aload0 => 0x2a,
+            Self::Aload1 => 0x2b,
+            Self::Aload2 => 0x2c,
+            Self::Aload3 => 0x2d,
+            Self::Iaload => 0x2e,
+            Self::Laload => 0x2f,
+            Self::Faload => 0x30,
+            Self::Daload => 0x31,
+            Self::Aaload => 0x32,
+            Self::Baload => 0x33,
+            Self::Caload => 0x34,
+            Self::Saload => 0x35,
+            Self::Istore(..) => 0x36,
+            Self::Lstore(..) => 0x37,
+            Self::Fstore(..) => 0x38,
+            Self::Dstore(..) => 0x39,
+            Self::Astore(..) => 0x3a,
+            Self::Istore0 => 0x3b,
+            Self::Istore1 => 0x3c,
+            Self::Istore2 => 0x3d,
+            Self::Istore3 => 0x3e,
+            Self::Lstore0 => 0x3f,
+            Self::Lstore1 => 0x40,
+            Self::Lstore2 => 0x41,
+            Self::Lstore3 => 0x42,
+            Self::Fstore0 => 0x43,
+            Self::Fstore1 => 0x44,
+            Self::Fstore2 => 0x45,
+            Self::Fstore3 => 0x46,
+            Self::Dstore0 => 0x47,
+            Self::Dstore1 => 0x48,
+            Self::Dstore2 => 0x49,
+            Self::Dstore3 => 0x4a,
+            Self::Astore0 => 0x4b,
+            Self::Astore1 => 0x4c,
+            Self::Astore2 => 0x4d,
+            Self::Astore3 => 0x4e,
+            Self::Iastore => 0x4f,
+            Self::Lastore => 0x50,
+            Self::Fastore => 0x51,
+            Self::Dastore => 0x52,
+            Self::Aastore => 0x53,
+            Self::Bastore => 0x54,
+            Self::Castore => 0x55,
+            Self::Sastore => 0x56,
+            Self::Pop => 0x57,
+            Self::Pop2 => 0x58,
+            Self::Dup => 0x59,
+            Self::DupX1 => 0x5a,
+            Self::DupX2 => 0x5b,
+            Self::Dup2 => 0x5c,
+            Self::Dup2X1 => 0x5d,
+            Self::Dup2X2 => 0x5e,
+            Self::Swap => 0x5f,
+            Self::Iadd => 0x60,
+            Self::Ladd => 0x61,
+            Self::Fadd => 0x62,
+            Self::Dadd => 0x63,
+            Self::Isub => 0x64,
+            Self::Lsub => 0x65,
+            Self::Fsub => 0x66,
+            Self::Dsub => 0x67,
+            Self::Imul => 0x68,
+            Self::Lmul => 0x69,
+            Self::Fmul => 0x6a,
+            Self::Dmul => 0x6b,
+            Self::Idiv => 0x6c,
+            Self::Ldiv => 0x6d,
+            Self::Fdiv => 0x6e,
+            Self::Ddiv => 0x6f,
+            Self::Irem => 0x70,
+            Self::Lrem => 0x71,
+            Self::Frem => 0x72,
+            Self::Drem => 0x73,
+            Self::Ineg => 0x74,
+            Self::Lneg => 0x75,
+            Self::Fneg => 0x76,
+            Self::Dneg => 0x77,
+            Self::Ishl => 0x78,
+            Self::Lshl => 0x79,
+            Self::Ishr => 0x7a,
+            Self::Lshr => 0x7b,
+            Self::Iushr => 0x7c,
+            Self::Lushr => 0x7d,
+            Self::Iand => 0x7e,
+            Self::Land => 0x7f,
+            Self::Ior => 0x80,
+            Self::Lor => 0x81,
+            Self::Ixor => 0x82,
+            Self::Lxor => 0x83,
+            Self::Iinc(..) => 0x84,
+            Self::I2l => 0x85,
+            Self::I2f => 0x86,
+            Self::I2d => 0x87,
+            Self::L2i => 0x88,
+            Self::L2f => 0x89,
+            Self::L2d => 0x8a,
+            Self::F2i => 0x8b,
+            Self::F2l => 0x8c,
+            Self::F2d => 0x8d,
+            Self::D2i => 0x8e,
+            Self::D2l => 0x8f,
+            Self::D2f => 0x90,
+            Self::I2b => 0x91,
+            Self::I2c => 0x92,
+            Self::I2s => 0x93,
+            Self::Lcmp => 0x94,
+            Self::Fcmpl => 0x95,
+            Self::Fcmpg => 0x96,
+            Self::Dcmpl => 0x97,
+            Self::Dcmpg => 0x98,
+            Self::Ifeq(..) => 0x99,
+            Self::IfNe(..) => 0x9a,
+            Self::Iflt(..) => 0x9b,
+            Self::Ifge(..) => 0x9c,
+            Self::Ifgt(..) => 0x9d,
+            Self::Ifle(..) => 0x9e,
+            Self::IfIcmpeq(..) => 0x9f,
+            Self::IfIcmpne(..) => 0xa0,
+            Self::IfIcmplt(..) => 0xa1,
+            Self::IfIcmpge(..) => 0xa2,
+            Self::IfIcmpgt(..) => 0xa3,
+            Self::IfIcmple(..) => 0xa4,
+            Self::IfAcmpeq(..) => 0xa5,
+            Self::IfAcmpne(..) => 0xa6,
+            Self::Goto(..) => 0xa7,
+            Self::TableSwitch { .. } => 0xaa,
+            Self::LookupSwitch { .. } => 0xab,
+            Self::Ireturn => 0xac,
+            Self::Lreturn => 0xad,
+            Self::Freturn => 0xae,
+            Self::Dreturn => 0xaf,
+            Self::Areturn => 0xb0,
+            Self::Return => 0xb1,
+            Self::GetStatic(..) => 0xb2,
+            Self::PutStatic(..) => 0xb3,
+            Self::GetField(..) => 0xb4,
+            Self::PutField(..) => 0xb5,
+            Self::InvokeVirtual(..) => 0xb6,
+            Self::InvokeSpecial(..) => 0xb7,
+            Self::InvokeStatic(..) => 0xb8,
+            Self::InvokeInterface(..) => 0xb9,
+            Self::InvokeDynamic(..) => 0xba,
+            Self::New(..) => 0xbb,
+            Self::Newarray(..) => 0xbc,
+            Self::Anewarray(..) => 0xbd,
+            Self::ArrayLength => 0xbe,
+            Self::Athrow => 0xbf,
+            Self::Checkcast(..) => 0xc0,
+            Self::Instanceof(..) => 0xc1,
+            Self::MonitorEnter => 0xc2,
+            Self::MonitorExit => 0xc3,
+            Self::Wide(_) => 0xc4,
+            Self::Multianewarray(..) => 0xc5,
+            Self::IfNull(..) => 0xc6,
+            Self::IfNonNull(..) => 0xc7,
+            Self::GotoW(..) => 0xc8,
+            // constants outside of the ones with dedicated op codes are never decoded
+            Self::Iconst(_) | Self::Lconst(_) | Self::Fconst(_) | Self::Dconst(_) => {
+                bail!("no op code for {self:?}")
+            }
+        };
+        Ok(op_code)
+    }
+
+    /// Name of the instruction as used in the JVM specification, `wide` for widened instructions
+    pub fn mnemonic(&self) -> Result<&'static str> {
+        Ok(MNEMONICS[self.op_code()? as usize])
+    }
+
+    /// The instruction to execute, with a `wide` prefix stripped
+    pub fn operation(&self) -> &Instruction {
+        match self {
+            Self::Wide(instruction) => instruction,
+            instruction => instruction,
         }
     }
 
@@ -479,31 +1096,58 @@ impl Instruction {
                 | Self::IfIcmpeq(_)
                 | Self::IfIcmpne(_)
                 | Self::IfAcmpne(_)
+                | Self::IfAcmpeq(_)
                 | Self::Goto(_)
+                | Self::GotoW(_)
                 | Self::TableSwitch { .. }
                 | Self::LookupSwitch { .. }
         )
     }
 }
 
+fn wide(bytes: &[u8]) -> Result<Instruction> {
+    let op_code = *bytes.get(1).context("premature end of code")?;
+    let index = short(&bytes[1..])?;
+
+    Ok(Instruction::Wide(Box::new(match op_code {
+        0x15 => Instruction::Iload(index),
+        0x16 => Instruction::Lload(index),
+        0x17 => Instruction::Fload(index),
+        0x18 => Instruction::Dload(index),
+        0x19 => Instruction::Aload(index),
+        0x36 => Instruction::Istore(index),
+        0x37 => Instruction::Lstore(index),
+        0x38 => Instruction::Fstore(index),
+        0x39 => Instruction::Dstore(index),
+        0x3a => Instruction::Astore(index),
+        0x84 => Instruction::Iinc(index, short(&bytes[3..])? as i16),
+        _ => bail!("unsupported wide instruction: 0x{op_code:x}"),
+    })))
+}
+
+/// Signed 32-bit operand at `index` of the 4-byte aligned switch operands
+fn switch_int(bytes: &[u8], index: usize) -> Result<i32> {
+    let bytes = bytes
+        .get(index * 4..index * 4 + 4)
+        .context("premature end of code")?;
+    Ok(i32::from_be_bytes(bytes.try_into()?))
+}
+
 fn table_switch(bytes: &[u8], pc: usize) -> Result<Instruction> {
-    let skip = pc % 4;
-    let bytes = &bytes[4 - skip..];
-    let default = i32::from_be_bytes(bytes[..4].try_into()?);
-    let bytes = &bytes[4..];
-    let low = i32::from_be_bytes(bytes[..4].try_into()?);
-    let bytes = &bytes[4..];
-    let high = i32::from_be_bytes(bytes[..4].try_into()?);
-
-    let mut bytes = &bytes[4..];
-    let mut jump_offsets = Vec::new();
-    for _ in 0..(high - low + 1) {
-        let offset = i32::from_be_bytes(bytes[..4].try_into()?);
-        jump_offsets.push(offset);
-        bytes = &bytes[4..];
+    // operands start at the next multiple of four
+    let skip = (4 - pc % 4) % 4;
+    let bytes = bytes.get(skip..).context("premature end of code")?;
+    let default = switch_int(bytes, 0)?;
+    let low = switch_int(bytes, 1)?;
+    let high = switch_int(bytes, 2)?;
+    if low > high {
+        bail!("tableswitch low {low} is greater than high {high}");
     }
 
-    // FIXME: is skip needed here?
+    let jump_offsets = (0..=(high as i64 - low as i64) as usize)
+        .map(|i| switch_int(bytes, 3 + i))
+        .collect::<Result<Vec<i32>>>()?;
+
     Ok(Instruction::TableSwitch {
         skip,
         default,
@@ -514,26 +1158,211 @@ fn table_switch(bytes: &[u8], pc: usize) -> Result<Instruction> {
 }
 
 fn lookup_switch(bytes: &[u8], pc: usize) -> Result<Instruction> {
-    let skip = pc % 4;
-    let bytes = &bytes[4 - skip..];
-    let default = i32::from_be_bytes(bytes[..4].try_into()?);
-    let bytes = &bytes[4..];
-    let n_pairs = i32::from_be_bytes(bytes[..4].try_into()?);
-
-    let mut bytes = &bytes[4..];
-    let mut offset_pairs = Vec::new();
-    for _ in 0..n_pairs {
-        let index = i32::from_be_bytes(bytes[..4].try_into()?);
-        bytes = &bytes[4..];
-        let offset = i32::from_be_bytes(bytes[..4].try_into()?);
-        offset_pairs.push((index, offset));
-        bytes = &bytes[4..];
+    // operands start at the next multiple of four
+    let skip = (4 - pc % 4) % 4;
+    let bytes = bytes.get(skip..).context("premature end of code")?;
+    let default = switch_int(bytes, 0)?;
+    let n_pairs = switch_int(bytes, 1)?;
+    if n_pairs < 0 {
+        bail!("lookupswitch has negative number of pairs {n_pairs}");
     }
 
-    // FIXME: is skip needed here?
+    let offset_pairs = (0..n_pairs as usize)
+        .map(|i| Ok((switch_int(bytes, 2 + 2 * i)?, switch_int(bytes, 3 + 2 * i)?)))
+        .collect::<Result<Vec<(i32, i32)>>>()?;
+
     Ok(Instruction::LookupSwitch {
         skip,
         default,
         offset_pairs,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constants() {
+        let decode = |op_code: u8| {
+            let instruction = Instruction::new(&[op_code], 0).unwrap();
+            assert_eq!(instruction.length(), 1);
+            instruction
+        };
+        assert!(matches!(decode(0x02), Instruction::Iconst(-1)));
+        assert!(matches!(decode(0x08), Instruction::Iconst(5)));
+        assert!(matches!(decode(0x09), Instruction::Lconst(0)));
+        assert!(matches!(decode(0x0a), Instruction::Lconst(1)));
+        assert!(matches!(decode(0x0b), Instruction::Fconst(0.0)));
+        assert!(matches!(decode(0x0c), Instruction::Fconst(1.0)));
+        assert!(matches!(decode(0x0d), Instruction::Fconst(2.0)));
+        assert!(matches!(decode(0x0e), Instruction::Dconst(0.0)));
+        assert!(matches!(decode(0x0f), Instruction::Dconst(1.0)));
+    }
+
+    #[test]
+    fn every_op_code_decodes() {
+        for op_code in (0x00..=0xc8).filter(|op_code| ![0xa8, 0xa9, 0xaa, 0xab].contains(op_code)) {
+            let bytes = [op_code, 0x15, 0x00, 0x01, 0x00];
+            let instruction = Instruction::new(&bytes, 0).unwrap();
+            if op_code != 0xc4 {
+                assert_eq!(instruction.op_code().unwrap(), op_code);
+            }
+        }
+    }
+
+    #[test]
+    fn double_locals() {
+        assert!(matches!(
+            Instruction::new(&[0x18, 0x04], 0).unwrap(),
+            Instruction::Dload(4)
+        ));
+        assert!(matches!(
+            Instruction::new(&[0x4a], 0).unwrap(),
+            Instruction::Dstore3
+        ));
+        let wide = Instruction::new(&[0xc4, 0x39, 0x01, 0x00], 0).unwrap();
+        assert!(matches!(wide.operation(), Instruction::Dstore(256)));
+        assert_eq!(wide.length(), 4);
+    }
+
+    #[test]
+    fn multianewarray() {
+        let instruction = Instruction::new(&[0xc5, 0x00, 0x07, 0x02], 0).unwrap();
+        assert!(matches!(
+            instruction,
+            Instruction::Multianewarray(CpIndex(7), 2)
+        ));
+        assert_eq!(instruction.length(), 4);
+        assert_eq!(instruction.mnemonic().unwrap(), "multianewarray");
+    }
+
+    #[test]
+    fn constant_without_op_code() {
+        assert!(Instruction::Iconst(7).op_code().is_err());
+        assert!(Instruction::Fconst(0.5).mnemonic().is_err());
+    }
+
+    #[test]
+    fn wide_iload() {
+        let instruction = Instruction::new(&[0xc4, 0x15, 0x01, 0x2c], 0).unwrap();
+        assert!(matches!(
+            instruction,
+            Instruction::Wide(ref i) if matches!(**i, Instruction::Iload(300))
+        ));
+        assert_eq!(instruction.length(), 4);
+    }
+
+    #[test]
+    fn wide_iinc() {
+        let instruction = Instruction::new(&[0xc4, 0x84, 0x01, 0x2c, 0xff, 0x00], 0).unwrap();
+        assert!(matches!(
+            instruction,
+            Instruction::Wide(ref i) if matches!(**i, Instruction::Iinc(300, -256))
+        ));
+        assert_eq!(instruction.length(), 6);
+    }
+
+    #[test]
+    fn reference_comparisons_are_jumps() {
+        for op_code in [0xa5, 0xa6] {
+            let instruction = Instruction::new(&[op_code, 0x00, 0x10], 0).unwrap();
+            assert!(instruction.is_jump(), "{instruction:?}");
+        }
+    }
+
+    #[test]
+    fn goto_w() {
+        let instruction = Instruction::new(&[0xc8, 0x00, 0x01, 0x86, 0xa0], 0).unwrap();
+        assert!(matches!(instruction, Instruction::GotoW(100000)));
+        assert_eq!(instruction.length(), 5);
+        assert!(instruction.is_jump());
+
+        let instruction = Instruction::new(&[0xc8, 0xff, 0xfe, 0x79, 0x60], 0).unwrap();
+        assert!(matches!(instruction, Instruction::GotoW(-100000)));
+    }
+
+    #[test]
+    fn jsr_is_rejected() {
+        let err = Instruction::new(&[0xa8, 0x00, 0x10], 0).unwrap_err();
+        assert!(err.to_string().contains("jsr/ret not supported"));
+    }
+
+    #[test]
+    fn table_switch_padding() {
+        // tableswitch at pc 3 has no padding, default 8, low 0, high 1
+        let bytes = [
+            0xaa, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 16, 0, 0, 0, 20,
+        ];
+        let instruction = Instruction::new(&bytes, 3).unwrap();
+        assert!(matches!(
+            instruction,
+            Instruction::TableSwitch { default: 8, low: 0, high: 1, ref jump_offsets, .. }
+                if jump_offsets == &[16, 20]
+        ));
+        assert_eq!(instruction.length(), bytes.len());
+
+        // at pc 0 three bytes of padding follow the op code
+        let mut padded = vec![0xaa, 0, 0, 0];
+        padded.extend(&bytes[1..]);
+        let instruction = Instruction::new(&padded, 0).unwrap();
+        assert!(matches!(
+            instruction,
+            Instruction::TableSwitch { default: 8, .. }
+        ));
+        assert_eq!(instruction.length(), padded.len());
+    }
+
+    #[test]
+    fn lookup_switch_padding() {
+        // lookupswitch at pc 1 with two bytes of padding, default 8, one pair 5 => 12
+        let bytes = [0xab, 0, 0, 0, 0, 0, 8, 0, 0, 0, 1, 0, 0, 0, 5, 0, 0, 0, 12];
+        let instruction = Instruction::new(&bytes, 1).unwrap();
+        assert!(matches!(
+            instruction,
+            Instruction::LookupSwitch { default: 8, ref offset_pairs, .. }
+                if offset_pairs == &[(5, 12)]
+        ));
+        assert_eq!(instruction.length(), bytes.len());
+    }
+
+    #[test]
+    fn switch_signed_operands() {
+        // tableswitch at pc 2 with one byte of padding, default -4, low -1, high 0
+        let bytes = [
+            0xaa, 0, 0xff, 0xff, 0xff, 0xfc, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0xff, 0xff, 0xff,
+            0xf0, 0, 0, 0, 24,
+        ];
+        let instruction = Instruction::new(&bytes, 2).unwrap();
+        assert!(matches!(
+            instruction,
+            Instruction::TableSwitch { skip: 1, default: -4, low: -1, high: 0, ref jump_offsets }
+                if jump_offsets == &[-16, 24]
+        ));
+        assert_eq!(instruction.length(), bytes.len());
+
+        // lookupswitch at pc 4 with three bytes of padding, one pair -2 => -8
+        let bytes = [
+            0xab, 0, 0, 0, 0, 0, 0, 12, 0, 0, 0, 1, 0xff, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xff, 0xf8,
+        ];
+        let instruction = Instruction::new(&bytes, 4).unwrap();
+        assert!(matches!(
+            instruction,
+            Instruction::LookupSwitch { skip: 3, default: 12, ref offset_pairs }
+                if offset_pairs == &[(-2, -8)]
+        ));
+        assert_eq!(instruction.length(), bytes.len());
+    }
+
+    #[test]
+    fn truncated_switch() {
+        // high - low + 1 = 2 jump offsets announced, only one present
+        let bytes = [0xaa, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 16];
+        let err = Instruction::new(&bytes, 3).unwrap_err();
+        assert!(err.to_string().contains("premature end of code"));
+
+        let bytes = [0xab, 0, 0, 0, 8, 0, 0, 0, 1, 0, 0, 0, 5];
+        let err = Instruction::new(&bytes, 3).unwrap_err();
+        assert!(err.to_string().contains("premature end of code"));
+    }
+}
@@ -1,30 +1,83 @@
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
 use anyhow::{Context, Result, bail};
 use code::Code;
-use common::{ClassIdentifier, FrameValue, HeapId};
+use common::{ClassIdentifier, FrameValue, HeapId, ReferenceValue};
 use instruction::Instruction;
-use parser::class::descriptor::MethodDescriptor;
+use parser::class::{
+    attribute::{Attribute, ExceptionHandler},
+    descriptor::MethodDescriptor,
+};
 use tracing::trace;
 
 pub mod code;
 pub mod instruction;
 
-#[derive(Debug, Default)]
+/// Name of the frames pushed by [`Stack::push_entry`], methods can't be named like this
+const ENTRY_METHOD_NAME: &str = "<entry>";
+
+/// Number of frames a stack holds unless configured otherwise
+pub const DEFAULT_MAX_FRAMES: usize = 1024;
+
+/// Pushing a frame onto a full stack, the interpreter throws it as
+/// `java.lang.StackOverflowError`
+#[derive(Debug)]
+pub struct StackOverflow {
+    pub max_frames: usize,
+}
+
+impl Display for StackOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "stack exceeds {} frames", self.max_frames)
+    }
+}
+
+impl std::error::Error for StackOverflow {}
+
+#[derive(Debug)]
 pub struct Stack {
     frames: Vec<Frame>,
+    max_frames: usize,
+}
+
+impl Default for Stack {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_FRAMES)
+    }
 }
 
 impl Stack {
+    pub fn new(max_frames: usize) -> Self {
+        Self {
+            frames: Vec::new(),
+            max_frames,
+        }
+    }
+
+    pub fn max_frames(&self) -> usize {
+        self.max_frames
+    }
+
+    pub fn set_max_frames(&mut self, max_frames: usize) {
+        self.max_frames = max_frames;
+    }
+
+    /// Fails with [`StackOverflow`] if the stack already holds `max_frames` frames
     pub fn push(
         &mut self,
         method_name: String,
         method_descriptor: MethodDescriptor,
-        mut local_variables: Vec<FrameValue>,
-        code: Code,
+        local_variables: Vec<FrameValue>,
+        code: Arc<Code>,
         class: ClassIdentifier,
         object_ref: Option<HeapId>,
-    ) {
-        while local_variables.len() < code.max_locals().into() {
-            local_variables.push(FrameValue::Reserved);
+    ) -> Result<()> {
+        if self.frames.len() >= self.max_frames {
+            return Err(StackOverflow {
+                max_frames: self.max_frames,
+            }
+            .into());
         }
 
         self.frames.push(Frame::new(
@@ -35,6 +88,39 @@ impl Stack {
             class,
             object_ref,
         ));
+        Ok(())
+    }
+
+    /// Pushes a frame without code for calls from Rust into Java, which holds the arguments
+    /// and receives the return value of the method invoked from it
+    pub fn push_entry(&mut self, class: ClassIdentifier) -> Result<()> {
+        let code = Code::new(Attribute::Code {
+            attribute_name_index: 0u16.into(),
+            attribute_length: 0,
+            max_stack: 0,
+            max_locals: 0,
+            code: vec![],
+            exception_table: vec![],
+            attributes: vec![],
+        })?;
+        self.push(
+            ENTRY_METHOD_NAME.to_string(),
+            MethodDescriptor::new("()V")?,
+            vec![],
+            Arc::new(code),
+            class,
+            None,
+        )
+    }
+
+    /// Number of frames on the stack
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Drops the frames above the first `depth` ones
+    pub fn truncate(&mut self, depth: usize) {
+        self.frames.truncate(depth);
     }
 
     pub fn pop(&mut self) -> Result<()> {
@@ -96,6 +182,10 @@ impl Stack {
         Ok(&self.frames.last().context("no frame found")?.method_name)
     }
 
+    pub fn pc(&self) -> Result<usize> {
+        Ok(self.frames.last().context("no frame found")?.pc)
+    }
+
     pub fn offset_pc(&mut self, offset: i32) -> Result<()> {
         self.frames
             .last_mut()
@@ -103,6 +193,25 @@ impl Stack {
             .offset_pc(offset)
     }
 
+    pub fn exception_handlers(&self) -> Result<Vec<ExceptionHandler>> {
+        let frame = self.frames.last().context("no frame found")?;
+        Ok(frame.code.exception_handlers(frame.pc as u16))
+    }
+
+    /// Continues execution of the current frame at the handler with only `exception` on its
+    /// operand stack
+    pub fn enter_exception_handler(
+        &mut self,
+        handler: &ExceptionHandler,
+        exception: HeapId,
+    ) -> Result<()> {
+        let frame = self.frames.last_mut().context("no frame found")?;
+        frame.operand_stack.clear();
+        frame.push_operand(FrameValue::Reference(ReferenceValue::HeapItem(exception)));
+        frame.pc = handler.handler_pc.into();
+        Ok(())
+    }
+
     pub fn method_descriptor(&self) -> Result<MethodDescriptor> {
         Ok(self
             .frames
@@ -123,7 +232,9 @@ impl Stack {
     pub fn caller_class(&self) -> Result<&ClassIdentifier> {
         Ok(&self
             .frames
-            .get(self.frames.len() - 2)
+            .len()
+            .checked_sub(2)
+            .and_then(|index| self.frames.get(index))
             .context("no caller found")?
             .class)
     }
@@ -137,6 +248,48 @@ impl Stack {
             .clone())
     }
 
+    /// Heap items referenced by any frame
+    pub fn references(&self) -> Vec<HeapId> {
+        let mut references = Vec::new();
+        for frame in &self.frames {
+            for value in frame.operand_stack.iter().chain(&frame.local_variables) {
+                if let FrameValue::Reference(ReferenceValue::HeapItem(heap_id)) = value {
+                    references.push(heap_id.clone());
+                }
+            }
+
+            references.extend(frame.object_ref.clone());
+        }
+
+        references
+    }
+
+    /// Frames of the stack trace of `throwable`, starting with the innermost one. Like in
+    /// HotSpot the frames filling in the stack trace and constructing `throwable` are left out.
+    pub fn backtrace(&self, throwable: &HeapId) -> Vec<BacktraceFrame> {
+        let receives_throwable = |frame: &&Frame| {
+            matches!(
+                frame.local_variables.first(),
+                Some(FrameValue::Reference(ReferenceValue::HeapItem(heap_id))) if heap_id == throwable
+            )
+        };
+
+        self.frames
+            .iter()
+            .rev()
+            .skip_while(|frame| {
+                frame.method_name == "fillInStackTrace" && receives_throwable(frame)
+            })
+            .skip_while(|frame| frame.method_name == "<init>" && receives_throwable(frame))
+            .filter(|frame| frame.method_name != ENTRY_METHOD_NAME)
+            .map(|frame| BacktraceFrame {
+                class: frame.class.clone(),
+                method_name: frame.method_name.clone(),
+                line_number: frame.code.line_number(frame.pc as u16),
+            })
+            .collect()
+    }
+
     pub fn stack_trace(&self) -> String {
         let mut res = String::new();
         for frame in self.frames.iter().rev() {
@@ -152,13 +305,21 @@ impl Stack {
     }
 }
 
+/// A method invocation recorded in the stack trace of a throwable
+#[derive(Debug, Clone)]
+pub struct BacktraceFrame {
+    pub class: ClassIdentifier,
+    pub method_name: String,
+    pub line_number: Option<u16>,
+}
+
 #[derive(Debug)]
 struct Frame {
     method_name: String,
     method_descriptor: MethodDescriptor,
     operand_stack: Vec<FrameValue>,
     local_variables: Vec<FrameValue>,
-    code: Code,
+    code: Arc<Code>,
     pc: usize,
     class: ClassIdentifier,
     object_ref: Option<HeapId>,
@@ -169,7 +330,7 @@ impl Frame {
         method_name: String,
         method_descriptor: MethodDescriptor,
         local_variables: Vec<FrameValue>,
-        code: Code,
+        code: Arc<Code>,
         class: ClassIdentifier,
         object_ref: Option<HeapId>,
     ) -> Self {
@@ -182,6 +343,12 @@ impl Frame {
                 lvs.push(lv.clone());
             }
         }
+        // longs and doubles take up two slots, so only pad after expanding them
+        lvs.resize(
+            lvs.len().max(code.max_locals().into()),
+            FrameValue::Reserved,
+        );
+
         Self {
             method_name,
             method_descriptor,
@@ -228,7 +395,8 @@ impl Frame {
     }
 
     fn current_instruction(&mut self) -> Result<Instruction> {
-        Instruction::new(&self.code.instructions()[self.pc..], self.pc)
+        self.code
+            .instruction(self.pc)
             .context(format!("no instruction found at pc {}", self.pc))
     }
 
@@ -240,26 +408,29 @@ impl Frame {
     }
 
     fn set_local_variable(&mut self, index: usize, value: FrameValue) -> Result<()> {
-        if index > self.local_variables.len() {
-            bail!("index out of bounds of local variables")
+        if index >= self.local_variables.len() {
+            self.local_variables.resize(index + 1, FrameValue::Reserved);
         }
 
         self.local_variables[index] = value;
         Ok(())
     }
 
+    /// Moves the pc by `offset`, which has to land inside of the code of the frame
     fn offset_pc(&mut self, offset: i32) -> Result<()> {
-        if offset < 0 {
-            let offset = offset.unsigned_abs() as usize;
-            if offset > self.pc {
-                bail!("pc cannot be negative")
-            }
-
-            self.pc -= offset;
-        } else {
-            self.pc += offset as usize;
+        let pc = self.pc as i64 + offset as i64;
+        if pc < 0 {
+            bail!("pc {} with offset {offset} cannot be negative", self.pc)
+        }
+        if pc as usize >= self.code.instructions().len() {
+            bail!(
+                "pc {} with offset {offset} is out of bounds of code with length {}",
+                self.pc,
+                self.code.instructions().len()
+            )
         }
 
+        self.pc = pc as usize;
         Ok(())
     }
 
@@ -271,3 +442,111 @@ impl Frame {
         &self.object_ref
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use parser::class::attribute::Attribute;
+
+    use super::*;
+
+    fn frame(max_locals: u16) -> Frame {
+        frame_with_arguments(max_locals, vec![FrameValue::Int(0), FrameValue::Int(0)])
+    }
+
+    fn frame_with_arguments(max_locals: u16, arguments: Vec<FrameValue>) -> Frame {
+        frame_with_code(max_locals, arguments, vec![])
+    }
+
+    fn frame_with_code(max_locals: u16, arguments: Vec<FrameValue>, code: Vec<u8>) -> Frame {
+        let code = Code::new(Attribute::Code {
+            attribute_name_index: 0u16.into(),
+            attribute_length: 0,
+            max_stack: 0,
+            max_locals,
+            code,
+            exception_table: vec![],
+            attributes: vec![],
+        })
+        .unwrap();
+
+        Frame::new(
+            "test".to_string(),
+            MethodDescriptor::new("()V").unwrap(),
+            arguments,
+            Arc::new(code),
+            ClassIdentifier::from_parts("", "Test"),
+            None,
+        )
+    }
+
+    #[test]
+    fn set_local_variable_overwrites() {
+        let mut frame = frame(2);
+        frame.set_local_variable(1, FrameValue::Int(1)).unwrap();
+        frame.set_local_variable(1, FrameValue::Int(2)).unwrap();
+
+        assert_eq!(frame.local_variables.len(), 2);
+        assert_eq!(frame.local_variable(0).unwrap().int().unwrap(), 0);
+        assert_eq!(frame.local_variable(1).unwrap().int().unwrap(), 2);
+    }
+
+    #[test]
+    fn set_local_variable_grows() {
+        let mut frame = frame(2);
+        frame.set_local_variable(3, FrameValue::Int(3)).unwrap();
+
+        assert_eq!(frame.local_variables.len(), 4);
+        assert!(matches!(
+            frame.local_variable(2).unwrap(),
+            FrameValue::Reserved
+        ));
+        assert_eq!(frame.local_variable(3).unwrap().int().unwrap(), 3);
+    }
+
+    #[test]
+    fn locals_sized_to_max_locals() {
+        let frame = frame_with_arguments(4, vec![FrameValue::Int(1)]);
+        assert_eq!(frame.local_variables.len(), 4);
+        assert_eq!(frame.local_variable(0).unwrap().int().unwrap(), 1);
+        assert!(matches!(
+            frame.local_variable(3).unwrap(),
+            FrameValue::Reserved
+        ));
+    }
+
+    #[test]
+    fn locals_sized_with_wide_arguments() {
+        let frame = frame_with_arguments(3, vec![FrameValue::Long(1), FrameValue::Int(2)]);
+        assert_eq!(frame.local_variables.len(), 3);
+        assert!(matches!(
+            frame.local_variable(1).unwrap(),
+            FrameValue::Reserved
+        ));
+        assert_eq!(frame.local_variable(2).unwrap().int().unwrap(), 2);
+    }
+
+    #[test]
+    fn offset_pc_wide_offsets() {
+        // nops spanning more than an i16 offset
+        let mut frame = frame_with_code(0, vec![], vec![0x00; 70_000]);
+
+        frame.offset_pc(65_000).unwrap();
+        assert_eq!(frame.pc, 65_000);
+        frame.offset_pc(-40_000).unwrap();
+        assert_eq!(frame.pc, 25_000);
+    }
+
+    #[test]
+    fn offset_pc_out_of_bounds() {
+        let mut frame = frame_with_code(0, vec![], vec![0x00; 10]);
+        frame.offset_pc(5).unwrap();
+
+        let err = frame.offset_pc(-6).unwrap_err();
+        assert!(err.to_string().contains("cannot be negative"));
+        let err = frame.offset_pc(i32::MIN).unwrap_err();
+        assert!(err.to_string().contains("cannot be negative"));
+        let err = frame.offset_pc(5).unwrap_err();
+        assert!(err.to_string().contains("out of bounds"));
+        assert_eq!(frame.pc, 5);
+    }
+}
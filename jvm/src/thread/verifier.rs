@@ -0,0 +1,1144 @@
+//! Type checking verification of method code against its `StackMapTable`, following JVMS 4.10.1.
+//!
+//! References are only checked to be initialized references, not for their classes, because
+//! that would need loading the classes during linking.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result, bail};
+use parser::class::{
+    attribute::{StackMapTableEntry, VerificationType},
+    constant_pool::{CpIndex, CpInfo},
+    descriptor::{BaseType, FieldType, MethodDescriptor, ReturnDescriptor},
+    method::Method,
+};
+
+use crate::thread::{
+    class::Class,
+    stack::{code::Code, instruction::Instruction},
+};
+
+const OBJECT: &str = "java/lang/Object";
+const THROWABLE: &str = "java/lang/Throwable";
+
+/// Verifies the code of all methods of `class`
+pub fn verify(class: &Class) -> Result<()> {
+    for method in class.methods() {
+        if method.code().is_none() {
+            continue;
+        }
+
+        if let Err(err) = verify_method(class, method) {
+            bail!(
+                "TODO: throw VerifyError, {:?}.{}{}: {err:#}",
+                class.identifier(),
+                class.method_name(method)?,
+                class.method_descriptor(method)?.raw()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Type {
+    Top,
+    Int,
+    Float,
+    Long,
+    Double,
+    Null,
+    UninitializedThis,
+    /// Created by the `new` instruction at the pc
+    Uninitialized(u16),
+    /// Internal name of the class, e.g. `java/lang/String` or `[I`
+    Object(String),
+}
+
+impl Type {
+    fn object(name: &str) -> Self {
+        Self::Object(name.to_string())
+    }
+
+    fn from_field_type(field_type: &FieldType) -> Self {
+        match field_type {
+            FieldType::BaseType(BaseType::Long) => Self::Long,
+            FieldType::BaseType(BaseType::Float) => Self::Float,
+            FieldType::BaseType(BaseType::Double) => Self::Double,
+            FieldType::BaseType(_) => Self::Int,
+            FieldType::ObjectType { class_name } => Self::Object(class_name.clone()),
+            FieldType::ComponentType(..) => Self::Object(field_type.descriptor()),
+        }
+    }
+
+    fn from_descriptor(descriptor: &str) -> Result<Self> {
+        let method_descriptor = MethodDescriptor::new(&format!("(){descriptor}"))?;
+        match method_descriptor.return_descriptor {
+            ReturnDescriptor::FieldType(field_type) => Ok(Self::from_field_type(&field_type)),
+            ReturnDescriptor::Void => bail!("no field type: {descriptor}"),
+        }
+    }
+
+    fn from_verification_type(class: &Class, verification_type: &VerificationType) -> Result<Self> {
+        Ok(match verification_type {
+            VerificationType::Top => Self::Top,
+            VerificationType::Integer => Self::Int,
+            VerificationType::Float => Self::Float,
+            VerificationType::Double => Self::Double,
+            VerificationType::Long => Self::Long,
+            VerificationType::Object(index) => Self::Object(class_name(class, index)?),
+            VerificationType::Uninitialized(offset) => Self::Uninitialized(*offset),
+            VerificationType::UninitializedThis => Self::UninitializedThis,
+            VerificationType::Null => Self::Null,
+        })
+    }
+
+    fn is_wide(&self) -> bool {
+        matches!(self, Self::Long | Self::Double)
+    }
+
+    fn is_reference(&self) -> bool {
+        matches!(
+            self,
+            Self::Null | Self::Object(_) | Self::UninitializedThis | Self::Uninitialized(_)
+        )
+    }
+
+    fn is_assignable_to(&self, other: &Type) -> bool {
+        match (self, other) {
+            (_, Self::Top) => true,
+            (Self::Null | Self::Object(_), Self::Object(_)) => true,
+            _ => self == other,
+        }
+    }
+
+    /// Type of the components of an array of this type
+    fn component(&self) -> Result<Self> {
+        match self {
+            Self::Null => Ok(Self::Null),
+            Self::Object(name) if name.starts_with('[') => Self::from_descriptor(&name[1..]),
+            _ => bail!("{self:?} is no array"),
+        }
+    }
+}
+
+/// Types of the locals and operand stack before an instruction
+#[derive(Clone, Debug, PartialEq)]
+struct Frame {
+    locals: Vec<Type>,
+    stack: Vec<Type>,
+}
+
+impl Frame {
+    fn is_assignable_to(&self, other: &Frame) -> bool {
+        self.locals.len() == other.locals.len()
+            && self.stack.len() == other.stack.len()
+            && self
+                .locals
+                .iter()
+                .zip(&other.locals)
+                .all(|(a, b)| a.is_assignable_to(b))
+            && self
+                .stack
+                .iter()
+                .zip(&other.stack)
+                .all(|(a, b)| a.is_assignable_to(b))
+    }
+
+    fn push(&mut self, value: Type) {
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self, expected: &Type) -> Result<Type> {
+        let value = self.stack.pop().context("operand stack underflow")?;
+        if !value.is_assignable_to(expected) {
+            bail!("expected {expected:?} on the operand stack, found {value:?}");
+        }
+
+        Ok(value)
+    }
+
+    /// Pops any reference, including uninitialized ones
+    fn pop_reference(&mut self) -> Result<Type> {
+        let value = self.stack.pop().context("operand stack underflow")?;
+        if !value.is_reference() {
+            bail!("expected a reference on the operand stack, found {value:?}");
+        }
+
+        Ok(value)
+    }
+
+    /// Pops the values taking up `words` stack slots, in stack order
+    fn pop_words(&mut self, words: usize) -> Result<Vec<Type>> {
+        let mut values = Vec::new();
+        let mut popped = 0;
+        while popped < words {
+            let value = self.stack.pop().context("operand stack underflow")?;
+            popped += if value.is_wide() { 2 } else { 1 };
+            values.push(value);
+        }
+        if popped != words {
+            bail!("cannot split a long or double on the operand stack");
+        }
+
+        values.reverse();
+        Ok(values)
+    }
+
+    fn apply(&mut self, pops: &[Type], push: Option<Type>) -> Result<()> {
+        for expected in pops.iter().rev() {
+            self.pop(expected)?;
+        }
+        if let Some(push) = push {
+            self.push(push);
+        }
+
+        Ok(())
+    }
+
+    fn load(&mut self, index: u16, expected: &Type) -> Result<()> {
+        let value = self
+            .locals
+            .get(index as usize)
+            .context(format!("no local variable at index {index}"))?
+            .clone();
+        let assignable = if matches!(expected, Type::Object(_)) {
+            value.is_reference()
+        } else {
+            value.is_assignable_to(expected)
+        };
+        if !assignable {
+            bail!("expected {expected:?} in local variable {index}, found {value:?}");
+        }
+
+        self.push(value);
+        Ok(())
+    }
+
+    fn store(&mut self, index: u16, expected: &Type) -> Result<()> {
+        let value = if matches!(expected, Type::Object(_)) {
+            self.pop_reference()?
+        } else {
+            self.pop(expected)?
+        };
+
+        let index = index as usize;
+        let width = if value.is_wide() { 2 } else { 1 };
+        if index + width > self.locals.len() {
+            bail!("no local variable at index {index}");
+        }
+        // a long or double whose second half gets overwritten is invalid
+        if index > 0 && self.locals[index - 1].is_wide() {
+            self.locals[index - 1] = Type::Top;
+        }
+        if width == 2 {
+            self.locals[index + 1] = Type::Top;
+        }
+        self.locals[index] = value;
+        Ok(())
+    }
+
+    /// Replaces all occurrences of the uninitialized `value` after its constructor was called
+    fn initialize(&mut self, value: &Type, initialized: Type) {
+        for slot in self.locals.iter_mut().chain(self.stack.iter_mut()) {
+            if slot == value {
+                *slot = initialized.clone();
+            }
+        }
+    }
+}
+
+/// Outcome of an instruction for the control flow
+struct Flow {
+    /// Frame after the instruction
+    frame: Frame,
+    /// Offsets of the branch targets relative to the instruction
+    targets: Vec<i64>,
+    /// Whether the next instruction can be reached from this one
+    falls_through: bool,
+}
+
+impl Flow {
+    fn next(frame: Frame) -> Self {
+        Self {
+            frame,
+            targets: vec![],
+            falls_through: true,
+        }
+    }
+
+    fn end(frame: Frame) -> Self {
+        Self {
+            frame,
+            targets: vec![],
+            falls_through: false,
+        }
+    }
+
+    fn branch(frame: Frame, targets: Vec<i64>, falls_through: bool) -> Self {
+        Self {
+            frame,
+            targets,
+            falls_through,
+        }
+    }
+}
+
+fn verify_method(class: &Class, method: &Method) -> Result<()> {
+    let code = class.code(method)?;
+    let descriptor = class.method_descriptor(method)?;
+    let initial = initial_frame(class, method, &descriptor, &code)?;
+    let frames = stack_map_frames(class, &code, &initial)?;
+
+    let mut current = Some(initial);
+    let mut pc = 0;
+    let code_length = code.instructions().len();
+    while pc < code_length {
+        let instruction = code.instruction(pc)?;
+        if let Some(frame) = frames.get(&pc) {
+            if let Some(current) = &current
+                && !current.is_assignable_to(frame)
+            {
+                bail!("{current:?} doesn't match stack map frame {frame:?} at pc {pc}");
+            }
+            current = Some(frame.clone());
+        }
+        let frame = current.context(format!("no stack map frame at pc {pc}"))?;
+
+        for handler in code.exception_handlers(pc as u16) {
+            let catch_type = match handler.catch_type {
+                0 => THROWABLE.to_string(),
+                index => class_name(class, &index.into())?,
+            };
+            let handler_frame = Frame {
+                locals: frame.locals.clone(),
+                stack: vec![Type::Object(catch_type)],
+            };
+            check_target(&frames, &handler_frame, handler.handler_pc as i64)
+                .context(format!("exception handler of pc {pc}"))?;
+        }
+
+        let flow = execute(class, &descriptor, &code, frame, &instruction, pc)
+            .context(format!("{instruction:?} at pc {pc}"))?;
+        for offset in &flow.targets {
+            check_target(&frames, &flow.frame, pc as i64 + offset)
+                .context(format!("{instruction:?} at pc {pc}"))?;
+        }
+
+        current = flow.falls_through.then_some(flow.frame);
+        pc += instruction.length();
+    }
+
+    if current.is_some() {
+        bail!("execution falls off the end of the code");
+    }
+
+    Ok(())
+}
+
+fn check_target(frames: &BTreeMap<usize, Frame>, frame: &Frame, target: i64) -> Result<()> {
+    let target_frame = usize::try_from(target)
+        .ok()
+        .and_then(|target| frames.get(&target))
+        .context(format!("no stack map frame at branch target {target}"))?;
+    if !frame.is_assignable_to(target_frame) {
+        bail!("{frame:?} doesn't match stack map frame {target_frame:?} at pc {target}");
+    }
+
+    Ok(())
+}
+
+fn initial_frame(
+    class: &Class,
+    method: &Method,
+    descriptor: &MethodDescriptor,
+    code: &Code,
+) -> Result<Frame> {
+    let mut locals = Vec::new();
+    if !method.is_static() {
+        let this = class.identifier().with_slashes()?;
+        if class.method_name(method)? == "<init>" && this != OBJECT {
+            locals.push(Type::UninitializedThis);
+        } else {
+            locals.push(Type::Object(this));
+        }
+    }
+    for parameter in &descriptor.parameters {
+        push_local(&mut locals, Type::from_field_type(parameter));
+    }
+
+    if locals.len() > code.max_locals() as usize {
+        bail!("parameters exceed max_locals {}", code.max_locals());
+    }
+    locals.resize(code.max_locals() as usize, Type::Top);
+
+    Ok(Frame {
+        locals,
+        stack: vec![],
+    })
+}
+
+/// Pushes `value` into the locals, followed by `Top` for the second slot of a long or double
+fn push_local(locals: &mut Vec<Type>, value: Type) {
+    let wide = value.is_wide();
+    locals.push(value);
+    if wide {
+        locals.push(Type::Top);
+    }
+}
+
+/// Frames declared by the `StackMapTable` of `code` by their pc, each relative to the previous
+/// one starting from the `initial` frame
+fn stack_map_frames(class: &Class, code: &Code, initial: &Frame) -> Result<BTreeMap<usize, Frame>> {
+    let max_locals = code.max_locals() as usize;
+    let mut frames = BTreeMap::new();
+    // locals as declared in the stack map, in which longs and doubles take up one entry
+    let mut declared = declared_locals(&initial.locals);
+    let mut previous_pc: Option<usize> = None;
+
+    for entry in code.stack_map_table() {
+        let (offset_delta, stack) = match entry {
+            StackMapTableEntry::Same { offset_delta }
+            | StackMapTableEntry::Extended { offset_delta } => (*offset_delta, vec![]),
+            StackMapTableEntry::SameLocals1StackItem {
+                offset_delta,
+                verification_type,
+            }
+            | StackMapTableEntry::SameLocals1StackItemExtended {
+                offset_delta,
+                stack: verification_type,
+            } => (
+                *offset_delta,
+                vec![Type::from_verification_type(class, verification_type)?],
+            ),
+            StackMapTableEntry::Chop {
+                offset_delta,
+                chopped,
+            } => {
+                let chopped = *chopped as usize;
+                if chopped > declared.len() {
+                    bail!("cannot chop {chopped} locals of {declared:?}");
+                }
+                declared.truncate(declared.len() - chopped);
+                (*offset_delta, vec![])
+            }
+            StackMapTableEntry::Append {
+                offset_delta,
+                locals,
+            } => {
+                for local in locals {
+                    declared.push(Type::from_verification_type(class, local)?);
+                }
+                (*offset_delta, vec![])
+            }
+            StackMapTableEntry::Full {
+                offset_delta,
+                locals,
+                stack,
+            } => {
+                declared = locals
+                    .iter()
+                    .map(|local| Type::from_verification_type(class, local))
+                    .collect::<Result<_>>()?;
+                let stack = stack
+                    .iter()
+                    .map(|value| Type::from_verification_type(class, value))
+                    .collect::<Result<_>>()?;
+                (*offset_delta, stack)
+            }
+        };
+
+        let pc = match previous_pc {
+            Some(previous_pc) => previous_pc + offset_delta as usize + 1,
+            None => offset_delta as usize,
+        };
+        previous_pc = Some(pc);
+
+        let mut locals = Vec::new();
+        for local in &declared {
+            push_local(&mut locals, local.clone());
+        }
+        if locals.len() > max_locals {
+            bail!("stack map frame at pc {pc} exceeds max_locals {max_locals}");
+        }
+        locals.resize(max_locals, Type::Top);
+
+        // the frame has to be at the start of an instruction
+        code.instruction(pc)
+            .context(format!("stack map frame at pc {pc}"))?;
+        frames.insert(pc, Frame { locals, stack });
+    }
+
+    Ok(frames)
+}
+
+/// Inverse of [`push_local`], dropping the `Top` after longs and doubles and trailing `Top`s
+fn declared_locals(locals: &[Type]) -> Vec<Type> {
+    let mut declared = Vec::new();
+    let mut i = 0;
+    while i < locals.len() {
+        declared.push(locals[i].clone());
+        i += if locals[i].is_wide() { 2 } else { 1 };
+    }
+    while declared.last() == Some(&Type::Top) {
+        declared.pop();
+    }
+
+    declared
+}
+
+fn class_name(class: &Class, index: &CpIndex) -> Result<String> {
+    let CpInfo::Class { name_index } = class.cp_item(index)? else {
+        bail!("no class at index {index:?}")
+    };
+    Ok(class.utf8(name_index)?.to_string())
+}
+
+/// Type of the dynamic constant with the name and type at `name_and_type_index`
+fn dynamic_constant_type(class: &Class, name_and_type_index: &CpIndex) -> Result<Type> {
+    let (_, descriptor) = class.name_and_type(name_and_type_index)?;
+    Type::from_descriptor(descriptor)
+}
+
+/// Class name and descriptor of the field or method referenced at `index`
+fn member_ref<'a>(class: &'a Class, index: &CpIndex) -> Result<(String, &'a str, &'a str)> {
+    let (class_index, name_and_type_index) = match class.cp_item(index)? {
+        CpInfo::FieldRef {
+            class_index,
+            name_and_type_index,
+        }
+        | CpInfo::MethodRef {
+            class_index,
+            name_and_type_index,
+        }
+        | CpInfo::InterfaceMethodRef {
+            class_index,
+            name_and_type_index,
+        } => (class_index, name_and_type_index),
+        item => bail!("no member reference at index {index:?}: {item:?}"),
+    };
+    let (name, descriptor) = class.name_and_type(name_and_type_index)?;
+
+    Ok((class_name(class, class_index)?, name, descriptor))
+}
+
+/// Pops the arguments of `descriptor`
+fn pop_arguments(frame: &mut Frame, descriptor: &MethodDescriptor) -> Result<()> {
+    for parameter in descriptor.parameters.iter().rev() {
+        let expected = Type::from_field_type(parameter);
+        frame.pop(&expected)?;
+    }
+
+    Ok(())
+}
+
+fn push_return(frame: &mut Frame, descriptor: &MethodDescriptor) {
+    if let ReturnDescriptor::FieldType(field_type) = &descriptor.return_descriptor {
+        frame.push(Type::from_field_type(field_type));
+    }
+}
+
+/// Frame after executing `instruction` in `frame`
+fn execute(
+    class: &Class,
+    descriptor: &MethodDescriptor,
+    code: &Code,
+    mut frame: Frame,
+    instruction: &Instruction,
+    pc: usize,
+) -> Result<Flow> {
+    use Type::{Double, Float, Int, Long};
+    let object = Type::object(OBJECT);
+
+    match instruction {
+        Instruction::Nop => {}
+        Instruction::AconstNull => frame.push(Type::Null),
+        Instruction::Iconst(_) | Instruction::Bipush(_) | Instruction::Sipush(_) => frame.push(Int),
+        Instruction::Lconst(_) => frame.push(Long),
+        Instruction::Fconst(_) => frame.push(Float),
+        Instruction::Dconst(_) => frame.push(Double),
+        Instruction::Ldc(index) | Instruction::LdcW(index) => {
+            let value = match class.cp_item(index)? {
+                CpInfo::Integer(_) => Int,
+                CpInfo::Float(_) => Float,
+                CpInfo::String { .. } => Type::object("java/lang/String"),
+                CpInfo::Class { .. } => Type::object("java/lang/Class"),
+                CpInfo::MethodType { .. } => Type::object("java/lang/invoke/MethodType"),
+                CpInfo::MethodHandle { .. } => Type::object("java/lang/invoke/MethodHandle"),
+                CpInfo::Dynamic {
+                    name_and_type_index,
+                    ..
+                } => match dynamic_constant_type(class, name_and_type_index)? {
+                    Long | Double => bail!("ldc of category 2 dynamic constant, it needs ldc2_w"),
+                    value => value,
+                },
+                item @ (CpInfo::Long(_) | CpInfo::Double(_)) => {
+                    bail!("ldc of category 2 constant {item:?}, it needs ldc2_w")
+                }
+                item => bail!("ldc of {item:?}"),
+            };
+            frame.push(value);
+        }
+        Instruction::Ldc2W(index) => {
+            let value = match class.cp_item(index)? {
+                CpInfo::Long(_) => Long,
+                CpInfo::Double(_) => Double,
+                CpInfo::Dynamic {
+                    name_and_type_index,
+                    ..
+                } => match dynamic_constant_type(class, name_and_type_index)? {
+                    value @ (Long | Double) => value,
+                    _ => bail!("ldc2_w of category 1 dynamic constant, it needs ldc or ldc_w"),
+                },
+                item @ (CpInfo::Integer(_) | CpInfo::Float(_)) => {
+                    bail!("ldc2_w of category 1 constant {item:?}, it needs ldc or ldc_w")
+                }
+                item => bail!("ldc2_w of {item:?}"),
+            };
+            frame.push(value);
+        }
+
+        Instruction::Iload(index) => frame.load(*index, &Int)?,
+        Instruction::Iload0 => frame.load(0, &Int)?,
+        Instruction::Iload1 => frame.load(1, &Int)?,
+        Instruction::Iload2 => frame.load(2, &Int)?,
+        Instruction::Iload3 => frame.load(3, &Int)?,
+        Instruction::Lload(index) => frame.load(*index, &Long)?,
+        Instruction::Lload0 => frame.load(0, &Long)?,
+        Instruction::Lload1 => frame.load(1, &Long)?,
+        Instruction::Lload2 => frame.load(2, &Long)?,
+        Instruction::Lload3 => frame.load(3, &Long)?,
+        Instruction::Fload(index) => frame.load(*index, &Float)?,
+        Instruction::Fload0 => frame.load(0, &Float)?,
+        Instruction::Fload1 => frame.load(1, &Float)?,
+        Instruction::Fload2 => frame.load(2, &Float)?,
+        Instruction::Fload3 => frame.load(3, &Float)?,
+        Instruction::Dload(index) => frame.load(*index, &Double)?,
+        Instruction::Dload0 => frame.load(0, &Double)?,
+        Instruction::Dload1 => frame.load(1, &Double)?,
+        Instruction::Dload2 => frame.load(2, &Double)?,
+        Instruction::Dload3 => frame.load(3, &Double)?,
+        Instruction::Aload(index) => frame.load(*index, &object)?,
+        Instruction::Aload0 => frame.load(0, &object)?,
+        Instruction::Aload1 => frame.load(1, &object)?,
+        Instruction::Aload2 => frame.load(2, &object)?,
+        Instruction::Aload3 => frame.load(3, &object)?,
+
+        Instruction::Istore(index) => frame.store(*index, &Int)?,
+        Instruction::Istore0 => frame.store(0, &Int)?,
+        Instruction::Istore1 => frame.store(1, &Int)?,
+        Instruction::Istore2 => frame.store(2, &Int)?,
+        Instruction::Istore3 => frame.store(3, &Int)?,
+        Instruction::Lstore(index) => frame.store(*index, &Long)?,
+        Instruction::Lstore0 => frame.store(0, &Long)?,
+        Instruction::Lstore1 => frame.store(1, &Long)?,
+        Instruction::Lstore2 => frame.store(2, &Long)?,
+        Instruction::Lstore3 => frame.store(3, &Long)?,
+        Instruction::Fstore(index) => frame.store(*index, &Float)?,
+        Instruction::Fstore0 => frame.store(0, &Float)?,
+        Instruction::Fstore1 => frame.store(1, &Float)?,
+        Instruction::Fstore2 => frame.store(2, &Float)?,
+        Instruction::Fstore3 => frame.store(3, &Float)?,
+        Instruction::Dstore(index) => frame.store(*index, &Double)?,
+        Instruction::Dstore0 => frame.store(0, &Double)?,
+        Instruction::Dstore1 => frame.store(1, &Double)?,
+        Instruction::Dstore2 => frame.store(2, &Double)?,
+        Instruction::Dstore3 => frame.store(3, &Double)?,
+        Instruction::Astore(index) => frame.store(*index, &object)?,
+        Instruction::Astore0 => frame.store(0, &object)?,
+        Instruction::Astore1 => frame.store(1, &object)?,
+        Instruction::Astore2 => frame.store(2, &object)?,
+        Instruction::Astore3 => frame.store(3, &object)?,
+        Instruction::Iinc(index, _) => {
+            let value = frame.locals.get(*index as usize);
+            if value != Some(&Int) {
+                bail!("expected Int in local variable {index}, found {value:?}");
+            }
+        }
+        Instruction::Wide(instruction) => {
+            return execute(class, descriptor, code, frame, instruction, pc);
+        }
+
+        Instruction::Iaload | Instruction::Baload | Instruction::Caload | Instruction::Saload => {
+            frame.apply(&[object, Int], Some(Int))?
+        }
+        Instruction::Laload => frame.apply(&[object, Int], Some(Long))?,
+        Instruction::Faload => frame.apply(&[object, Int], Some(Float))?,
+        Instruction::Daload => frame.apply(&[object, Int], Some(Double))?,
+        Instruction::Aaload => {
+            frame.pop(&Int)?;
+            let array = frame.pop(&object)?;
+            let component = array.component()?;
+            if !component.is_reference() {
+                bail!("aaload from {array:?}");
+            }
+            frame.push(component);
+        }
+        Instruction::Iastore
+        | Instruction::Bastore
+        | Instruction::Castore
+        | Instruction::Sastore => frame.apply(&[object, Int, Int], None)?,
+        Instruction::Lastore => frame.apply(&[object, Int, Long], None)?,
+        Instruction::Fastore => frame.apply(&[object, Int, Float], None)?,
+        Instruction::Dastore => frame.apply(&[object, Int, Double], None)?,
+        Instruction::Aastore => frame.apply(&[object.clone(), Int, object], None)?,
+        Instruction::ArrayLength => frame.apply(&[object], Some(Int))?,
+
+        Instruction::Pop => {
+            frame.pop_words(1)?;
+        }
+        Instruction::Pop2 => {
+            frame.pop_words(2)?;
+        }
+        Instruction::Dup | Instruction::Dup2 => {
+            let words = if matches!(instruction, Instruction::Dup) {
+                1
+            } else {
+                2
+            };
+            let values = frame.pop_words(words)?;
+            frame.stack.extend(values.clone());
+            frame.stack.extend(values);
+        }
+        Instruction::DupX1 | Instruction::DupX2 | Instruction::Dup2X1 | Instruction::Dup2X2 => {
+            let (top_words, below_words) = match instruction {
+                Instruction::DupX1 => (1, 1),
+                Instruction::DupX2 => (1, 2),
+                Instruction::Dup2X1 => (2, 1),
+                _ => (2, 2),
+            };
+            let top = frame.pop_words(top_words)?;
+            let below = frame.pop_words(below_words)?;
+            frame.stack.extend(top.clone());
+            frame.stack.extend(below);
+            frame.stack.extend(top);
+        }
+        Instruction::Swap => {
+            let top = frame.pop_words(1)?;
+            let below = frame.pop_words(1)?;
+            frame.stack.extend(top);
+            frame.stack.extend(below);
+        }
+
+        Instruction::Iadd
+        | Instruction::Isub
+        | Instruction::Imul
+        | Instruction::Idiv
+        | Instruction::Irem
+        | Instruction::Iand
+        | Instruction::Ior
+        | Instruction::Ixor
+        | Instruction::Ishl
+        | Instruction::Ishr
+        | Instruction::Iushr => frame.apply(&[Int, Int], Some(Int))?,
+        Instruction::Ladd
+        | Instruction::Lsub
+        | Instruction::Lmul
+        | Instruction::Ldiv
+        | Instruction::Lrem
+        | Instruction::Land
+        | Instruction::Lor
+        | Instruction::Lxor => frame.apply(&[Long, Long], Some(Long))?,
+        Instruction::Lshl | Instruction::Lshr | Instruction::Lushr => {
+            frame.apply(&[Long, Int], Some(Long))?
+        }
+        Instruction::Fadd
+        | Instruction::Fsub
+        | Instruction::Fmul
+        | Instruction::Fdiv
+        | Instruction::Frem => frame.apply(&[Float, Float], Some(Float))?,
+        Instruction::Dadd
+        | Instruction::Dsub
+        | Instruction::Dmul
+        | Instruction::Ddiv
+        | Instruction::Drem => frame.apply(&[Double, Double], Some(Double))?,
+        Instruction::Ineg | Instruction::I2b | Instruction::I2c | Instruction::I2s => {
+            frame.apply(&[Int], Some(Int))?
+        }
+        Instruction::Lneg => frame.apply(&[Long], Some(Long))?,
+        Instruction::Fneg => frame.apply(&[Float], Some(Float))?,
+        Instruction::Dneg => frame.apply(&[Double], Some(Double))?,
+        Instruction::I2l => frame.apply(&[Int], Some(Long))?,
+        Instruction::I2f => frame.apply(&[Int], Some(Float))?,
+        Instruction::I2d => frame.apply(&[Int], Some(Double))?,
+        Instruction::L2i => frame.apply(&[Long], Some(Int))?,
+        Instruction::L2f => frame.apply(&[Long], Some(Float))?,
+        Instruction::L2d => frame.apply(&[Long], Some(Double))?,
+        Instruction::F2i => frame.apply(&[Float], Some(Int))?,
+        Instruction::F2l => frame.apply(&[Float], Some(Long))?,
+        Instruction::F2d => frame.apply(&[Float], Some(Double))?,
+        Instruction::D2i => frame.apply(&[Double], Some(Int))?,
+        Instruction::D2l => frame.apply(&[Double], Some(Long))?,
+        Instruction::D2f => frame.apply(&[Double], Some(Float))?,
+        Instruction::Lcmp => frame.apply(&[Long, Long], Some(Int))?,
+        Instruction::Fcmpl | Instruction::Fcmpg => frame.apply(&[Float, Float], Some(Int))?,
+        Instruction::Dcmpl | Instruction::Dcmpg => frame.apply(&[Double, Double], Some(Int))?,
+
+        Instruction::Ifeq(offset)
+        | Instruction::IfNe(offset)
+        | Instruction::Iflt(offset)
+        | Instruction::Ifge(offset)
+        | Instruction::Ifgt(offset)
+        | Instruction::Ifle(offset) => {
+            frame.pop(&Int)?;
+            return Ok(Flow::branch(frame, vec![*offset as i64], true));
+        }
+        Instruction::IfIcmpeq(offset)
+        | Instruction::IfIcmpne(offset)
+        | Instruction::IfIcmplt(offset)
+        | Instruction::IfIcmpge(offset)
+        | Instruction::IfIcmpgt(offset)
+        | Instruction::IfIcmple(offset) => {
+            frame.apply(&[Int, Int], None)?;
+            return Ok(Flow::branch(frame, vec![*offset as i64], true));
+        }
+        Instruction::IfAcmpeq(offset) | Instruction::IfAcmpne(offset) => {
+            frame.pop_reference()?;
+            frame.pop_reference()?;
+            return Ok(Flow::branch(frame, vec![*offset as i64], true));
+        }
+        Instruction::IfNull(offset) | Instruction::IfNonNull(offset) => {
+            frame.pop_reference()?;
+            return Ok(Flow::branch(frame, vec![*offset as i64], true));
+        }
+        Instruction::Goto(offset) => {
+            return Ok(Flow::branch(frame, vec![*offset as i64], false));
+        }
+        Instruction::GotoW(offset) => {
+            return Ok(Flow::branch(frame, vec![*offset as i64], false));
+        }
+        Instruction::TableSwitch {
+            default,
+            jump_offsets,
+            ..
+        } => {
+            frame.pop(&Int)?;
+            let mut targets = vec![*default as i64];
+            targets.extend(jump_offsets.iter().map(|offset| *offset as i64));
+            return Ok(Flow::branch(frame, targets, false));
+        }
+        Instruction::LookupSwitch {
+            default,
+            offset_pairs,
+            ..
+        } => {
+            frame.pop(&Int)?;
+            let mut targets = vec![*default as i64];
+            targets.extend(offset_pairs.iter().map(|(_, offset)| *offset as i64));
+            return Ok(Flow::branch(frame, targets, false));
+        }
+
+        Instruction::Ireturn
+        | Instruction::Lreturn
+        | Instruction::Freturn
+        | Instruction::Dreturn
+        | Instruction::Areturn => {
+            let ReturnDescriptor::FieldType(field_type) = &descriptor.return_descriptor else {
+                bail!("value returned from void method");
+            };
+            let expected = Type::from_field_type(field_type);
+            let matches_return_type = match instruction {
+                Instruction::Ireturn => expected == Int,
+                Instruction::Lreturn => expected == Long,
+                Instruction::Freturn => expected == Float,
+                Instruction::Dreturn => expected == Double,
+                _ => matches!(expected, Type::Object(_)),
+            };
+            if !matches_return_type {
+                bail!("{instruction:?} in method returning {expected:?}");
+            }
+            frame.pop(&expected)?;
+            return Ok(Flow::end(frame));
+        }
+        Instruction::Return => {
+            if !descriptor.is_void() {
+                bail!("return in method returning a value");
+            }
+            if frame.locals.contains(&Type::UninitializedThis) {
+                bail!("constructor returns before initializing this");
+            }
+            return Ok(Flow::end(frame));
+        }
+        Instruction::Athrow => {
+            frame.pop(&object)?;
+            return Ok(Flow::end(frame));
+        }
+
+        Instruction::GetStatic(index) => {
+            let (_, _, field_descriptor) = member_ref(class, index)?;
+            frame.push(Type::from_descriptor(field_descriptor)?);
+        }
+        Instruction::PutStatic(index) => {
+            let (_, _, field_descriptor) = member_ref(class, index)?;
+            frame.pop(&Type::from_descriptor(field_descriptor)?)?;
+        }
+        Instruction::GetField(index) => {
+            let (_, _, field_descriptor) = member_ref(class, index)?;
+            frame.pop(&object)?;
+            frame.push(Type::from_descriptor(field_descriptor)?);
+        }
+        Instruction::PutField(index) => {
+            let (_, _, field_descriptor) = member_ref(class, index)?;
+            frame.pop(&Type::from_descriptor(field_descriptor)?)?;
+            // constructors may assign fields of this before calling the super constructor
+            let receiver = frame.pop_reference()?;
+            if matches!(receiver, Type::Uninitialized(_)) {
+                bail!("putfield on uninitialized {receiver:?}");
+            }
+        }
+
+        Instruction::InvokeStatic(index) => {
+            let (_, _, method_descriptor) = member_ref(class, index)?;
+            let method_descriptor = MethodDescriptor::new(method_descriptor)?;
+            pop_arguments(&mut frame, &method_descriptor)?;
+            push_return(&mut frame, &method_descriptor);
+        }
+        Instruction::InvokeVirtual(index) | Instruction::InvokeInterface(index, _) => {
+            let (_, _, method_descriptor) = member_ref(class, index)?;
+            let method_descriptor = MethodDescriptor::new(method_descriptor)?;
+            pop_arguments(&mut frame, &method_descriptor)?;
+            frame.pop(&object)?;
+            push_return(&mut frame, &method_descriptor);
+        }
+        Instruction::InvokeSpecial(index) => {
+            let (owner, name, method_descriptor) = member_ref(class, index)?;
+            let method_descriptor = MethodDescriptor::new(method_descriptor)?;
+            pop_arguments(&mut frame, &method_descriptor)?;
+            if name == "<init>" {
+                let receiver = frame.pop_reference()?;
+                let initialized = match receiver {
+                    Type::UninitializedThis => Type::Object(class.identifier().with_slashes()?),
+                    Type::Uninitialized(offset) => match code.instruction(offset.into())? {
+                        Instruction::New(index) => Type::Object(class_name(class, &index)?),
+                        instruction => bail!("{instruction:?} at pc {offset} is no new"),
+                    },
+                    _ => bail!("constructor of {owner} called on {receiver:?}"),
+                };
+                frame.initialize(&receiver, initialized);
+            } else {
+                frame.pop(&object)?;
+            }
+            push_return(&mut frame, &method_descriptor);
+        }
+        Instruction::InvokeDynamic(index) => {
+            let CpInfo::InvokeDynamic {
+                name_and_type_index,
+                ..
+            } = class.cp_item(index)?
+            else {
+                bail!("no invokedynamic at index {index:?}");
+            };
+            let (_, method_descriptor) = class.name_and_type(name_and_type_index)?;
+            let method_descriptor = MethodDescriptor::new(method_descriptor)?;
+            pop_arguments(&mut frame, &method_descriptor)?;
+            push_return(&mut frame, &method_descriptor);
+        }
+
+        Instruction::New(_) => frame.push(Type::Uninitialized(pc as u16)),
+        Instruction::Newarray(array_type) => {
+            let component = match array_type {
+                4 => "Z",
+                5 => "C",
+                6 => "F",
+                7 => "D",
+                8 => "B",
+                9 => "S",
+                10 => "I",
+                11 => "J",
+                _ => bail!("invalid array type {array_type}"),
+            };
+            frame.apply(&[Int], Some(Type::Object(format!("[{component}"))))?;
+        }
+        Instruction::Anewarray(index) => {
+            let component = class_name(class, index)?;
+            let array = if component.starts_with('[') {
+                format!("[{component}")
+            } else {
+                format!("[L{component};")
+            };
+            frame.apply(&[Int], Some(Type::Object(array)))?;
+        }
+        Instruction::Multianewarray(index, dimensions) => {
+            for _ in 0..*dimensions {
+                frame.pop(&Int)?;
+            }
+            frame.push(Type::Object(class_name(class, index)?));
+        }
+        Instruction::Checkcast(index) => {
+            frame.pop(&object)?;
+            frame.push(Type::Object(class_name(class, index)?));
+        }
+        Instruction::Instanceof(_) => frame.apply(&[object], Some(Int))?,
+        Instruction::MonitorEnter | Instruction::MonitorExit => frame.apply(&[object], None)?,
+    }
+
+    Ok(Flow::next(frame))
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use std::collections::HashSet;
+
+    use common::ClassIdentifier;
+    use parser::class::{
+        ClassFile, access_flags::AccessFlag, attribute::Attribute, constant_pool::ConstantPool,
+        method::AccessFlag as MethodAccessFlag,
+    };
+
+    use super::*;
+
+    /// Class `Test` with the static method `run` with `descriptor`, `code` and `stack_map`.
+    /// Its constant pool holds the double 1.5 at index 7 and the int 42 at index 9.
+    pub(in crate::thread) fn class(
+        descriptor: &str,
+        code: Vec<u8>,
+        stack_map: Vec<StackMapTableEntry>,
+    ) -> Class {
+        class_with(descriptor, code, stack_map, vec![], vec![])
+    }
+
+    /// Like [`class`], with `constants` appended to the constant pool from index 10 on and the
+    /// class `attributes`
+    pub(in crate::thread) fn class_with(
+        descriptor: &str,
+        code: Vec<u8>,
+        stack_map: Vec<StackMapTableEntry>,
+        constants: Vec<CpInfo>,
+        attributes: Vec<Attribute>,
+    ) -> Class {
+        let method = Method {
+            access_flags: HashSet::from([MethodAccessFlag::Public, MethodAccessFlag::Static]),
+            name_index: CpIndex(3),
+            descriptor_index: CpIndex(4),
+            attributes: vec![Attribute::Code {
+                attribute_name_index: CpIndex(5),
+                attribute_length: 0,
+                max_stack: 2,
+                max_locals: 1,
+                code,
+                exception_table: vec![],
+                attributes: vec![Attribute::StackMapTable {
+                    attribute_name_index: CpIndex(6),
+                    attribute_length: 0,
+                    entries: stack_map,
+                }],
+            }],
+        };
+        let class_file = ClassFile {
+            minor_version: 0,
+            major_version: 61,
+            constant_pool: ConstantPool {
+                infos: vec![
+                    CpInfo::Reserved,
+                    CpInfo::Utf8("Test".to_string()),
+                    CpInfo::Class {
+                        name_index: CpIndex(1),
+                    },
+                    CpInfo::Utf8("run".to_string()),
+                    CpInfo::Utf8(descriptor.to_string()),
+                    CpInfo::Utf8("Code".to_string()),
+                    CpInfo::Utf8("StackMapTable".to_string()),
+                    CpInfo::Double(1.5),
+                    CpInfo::Reserved,
+                    CpInfo::Integer(42),
+                ]
+                .into_iter()
+                .chain(constants)
+                .collect(),
+            },
+            access_flags: HashSet::from([AccessFlag::Public]),
+            this_class: CpIndex(2),
+            super_class: CpIndex(0),
+            interfaces: vec![],
+            fields: vec![],
+            methods: vec![method],
+            attributes,
+        };
+
+        Class::new(ClassIdentifier::new("Test").unwrap(), class_file)
+    }
+
+    #[test]
+    fn valid_branches() {
+        let code = vec![
+            0x03, // iconst_0
+            0x99, 0x00, 0x05, // ifeq +5
+            0x04, // iconst_1
+            0xac, // ireturn
+            0x05, // iconst_2
+            0xac, // ireturn
+        ];
+        let stack_map = vec![StackMapTableEntry::Same { offset_delta: 6 }];
+
+        verify(&class("()I", code, stack_map)).unwrap();
+    }
+
+    #[test]
+    fn ldc_of_wrong_category() {
+        let code = vec![
+            0x12, 0x07, // ldc #7
+            0xaf, // dreturn
+        ];
+        let err = verify(&class("()D", code, vec![])).unwrap_err();
+        assert!(err.to_string().contains("VerifyError"), "{err}");
+        assert!(err.to_string().contains("needs ldc2_w"), "{err}");
+
+        let code = vec![
+            0x14, 0x00, 0x09, // ldc2_w #9
+            0xad, // lreturn
+        ];
+        let err = verify(&class("()J", code, vec![])).unwrap_err();
+        assert!(err.to_string().contains("needs ldc or ldc_w"), "{err}");
+    }
+
+    #[test]
+    fn wrong_return_type() {
+        let code = vec![
+            0x03, // iconst_0
+            0xb0, // areturn
+        ];
+
+        let err = verify(&class("()Ljava/lang/Object;", code, vec![])).unwrap_err();
+        assert!(err.to_string().contains("VerifyError"), "{err}");
+    }
+
+    #[test]
+    fn inconsistent_locals_at_branch_target() {
+        let code = vec![
+            0x03, // iconst_0
+            0x99, 0x00, 0x08, // ifeq +8
+            0x0b, // fconst_0
+            0x43, // fstore_0
+            0xa7, 0x00, 0x03, // goto +3
+            0xb1, // return
+        ];
+        // declares an int in local 0 which neither branch stores
+        let stack_map = vec![StackMapTableEntry::Append {
+            offset_delta: 9,
+            locals: vec![VerificationType::Integer],
+        }];
+
+        let err = verify(&class("()V", code, stack_map)).unwrap_err();
+        assert!(
+            err.to_string().contains("doesn't match stack map frame"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn missing_frame_after_goto() {
+        let code = vec![
+            0xa7, 0x00, 0x04, // goto +4
+            0x00, // nop, unreachable without a frame
+            0xb1, // return
+        ];
+        let stack_map = vec![StackMapTableEntry::Same { offset_delta: 4 }];
+
+        let err = verify(&class("()V", code, stack_map)).unwrap_err();
+        assert!(
+            err.to_string().contains("no stack map frame at pc 3"),
+            "{err}"
+        );
+    }
+}
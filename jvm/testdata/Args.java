@@ -0,0 +1,11 @@
+public class Args {
+    static int count;
+    static String first;
+    static String second;
+
+    public static void main(String[] args) {
+        count = args.length;
+        first = args[0];
+        second = args[1];
+    }
+}
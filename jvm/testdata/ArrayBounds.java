@@ -0,0 +1,48 @@
+public class ArrayBounds {
+    static int caught;
+    static String message;
+
+    static void run() {
+        int[] ints = new int[2];
+        long[] longs = new long[2];
+        byte[] bytes = new byte[2];
+        Object[] objects = new Object[2];
+
+        try {
+            ints[2] = 1;
+        } catch (ArrayIndexOutOfBoundsException e) {
+            caught++;
+        }
+        try {
+            ints[0] = ints[-1];
+        } catch (ArrayIndexOutOfBoundsException e) {
+            caught++;
+        }
+        try {
+            longs[-1] = 1L;
+        } catch (ArrayIndexOutOfBoundsException e) {
+            caught++;
+        }
+        try {
+            bytes[2] = bytes[0];
+        } catch (ArrayIndexOutOfBoundsException e) {
+            caught++;
+        }
+        try {
+            bytes[0] = bytes[3];
+        } catch (ArrayIndexOutOfBoundsException e) {
+            caught++;
+        }
+        try {
+            objects[-1] = objects[0];
+        } catch (ArrayIndexOutOfBoundsException e) {
+            caught++;
+        }
+        try {
+            objects[0] = objects[5];
+        } catch (ArrayIndexOutOfBoundsException e) {
+            caught++;
+            message = e.getMessage();
+        }
+    }
+}
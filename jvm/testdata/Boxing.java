@@ -0,0 +1,21 @@
+public class Boxing {
+    static boolean smallSame;
+    static boolean largeSame;
+    static int sum;
+    static boolean primitive;
+    static boolean wrapperPrimitive;
+    static boolean sameClass;
+
+    static void run() {
+        smallSame = Integer.valueOf(100) == Integer.valueOf(100);
+        largeSame = Integer.valueOf(1000) == Integer.valueOf(1000);
+
+        Integer boxed = 20;
+        Long wide = 22L;
+        sum = boxed + (int) (long) wide;
+
+        primitive = int.class.isPrimitive();
+        wrapperPrimitive = Integer.class.isPrimitive();
+        sameClass = int.class == (Object) Integer.class;
+    }
+}
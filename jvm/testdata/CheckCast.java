@@ -0,0 +1,37 @@
+public class CheckCast {
+    static int upcast;
+    static int arrayUpcast;
+    static int failedCast;
+    static int failedArrayCast;
+    static int nullCast;
+
+    interface Shape {}
+
+    static class Square implements Shape {}
+
+    static void run() {
+        Object square = new Square();
+        Shape shape = (Shape) square;
+        upcast = shape == square ? 1 : 0;
+
+        Object strings = new String[1];
+        Object[] objects = (Object[]) strings;
+        arrayUpcast = objects == strings ? 1 : 0;
+
+        try {
+            String string = (String) square;
+        } catch (ClassCastException e) {
+            failedCast = 1;
+        }
+
+        try {
+            int[] ints = (int[]) strings;
+        } catch (ClassCastException e) {
+            failedArrayCast = 1;
+        }
+
+        Object nothing = null;
+        Square nullSquare = (Square) nothing;
+        nullCast = nullSquare == null ? 1 : 0;
+    }
+}
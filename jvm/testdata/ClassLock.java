@@ -0,0 +1,18 @@
+public class ClassLock {
+    static int counter;
+
+    static synchronized void incrementStatic() {
+        int value = counter;
+        counter = value + 1;
+    }
+
+    static void count() {
+        for (int i = 0; i < 100; i++) {
+            synchronized (ClassLock.class) {
+                int value = counter;
+                counter = value + 1;
+            }
+            incrementStatic();
+        }
+    }
+}
@@ -0,0 +1,22 @@
+public class ClassMirrors {
+    static int sameObject;
+    static int sameClass;
+    static int sameHash;
+    static int locked;
+
+    static synchronized void increment() {
+        locked++;
+    }
+
+    static void run() {
+        Object object = new Object();
+        sameObject = object.getClass() == object.getClass() ? 1 : 0;
+        sameClass = "value".getClass() == String.class ? 1 : 0;
+        sameHash = ClassMirrors.class.hashCode() == ClassMirrors.class.hashCode() ? 1 : 0;
+
+        // static synchronized methods lock the same monitor
+        synchronized (ClassMirrors.class) {
+            increment();
+        }
+    }
+}
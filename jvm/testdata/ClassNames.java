@@ -0,0 +1,19 @@
+import java.util.Map;
+
+public class ClassNames {
+    static String name;
+    static String simpleName;
+    static String canonicalName;
+    static String arrayName;
+    static boolean sameArrayClass;
+
+    static void run() {
+        name = Map.Entry.class.getName();
+        simpleName = Map.Entry.class.getSimpleName();
+        canonicalName = Map.Entry.class.getCanonicalName();
+
+        Object strings = new String[1][];
+        arrayName = strings.getClass().getName();
+        sameArrayClass = strings.getClass() == String[][].class;
+    }
+}
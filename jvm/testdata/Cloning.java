@@ -0,0 +1,40 @@
+public class Cloning implements Cloneable {
+    static int distinct;
+    static int sameValue;
+    static int sameName;
+    static int arrayDistinct;
+    static int arrayCopied;
+    static int notCloneable;
+
+    int value;
+    String name;
+
+    static class Plain {
+        Object copy() throws CloneNotSupportedException {
+            return clone();
+        }
+    }
+
+    public static void run() throws CloneNotSupportedException {
+        Cloning original = new Cloning();
+        original.value = 42;
+        original.name = "original";
+        Cloning copy = (Cloning) original.clone();
+        copy.value = 7;
+        distinct = copy != original ? 1 : 0;
+        sameValue = original.value == 42 && copy.value == 7 ? 1 : 0;
+        sameName = copy.name == original.name ? 1 : 0;
+
+        int[] numbers = {1, 2, 3};
+        int[] copied = numbers.clone();
+        copied[0] = 10;
+        arrayDistinct = copied != numbers && numbers[0] == 1 ? 1 : 0;
+        arrayCopied = copied.length == 3 && copied[0] == 10 && copied[2] == 3 ? 1 : 0;
+
+        try {
+            new Plain().copy();
+        } catch (CloneNotSupportedException e) {
+            notCloneable = 1;
+        }
+    }
+}
@@ -0,0 +1,46 @@
+public class Concat {
+    static String described;
+    static String mixed;
+    static String caught;
+
+    static class Point {
+        int x = 1;
+        int y = 2;
+
+        @Override
+        public String toString() {
+            return "(" + x + ", " + y + ")";
+        }
+    }
+
+    static class Unprintable {
+        @Override
+        public String toString() {
+            throw new IllegalStateException("no string");
+        }
+    }
+
+    static String describe(int count, String name) {
+        return count + " " + name;
+    }
+
+    static void run() {
+        described = describe(3, "apples");
+    }
+
+    static String join(char c, boolean b, long l, float f, String s, Object o) {
+        return c + "=" + b + ", " + l + ", " + f + ", " + s + ", " + o;
+    }
+
+    static void runMixed() {
+        mixed = join('c', true, 5L, 1e10f, null, new Point());
+    }
+
+    static void runThrowing() {
+        try {
+            caught = "value: " + new Unprintable();
+        } catch (IllegalStateException e) {
+            caught = e.getMessage();
+        }
+    }
+}
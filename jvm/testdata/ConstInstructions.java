@@ -0,0 +1,25 @@
+public class ConstInstructions {
+    static int intMinusOne;
+    static int bytePush;
+    static int shortPush;
+    static long longZero;
+    static long longOne;
+    static float floatZero;
+    static float floatOne;
+    static float floatTwo;
+    static double doubleZero;
+    static double doubleOne;
+
+    static void run() {
+        intMinusOne = -1;
+        bytePush = -100;
+        shortPush = -1000;
+        longZero = 0L;
+        longOne = 1L;
+        floatZero = 0f;
+        floatOne = 1f;
+        floatTwo = 2f;
+        doubleZero = 0.0;
+        doubleOne = 1.0;
+    }
+}
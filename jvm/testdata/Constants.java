@@ -0,0 +1,15 @@
+public class Constants {
+    static final int NEGATIVE_INT = -100000;
+    static final long NEGATIVE_LONG = -5000000000L;
+    static final double RATIO = 1.25;
+
+    static int loadedInt;
+    static long loadedLong;
+    static double loadedDouble;
+
+    static void load() {
+        loadedInt = -100000;
+        loadedLong = -5000000000L;
+        loadedDouble = -2.5;
+    }
+}
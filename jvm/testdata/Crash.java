@@ -0,0 +1,11 @@
+public class Crash {
+    static native void crash();
+
+    static void inner() {
+        crash();
+    }
+
+    static void outer() {
+        inner();
+    }
+}
@@ -0,0 +1,31 @@
+public class Daemons {
+    static int finished;
+
+    static void start(Thread daemon, Thread thread) {
+        daemon.start();
+        thread.start();
+    }
+
+    static class Forever implements Runnable {
+        public void run() {
+            while (true) {
+                try {
+                    Thread.sleep(10);
+                } catch (InterruptedException e) {
+                    return;
+                }
+            }
+        }
+    }
+
+    static class Finishing implements Runnable {
+        public void run() {
+            try {
+                Thread.sleep(50);
+            } catch (InterruptedException e) {
+                return;
+            }
+            finished = 1;
+        }
+    }
+}
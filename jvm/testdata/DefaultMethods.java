@@ -0,0 +1,39 @@
+public class DefaultMethods {
+    static int viaInterface;
+    static int viaClass;
+    static int overridden;
+    static int mostSpecific;
+
+    interface Greeter {
+        default int greet() {
+            return 1;
+        }
+    }
+
+    interface LoudGreeter extends Greeter {
+        default int greet() {
+            return 2;
+        }
+    }
+
+    static class Plain implements Greeter {}
+
+    static class Loud implements LoudGreeter, Greeter {}
+
+    static class Custom implements Greeter {
+        public int greet() {
+            return 3;
+        }
+    }
+
+    static void run() {
+        Greeter plain = new Plain();
+        viaInterface = plain.greet();
+        viaClass = new Plain().greet();
+
+        Greeter custom = new Custom();
+        overridden = custom.greet();
+
+        mostSpecific = new Loud().greet();
+    }
+}
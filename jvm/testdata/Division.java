@@ -0,0 +1,33 @@
+public class Division {
+    static int caught;
+
+    static int zero() {
+        return 0;
+    }
+
+    static void run() {
+        int i = 1;
+        long l = 1L;
+
+        try {
+            i = i / zero();
+        } catch (ArithmeticException e) {
+            caught++;
+        }
+        try {
+            i = i % zero();
+        } catch (ArithmeticException e) {
+            caught++;
+        }
+        try {
+            l = l / zero();
+        } catch (ArithmeticException e) {
+            caught++;
+        }
+        try {
+            l = l % zero();
+        } catch (ArithmeticException e) {
+            caught++;
+        }
+    }
+}
@@ -0,0 +1,44 @@
+public class Exceptions {
+    static int result;
+    static Throwable caught;
+
+    static class TestException extends Throwable {
+        @Override
+        public Throwable fillInStackTrace() {
+            return this;
+        }
+    }
+
+    static void thrower() throws TestException {
+        throw new TestException();
+    }
+
+    static void catchInSameMethod() {
+        try {
+            throw new TestException();
+        } catch (TestException e) {
+            result = 1;
+        }
+    }
+
+    static void catchInCaller() {
+        try {
+            thrower();
+            result = 0;
+        } catch (TestException e) {
+            result = 2;
+        }
+    }
+
+    static void suppressInVmException() {
+        int zero = 0;
+        try {
+            result = 1 / zero;
+        } catch (ArithmeticException e) {
+            e.addSuppressed(new TestException());
+            e.initCause(new TestException());
+            result = e.getCause() != null ? 1 : 0;
+            caught = e;
+        }
+    }
+}
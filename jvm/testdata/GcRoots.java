@@ -0,0 +1,17 @@
+public class GcRoots {
+    static Object[] garbage;
+
+    static {
+        allocate();
+    }
+
+    public static void allocate() {
+        for (int i = 0; i < 50; i++) {
+            garbage = new Object[1];
+        }
+    }
+
+    public static int length(String s) {
+        return s.length();
+    }
+}
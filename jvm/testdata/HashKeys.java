@@ -0,0 +1,59 @@
+import java.util.HashMap;
+import java.util.HashSet;
+
+public class HashKeys {
+    static int found;
+    static int replaced;
+    static int size;
+    static int setSize;
+    static int overriddenHash;
+    static int identityHash;
+    static int overriddenToString;
+
+    static class Key {
+        final int id;
+
+        Key(int id) {
+            this.id = id;
+        }
+
+        @Override
+        public int hashCode() {
+            return id % 3;
+        }
+
+        @Override
+        public boolean equals(Object other) {
+            return other instanceof Key && ((Key) other).id == id;
+        }
+
+        @Override
+        public String toString() {
+            return "key";
+        }
+    }
+
+    public static void run() {
+        HashMap<Key, Integer> map = new HashMap<>();
+        for (int i = 0; i < 10; i++) {
+            map.put(new Key(i), i * 10);
+        }
+        Integer previous = map.put(new Key(4), 44);
+        found = map.get(new Key(7));
+        replaced = previous + map.get(new Key(4));
+        size = map.size();
+
+        HashSet<Key> set = new HashSet<>();
+        set.add(new Key(1));
+        set.add(new Key(1));
+        set.add(new Key(2));
+        setSize = set.size();
+
+        Object key = new Key(5);
+        overriddenHash = key.hashCode();
+        Object plain = new Object();
+        boolean stable = plain.hashCode() == plain.hashCode();
+        identityHash = stable && plain.hashCode() != new Object().hashCode() ? 1 : 0;
+        overriddenToString = key.toString().equals("key") ? 1 : 0;
+    }
+}
@@ -0,0 +1,11 @@
+public class IdentityHash {
+    static int stable;
+    static int distinct;
+
+    static void run() {
+        Object first = new Object();
+        Object second = new Object();
+        stable = first.hashCode() == first.hashCode() ? 1 : 0;
+        distinct = first.hashCode() != second.hashCode() ? 1 : 0;
+    }
+}
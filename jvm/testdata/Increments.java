@@ -0,0 +1,25 @@
+public class Increments {
+    static int wrapped;
+    static int wrappedDown;
+    static int wideIncrement;
+    static int wideDecrement;
+
+    public static void run() {
+        int max = Integer.MAX_VALUE;
+        max++;
+        wrapped = max;
+
+        int min = Integer.MIN_VALUE;
+        min--;
+        wrappedDown = min;
+
+        // constants outside of a byte need the wide form of iinc
+        int up = 5;
+        up += 1000;
+        wideIncrement = up;
+
+        int down = 5;
+        down -= 30000;
+        wideDecrement = down;
+    }
+}
@@ -0,0 +1,32 @@
+public class InitCycle {
+    static int a;
+    static int b;
+    static int slow;
+
+    static class A {
+        static int value = B.value + 1;
+    }
+
+    static class B {
+        static int value = A.value + 10;
+    }
+
+    static class Slow {
+        static int value;
+
+        static {
+            for (int i = 0; i < 2000; i++) {
+                value++;
+            }
+        }
+    }
+
+    static void run() {
+        a = A.value;
+        b = B.value;
+    }
+
+    static void readSlow() {
+        slow = Slow.value;
+    }
+}
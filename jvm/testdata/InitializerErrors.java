@@ -0,0 +1,39 @@
+public class InitializerErrors {
+    static int caught;
+    static boolean causeKept;
+    static boolean errorKept;
+
+    static class Failing {
+        static int value = fail();
+
+        static int fail() {
+            throw new IllegalStateException("failing");
+        }
+    }
+
+    static class FailingError {
+        static int value = fail();
+
+        static int fail() {
+            throw new Error("failing");
+        }
+    }
+
+    static void run() {
+        try {
+            caught = Failing.value;
+        } catch (ExceptionInInitializerError e) {
+            caught = 1;
+            causeKept = e.getCause() instanceof IllegalStateException && e.getMessage() == null;
+        }
+
+        try {
+            caught += FailingError.value;
+        } catch (ExceptionInInitializerError e) {
+            caught += 10;
+        } catch (Error e) {
+            caught += 2;
+            errorKept = "failing".equals(e.getMessage());
+        }
+    }
+}
@@ -0,0 +1,32 @@
+public class InstanceOf {
+    static int intArrayIsObject;
+    static int intArrayIsLongArray;
+    static int stringArrayIsObjectArray;
+    static int stringArrayIsIntArray;
+    static int implementsTransitively;
+    static int implementsUnrelated;
+
+    interface Root {}
+
+    interface Child extends Root {}
+
+    interface Unrelated {}
+
+    static class Base implements Child {}
+
+    static class Impl extends Base {}
+
+    static void run() {
+        Object ints = new int[1];
+        intArrayIsObject = ints instanceof Object ? 1 : 0;
+        intArrayIsLongArray = ints instanceof long[] ? 1 : 0;
+
+        Object strings = new String[1];
+        stringArrayIsObjectArray = strings instanceof Object[] ? 1 : 0;
+        stringArrayIsIntArray = strings instanceof int[] ? 1 : 0;
+
+        Object impl = new Impl();
+        implementsTransitively = impl instanceof Root ? 1 : 0;
+        implementsUnrelated = impl instanceof Unrelated ? 1 : 0;
+    }
+}
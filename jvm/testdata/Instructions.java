@@ -0,0 +1,64 @@
+public class Instructions {
+    static double doubleSum;
+    static float floatDifference;
+    static int shortOverflow;
+    static int intOverflow;
+    static long longOverflow;
+    static float returnedFloat;
+    static int intArrayLength;
+    static int intInnerLength;
+    static int intElement;
+    static int stringInnerLength;
+    static boolean stringElementNull;
+    static boolean partialInnerNull;
+
+    static float half(float value) {
+        return value / 2f;
+    }
+
+    static void run() {
+        floats();
+        doubles();
+
+        int big = 40000;
+        shortOverflow = (short) big;
+        int max = Integer.MAX_VALUE;
+        intOverflow = max + 1;
+        long min = Long.MIN_VALUE;
+        longOverflow = min - 1;
+        returnedFloat = half(3f);
+
+        arrays();
+    }
+
+    static void floats() {
+        float e = 5f;
+        float f = 1.5f;
+        float g = e - f;
+        float h = g;
+        floatDifference = h;
+    }
+
+    static void doubles() {
+        double a = 1.5;
+        double b = 2.25;
+        double c = a + b;
+        double d = c * 2.0;
+        doubleSum = d;
+    }
+
+    static void arrays() {
+        int[][] ints = new int[2][3];
+        ints[1][2] = 7;
+        intArrayLength = ints.length;
+        intInnerLength = ints[1].length;
+        intElement = ints[1][2] + ints[0][0];
+
+        String[][] strings = new String[2][4];
+        stringInnerLength = strings[1].length;
+        stringElementNull = strings[1][3] == null;
+
+        int[][][] partial = new int[2][3][];
+        partialInnerNull = partial[1][2] == null;
+    }
+}
@@ -0,0 +1,37 @@
+public class InterfaceNatives {
+    static int stableHash;
+    static int synchronizedCall;
+
+    interface Hashed {
+        // redeclared, so that calls go through invokeinterface to the native Object.hashCode
+        int hashCode();
+
+        default int hash() {
+            return hashCode();
+        }
+    }
+
+    interface Counter {
+        int next();
+    }
+
+    static class Plain implements Hashed {
+    }
+
+    static class LockedCounter implements Counter {
+        int count;
+
+        public synchronized int next() {
+            return ++count;
+        }
+    }
+
+    static void run() {
+        Hashed hashed = new Plain();
+        stableHash = hashed.hash() == hashed.hash() ? 1 : 0;
+
+        Counter counter = new LockedCounter();
+        counter.next();
+        synchronizedCall = counter.next();
+    }
+}
@@ -0,0 +1,14 @@
+public class Intern {
+    static String field = "hello";
+    static int sameLiteral;
+    static int constantField;
+    static int interned;
+
+    static void run() {
+        String first = "hello";
+        String second = "hello";
+        sameLiteral = first == second ? 1 : 0;
+        constantField = first == field ? 1 : 0;
+        interned = first.intern() == second ? 1 : 0;
+    }
+}
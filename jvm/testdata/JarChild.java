@@ -0,0 +1,2 @@
+public class JarChild extends JarParent {
+}
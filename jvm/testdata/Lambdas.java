@@ -0,0 +1,67 @@
+import java.util.function.Function;
+import java.util.function.IntBinaryOperator;
+import java.util.function.IntUnaryOperator;
+import java.util.function.Supplier;
+
+public class Lambdas {
+    static int ran;
+    static int captured;
+    static int constructed;
+    static int combined;
+    static int absolute;
+    static long widened;
+    static int unboxed;
+
+    int offset = 40;
+
+    static void increment() {
+        ran++;
+    }
+
+    int add(int value) {
+        return offset + value;
+    }
+
+    static long twice(long value) {
+        return value * 2;
+    }
+
+    static Integer negate(Integer value) {
+        return -value;
+    }
+
+    static void run() {
+        Runnable runnable = () -> ran++;
+        runnable.run();
+        runnable.run();
+    }
+
+    static void runCapturing() {
+        Lambdas lambdas = new Lambdas();
+        int value = 2;
+        Runnable runnable = () -> captured = lambdas.add(value);
+        runnable.run();
+    }
+
+    static void runMethodReferences() {
+        Runnable runnable = Lambdas::increment;
+        runnable.run();
+
+        Supplier<Lambdas> supplier = Lambdas::new;
+        constructed = supplier.get().offset;
+
+        IntBinaryOperator operator = Math::max;
+        combined = operator.applyAsInt(3, 7);
+    }
+
+    static void runAdapted() {
+        Function<Integer, Integer> abs = Math::abs;
+        absolute = abs.apply(-5);
+
+        Function<Integer, Long> twice = Lambdas::twice;
+        widened = twice.apply(21);
+
+        IntUnaryOperator negate = Lambdas::negate;
+        unboxed = negate.applyAsInt(8);
+    }
+}
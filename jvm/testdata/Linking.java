@@ -0,0 +1,23 @@
+public class Linking {
+    static int initialized;
+
+    static class Lazy {
+        static {
+            initialized++;
+        }
+
+        static void touch() {}
+    }
+
+    static void createArray() {
+        Lazy[] lazies = new Lazy[2];
+    }
+
+    static void loadClass() {
+        Class<?> lazy = Lazy.class;
+    }
+
+    static void callStatic() {
+        Lazy.touch();
+    }
+}
@@ -0,0 +1,15 @@
+public class Locals {
+    static int result;
+
+    static int store(int a, int b) {
+        int unused1;
+        int unused2;
+        int unused3;
+        int sum = a + b;
+        return sum;
+    }
+
+    static void run() {
+        result = store(20, 22);
+    }
+}
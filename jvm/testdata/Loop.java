@@ -0,0 +1,11 @@
+public class Loop {
+    static int result;
+
+    static void run() {
+        int sum = 0;
+        for (int i = 0; i < 100; i++) {
+            sum += i;
+        }
+        result = sum;
+    }
+}
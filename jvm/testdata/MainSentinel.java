@@ -0,0 +1,13 @@
+public class MainSentinel {
+    static int sentinel;
+
+    public static void main(String[] args) {
+        sentinel = 42;
+    }
+
+    static class NoMain {}
+
+    static class InstanceMain {
+        public void main(String[] args) {}
+    }
+}
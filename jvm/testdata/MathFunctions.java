@@ -0,0 +1,19 @@
+public class MathFunctions {
+    static double sqrt;
+    static double pow;
+    static double sin;
+    static double log;
+    static int absMin;
+    static long absMinLong;
+    static double absNegativeZero;
+
+    public static void run() {
+        sqrt = Math.sqrt(2.0);
+        pow = Math.pow(2, 10);
+        sin = Math.sin(Math.PI / 2);
+        log = Math.log(Math.E);
+        absMin = Math.abs(Integer.MIN_VALUE);
+        absMinLong = Math.abs(Long.MIN_VALUE);
+        absNegativeZero = 1 / Math.abs(-0.0);
+    }
+}
@@ -0,0 +1,9 @@
+public class NativeCall {
+    static int result;
+
+    static native int answer(int value);
+
+    static void run() {
+        result = answer(21);
+    }
+}
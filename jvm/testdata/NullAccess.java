@@ -0,0 +1,48 @@
+public class NullAccess {
+    static int caught;
+    static NullPointerException exception;
+
+    int value;
+
+    int value() {
+        return value;
+    }
+
+    static void run() {
+        NullAccess access = null;
+        int[] ints = null;
+        Object[] objects = null;
+
+        try {
+            access.value();
+        } catch (NullPointerException e) {
+            caught++;
+            exception = e;
+        }
+        try {
+            int value = access.value;
+        } catch (NullPointerException e) {
+            caught++;
+        }
+        try {
+            access.value = 1;
+        } catch (NullPointerException e) {
+            caught++;
+        }
+        try {
+            int length = ints.length;
+        } catch (NullPointerException e) {
+            caught++;
+        }
+        try {
+            ints[0] = ints[1];
+        } catch (NullPointerException e) {
+            caught++;
+        }
+        try {
+            objects[0] = objects[1];
+        } catch (NullPointerException e) {
+            caught++;
+        }
+    }
+}
@@ -0,0 +1,23 @@
+import java.util.ArrayList;
+import java.util.List;
+
+public class OutOfMemory {
+    static int allocated;
+    static String message;
+
+    public static void run() {
+        try {
+            fill();
+        } catch (OutOfMemoryError e) {
+            message = e.getMessage();
+        }
+    }
+
+    private static void fill() {
+        List<Object> retained = new ArrayList<>();
+        while (true) {
+            retained.add(new int[4]);
+            allocated++;
+        }
+    }
+}
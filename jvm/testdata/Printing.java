@@ -0,0 +1,7 @@
+public class Printing {
+    public static void run() {
+        System.out.println("Hello, World!");
+        System.out.println(42);
+        System.err.print("oops");
+    }
+}
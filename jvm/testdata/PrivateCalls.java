@@ -0,0 +1,12 @@
+// compiled with --release 8, later releases call private methods with invokevirtual
+public class PrivateCalls {
+    static int result;
+
+    private int secret() {
+        return 5;
+    }
+
+    static void run() {
+        result = new PrivateCalls().secret();
+    }
+}
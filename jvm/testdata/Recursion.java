@@ -0,0 +1,28 @@
+public class Recursion {
+    static int depth;
+    static int caught;
+    static int withoutMessage;
+    static int unwound;
+
+    public static void run() {
+        try {
+            recurse();
+        } catch (StackOverflowError e) {
+            caught = depth;
+            withoutMessage = e.getMessage() == null ? 1 : 0;
+        }
+
+        // the stack is usable again once the error unwound it
+        depth = 0;
+        unwound = count(10);
+    }
+
+    private static void recurse() {
+        depth++;
+        recurse();
+    }
+
+    private static int count(int n) {
+        return n == 0 ? 0 : 1 + count(n - 1);
+    }
+}
@@ -0,0 +1,35 @@
+public class Sleep {
+    static int slept;
+    static int interruptedBySelf;
+    static int clearedByInterrupted;
+    static int clearedByThrow;
+    static int interruptedByOther;
+
+    static void sleepBriefly() throws InterruptedException {
+        Thread.sleep(10);
+        Thread.sleep(1, 500000);
+        slept = 1;
+    }
+
+    static void interruptSelf() {
+        Thread thread = Thread.currentThread();
+        thread.interrupt();
+        interruptedBySelf = thread.isInterrupted() ? 1 : 0;
+        clearedByInterrupted = Thread.interrupted() && !Thread.interrupted() ? 1 : 0;
+
+        thread.interrupt();
+        try {
+            Thread.sleep(60000);
+        } catch (InterruptedException e) {
+            clearedByThrow = thread.isInterrupted() ? 0 : 1;
+        }
+    }
+
+    static void sleepUntilInterrupted() {
+        try {
+            Thread.sleep(60000);
+        } catch (InterruptedException e) {
+            interruptedByOther = 1;
+        }
+    }
+}
@@ -0,0 +1,15 @@
+public class StackTraces {
+    static Throwable caught;
+
+    static void thrower() {
+        throw new IllegalStateException("boom");
+    }
+
+    static void capture() {
+        try {
+            thrower();
+        } catch (IllegalStateException e) {
+            caught = e;
+        }
+    }
+}
@@ -0,0 +1,23 @@
+import java.util.HashMap;
+
+public class StringHashes {
+    static int hello;
+    static int throughObject;
+    static int utf16;
+    static int empty;
+    static int found;
+
+    public static void run() {
+        hello = "hello".hashCode();
+        Object object = "hello";
+        throughObject = object.hashCode();
+        utf16 = "h\u00e9llo \u4e16\u754c".hashCode();
+        empty = "".hashCode();
+
+        HashMap<String, Integer> map = new HashMap<>();
+        map.put("hello", 7);
+        // a different object with the same contents has to find the entry
+        String built = new StringBuilder("hel").append("lo").toString();
+        found = map.get(built);
+    }
+}
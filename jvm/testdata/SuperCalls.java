@@ -0,0 +1,57 @@
+public class SuperCalls {
+    static int superCall;
+    static int defaultCall;
+    static int synchronizedCall;
+    static int nativeCall;
+
+    static class Base {
+        int value() {
+            return 1;
+        }
+
+        synchronized int locked() {
+            return 10;
+        }
+    }
+
+    static class Middle extends Base {
+    }
+
+    static class Child extends Middle {
+        int value() {
+            return super.value() + 2;
+        }
+
+        int locked() {
+            return super.locked() + 1;
+        }
+
+        public int hashCode() {
+            return 42;
+        }
+
+        int identityHash() {
+            return super.hashCode();
+        }
+    }
+
+    interface Greeter {
+        default int greet() {
+            return 100;
+        }
+    }
+
+    static class Polite implements Greeter {
+        public int greet() {
+            return Greeter.super.greet() + 1;
+        }
+    }
+
+    static void run() {
+        Child child = new Child();
+        superCall = child.value();
+        defaultCall = new Polite().greet();
+        synchronizedCall = child.locked();
+        nativeCall = child.identityHash() != child.hashCode() ? 1 : 0;
+    }
+}
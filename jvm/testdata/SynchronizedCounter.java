@@ -0,0 +1,22 @@
+public class SynchronizedCounter {
+    static final SynchronizedCounter instance = new SynchronizedCounter();
+    static int staticCounter;
+    static int objectCounter;
+
+    static synchronized void incrementStatic() {
+        int value = staticCounter;
+        staticCounter = value + 1;
+    }
+
+    synchronized void incrementObject() {
+        int value = objectCounter;
+        objectCounter = value + 1;
+    }
+
+    static void count() {
+        for (int i = 0; i < 100; i++) {
+            incrementStatic();
+            instance.incrementObject();
+        }
+    }
+}
@@ -0,0 +1,16 @@
+public class SystemBootstrap {
+    static int out;
+    static int err;
+    static int in;
+    static int systemClassLoader;
+    static int contextClassLoader;
+
+    public static void run() {
+        out = System.out != null ? 1 : 0;
+        err = System.err != null ? 1 : 0;
+        in = System.in != null ? 1 : 0;
+        ClassLoader loader = ClassLoader.getSystemClassLoader();
+        systemClassLoader = loader != null ? 1 : 0;
+        contextClassLoader = Thread.currentThread().getContextClassLoader() == loader ? 1 : 0;
+    }
+}
@@ -0,0 +1,13 @@
+public class SystemProperties {
+    static String osName;
+    static String userDir;
+    static String javaHome;
+    static String lineSeparator;
+
+    public static void run() {
+        osName = System.getProperty("os.name");
+        userDir = System.getProperty("user.dir");
+        javaHome = System.getProperty("java.home");
+        lineSeparator = System.lineSeparator();
+    }
+}
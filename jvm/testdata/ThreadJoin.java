@@ -0,0 +1,16 @@
+public class ThreadJoin {
+    static int flag;
+    static int aliveAfterJoin;
+
+    static void startAndJoin(Thread thread) throws InterruptedException {
+        thread.start();
+        thread.join();
+        aliveAfterJoin = thread.isAlive() ? 1 : 0;
+    }
+
+    static class Flag implements Runnable {
+        public void run() {
+            flag = 1;
+        }
+    }
+}
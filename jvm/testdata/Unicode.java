@@ -0,0 +1,14 @@
+public class Unicode {
+    static int length;
+    static int snowman;
+    static String concatenated;
+    static String fromChars;
+
+    static void run() {
+        String snow = "snow \u2603";
+        length = snow.length();
+        snowman = snow.charAt(5);
+        concatenated = snow + "!";
+        fromChars = new String(new char[] {'g', '\u00fc', '\u2603'});
+    }
+}
@@ -0,0 +1,4 @@
+public class UnsafeAccess {
+    long longValue;
+    int intValue;
+}
@@ -0,0 +1,22 @@
+public class WaitNotify {
+    static final Object lock = new Object();
+    static boolean ready;
+    static int result;
+
+    static void waitUntilReady() throws InterruptedException {
+        synchronized (lock) {
+            while (!ready) {
+                lock.wait();
+            }
+
+            result = 1;
+        }
+    }
+
+    static void notifyReady() {
+        synchronized (lock) {
+            ready = true;
+            lock.notifyAll();
+        }
+    }
+}
@@ -0,0 +1,22 @@
+public class WideFields {
+    long instanceLong;
+    double instanceDouble;
+
+    static long staticLong;
+    static double staticDouble;
+    static long chained;
+    static long longResult;
+
+    static void run() {
+        WideFields fields = new WideFields();
+        fields.instanceLong = 5000000000L;
+        fields.instanceDouble = 0.5;
+        fields.instanceLong += 1;
+        fields.instanceDouble *= 3;
+
+        staticLong = fields.instanceLong * 2;
+        staticDouble = fields.instanceDouble + 1;
+        chained = fields.instanceLong = 7L;
+        longResult = staticLong + fields.instanceLong;
+    }
+}
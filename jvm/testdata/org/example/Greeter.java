@@ -0,0 +1,5 @@
+package org.example;
+
+public class Greeter {
+    static String greeting = "hello";
+}
@@ -28,6 +28,21 @@ pub enum AccessFlag {
 }
 
 impl AccessFlag {
+    /// Bit of this flag in the `access_flags` item of a class file
+    pub fn value(&self) -> u16 {
+        match self {
+            AccessFlag::Public => ACC_PUBLIC,
+            AccessFlag::Final => ACC_FINAL,
+            AccessFlag::Super => ACC_SUPER,
+            AccessFlag::Interface => ACC_INTERFACE,
+            AccessFlag::Abstract => ACC_ABSTRACT,
+            AccessFlag::Synthetic => ACC_SYNTHETIC,
+            AccessFlag::Annotation => ACC_ANNOTATION,
+            AccessFlag::Enum => ACC_ENUM,
+            AccessFlag::Module => ACC_MODULE,
+        }
+    }
+
     pub fn flags(r: &mut impl Read) -> Result<HashSet<AccessFlag>> {
         let raw_flags = u2(r)?;
 
@@ -30,6 +30,11 @@ const METHOD_PARAMETERS_ATTR_NAME: &str = "MethodParameters";
 const NEST_HOST_ATTR_NAME: &str = "NestHost";
 const ENCLOSING_METHOD_ATTR_NAME: &str = "EnclosingMethod";
 const PERMITTED_SUBCLASSES_ATTR_NAME: &str = "PermittedSubclasses";
+const RECORD_ATTR_NAME: &str = "Record";
+const SOURCE_DEBUG_EXTENSION_ATTR_NAME: &str = "SourceDebugExtension";
+const MODULE_ATTR_NAME: &str = "Module";
+const MODULE_PACKAGES_ATTR_NAME: &str = "ModulePackages";
+const MODULE_MAIN_CLASS_ATTR_NAME: &str = "ModuleMainClass";
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Attribute {
@@ -127,6 +132,37 @@ pub enum Attribute {
         attribute_length: u32,
         classes: Vec<CpIndex>,
     },
+    Record {
+        attribute_name_index: CpIndex,
+        attribute_length: u32,
+        components: Vec<RecordComponent>,
+    },
+    SourceDebugExtension {
+        attribute_name_index: CpIndex,
+        attribute_length: u32,
+        debug_extension: Vec<u8>,
+    },
+    Module {
+        attribute_name_index: CpIndex,
+        attribute_length: u32,
+        module: Module,
+    },
+    ModulePackages {
+        attribute_name_index: CpIndex,
+        attribute_length: u32,
+        packages: Vec<CpIndex>,
+    },
+    ModuleMainClass {
+        attribute_name_index: CpIndex,
+        attribute_length: u32,
+        main_class_index: CpIndex,
+    },
+    /// Attribute which isn't recognized, its contents are kept as is
+    Unknown {
+        attribute_name_index: CpIndex,
+        attribute_length: u32,
+        info: Vec<u8>,
+    },
 }
 
 impl Attribute {
@@ -140,7 +176,7 @@ impl Attribute {
         trace!("attribute_length: {attribute_length}");
         let before = r.stream_position()?;
 
-        let attr = Ok(match name {
+        let attr = match name {
             CONSTANT_VALUE_ATTR_NAME => Self::ConstantValue {
                 attribute_name_index,
                 attribute_length,
@@ -345,10 +381,56 @@ impl Attribute {
                     classes,
                 }
             }
-            _ => bail!("unknown attribute {}", name),
-        });
-        trace!("parsed bytes: {}", r.stream_position()? - before);
-        attr
+            RECORD_ATTR_NAME => {
+                let components_count = u2(r)?;
+                let mut components = Vec::new();
+                for _ in 0..components_count {
+                    components.push(RecordComponent::new(r, cp)?);
+                }
+
+                Self::Record {
+                    attribute_name_index,
+                    attribute_length,
+                    components,
+                }
+            }
+            SOURCE_DEBUG_EXTENSION_ATTR_NAME => Self::SourceDebugExtension {
+                attribute_name_index,
+                attribute_length,
+                debug_extension: vec(r, attribute_length as usize)?,
+            },
+            MODULE_ATTR_NAME => Self::Module {
+                attribute_name_index,
+                attribute_length,
+                module: Module::new(r)?,
+            },
+            MODULE_PACKAGES_ATTR_NAME => Self::ModulePackages {
+                attribute_name_index,
+                attribute_length,
+                packages: indices(r)?,
+            },
+            MODULE_MAIN_CLASS_ATTR_NAME => Self::ModuleMainClass {
+                attribute_name_index,
+                attribute_length,
+                main_class_index: u2(r)?.into(),
+            },
+            _ => {
+                trace!("skipping unknown attribute {name}");
+                Self::Unknown {
+                    attribute_name_index,
+                    attribute_length,
+                    info: vec(r, attribute_length as usize)?,
+                }
+            }
+        };
+
+        let parsed = r.stream_position()? - before;
+        trace!("parsed bytes: {parsed}");
+        if parsed != attribute_length.into() {
+            bail!("parsed {parsed} bytes of {name} attribute with length {attribute_length}");
+        }
+
+        Ok(attr)
     }
 
     pub fn attributes(
@@ -366,6 +448,27 @@ impl Attribute {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordComponent {
+    pub name_index: CpIndex,
+    pub descriptor_index: CpIndex,
+    pub attributes: Vec<Attribute>,
+}
+
+impl RecordComponent {
+    fn new(r: &mut (impl Read + Seek), cp: &ConstantPool) -> Result<Self> {
+        let name_index = u2(r)?.into();
+        let descriptor_index = u2(r)?.into();
+        let attributes_count = u2(r)?;
+
+        Ok(Self {
+            name_index,
+            descriptor_index,
+            attributes: Attribute::attributes(r, cp, attributes_count.into())?,
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Annotation {
     pub type_index: CpIndex,
@@ -415,8 +518,22 @@ impl ElementValuePair {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ElementValue {
+    Byte(CpIndex),
+    Char(CpIndex),
+    Double(CpIndex),
+    Float(CpIndex),
+    Int(CpIndex),
+    Long(CpIndex),
+    Short(CpIndex),
     Boolean(CpIndex),
     String(CpIndex),
+    Enum {
+        type_name_index: CpIndex,
+        const_name_index: CpIndex,
+    },
+    Class(CpIndex),
+    Annotation(Annotation),
+    Array(Vec<ElementValue>),
 }
 
 impl ElementValue {
@@ -424,8 +541,30 @@ impl ElementValue {
         let tag: char = u1(r)?.into();
 
         Ok(match tag {
+            'B' => Self::Byte(u2(r)?.into()),
+            'C' => Self::Char(u2(r)?.into()),
+            'D' => Self::Double(u2(r)?.into()),
+            'F' => Self::Float(u2(r)?.into()),
+            'I' => Self::Int(u2(r)?.into()),
+            'J' => Self::Long(u2(r)?.into()),
+            'S' => Self::Short(u2(r)?.into()),
             'Z' => Self::Boolean(u2(r)?.into()),
             's' => Self::String(u2(r)?.into()),
+            'e' => Self::Enum {
+                type_name_index: u2(r)?.into(),
+                const_name_index: u2(r)?.into(),
+            },
+            'c' => Self::Class(u2(r)?.into()),
+            '@' => Self::Annotation(Annotation::new(r)?),
+            '[' => {
+                let num_values = u2(r)?;
+                let mut values = Vec::new();
+                for _ in 0..num_values {
+                    values.push(ElementValue::new(r)?);
+                }
+
+                Self::Array(values)
+            }
             _ => bail!("invalid element value tag: {tag}"),
         })
     }
@@ -474,6 +613,7 @@ pub enum VerificationType {
     Long,
     Object(CpIndex),
     Uninitialized(u16),
+    UninitializedThis,
     Null,
 }
 
@@ -488,6 +628,7 @@ impl VerificationType {
             3 => Self::Double,
             4 => Self::Long,
             5 => Self::Null,
+            6 => Self::UninitializedThis,
             7 => Self::Object(u2(r)?.into()),
             8 => Self::Uninitialized(u2(r)?),
             _ => bail!("invalid verification type tag: {tag}"),
@@ -497,8 +638,11 @@ impl VerificationType {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum StackMapTableEntry {
-    Same,
+    Same {
+        offset_delta: u16,
+    },
     SameLocals1StackItem {
+        offset_delta: u16,
         verification_type: VerificationType,
     },
     SameLocals1StackItemExtended {
@@ -507,6 +651,8 @@ pub enum StackMapTableEntry {
     },
     Chop {
         offset_delta: u16,
+        /// Number of locals removed from the end of the previous frame's locals
+        chopped: u8,
     },
     Extended {
         offset_delta: u16,
@@ -528,8 +674,11 @@ impl StackMapTableEntry {
         trace!("stack map table entry tag: {tag}");
 
         Ok(match tag {
-            0..=63 => Self::Same,
+            0..=63 => Self::Same {
+                offset_delta: tag.into(),
+            },
             64..=127 => Self::SameLocals1StackItem {
+                offset_delta: (tag - 64).into(),
                 verification_type: VerificationType::new(r)?,
             },
             247 => Self::SameLocals1StackItemExtended {
@@ -538,6 +687,7 @@ impl StackMapTableEntry {
             },
             248..=250 => Self::Chop {
                 offset_delta: u2(r)?,
+                chopped: 251 - tag,
             },
             251 => Self::Extended {
                 offset_delta: u2(r)?,
@@ -759,3 +909,378 @@ impl MethodParameterAccessFlag {
         Ok(flags)
     }
 }
+
+/// Contents of the Module attribute of a module-info class, flags are kept raw
+#[derive(Clone, Debug, PartialEq)]
+pub struct Module {
+    pub module_name_index: CpIndex,
+    pub module_flags: u16,
+    /// Zero if the module has no version
+    pub module_version_index: CpIndex,
+    pub requires: Vec<ModuleRequires>,
+    pub exports: Vec<ModulePackage>,
+    pub opens: Vec<ModulePackage>,
+    pub uses: Vec<CpIndex>,
+    pub provides: Vec<ModuleProvides>,
+}
+
+impl Module {
+    fn new(r: &mut impl Read) -> Result<Self> {
+        let module_name_index = u2(r)?.into();
+        let module_flags = u2(r)?;
+        let module_version_index = u2(r)?.into();
+
+        let mut requires = Vec::new();
+        for _ in 0..u2(r)? {
+            requires.push(ModuleRequires::new(r)?);
+        }
+
+        let mut exports = Vec::new();
+        for _ in 0..u2(r)? {
+            exports.push(ModulePackage::new(r)?);
+        }
+
+        let mut opens = Vec::new();
+        for _ in 0..u2(r)? {
+            opens.push(ModulePackage::new(r)?);
+        }
+
+        let uses = indices(r)?;
+
+        let mut provides = Vec::new();
+        for _ in 0..u2(r)? {
+            provides.push(ModuleProvides::new(r)?);
+        }
+
+        Ok(Self {
+            module_name_index,
+            module_flags,
+            module_version_index,
+            requires,
+            exports,
+            opens,
+            uses,
+            provides,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModuleRequires {
+    pub requires_index: CpIndex,
+    pub requires_flags: u16,
+    /// Zero if the version isn't recorded
+    pub requires_version_index: CpIndex,
+}
+
+impl ModuleRequires {
+    fn new(r: &mut impl Read) -> Result<Self> {
+        Ok(Self {
+            requires_index: u2(r)?.into(),
+            requires_flags: u2(r)?,
+            requires_version_index: u2(r)?.into(),
+        })
+    }
+}
+
+/// Package that is exported or opened, to every module if `to` is empty
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModulePackage {
+    pub package_index: CpIndex,
+    pub flags: u16,
+    pub to: Vec<CpIndex>,
+}
+
+impl ModulePackage {
+    fn new(r: &mut impl Read) -> Result<Self> {
+        Ok(Self {
+            package_index: u2(r)?.into(),
+            flags: u2(r)?,
+            to: indices(r)?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModuleProvides {
+    pub provides_index: CpIndex,
+    pub with: Vec<CpIndex>,
+}
+
+impl ModuleProvides {
+    fn new(r: &mut impl Read) -> Result<Self> {
+        Ok(Self {
+            provides_index: u2(r)?.into(),
+            with: indices(r)?,
+        })
+    }
+}
+
+/// Reads a u2 count followed by that many constant pool indices
+fn indices(r: &mut impl Read) -> Result<Vec<CpIndex>> {
+    let mut indices = Vec::new();
+    for _ in 0..u2(r)? {
+        indices.push(u2(r)?.into());
+    }
+    Ok(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::class::{ClassFile, constant_pool::CpInfo};
+
+    use super::*;
+
+    fn constant_pool(names: &[&str]) -> ConstantPool {
+        let mut infos = vec![CpInfo::Reserved];
+        infos.extend(names.iter().map(|name| CpInfo::Utf8(name.to_string())));
+        ConstantPool { infos }
+    }
+
+    fn attribute_bytes(name_index: u16, info: &[u8]) -> Vec<u8> {
+        let mut bytes = name_index.to_be_bytes().to_vec();
+        bytes.extend((info.len() as u32).to_be_bytes());
+        bytes.extend(info);
+        bytes
+    }
+
+    #[test]
+    fn record_class() {
+        let bytes = include_bytes!("../../testdata/Point.class");
+        let class = ClassFile::new(&mut Cursor::new(bytes)).unwrap();
+
+        let components = class
+            .attributes
+            .iter()
+            .find_map(|attr| match attr {
+                Attribute::Record { components, .. } => Some(components),
+                _ => None,
+            })
+            .unwrap();
+        let components: Vec<(&str, &str)> = components
+            .iter()
+            .map(|component| {
+                (
+                    class.constant_pool.utf8(&component.name_index).unwrap(),
+                    class
+                        .constant_pool
+                        .utf8(&component.descriptor_index)
+                        .unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            components,
+            vec![("x", "I"), ("label", "Ljava/lang/String;")]
+        );
+    }
+
+    #[test]
+    fn source_debug_extension() {
+        let cp = constant_pool(&[SOURCE_DEBUG_EXTENSION_ATTR_NAME]);
+        let bytes = attribute_bytes(1, b"SMAP");
+        let attr = Attribute::new(&mut Cursor::new(bytes), &cp).unwrap();
+
+        assert_eq!(
+            attr,
+            Attribute::SourceDebugExtension {
+                attribute_name_index: 1u16.into(),
+                attribute_length: 4,
+                debug_extension: b"SMAP".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn stack_map_table_offsets() {
+        let cp = constant_pool(&[STACK_MAP_TABLE_ATTR_NAME]);
+        let mut info = 3u16.to_be_bytes().to_vec(); // number_of_entries
+        info.push(5); // same_frame
+        info.extend([64 + 7, 1]); // same_locals_1_stack_item_frame with an int
+        info.extend([249, 0, 3]); // chop_frame removing two locals
+        let bytes = attribute_bytes(1, &info);
+        let attr = Attribute::new(&mut Cursor::new(bytes), &cp).unwrap();
+
+        let Attribute::StackMapTable { entries, .. } = attr else {
+            panic!("expected StackMapTable attribute, got {attr:?}");
+        };
+        assert_eq!(
+            entries,
+            vec![
+                StackMapTableEntry::Same { offset_delta: 5 },
+                StackMapTableEntry::SameLocals1StackItem {
+                    offset_delta: 7,
+                    verification_type: VerificationType::Integer,
+                },
+                StackMapTableEntry::Chop {
+                    offset_delta: 3,
+                    chopped: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_attribute() {
+        let cp = constant_pool(&["VendorAttribute", SOURCE_FILE_ATTR_NAME]);
+        let mut bytes = attribute_bytes(1, &[1, 2, 3]);
+        bytes.extend(attribute_bytes(2, &3u16.to_be_bytes()));
+        let attributes = Attribute::attributes(&mut Cursor::new(bytes), &cp, 2).unwrap();
+
+        assert_eq!(
+            attributes,
+            vec![
+                Attribute::Unknown {
+                    attribute_name_index: 1u16.into(),
+                    attribute_length: 3,
+                    info: vec![1, 2, 3],
+                },
+                Attribute::SourceFile {
+                    attribute_name_index: 2u16.into(),
+                    attribute_length: 2,
+                    source_file_index: 3u16.into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_attribute_in_code() {
+        let cp = constant_pool(&[CODE_ATTR_NAME, "VendorAttribute", SOURCE_FILE_ATTR_NAME]);
+        let mut info = Vec::new();
+        info.extend(1u16.to_be_bytes()); // max_stack
+        info.extend(1u16.to_be_bytes()); // max_locals
+        info.extend(1u32.to_be_bytes()); // code_length
+        info.push(0xb1); // return
+        info.extend(0u16.to_be_bytes()); // exception_table_length
+        info.extend(1u16.to_be_bytes()); // attributes_count
+        info.extend(attribute_bytes(2, &[0xca, 0xfe]));
+
+        let mut bytes = attribute_bytes(1, &info);
+        bytes.extend(attribute_bytes(3, &4u16.to_be_bytes()));
+        let mut r = Cursor::new(bytes);
+        let attributes = Attribute::attributes(&mut r, &cp, 2).unwrap();
+
+        assert_eq!(r.position(), r.get_ref().len() as u64);
+        let Attribute::Code {
+            attributes: nested, ..
+        } = &attributes[0]
+        else {
+            panic!("expected Code attribute, got {:?}", attributes[0]);
+        };
+        assert_eq!(
+            nested,
+            &vec![Attribute::Unknown {
+                attribute_name_index: 2u16.into(),
+                attribute_length: 2,
+                info: vec![0xca, 0xfe],
+            }]
+        );
+        assert!(matches!(attributes[1], Attribute::SourceFile { .. }));
+    }
+
+    #[test]
+    fn attribute_length_mismatch() {
+        let cp = constant_pool(&[SOURCE_FILE_ATTR_NAME]);
+        let bytes = attribute_bytes(1, &[0, 1, 2]);
+        assert!(Attribute::new(&mut Cursor::new(bytes), &cp).is_err());
+    }
+
+    #[test]
+    fn enum_element_values() {
+        let cp = constant_pool(&[
+            RUNTIME_VISIBLE_ANNOTATIONS_ATTR_NAME,
+            "Ljava/lang/annotation/Retention;",
+            "value",
+            "Ljava/lang/annotation/RetentionPolicy;",
+            "RUNTIME",
+            "Ljava/lang/annotation/Target;",
+            "Ljava/lang/annotation/ElementType;",
+            "TYPE",
+            "METHOD",
+        ]);
+        let mut info = Vec::new();
+        info.extend(2u16.to_be_bytes()); // num_annotations
+        // @Retention(RetentionPolicy.RUNTIME)
+        info.extend(2u16.to_be_bytes());
+        info.extend(1u16.to_be_bytes());
+        info.extend(3u16.to_be_bytes());
+        info.push(b'e');
+        info.extend(4u16.to_be_bytes());
+        info.extend(5u16.to_be_bytes());
+        // @Target({ElementType.TYPE, ElementType.METHOD})
+        info.extend(6u16.to_be_bytes());
+        info.extend(1u16.to_be_bytes());
+        info.extend(3u16.to_be_bytes());
+        info.push(b'[');
+        info.extend(2u16.to_be_bytes());
+        for const_name_index in [8u16, 9] {
+            info.push(b'e');
+            info.extend(7u16.to_be_bytes());
+            info.extend(const_name_index.to_be_bytes());
+        }
+
+        let bytes = attribute_bytes(1, &info);
+        let attr = Attribute::new(&mut Cursor::new(bytes), &cp).unwrap();
+        let Attribute::RuntimeVisibleAnnoations { annotations, .. } = attr else {
+            panic!("expected RuntimeVisibleAnnotations attribute, got {attr:?}");
+        };
+
+        let enum_value = |type_name_index: u16, const_name_index: u16| ElementValue::Enum {
+            type_name_index: type_name_index.into(),
+            const_name_index: const_name_index.into(),
+        };
+        assert_eq!(
+            annotations,
+            vec![
+                Annotation {
+                    type_index: 2u16.into(),
+                    element_value_pairs: vec![ElementValuePair {
+                        element_name_index: 3u16.into(),
+                        value: enum_value(4, 5),
+                    }],
+                },
+                Annotation {
+                    type_index: 6u16.into(),
+                    element_value_pairs: vec![ElementValuePair {
+                        element_name_index: 3u16.into(),
+                        value: ElementValue::Array(vec![enum_value(7, 8), enum_value(7, 9)]),
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_element_values() {
+        let mut bytes = vec![b'@'];
+        bytes.extend(1u16.to_be_bytes()); // type_index
+        bytes.extend(2u16.to_be_bytes()); // num_element_value_pairs
+        bytes.extend(2u16.to_be_bytes());
+        bytes.push(b'I');
+        bytes.extend(3u16.to_be_bytes());
+        bytes.extend(4u16.to_be_bytes());
+        bytes.push(b'c');
+        bytes.extend(5u16.to_be_bytes());
+
+        let value = ElementValue::new(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(
+            value,
+            ElementValue::Annotation(Annotation {
+                type_index: 1u16.into(),
+                element_value_pairs: vec![
+                    ElementValuePair {
+                        element_name_index: 2u16.into(),
+                        value: ElementValue::Int(3u16.into()),
+                    },
+                    ElementValuePair {
+                        element_name_index: 4u16.into(),
+                        value: ElementValue::Class(5u16.into()),
+                    },
+                ],
+            })
+        );
+    }
+}
@@ -90,6 +90,30 @@ impl ConstantPool {
         }
     }
 
+    pub fn module_name(&self, index: &CpIndex) -> Result<&str> {
+        if let CpInfo::Module { name_index } = self
+            .infos
+            .get(index.0 as usize)
+            .context(format!("constant pool item at index {} not found", index.0))?
+        {
+            self.utf8(name_index)
+        } else {
+            bail!("no module constant pool item found at index {index:?}")
+        }
+    }
+
+    pub fn package_name(&self, index: &CpIndex) -> Result<&str> {
+        if let CpInfo::Package { name_index } = self
+            .infos
+            .get(index.0 as usize)
+            .context(format!("constant pool item at index {} not found", index.0))?
+        {
+            self.utf8(name_index)
+        } else {
+            bail!("no package constant pool item found at index {index:?}")
+        }
+    }
+
     pub fn name_and_type(&self, index: &CpIndex) -> Result<(&str, &str)> {
         if let CpInfo::NameAndType {
             name_index,
@@ -104,6 +128,115 @@ impl ConstantPool {
             bail!("no name_and_type constant pool item found at index {index:?}")
         }
     }
+
+    /// Checks that the indices inside of every item point to an item of the expected kind
+    pub fn validate(&self) -> Result<()> {
+        for (i, info) in self.infos.iter().enumerate() {
+            let utf8 = |info: &CpInfo| matches!(info, CpInfo::Utf8(_));
+            let class = |info: &CpInfo| matches!(info, CpInfo::Class { .. });
+            let name_and_type = |info: &CpInfo| matches!(info, CpInfo::NameAndType { .. });
+
+            match info {
+                CpInfo::Class { name_index } => self.expect(i, name_index, "Utf8", utf8)?,
+                CpInfo::String { string_index } => self.expect(i, string_index, "Utf8", utf8)?,
+                CpInfo::FieldRef {
+                    class_index,
+                    name_and_type_index,
+                }
+                | CpInfo::MethodRef {
+                    class_index,
+                    name_and_type_index,
+                }
+                | CpInfo::InterfaceMethodRef {
+                    class_index,
+                    name_and_type_index,
+                } => {
+                    self.expect(i, class_index, "Class", class)?;
+                    self.expect(i, name_and_type_index, "NameAndType", name_and_type)?;
+                }
+                CpInfo::NameAndType {
+                    name_index,
+                    descriptor_index,
+                } => {
+                    self.expect(i, name_index, "Utf8", utf8)?;
+                    self.expect(i, descriptor_index, "Utf8", utf8)?;
+                }
+                CpInfo::MethodHandle {
+                    reference_kind,
+                    reference_index,
+                } => match reference_kind {
+                    ReferenceKind::GetField
+                    | ReferenceKind::GetStatic
+                    | ReferenceKind::PutField
+                    | ReferenceKind::PutStatic => {
+                        self.expect(i, reference_index, "FieldRef", |info| {
+                            matches!(info, CpInfo::FieldRef { .. })
+                        })?
+                    }
+                    ReferenceKind::InvokeVirtual | ReferenceKind::NewInvokeSpecial => {
+                        self.expect(i, reference_index, "MethodRef", |info| {
+                            matches!(info, CpInfo::MethodRef { .. })
+                        })?
+                    }
+                    ReferenceKind::InvokeStatic | ReferenceKind::InvokeSpecial => self.expect(
+                        i,
+                        reference_index,
+                        "MethodRef or InterfaceMethodRef",
+                        |info| {
+                            matches!(
+                                info,
+                                CpInfo::MethodRef { .. } | CpInfo::InterfaceMethodRef { .. }
+                            )
+                        },
+                    )?,
+                    ReferenceKind::InvokeInterface => {
+                        self.expect(i, reference_index, "InterfaceMethodRef", |info| {
+                            matches!(info, CpInfo::InterfaceMethodRef { .. })
+                        })?
+                    }
+                },
+                CpInfo::MethodType { descriptor_index } => {
+                    self.expect(i, descriptor_index, "Utf8", utf8)?
+                }
+                CpInfo::Dynamic {
+                    name_and_type_index,
+                    ..
+                }
+                | CpInfo::InvokeDynamic {
+                    name_and_type_index,
+                    ..
+                } => self.expect(i, name_and_type_index, "NameAndType", name_and_type)?,
+                CpInfo::Module { name_index } | CpInfo::Package { name_index } => {
+                    self.expect(i, name_index, "Utf8", utf8)?
+                }
+                CpInfo::Reserved
+                | CpInfo::Utf8(_)
+                | CpInfo::Integer(_)
+                | CpInfo::Float(_)
+                | CpInfo::Double(_)
+                | CpInfo::Long(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `index`, found in the item at `item`, points to an item that is `expected`
+    fn expect(
+        &self,
+        item: usize,
+        index: &CpIndex,
+        expected: &str,
+        is_expected: impl Fn(&CpInfo) -> bool,
+    ) -> Result<()> {
+        match self.infos.get(index.0 as usize) {
+            Some(info) if is_expected(info) => Ok(()),
+            found => bail!(
+                "constant pool item {item} points to {found:?} at index {} instead of {expected} (TODO: throw ClassFormatError)",
+                index.0
+            ),
+        }
+    }
 }
 
 const UTF8_TAG: u8 = 1;
@@ -119,7 +252,10 @@ const INTERFACE_METHOD_REF_TAG: u8 = 11;
 const NAME_AND_TYPE_TAG: u8 = 12;
 const METHOD_HANDLE_TAG: u8 = 15;
 const METHOD_TYPE_TAG: u8 = 16;
+const DYNAMIC_TAG: u8 = 17;
 const INVOKE_DYNAMIC_TAG: u8 = 18;
+const MODULE_TAG: u8 = 19;
+const PACKAGE_TAG: u8 = 20;
 
 #[derive(Debug, Clone)]
 pub enum CpInfo {
@@ -158,10 +294,23 @@ pub enum CpInfo {
     MethodType {
         descriptor_index: CpIndex,
     },
+    /// Constant computed by a bootstrap method when it is first loaded
+    Dynamic {
+        bootstrap_method_attr_index: CpIndex,
+        name_and_type_index: CpIndex,
+    },
     InvokeDynamic {
         bootstrap_method_attr_index: CpIndex,
         name_and_type_index: CpIndex,
     },
+    /// Only allowed in module-info classes
+    Module {
+        name_index: CpIndex,
+    },
+    /// Only allowed in module-info classes
+    Package {
+        name_index: CpIndex,
+    },
 }
 
 impl CpInfo {
@@ -206,10 +355,20 @@ impl CpInfo {
             METHOD_TYPE_TAG => Ok(Self::MethodType {
                 descriptor_index: u2(r)?.into(),
             }),
+            DYNAMIC_TAG => Ok(Self::Dynamic {
+                bootstrap_method_attr_index: u2(r)?.into(),
+                name_and_type_index: u2(r)?.into(),
+            }),
             INVOKE_DYNAMIC_TAG => Ok(Self::InvokeDynamic {
                 bootstrap_method_attr_index: u2(r)?.into(),
                 name_and_type_index: u2(r)?.into(),
             }),
+            MODULE_TAG => Ok(Self::Module {
+                name_index: u2(r)?.into(),
+            }),
+            PACKAGE_TAG => Ok(Self::Package {
+                name_index: u2(r)?.into(),
+            }),
             _ => bail!("invalid constant pool info tag {tag}"),
         }
     }
@@ -243,4 +402,127 @@ impl ReferenceKind {
             _ => bail!("invalid reference kind {value}"),
         })
     }
+
+    /// The `reference_kind` item as stored in the class file
+    pub fn value(&self) -> u8 {
+        match self {
+            Self::GetField => 1,
+            Self::GetStatic => 2,
+            Self::PutField => 3,
+            Self::PutStatic => 4,
+            Self::InvokeVirtual => 5,
+            Self::InvokeStatic => 6,
+            Self::InvokeSpecial => 7,
+            Self::NewInvokeSpecial => 8,
+            Self::InvokeInterface => 9,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn negative_integer() {
+        let mut bytes = vec![INTEGER_TAG];
+        bytes.extend((-100000i32).to_be_bytes());
+        let info = CpInfo::new(&mut Cursor::new(bytes)).unwrap();
+        assert!(matches!(info, CpInfo::Integer(-100000)));
+    }
+
+    #[test]
+    fn dynamic_constant() {
+        let mut bytes = vec![DYNAMIC_TAG];
+        bytes.extend(0u16.to_be_bytes());
+        bytes.extend(2u16.to_be_bytes());
+        bytes.push(NAME_AND_TYPE_TAG);
+        bytes.extend(3u16.to_be_bytes());
+        bytes.extend(4u16.to_be_bytes());
+        for value in ["_", "I"] {
+            bytes.push(UTF8_TAG);
+            bytes.extend(1u16.to_be_bytes());
+            bytes.extend(value.as_bytes());
+        }
+        let cp = ConstantPool::new(&mut Cursor::new(bytes), 5).unwrap();
+
+        assert!(matches!(
+            &cp.infos[1],
+            CpInfo::Dynamic {
+                bootstrap_method_attr_index: CpIndex(0),
+                name_and_type_index: CpIndex(2),
+            }
+        ));
+        cp.validate().unwrap();
+        assert_eq!(cp.name_and_type(&2u16.into()).unwrap(), ("_", "I"));
+    }
+
+    #[test]
+    fn double_takes_two_slots() {
+        let mut bytes = vec![DOUBLE_TAG];
+        bytes.extend(1.5f64.to_be_bytes());
+        bytes.push(UTF8_TAG);
+        bytes.extend(1u16.to_be_bytes());
+        bytes.push(b'x');
+        // both items plus the reserved first item and the slot after the double
+        let cp = ConstantPool::new(&mut Cursor::new(bytes), 4).unwrap();
+
+        assert!(matches!(cp.infos[1], CpInfo::Double(1.5)));
+        assert!(matches!(cp.infos[2], CpInfo::Reserved));
+        assert_eq!(cp.utf8(&3u16.into()).unwrap(), "x");
+    }
+
+    #[test]
+    fn validate_indices() {
+        let mut cp = ConstantPool {
+            infos: vec![
+                CpInfo::Reserved,
+                CpInfo::Utf8("Test".to_string()),
+                CpInfo::Class {
+                    name_index: 1u16.into(),
+                },
+                CpInfo::Utf8("run".to_string()),
+                CpInfo::Utf8("()V".to_string()),
+                CpInfo::NameAndType {
+                    name_index: 3u16.into(),
+                    descriptor_index: 4u16.into(),
+                },
+                CpInfo::MethodRef {
+                    class_index: 2u16.into(),
+                    name_and_type_index: 5u16.into(),
+                },
+                CpInfo::MethodHandle {
+                    reference_kind: ReferenceKind::InvokeStatic,
+                    reference_index: 6u16.into(),
+                },
+            ],
+        };
+        cp.validate().unwrap();
+
+        // the class of the method points to its name instead
+        cp.infos[6] = CpInfo::MethodRef {
+            class_index: 1u16.into(),
+            name_and_type_index: 5u16.into(),
+        };
+        let err = cp.validate().unwrap_err();
+        assert!(err.to_string().contains("item 6"), "{err}");
+        assert!(err.to_string().contains("instead of Class"), "{err}");
+
+        // out of bounds
+        cp.infos[2] = CpInfo::Class {
+            name_index: 42u16.into(),
+        };
+        let err = cp.validate().unwrap_err();
+        assert!(err.to_string().contains("None at index 42"), "{err}");
+    }
+
+    #[test]
+    fn negative_long() {
+        let mut bytes = vec![LONG_TAG];
+        bytes.extend((-5000000000i64).to_be_bytes());
+        let info = CpInfo::new(&mut Cursor::new(bytes)).unwrap();
+        assert!(matches!(info, CpInfo::Long(-5000000000)));
+    }
 }
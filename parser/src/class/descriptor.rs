@@ -120,6 +120,22 @@ pub enum BaseType {
     Boolean,
 }
 
+impl BaseType {
+    /// Name of the primitive type in the Java language, e.g. `int`
+    pub fn name(&self) -> &'static str {
+        match self {
+            BaseType::Byte => "byte",
+            BaseType::Char => "char",
+            BaseType::Double => "double",
+            BaseType::Float => "float",
+            BaseType::Int => "int",
+            BaseType::Long => "long",
+            BaseType::Short => "short",
+            BaseType::Boolean => "boolean",
+        }
+    }
+}
+
 impl FieldType {
     fn new(raw: &str) -> Result<Self> {
         Ok(match &raw[0..1] {
@@ -139,6 +155,25 @@ impl FieldType {
         })
     }
 
+    /// Descriptor as it appears in a class file, e.g. `Ljava/lang/String;`
+    pub fn descriptor(&self) -> String {
+        match self {
+            FieldType::BaseType(base_type) => match base_type {
+                BaseType::Byte => "B",
+                BaseType::Char => "C",
+                BaseType::Double => "D",
+                BaseType::Float => "F",
+                BaseType::Int => "I",
+                BaseType::Long => "J",
+                BaseType::Short => "S",
+                BaseType::Boolean => "Z",
+            }
+            .to_string(),
+            FieldType::ObjectType { class_name } => format!("L{class_name};"),
+            FieldType::ComponentType(field_type) => format!("[{}", field_type.descriptor()),
+        }
+    }
+
     fn length(&self) -> usize {
         match self {
             FieldType::BaseType(_) => 1,
@@ -206,4 +241,15 @@ mod tests {
 
         assert_eq!(descriptor.return_descriptor, ReturnDescriptor::Void);
     }
+
+    #[test]
+    fn field_type_descriptor() {
+        let descriptor = MethodDescriptor::new("([[DLjava/lang/String;JZ)V").unwrap();
+        let parameters: Vec<String> = descriptor
+            .parameters
+            .iter()
+            .map(FieldType::descriptor)
+            .collect();
+        assert_eq!(parameters, vec!["[[D", "Ljava/lang/String;", "J", "Z"]);
+    }
 }
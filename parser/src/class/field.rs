@@ -76,7 +76,7 @@ impl Field {
 }
 
 const ACC_PUBLIC: u16 = 0x0001;
-const ACC_PRIVATE: u16 = 0x0003;
+const ACC_PRIVATE: u16 = 0x0002;
 const ACC_PROTECTED: u16 = 0x0004;
 const ACC_STATIC: u16 = 0x0008;
 const ACC_FINAL: u16 = 0x0010;
@@ -143,3 +143,26 @@ impl AccessFlag {
         Ok(flags)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn public_field_flags() {
+        let flags = AccessFlag::flags(&mut Cursor::new(ACC_PUBLIC.to_be_bytes())).unwrap();
+        assert_eq!(flags, HashSet::from([AccessFlag::Public]));
+    }
+
+    #[test]
+    fn private_field_flags() {
+        let raw = ACC_PRIVATE | ACC_STATIC | ACC_FINAL;
+        let flags = AccessFlag::flags(&mut Cursor::new(raw.to_be_bytes())).unwrap();
+        assert_eq!(
+            flags,
+            HashSet::from([AccessFlag::Private, AccessFlag::Static, AccessFlag::Final])
+        );
+    }
+}
@@ -9,7 +9,7 @@ use tracing::trace;
 use crate::{
     class::{
         access_flags::AccessFlag,
-        attribute::Attribute,
+        attribute::{Attribute, BootStrapMethod},
         constant_pool::{ConstantPool, CpIndex, CpInfo},
         descriptor::FieldType,
         field::Field,
@@ -130,6 +130,32 @@ impl ClassFile {
         bail!("no field with name '{name}' and descriptor '{descriptor}' found")
     }
 
+    pub fn bootstrap_method(&self, index: u16) -> Result<&BootStrapMethod> {
+        self.attributes
+            .iter()
+            .find_map(|attr| match attr {
+                Attribute::BootstrapMethods { methods, .. } => Some(methods),
+                _ => None,
+            })
+            .context("no BootstrapMethods attribute found")?
+            .get(index as usize)
+            .context(format!("no bootstrap method at index {index}"))
+    }
+
+    /// Name of the source file this class was compiled from, if recorded
+    pub fn source_file(&self) -> Result<Option<&str>> {
+        self.attributes
+            .iter()
+            .find_map(|attr| match attr {
+                Attribute::SourceFile {
+                    source_file_index, ..
+                } => Some(source_file_index),
+                _ => None,
+            })
+            .map(|index| self.constant_pool.utf8(index))
+            .transpose()
+    }
+
     pub fn super_class(&self) -> Result<&str> {
         self.constant_pool.class_name(&self.super_class)
     }
@@ -0,0 +1 @@
+public record Point(int x, String label) {}